@@ -0,0 +1,383 @@
+//! Evaluates constant expressions to a value.
+//!
+//! Array bounds, subrange limits and string lengths are constant
+//! expressions. A constant expression is an integer expression of literals
+//! (including typed literals such as `INT#5` and `BYTE#16#FF`) and
+//! variables declared `CONSTANT` that are combined with arithmetic (`+`,
+//! `-`, `*`, `/`, `MOD`, `**`) and bit-wise (`AND`, `OR`, `XOR`, `NOT`)
+//! operators.
+//!
+//! The evaluator computes using the widest integer and then checks that the
+//! value is within the range of the type of the value (when the type is
+//! known).
+use ironplc_dsl::{
+    common::*,
+    core::{Id, Located, SourceSpan},
+    diagnostic::{Diagnostic, Label},
+    textual::*,
+};
+use ironplc_problems::Problem;
+
+use crate::{
+    expr_type::{widens_to, ExprType},
+    scoped_table::Value,
+};
+
+/// The value of a constant expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstantValue {
+    pub value: i128,
+    /// The type of the value or `None` for an untyped literal (or an
+    /// expression of untyped literals).
+    pub data_type: Option<ElementaryTypeName>,
+}
+
+impl Value for ConstantValue {}
+
+impl ConstantValue {
+    pub fn new(value: i128, data_type: Option<ElementaryTypeName>) -> Self {
+        Self { value, data_type }
+    }
+
+    /// Returns the value of a literal or `None` if the literal is not an
+    /// integer.
+    pub fn from_constant(constant: &ConstantKind) -> Option<Self> {
+        match constant {
+            ConstantKind::IntegerLiteral(literal) => Some(Self::new(
+                literal.value.clone().try_into().ok()?,
+                literal.data_type.clone(),
+            )),
+            ConstantKind::BitStringLiteral(literal) => Some(Self::new(
+                literal.value.clone().try_into().ok()?,
+                literal.data_type.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the initial value of a variable having the initializer or
+    /// `None` if the initializer does not assign an integer value.
+    pub fn from_initializer(initializer: &InitialValueAssignmentKind) -> Option<Self> {
+        let InitialValueAssignmentKind::Simple(simple) = initializer else {
+            return None;
+        };
+        let mut value = Self::from_constant(simple.initial_value.as_ref()?)?;
+        // The declared type is the type of the variable (rather than the
+        // type of the literal).
+        if let Some(ExprType::Elementary(data_type)) =
+            ExprType::from_elementary_name(&simple.type_name)
+        {
+            value.data_type = Some(data_type);
+        }
+        Some(value)
+    }
+}
+
+/// Evaluates the expression to a value.
+///
+/// The `find` function returns the value of a named constant or `None`
+/// if the name is not a constant. The `context` is the location for
+/// diagnostics when the expression does not have a location (literals do
+/// not have reliable locations).
+pub fn evaluate(
+    expr: &ExprKind,
+    find: &mut dyn FnMut(&Id) -> Option<ConstantValue>,
+    context: &SourceSpan,
+) -> Result<ConstantValue, Diagnostic> {
+    Evaluator { find, context }.evaluate(expr)
+}
+
+struct Evaluator<'a> {
+    find: &'a mut dyn FnMut(&Id) -> Option<ConstantValue>,
+    context: &'a SourceSpan,
+}
+
+impl Evaluator<'_> {
+    fn evaluate(&mut self, expr: &ExprKind) -> Result<ConstantValue, Diagnostic> {
+        match expr {
+            ExprKind::Const(constant) => ConstantValue::from_constant(constant)
+                .ok_or_else(|| self.required(self.context, "literal is not an integer")),
+            ExprKind::Expression(inner) => self.evaluate(inner),
+            ExprKind::LateBound(late_bound) => self.named(&late_bound.value),
+            ExprKind::Variable(Variable::Symbolic(SymbolicVariableKind::Named(named))) => {
+                self.named(&named.name)
+            }
+            ExprKind::UnaryOp(unary) => {
+                let term = self.evaluate(&unary.term)?;
+                match unary.op {
+                    UnaryOp::Neg => self.checked(term.value.checked_neg(), term.data_type),
+                    UnaryOp::Not => Ok(complement(term)),
+                }
+            }
+            ExprKind::BinaryOp(binary) => {
+                let left = self.evaluate(&binary.left)?;
+                let right = self.evaluate(&binary.right)?;
+                let data_type = self.common_type(&left, &right)?;
+                let (l, r) = (left.value, right.value);
+                let value = match binary.op {
+                    Operator::Add => l.checked_add(r),
+                    Operator::Sub => l.checked_sub(r),
+                    Operator::Mul => l.checked_mul(r),
+                    Operator::Div | Operator::Mod if r == 0 => {
+                        return Err(self.invalid("division by zero"));
+                    }
+                    // Integer division truncates toward zero (as Rust does)
+                    Operator::Div => l.checked_div(r),
+                    Operator::Mod => l.checked_rem(r),
+                    Operator::Pow => u32::try_from(r).ok().and_then(|r| l.checked_pow(r)),
+                };
+                self.checked(value, data_type)
+            }
+            ExprKind::Compare(compare) => {
+                let left = self.evaluate(&compare.left)?;
+                let right = self.evaluate(&compare.right)?;
+                let data_type = self.common_type(&left, &right)?;
+                let (l, r) = (left.value, right.value);
+                let value = match compare.op {
+                    CompareOp::And => l & r,
+                    CompareOp::Or => l | r,
+                    CompareOp::Xor => l ^ r,
+                    CompareOp::Eq
+                    | CompareOp::Ne
+                    | CompareOp::Lt
+                    | CompareOp::Gt
+                    | CompareOp::LtEq
+                    | CompareOp::GtEq => {
+                        return Err(self.required(self.context, "comparison is not an integer"))
+                    }
+                };
+                self.checked(Some(value), data_type)
+            }
+            ExprKind::Variable(Variable::Symbolic(variable)) => {
+                Err(self.required(&variable.span(), "variable is not a constant"))
+            }
+            ExprKind::Variable(Variable::Direct(_)) => {
+                Err(self.required(self.context, "variable is not a constant"))
+            }
+            ExprKind::Function(function) => {
+                Err(self.required(&function.name.span(), "function invocation"))
+            }
+            ExprKind::EnumeratedValue(_)
+            | ExprKind::MethodCall(_)
+            | ExprKind::Ref(_)
+            | ExprKind::Null(_) => Err(self.required(self.context, "expression is not an integer")),
+        }
+    }
+
+    /// Returns the value of the named constant.
+    fn named(&mut self, name: &Id) -> Result<ConstantValue, Diagnostic> {
+        (self.find)(name).ok_or_else(|| {
+            Diagnostic::problem(
+                Problem::ConstantExpressionRequired,
+                Label::span(name.span(), "Constant expression"),
+            )
+            .with_context_id("variable", name)
+            .with_context("reason", &String::from("variable is not a constant"))
+        })
+    }
+
+    /// Returns the type of the result of an operator applied to the values.
+    fn common_type(
+        &self,
+        left: &ConstantValue,
+        right: &ConstantValue,
+    ) -> Result<Option<ElementaryTypeName>, Diagnostic> {
+        match (&left.data_type, &right.data_type) {
+            (None, other) | (other, None) => Ok(other.clone()),
+            (Some(l), Some(r)) if widens_to(l, r) => Ok(Some(r.clone())),
+            (Some(l), Some(r)) if widens_to(r, l) => Ok(Some(l.clone())),
+            (Some(l), Some(r)) => Err(self.invalid(&format!(
+                "operands have incompatible types {} and {}",
+                ExprType::Elementary(l.clone()),
+                ExprType::Elementary(r.clone())
+            ))),
+        }
+    }
+
+    /// Returns the value if the value is in the range of the type.
+    fn checked(
+        &self,
+        value: Option<i128>,
+        data_type: Option<ElementaryTypeName>,
+    ) -> Result<ConstantValue, Diagnostic> {
+        let value = value.ok_or_else(|| self.invalid("overflow"))?;
+        if let Some(data_type) = &data_type {
+            if let Some((min, max)) = integer_range(data_type) {
+                if value < min || value > max {
+                    return Err(self.invalid(&format!(
+                        "value {value} is out of range for {}",
+                        ExprType::Elementary(data_type.clone())
+                    )));
+                }
+            }
+        }
+        Ok(ConstantValue::new(value, data_type))
+    }
+
+    fn required(&self, span: &SourceSpan, reason: &str) -> Diagnostic {
+        Diagnostic::problem(
+            Problem::ConstantExpressionRequired,
+            Label::span(span.clone(), "Constant expression"),
+        )
+        .with_context("reason", &String::from(reason))
+    }
+
+    fn invalid(&self, reason: &str) -> Diagnostic {
+        Diagnostic::problem(
+            Problem::ConstantExpressionInvalid,
+            Label::span(self.context.clone(), "Constant expression"),
+        )
+        .with_context("reason", &String::from(reason))
+    }
+}
+
+/// Returns the bit-wise complement of the value. Values of unsigned and bit
+/// string types are complemented within the size of the type.
+fn complement(value: ConstantValue) -> ConstantValue {
+    let complement = match value.data_type.as_ref().and_then(integer_range) {
+        Some((0, max)) => max & !value.value,
+        _ => !value.value,
+    };
+    ConstantValue::new(complement, value.data_type)
+}
+
+/// Returns the minimum and maximum values of an integer or bit string type
+/// or `None` if the type is not an integer or bit string type.
+fn integer_range(data_type: &ElementaryTypeName) -> Option<(i128, i128)> {
+    use ElementaryTypeName::*;
+    let range = match data_type {
+        SINT => (i8::MIN.into(), i8::MAX.into()),
+        INT => (i16::MIN.into(), i16::MAX.into()),
+        DINT => (i32::MIN.into(), i32::MAX.into()),
+        LINT => (i64::MIN.into(), i64::MAX.into()),
+        USINT | BYTE => (0, u8::MAX.into()),
+        UINT | WORD => (0, u16::MAX.into()),
+        UDINT | DWORD => (0, u32::MAX.into()),
+        ULINT | LWORD => (0, u64::MAX.into()),
+        _ => return None,
+    };
+    Some(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ironplc_dsl::core::FileId;
+    use ironplc_parser::{options::ParseOptions, parse_program};
+
+    use super::*;
+
+    /// Parses the text as the initial value of an assignment and evaluates
+    /// the expression with the constants.
+    fn evaluate_text(
+        text: &str,
+        constants: &[(&str, ConstantValue)],
+    ) -> Result<ConstantValue, Diagnostic> {
+        let program = format!("PROGRAM main\nx := {text};\nEND_PROGRAM");
+        let library =
+            parse_program(&program, &FileId::default(), &ParseOptions::default()).unwrap();
+        let LibraryElementKind::ProgramDeclaration(program) = &library.elements[0] else {
+            panic!("expected program");
+        };
+        let FunctionBlockBodyKind::Statements(body) = &program.body else {
+            panic!("expected statements");
+        };
+        let StmtKind::Assignment(assignment) = &body.body[0] else {
+            panic!("expected assignment");
+        };
+
+        let constants: HashMap<Id, ConstantValue> = constants
+            .iter()
+            .map(|(name, value)| (Id::from(name), value.clone()))
+            .collect();
+        evaluate(
+            &assignment.value,
+            &mut |name| constants.get(name).cloned(),
+            &SourceSpan::default(),
+        )
+    }
+
+    fn assert_problem(result: Result<ConstantValue, Diagnostic>, problem: Problem) {
+        assert_eq!(problem.code(), result.unwrap_err().code);
+    }
+
+    #[test]
+    fn evaluate_when_arithmetic_then_folds() {
+        let result = evaluate_text("(2 + 3) * 4 - 10 / 3 + 7 MOD 4 + 2 ** 3", &[]).unwrap();
+        assert_eq!(ConstantValue::new(28, None), result);
+    }
+
+    #[test]
+    fn evaluate_when_negative_then_folds() {
+        let result = evaluate_text("-(4 - 10)", &[]).unwrap();
+        assert_eq!(6, result.value);
+    }
+
+    #[test]
+    fn evaluate_when_bit_operations_then_folds() {
+        let result = evaluate_text("(16#F0 OR 16#0F) AND 16#3C XOR 1", &[]).unwrap();
+        assert_eq!(0x3D, result.value);
+    }
+
+    #[test]
+    fn evaluate_when_not_typed_unsigned_then_complements_within_size() {
+        let result = evaluate_text("NOT BYTE#16#0F", &[]).unwrap();
+        assert_eq!(
+            ConstantValue::new(0xF0, Some(ElementaryTypeName::BYTE)),
+            result
+        );
+    }
+
+    #[test]
+    fn evaluate_when_constant_variable_then_uses_value() {
+        let result = evaluate_text(
+            "MAX_AXES - 1",
+            &[(
+                "MAX_AXES",
+                ConstantValue::new(8, Some(ElementaryTypeName::INT)),
+            )],
+        )
+        .unwrap();
+        assert_eq!(ConstantValue::new(7, Some(ElementaryTypeName::INT)), result);
+    }
+
+    #[test]
+    fn evaluate_when_typed_operands_then_widens() {
+        let result = evaluate_text("SINT#5 + DINT#100000", &[]).unwrap();
+        assert_eq!(
+            ConstantValue::new(100005, Some(ElementaryTypeName::DINT)),
+            result
+        );
+    }
+
+    #[test]
+    fn evaluate_when_not_constant_then_error() {
+        let result = evaluate_text("COUNT + 1", &[]);
+        assert_problem(result, Problem::ConstantExpressionRequired);
+    }
+
+    #[test]
+    fn evaluate_when_function_then_error() {
+        let result = evaluate_text("ABS(1)", &[]);
+        assert_problem(result, Problem::ConstantExpressionRequired);
+    }
+
+    #[test]
+    fn evaluate_when_division_by_zero_then_error() {
+        let result = evaluate_text("10 / (2 - 2)", &[]);
+        assert_problem(result, Problem::ConstantExpressionInvalid);
+    }
+
+    #[test]
+    fn evaluate_when_out_of_range_for_type_then_error() {
+        let result = evaluate_text("SINT#100 + 100", &[]);
+        assert_problem(result, Problem::ConstantExpressionInvalid);
+    }
+
+    #[test]
+    fn evaluate_when_incompatible_types_then_error() {
+        let result = evaluate_text("INT#1 + UINT#1", &[]);
+        assert_problem(result, Problem::ConstantExpressionInvalid);
+    }
+}
@@ -0,0 +1,545 @@
+//! Types of expressions in the IronPLC compiler.
+//!
+//! This module defines the type of an expression (as opposed to the type of
+//! a declaration) and the rules for combining types of expressions. The rules
+//! follow the implicit conversions of IEC 61131-3 so that, for example, an
+//! `INT` widens to a `DINT` but a `DINT` does not narrow to an `INT`.
+//!
+//! Literals without a type prefix do not have a single type. Instead, the
+//! literal takes the type from the context where the literal is used.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use ironplc_dsl::{
+    common::{ElementaryTypeName, TypeName},
+    core::{FileId, SourceSpan},
+};
+
+use crate::intermediate_type::{ByteSized, IntermediateType};
+
+/// The type of an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprType {
+    /// An elementary type (or a type derived from an elementary type).
+    Elementary(ElementaryTypeName),
+    /// An integer literal without a type prefix.
+    AnyInt,
+    /// A real literal without a type prefix.
+    AnyReal,
+    /// A character string literal.
+    AnyString,
+    /// A value of an enumeration. The name is not known for values of
+    /// enumerations that are declared as part of a variable declaration.
+    Enumeration(Option<TypeName>),
+    /// A structure or array type. Two derived types are the same type when
+    /// the types have the same representation.
+    Derived(TypeName, IntermediateType),
+}
+
+impl ExprType {
+    /// Returns the type for the intermediate representation of a type or
+    /// `None` if the representation does not identify a single type.
+    pub fn from_representation(
+        name: Option<&TypeName>,
+        representation: &IntermediateType,
+    ) -> Option<ExprType> {
+        use ElementaryTypeName::*;
+        let elementary = match representation {
+            IntermediateType::Bool => BOOL,
+            IntermediateType::Int { size } => match size {
+                ByteSized::B8 => SINT,
+                ByteSized::B16 => INT,
+                ByteSized::B32 => DINT,
+                ByteSized::B64 => LINT,
+            },
+            IntermediateType::UInt { size } => match size {
+                ByteSized::B8 => USINT,
+                ByteSized::B16 => UINT,
+                ByteSized::B32 => UDINT,
+                ByteSized::B64 => ULINT,
+            },
+            IntermediateType::Real { size } => match size {
+                ByteSized::B64 => LREAL,
+                _ => REAL,
+            },
+            IntermediateType::Bytes { size } => match size {
+                ByteSized::B8 => BYTE,
+                ByteSized::B16 => WORD,
+                ByteSized::B32 => DWORD,
+                ByteSized::B64 => LWORD,
+            },
+            IntermediateType::Subrange { base_type, .. } => {
+                return ExprType::from_representation(None, base_type)
+            }
+            IntermediateType::Enumeration { .. } => {
+                return Some(ExprType::Enumeration(name.cloned()))
+            }
+            IntermediateType::Structure { .. } | IntermediateType::Array { .. } => {
+                return name.map(|name| ExprType::Derived(name.clone(), representation.clone()))
+            }
+            // The representation of times, dates and strings is shared by
+            // more than one type.
+            IntermediateType::Time
+            | IntermediateType::Date
+            | IntermediateType::String { .. }
+            | IntermediateType::Reference { .. }
+            | IntermediateType::FunctionBlock { .. }
+            | IntermediateType::Function { .. } => return None,
+        };
+        Some(ExprType::Elementary(elementary))
+    }
+
+    /// Returns the elementary type with the name or `None` if the name is
+    /// not the name of an elementary type.
+    pub fn from_elementary_name(name: &TypeName) -> Option<ExprType> {
+        use ElementaryTypeName::*;
+        let elementary = match name.name.lower_case().as_str() {
+            "bool" => BOOL,
+            "sint" => SINT,
+            "int" => INT,
+            "dint" => DINT,
+            "lint" => LINT,
+            "usint" => USINT,
+            "uint" => UINT,
+            "udint" => UDINT,
+            "ulint" => ULINT,
+            "real" => REAL,
+            "lreal" => LREAL,
+            "time" => TIME,
+            "date" => DATE,
+            "time_of_day" | "tod" => TimeOfDay,
+            "date_and_time" | "dt" => DateAndTime,
+            "string" => STRING,
+            "wstring" => WSTRING,
+            "byte" => BYTE,
+            "word" => WORD,
+            "dword" => DWORD,
+            "lword" => LWORD,
+            _ => return None,
+        };
+        Some(ExprType::Elementary(elementary))
+    }
+
+    /// Returns the name of the type when the type is a single declared type.
+    pub fn type_name(&self) -> Option<TypeName> {
+        match self {
+            ExprType::Elementary(elementary) => Some(elementary.clone().into()),
+            ExprType::Enumeration(name) => name.clone(),
+            ExprType::Derived(name, _) => Some(name.clone()),
+            ExprType::AnyInt | ExprType::AnyReal | ExprType::AnyString => None,
+        }
+    }
+
+    pub fn is_bool(&self) -> bool {
+        matches!(self, ExprType::Elementary(ElementaryTypeName::BOOL))
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        match self {
+            ExprType::Elementary(elementary) => is_int(elementary) || is_real(elementary),
+            ExprType::AnyInt | ExprType::AnyReal => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        match self {
+            ExprType::Elementary(elementary) => is_int(elementary),
+            ExprType::AnyInt => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_bit_string(&self) -> bool {
+        match self {
+            ExprType::Elementary(elementary) => is_bit(elementary),
+            ExprType::AnyInt => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_real(&self) -> bool {
+        match self {
+            ExprType::Elementary(elementary) => is_real(elementary),
+            // Integer literals convert to real types
+            ExprType::AnyInt | ExprType::AnyReal => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        match self {
+            ExprType::Elementary(elementary) => is_string(elementary),
+            ExprType::AnyString => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_elementary(&self) -> bool {
+        !matches!(self, ExprType::Enumeration(_) | ExprType::Derived(_, _))
+    }
+
+    /// Returns the type that values of both types convert to or `None` if
+    /// there is no such type.
+    pub fn unify(&self, other: &ExprType) -> Option<ExprType> {
+        if other.is_assignable_to(self) {
+            return Some(self.clone());
+        }
+        if self.is_assignable_to(other) {
+            return Some(other.clone());
+        }
+        common_numeric(self, other)
+    }
+
+    /// Returns true if a value of this type can be assigned to a variable
+    /// of the target type (possibly by an implicit conversion).
+    pub fn is_assignable_to(&self, target: &ExprType) -> bool {
+        match (self, target) {
+            (ExprType::Elementary(from), ExprType::Elementary(to)) => widens_to(from, to),
+            (ExprType::AnyInt, ExprType::Elementary(to)) => {
+                is_int(to) || is_real(to) || (is_bit(to) && *to != ElementaryTypeName::BOOL)
+            }
+            (ExprType::AnyReal, ExprType::Elementary(to)) => is_real(to),
+            (ExprType::AnyString, ExprType::Elementary(to)) => is_string(to),
+            (ExprType::AnyInt, ExprType::AnyInt)
+            | (ExprType::AnyInt | ExprType::AnyReal, ExprType::AnyReal)
+            | (ExprType::AnyString, ExprType::AnyString) => true,
+            // Enumerations may be aliases of each other and aliases do not
+            // retain the name of the aliased type.
+            (ExprType::Enumeration(_), ExprType::Enumeration(_)) => true,
+            (ExprType::Derived(_, from), ExprType::Derived(_, to)) => from == to,
+            _ => false,
+        }
+    }
+
+    /// Returns the type of the result of an arithmetic operator (`+`, `-`,
+    /// `*`, `/`, `MOD`, `**`) or `None` if the operator is not defined for
+    /// the operand types.
+    pub fn arithmetic(
+        op: &ironplc_dsl::textual::Operator,
+        left: &ExprType,
+        right: &ExprType,
+    ) -> Option<ExprType> {
+        use ironplc_dsl::textual::Operator;
+        use ElementaryTypeName::*;
+
+        // Arithmetic on times and dates
+        if let (ExprType::Elementary(l), r) = (left, right) {
+            match (op, l, r) {
+                (Operator::Add | Operator::Sub, TIME, ExprType::Elementary(TIME)) => {
+                    return Some(ExprType::Elementary(TIME))
+                }
+                (Operator::Mul | Operator::Div, TIME, r) if r.is_numeric() => {
+                    return Some(ExprType::Elementary(TIME))
+                }
+                (Operator::Add | Operator::Sub, TimeOfDay, ExprType::Elementary(TIME)) => {
+                    return Some(ExprType::Elementary(TimeOfDay))
+                }
+                (Operator::Add | Operator::Sub, DateAndTime, ExprType::Elementary(TIME)) => {
+                    return Some(ExprType::Elementary(DateAndTime))
+                }
+                (Operator::Sub, DATE, ExprType::Elementary(DATE))
+                | (Operator::Sub, TimeOfDay, ExprType::Elementary(TimeOfDay))
+                | (Operator::Sub, DateAndTime, ExprType::Elementary(DateAndTime)) => {
+                    return Some(ExprType::Elementary(TIME))
+                }
+                _ => {}
+            }
+        }
+
+        match op {
+            Operator::Mod if !left.is_integer() || !right.is_integer() => None,
+            // The exponent is any number and the result has the type of the
+            // base.
+            Operator::Pow if left.is_numeric() && right.is_numeric() => match left {
+                ExprType::AnyInt => Some(ExprType::AnyReal),
+                _ => Some(left.clone()),
+            },
+            _ => common_numeric(left, right),
+        }
+    }
+
+    /// Returns the type of the result of a logical operator (`AND`, `OR`,
+    /// `XOR`) or `None` if the operator is not defined for the operand types.
+    pub fn logical(left: &ExprType, right: &ExprType) -> Option<ExprType> {
+        if !left.is_bit_string() || !right.is_bit_string() {
+            return None;
+        }
+        match (left, right) {
+            (ExprType::AnyInt, other) | (other, ExprType::AnyInt) => {
+                // A literal is not a BOOL
+                if other.is_bool() {
+                    return None;
+                }
+                Some(other.clone())
+            }
+            (ExprType::Elementary(l), ExprType::Elementary(r)) => common_elementary(l, r),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the comparison operators (`=`, `<>`, `<`, `>`, `<=`,
+    /// `>=`) are defined for the operand types.
+    pub fn is_comparable(left: &ExprType, right: &ExprType) -> bool {
+        if left.is_numeric() && right.is_numeric() {
+            return common_numeric(left, right).is_some();
+        }
+        match (left, right) {
+            (ExprType::Elementary(l), ExprType::Elementary(r)) => common_elementary(l, r).is_some(),
+            (ExprType::AnyInt, ExprType::Elementary(other))
+            | (ExprType::Elementary(other), ExprType::AnyInt) => {
+                is_bit(other) && *other != ElementaryTypeName::BOOL
+            }
+            (ExprType::AnyString, ExprType::Elementary(other))
+            | (ExprType::Elementary(other), ExprType::AnyString) => is_string(other),
+            (ExprType::AnyString, ExprType::AnyString) => true,
+            (ExprType::Enumeration(_), ExprType::Enumeration(_)) => true,
+            (ExprType::Derived(_, l), ExprType::Derived(_, r)) => l == r,
+            _ => false,
+        }
+    }
+
+    /// Returns the type of the result of negation or `None` if negation
+    /// is not defined for the operand type.
+    pub fn negate(term: &ExprType) -> Option<ExprType> {
+        match term {
+            ExprType::Elementary(ElementaryTypeName::TIME) => Some(term.clone()),
+            term if term.is_numeric() => Some(term.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of the result of the complement (`NOT`) or `None`
+    /// if the complement is not defined for the operand type.
+    pub fn complement(term: &ExprType) -> Option<ExprType> {
+        match term.is_bit_string() {
+            true => Some(term.clone()),
+            false => None,
+        }
+    }
+}
+
+impl fmt::Display for ExprType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprType::Elementary(elementary) => write!(f, "{}", elementary.as_id()),
+            ExprType::AnyInt => write!(f, "ANY_INT"),
+            ExprType::AnyReal => write!(f, "ANY_REAL"),
+            ExprType::AnyString => write!(f, "ANY_STRING"),
+            ExprType::Enumeration(Some(name)) => write!(f, "{name}"),
+            ExprType::Enumeration(None) => write!(f, "enumeration"),
+            ExprType::Derived(name, _) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// The resolved types of expressions, keyed by the location of the
+/// expression in the source.
+#[derive(Debug, Default)]
+pub struct ExpressionTypes {
+    types: HashMap<(FileId, usize, usize), TypeName>,
+}
+
+impl ExpressionTypes {
+    pub fn insert(&mut self, span: &SourceSpan, type_name: TypeName) {
+        self.types
+            .insert((span.file_id.clone(), span.start, span.end), type_name);
+    }
+
+    /// Gets the type of the expression at the location.
+    #[allow(dead_code)]
+    pub fn get(&self, span: &SourceSpan) -> Option<&TypeName> {
+        self.types
+            .get(&(span.file_id.clone(), span.start, span.end))
+    }
+}
+
+fn is_int(elementary: &ElementaryTypeName) -> bool {
+    use ElementaryTypeName::*;
+    matches!(
+        elementary,
+        SINT | INT | DINT | LINT | USINT | UINT | UDINT | ULINT
+    )
+}
+
+fn is_real(elementary: &ElementaryTypeName) -> bool {
+    matches!(
+        elementary,
+        ElementaryTypeName::REAL | ElementaryTypeName::LREAL
+    )
+}
+
+fn is_bit(elementary: &ElementaryTypeName) -> bool {
+    use ElementaryTypeName::*;
+    matches!(elementary, BOOL | BYTE | WORD | DWORD | LWORD)
+}
+
+fn is_string(elementary: &ElementaryTypeName) -> bool {
+    matches!(
+        elementary,
+        ElementaryTypeName::STRING | ElementaryTypeName::WSTRING
+    )
+}
+
+/// Returns true if the type is the same as or can be implicitly converted
+/// to the target type without loss.
+///
+/// See IEC 61131-3 table 11 (implicit conversions).
+pub fn widens_to(from: &ElementaryTypeName, to: &ElementaryTypeName) -> bool {
+    use ElementaryTypeName::*;
+    if from == to {
+        return true;
+    }
+    let targets: &[ElementaryTypeName] = match from {
+        SINT => &[INT, DINT, LINT, REAL, LREAL],
+        INT => &[DINT, LINT, REAL, LREAL],
+        DINT => &[LINT, LREAL],
+        USINT => &[UINT, UDINT, ULINT, INT, DINT, LINT, REAL, LREAL],
+        UINT => &[UDINT, ULINT, DINT, LINT, REAL, LREAL],
+        UDINT => &[ULINT, LINT, LREAL],
+        REAL => &[LREAL],
+        BOOL => &[BYTE, WORD, DWORD, LWORD],
+        BYTE => &[WORD, DWORD, LWORD],
+        WORD => &[DWORD, LWORD],
+        DWORD => &[LWORD],
+        _ => &[],
+    };
+    targets.contains(to)
+}
+
+/// Returns the smallest type that both types widen to.
+fn common_elementary(left: &ElementaryTypeName, right: &ElementaryTypeName) -> Option<ExprType> {
+    use ElementaryTypeName::*;
+    if widens_to(left, right) {
+        return Some(ExprType::Elementary(right.clone()));
+    }
+    if widens_to(right, left) {
+        return Some(ExprType::Elementary(left.clone()));
+    }
+    [
+        SINT, USINT, INT, UINT, DINT, UDINT, LINT, ULINT, REAL, LREAL,
+    ]
+    .into_iter()
+    .find(|candidate| widens_to(left, candidate) && widens_to(right, candidate))
+    .map(ExprType::Elementary)
+}
+
+/// Returns the type that is the result of combining two numeric types.
+fn common_numeric(left: &ExprType, right: &ExprType) -> Option<ExprType> {
+    if !left.is_numeric() || !right.is_numeric() {
+        return None;
+    }
+    match (left, right) {
+        (ExprType::AnyInt, ExprType::AnyInt) => Some(ExprType::AnyInt),
+        (ExprType::AnyInt | ExprType::AnyReal, ExprType::AnyInt | ExprType::AnyReal) => {
+            Some(ExprType::AnyReal)
+        }
+        (ExprType::AnyInt, other) | (other, ExprType::AnyInt) => Some(other.clone()),
+        (ExprType::AnyReal, ExprType::Elementary(other))
+        | (ExprType::Elementary(other), ExprType::AnyReal) => {
+            common_elementary(other, &ElementaryTypeName::REAL)
+        }
+        (ExprType::Elementary(l), ExprType::Elementary(r)) => common_elementary(l, r),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ironplc_dsl::textual::Operator;
+    use ElementaryTypeName::*;
+
+    fn elementary(name: ElementaryTypeName) -> ExprType {
+        ExprType::Elementary(name)
+    }
+
+    #[test]
+    fn widens_to_when_smaller_signed_integer_then_true() {
+        assert!(widens_to(&SINT, &INT));
+        assert!(widens_to(&INT, &LREAL));
+        assert!(widens_to(&USINT, &INT));
+    }
+
+    #[test]
+    fn widens_to_when_narrowing_or_unrelated_then_false() {
+        assert!(!widens_to(&DINT, &INT));
+        assert!(!widens_to(&INT, &UINT));
+        assert!(!widens_to(&DINT, &REAL));
+        assert!(!widens_to(&INT, &WORD));
+        assert!(!widens_to(&STRING, &WSTRING));
+    }
+
+    #[test]
+    fn arithmetic_when_signed_and_unsigned_then_common_type() {
+        let result = ExprType::arithmetic(&Operator::Add, &elementary(INT), &elementary(UINT));
+        assert_eq!(Some(elementary(DINT)), result);
+    }
+
+    #[test]
+    fn arithmetic_when_literal_then_type_of_other_operand() {
+        let result = ExprType::arithmetic(&Operator::Mul, &ExprType::AnyInt, &elementary(SINT));
+        assert_eq!(Some(elementary(SINT)), result);
+
+        let result = ExprType::arithmetic(&Operator::Mul, &ExprType::AnyReal, &elementary(INT));
+        assert_eq!(Some(elementary(REAL)), result);
+    }
+
+    #[test]
+    fn arithmetic_when_real_and_string_then_none() {
+        let result = ExprType::arithmetic(&Operator::Add, &elementary(REAL), &ExprType::AnyString);
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn arithmetic_when_mod_real_then_none() {
+        let result = ExprType::arithmetic(&Operator::Mod, &elementary(REAL), &ExprType::AnyInt);
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn arithmetic_when_time_of_day_minus_time_of_day_then_time() {
+        let result = ExprType::arithmetic(
+            &Operator::Sub,
+            &elementary(TimeOfDay),
+            &elementary(TimeOfDay),
+        );
+        assert_eq!(Some(elementary(TIME)), result);
+    }
+
+    #[test]
+    fn logical_when_bool_and_literal_then_none() {
+        assert_eq!(
+            None,
+            ExprType::logical(&elementary(BOOL), &ExprType::AnyInt)
+        );
+        assert_eq!(
+            Some(elementary(WORD)),
+            ExprType::logical(&elementary(BYTE), &elementary(WORD))
+        );
+    }
+
+    #[test]
+    fn is_assignable_to_when_literal_then_depends_on_target() {
+        assert!(ExprType::AnyInt.is_assignable_to(&elementary(REAL)));
+        assert!(!ExprType::AnyReal.is_assignable_to(&elementary(INT)));
+        assert!(!ExprType::AnyInt.is_assignable_to(&elementary(BOOL)));
+        assert!(ExprType::AnyString.is_assignable_to(&elementary(WSTRING)));
+    }
+
+    #[test]
+    fn from_representation_when_subrange_then_base_type() {
+        let subrange = IntermediateType::Subrange {
+            base_type: Box::new(IntermediateType::Int {
+                size: ByteSized::B16,
+            }),
+            min_value: 1,
+            max_value: 10,
+        };
+        assert_eq!(
+            Some(elementary(INT)),
+            ExprType::from_representation(None, &subrange)
+        );
+    }
+}
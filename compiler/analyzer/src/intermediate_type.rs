@@ -8,7 +8,7 @@
 //! booleans) and complex types (like structures, arrays, and function blocks) found in
 //! IEC 61131-3 standard and similar PLC programming languages.
 
-use ironplc_dsl::core::Id;
+use ironplc_dsl::{common::TypeName, core::Id};
 
 /// Represents the size of a data type in bits, aligned to common byte boundaries.
 /// Used for memory layout and type checking of numeric and bit-based types.
@@ -106,6 +106,13 @@ pub enum IntermediateType {
         /// Ordered list of fields (variables) in the function block instance
         fields: Vec<IntermediateStructField>,
     },
+    /// Reference to an instance of another type (REF_TO)
+    Reference {
+        /// Name of the type that the reference refers to. The reference
+        /// holds the name rather than the type so that types may refer
+        /// to themselves.
+        referenced_type_name: TypeName,
+    },
     /// Function type with return type and parameters
     Function {
         /// Return type of the function, None for procedures
@@ -161,6 +168,11 @@ impl IntermediateType {
         matches!(self, IntermediateType::Function { .. })
     }
 
+    /// Returns if the type is a reference.
+    pub fn is_reference(&self) -> bool {
+        matches!(self, IntermediateType::Reference { .. })
+    }
+
     /// Returns if the type is numeric (integer, unsigned integer, or real).
     pub fn is_numeric(&self) -> bool {
         matches!(
@@ -255,6 +267,7 @@ impl IntermediateType {
 
                 Some(total_size as u8)
             }
+            IntermediateType::Reference { .. } => Some(8), // 64-bit address
             IntermediateType::Function { .. } => {
                 // Functions don't have memory layout in the traditional sense
                 None
@@ -302,6 +315,7 @@ impl IntermediateType {
                     .max()
                     .unwrap_or(1)
             }
+            IntermediateType::Reference { .. } => 8, // 64-bit alignment
             IntermediateType::Function { .. } => 1, // Default alignment (functions don't have memory layout)
         }
     }
@@ -336,6 +350,7 @@ impl IntermediateType {
                 size.is_some() && element_type.has_explicit_size()
             }
             IntermediateType::FunctionBlock { .. } => true, // Function block instances have explicit size
+            IntermediateType::Reference { .. } => true,     // References are always an address
             IntermediateType::Function { .. } => true, // Functions have explicit size (no variable size)
         }
     }
@@ -377,13 +392,48 @@ impl IntermediateType {
     /// alignment and padding rules.
     #[allow(dead_code)]
     pub fn get_field_offset(&self, field_name: &ironplc_dsl::core::Id) -> Option<u32> {
+        self.get_field(field_name).map(|field| field.offset)
+    }
+
+    /// Gets the memory offset of a field within nested structure types.
+    ///
+    /// Each item in the path is the name of a field of the structure that
+    /// contains the field, so that the path `["b", "c"]` is the field `c`
+    /// of the structure that is the field `b` of this structure.
+    ///
+    /// # Returns
+    /// - `Some(offset)`: The byte offset of the innermost field from the start
+    ///   of this structure
+    /// - `None`: If the path is empty, any field is not found or any item
+    ///   before the last is not a structure type
+    #[allow(dead_code)]
+    pub fn get_field_path_offset(&self, path: &[ironplc_dsl::core::Id]) -> Option<u32> {
+        let (first, rest) = path.split_first()?;
+        let field = self.get_field(first)?;
+        if rest.is_empty() {
+            return Some(field.offset);
+        }
+        field
+            .field_type
+            .get_field_path_offset(rest)
+            .map(|offset| field.offset + offset)
+    }
+
+    /// Gets a field within a structure type.
+    ///
+    /// Uses case-insensitive comparison following IEC 61131-3 identifier rules.
+    ///
+    /// # Returns
+    /// - `Some(field)`: The field with the name
+    /// - `None`: If the field is not found or this is not a structure type
+    pub fn get_field(
+        &self,
+        field_name: &ironplc_dsl::core::Id,
+    ) -> Option<&IntermediateStructField> {
         match self {
             IntermediateType::Structure { fields } => {
                 // Find the field by name using case-insensitive Id comparison
-                fields
-                    .iter()
-                    .find(|field| field.name == *field_name)
-                    .map(|field| field.offset)
+                fields.iter().find(|field| field.name == *field_name)
             }
             _ => None, // Not a structure type
         }
@@ -755,6 +805,52 @@ mod tests {
         assert_eq!(array_type.get_field_offset(&field_id), None);
     }
 
+    #[test]
+    fn get_field_path_offset_with_nested_structure_then_returns_sum_of_offsets() {
+        use super::IntermediateStructField;
+        use ironplc_dsl::core::Id;
+
+        let inner = IntermediateType::Structure {
+            fields: vec![
+                IntermediateStructField {
+                    name: Id::from("a"),
+                    field_type: IntermediateType::Bool,
+                    offset: 0,
+                },
+                IntermediateStructField {
+                    name: Id::from("b"),
+                    field_type: IntermediateType::Int {
+                        size: ByteSized::B32,
+                    },
+                    offset: 4,
+                },
+            ],
+        };
+        let outer = IntermediateType::Structure {
+            fields: vec![
+                IntermediateStructField {
+                    name: Id::from("first"),
+                    field_type: IntermediateType::Bool,
+                    offset: 0,
+                },
+                IntermediateStructField {
+                    name: Id::from("inner"),
+                    field_type: inner,
+                    offset: 4,
+                },
+            ],
+        };
+
+        let path = [Id::from("inner"), Id::from("b")];
+        assert_eq!(outer.get_field_path_offset(&path), Some(8));
+        assert_eq!(outer.get_field_path_offset(&[Id::from("inner")]), Some(4));
+        assert_eq!(
+            outer.get_field_path_offset(&[Id::from("first"), Id::from("a")]),
+            None
+        );
+        assert_eq!(outer.get_field_path_offset(&[]), None);
+    }
+
     #[test]
     fn validate_bounds_with_all_integer_types_then_validates_correctly() {
         use ironplc_dsl::common::TypeName;
@@ -4,6 +4,7 @@
 //! including validation of array bounds and element types.
 
 use crate::intermediate_type::IntermediateType;
+use crate::intermediates::bound_value;
 use crate::type_environment::{TypeAttributes, TypeEnvironment};
 use ironplc_dsl::common::*;
 use ironplc_dsl::core::Located;
@@ -76,15 +77,17 @@ pub fn validate_array_bounds(ranges: &[Subrange], type_name: &TypeName) -> Resul
     }
 
     for range in ranges.iter() {
-        let min_value = if range.start.is_neg {
-            -(range.start.value.value as i128)
+        let start = bound_value(&range.start)?;
+        let end = bound_value(&range.end)?;
+        let min_value = if start.is_neg {
+            -(start.value.value as i128)
         } else {
-            range.start.value.value as i128
+            start.value.value as i128
         };
-        let max_value = if range.end.is_neg {
-            -(range.end.value.value as i128)
+        let max_value = if end.is_neg {
+            -(end.value.value as i128)
         } else {
-            range.end.value.value as i128
+            end.value.value as i128
         };
 
         if min_value > max_value {
@@ -93,11 +96,11 @@ pub fn validate_array_bounds(ranges: &[Subrange], type_name: &TypeName) -> Resul
                 Label::span(type_name.span(), "Array declaration"),
             )
             .with_secondary(Label::span(
-                range.start.value.span(),
+                start.value.span(),
                 format!("Minimum value: {}", min_value),
             ))
             .with_secondary(Label::span(
-                range.end.value.span(),
+                end.value.span(),
                 format!("Maximum value: {}", max_value),
             )));
         }
@@ -109,8 +112,8 @@ pub fn validate_array_bounds(ranges: &[Subrange], type_name: &TypeName) -> Resul
                 Problem::ArraySizeOverflow,
                 Label::span(type_name.span(), "Array declaration"),
             )
-            .with_secondary(Label::span(range.start.value.span(), "Dimension start"))
-            .with_secondary(Label::span(range.end.value.span(), "Dimension end")));
+            .with_secondary(Label::span(start.value.span(), "Dimension start"))
+            .with_secondary(Label::span(end.value.span(), "Dimension end")));
         }
     }
 
@@ -122,22 +125,24 @@ fn calculate_array_size(ranges: &[Subrange]) -> Result<u32, Diagnostic> {
     let mut total_size: u64 = 1;
 
     for range in ranges {
-        let min_value = if range.start.is_neg {
-            -(range.start.value.value as i128)
+        let start = bound_value(&range.start)?;
+        let end = bound_value(&range.end)?;
+        let min_value = if start.is_neg {
+            -(start.value.value as i128)
         } else {
-            range.start.value.value as i128
+            start.value.value as i128
         };
-        let max_value = if range.end.is_neg {
-            -(range.end.value.value as i128)
+        let max_value = if end.is_neg {
+            -(end.value.value as i128)
         } else {
-            range.end.value.value as i128
+            end.value.value as i128
         };
 
         let dimension_size = (max_value - min_value + 1) as u64;
         total_size = total_size.checked_mul(dimension_size).ok_or_else(|| {
             Diagnostic::problem(
                 Problem::ArraySizeOverflow,
-                Label::span(range.start.value.span(), "Array dimension"),
+                Label::span(start.value.span(), "Array dimension"),
             )
         })?;
 
@@ -145,7 +150,7 @@ fn calculate_array_size(ranges: &[Subrange]) -> Result<u32, Diagnostic> {
         if total_size > u32::MAX as u64 {
             return Err(Diagnostic::problem(
                 Problem::ArraySizeOverflow,
-                Label::span(range.start.value.span(), "Array dimension"),
+                Label::span(start.value.span(), "Array dimension"),
             ));
         }
     }
@@ -171,14 +176,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 10,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
             Subrange {
                 start: SignedInteger {
@@ -187,14 +194,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 5,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
         ];
 
@@ -218,14 +227,16 @@ mod tests {
                     value: 10,
                 },
                 is_neg: false,
-            },
+            }
+            .into(),
             end: SignedInteger {
                 value: Integer {
                     span: SourceSpan::default(),
                     value: 1,
                 },
                 is_neg: false,
-            },
+            }
+            .into(),
         }];
 
         let result = validate_array_bounds(&ranges, &TypeName::from("TEST_ARRAY"));
@@ -241,14 +252,16 @@ mod tests {
                     value: 5,
                 },
                 is_neg: true, // -5
-            },
+            }
+            .into(),
             end: SignedInteger {
                 value: Integer {
                     span: SourceSpan::default(),
                     value: 5,
                 },
                 is_neg: false, // 5
-            },
+            }
+            .into(),
         }];
 
         let result = validate_array_bounds(&ranges, &TypeName::from("TEST_ARRAY"));
@@ -264,14 +277,16 @@ mod tests {
                     value: 1,
                 },
                 is_neg: false,
-            },
+            }
+            .into(),
             end: SignedInteger {
                 value: Integer {
                     span: SourceSpan::default(),
                     value: 10,
                 },
                 is_neg: false,
-            },
+            }
+            .into(),
         }];
 
         let result = calculate_array_size(&ranges);
@@ -289,14 +304,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 10,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
             Subrange {
                 start: SignedInteger {
@@ -305,14 +322,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 5,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
         ];
 
@@ -330,14 +349,16 @@ mod tests {
                     value: 5,
                 },
                 is_neg: true, // -5
-            },
+            }
+            .into(),
             end: SignedInteger {
                 value: Integer {
                     span: SourceSpan::default(),
                     value: 5,
                 },
                 is_neg: false, // 5
-            },
+            }
+            .into(),
         }];
 
         let result = calculate_array_size(&ranges);
@@ -360,14 +381,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 10,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             }],
             type_name: TypeName::from("int"),
         };
@@ -436,14 +459,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 10,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             }],
             type_name: TypeName::from("MISSING_TYPE"),
         };
@@ -482,14 +507,16 @@ mod tests {
                             value: 1,
                         },
                         is_neg: false,
-                    },
+                    }
+                    .into(),
                     end: SignedInteger {
                         value: Integer {
                             span: SourceSpan::default(),
                             value: 3,
                         },
                         is_neg: false,
-                    },
+                    }
+                    .into(),
                 },
                 Subrange {
                     start: SignedInteger {
@@ -498,14 +525,16 @@ mod tests {
                             value: 1,
                         },
                         is_neg: false,
-                    },
+                    }
+                    .into(),
                     end: SignedInteger {
                         value: Integer {
                             span: SourceSpan::default(),
                             value: 4,
                         },
                         is_neg: false,
-                    },
+                    }
+                    .into(),
                 },
             ],
             type_name: TypeName::from("bool"),
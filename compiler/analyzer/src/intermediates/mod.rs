@@ -1,5 +1,21 @@
 pub mod array;
 pub mod enumeration;
+pub mod reference;
 pub mod string;
 pub mod structure;
 pub mod subrange;
+
+use ironplc_dsl::{
+    common::{SignedInteger, SubrangeBoundKind},
+    diagnostic::Diagnostic,
+};
+
+/// Returns the value of a bound of a subrange.
+///
+/// Constant expressions are evaluated before types are resolved, so a bound
+/// that is not an integer literal indicates a bug in the compiler.
+fn bound_value(bound: &SubrangeBoundKind) -> Result<&SignedInteger, Diagnostic> {
+    bound
+        .as_signed_integer()
+        .ok_or_else(|| Diagnostic::internal_error(file!(), line!()))
+}
@@ -0,0 +1,86 @@
+//! Reference type processing
+//!
+//! This module handles creating reference (REF_TO) types. A reference has
+//! the same layout regardless of the referenced type.
+use crate::{intermediate_type::IntermediateType, type_environment::TypeAttributes};
+
+use ironplc_dsl::common::{ReferenceDeclaration, ReferenceInitialValueAssignment};
+use ironplc_dsl::core::Located;
+
+pub fn from(spec: &ReferenceInitialValueAssignment) -> TypeAttributes {
+    // Reference type in a variable declaration: MY_REF : REF_TO INT;
+    TypeAttributes::new(
+        spec.referenced_type_name.span(),
+        IntermediateType::Reference {
+            referenced_type_name: spec.referenced_type_name.clone(),
+        },
+    )
+}
+
+pub fn from_decl(decl: &ReferenceDeclaration) -> TypeAttributes {
+    TypeAttributes::new(
+        decl.type_name.span(),
+        IntermediateType::Reference {
+            referenced_type_name: decl.spec_init.referenced_type_name.clone(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ironplc_dsl::{common::TypeName, core::FileId};
+    use ironplc_parser::options::ParseOptions;
+
+    use crate::{
+        intermediate_type::IntermediateType, type_environment::TypeEnvironmentBuilder,
+        xform_resolve_type_decl_environment::apply,
+    };
+
+    #[test]
+    fn apply_when_reference_type_declaration_then_creates_reference_type() {
+        let program = "
+TYPE
+INT_REF : REF_TO INT;
+END_TYPE
+        ";
+        let input =
+            ironplc_parser::parse_program(program, &FileId::default(), &ParseOptions::default())
+                .unwrap();
+        let mut env = TypeEnvironmentBuilder::new()
+            .with_elementary_types()
+            .build()
+            .unwrap();
+        let _library = apply(input, &mut env).unwrap();
+
+        let int_ref_type = env.get(&TypeName::from("INT_REF")).unwrap();
+        assert_eq!(
+            int_ref_type.representation,
+            IntermediateType::Reference {
+                referenced_type_name: TypeName::from("INT")
+            }
+        );
+        assert_eq!(int_ref_type.size_bytes(), Some(8));
+        assert_eq!(int_ref_type.alignment_bytes(), 8);
+    }
+
+    #[test]
+    fn apply_when_reference_to_reference_alias_then_creates_reference_type() {
+        let program = "
+TYPE
+INT_REF : REF_TO INT;
+INT_REF_ALIAS : INT_REF;
+END_TYPE
+        ";
+        let input =
+            ironplc_parser::parse_program(program, &FileId::default(), &ParseOptions::default())
+                .unwrap();
+        let mut env = TypeEnvironmentBuilder::new()
+            .with_elementary_types()
+            .build()
+            .unwrap();
+        let _library = apply(input, &mut env).unwrap();
+
+        let alias_type = env.get(&TypeName::from("INT_REF_ALIAS")).unwrap();
+        assert!(alias_type.representation.is_reference());
+    }
+}
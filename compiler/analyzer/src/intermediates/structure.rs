@@ -79,10 +79,17 @@ fn resolve_field_type(
                 })?;
             Ok(type_attrs.representation.clone())
         }
-        InitialValueAssignmentKind::LateResolvedType(_type_name) => {
-            // LateResolvedType should have been resolved by xform_resolve_late_bound_type_initializer
-            // before structure processing runs. If we see this, it's a compiler bug.
-            Err(Diagnostic::internal_error(file!(), line!()))
+        InitialValueAssignmentKind::LateResolvedType(type_name) => {
+            // Field types that are ambiguous when parsing (such as other
+            // structures) are resolved after the type environment is built,
+            // but the declarations are sorted so that the type is known.
+            let type_attrs = type_environment.get(type_name).ok_or_else(|| {
+                Diagnostic::problem(
+                    Problem::StructFieldTypeNotDeclared,
+                    Label::span(type_name.span(), "Field type"),
+                )
+            })?;
+            Ok(type_attrs.representation.clone())
         }
         InitialValueAssignmentKind::Subrange(subrange_spec) => {
             // Handle subrange field types
@@ -125,17 +132,27 @@ fn resolve_field_type(
                 })?;
             Ok(type_attrs.representation.clone())
         }
-        InitialValueAssignmentKind::Structure(_structure_init) => {
-            // Nested structures are not yet supported
-            Err(Diagnostic::problem(
-                Problem::NestedStructuresNotSupported,
-                Label::span(ironplc_dsl::core::SourceSpan::default(), "Nested structure"),
-            ))
+        InitialValueAssignmentKind::Structure(structure_init) => {
+            // Handle nested structure field types
+            let type_attrs = type_environment
+                .get(&structure_init.type_name)
+                .ok_or_else(|| {
+                    Diagnostic::problem(
+                        Problem::StructFieldTypeNotDeclared,
+                        Label::span(structure_init.type_name.span(), "Structure type"),
+                    )
+                })?;
+            Ok(type_attrs.representation.clone())
         }
         InitialValueAssignmentKind::Array(_array_init) => {
             // Array fields are not yet supported
             Err(Diagnostic::todo(file!(), line!()))
         }
+        InitialValueAssignmentKind::Reference(reference_init) => {
+            // References have the same layout for any referenced type, so the
+            // referenced type need not be resolved (it may be this structure).
+            Ok(crate::intermediates::reference::from(reference_init).representation)
+        }
         _other => {
             // Other types are not yet supported
             Err(Diagnostic::todo(file!(), line!()))
@@ -374,6 +391,92 @@ END_TYPE
         assert!(matches!(fields[0].field_type, IntermediateType::Int { .. }));
     }
 
+    #[test]
+    fn parse_structure_with_self_reference_field_then_creates_structure_with_reference() {
+        let program = "
+TYPE
+    Node : STRUCT
+        value : INT;
+        next : REF_TO Node;
+    END_STRUCT;
+END_TYPE
+        ";
+        let env = parse_and_apply(program);
+
+        let struct_type = env.get(&TypeName::from("Node")).unwrap();
+        let fields = match &struct_type.representation {
+            IntermediateType::Structure { fields } => fields,
+            _ => panic!(
+                "Expected Structure type, got {:?}",
+                struct_type.representation
+            ),
+        };
+
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[1].name, Id::from("next"));
+        assert_eq!(
+            fields[1].field_type,
+            IntermediateType::Reference {
+                referenced_type_name: TypeName::from("Node")
+            }
+        );
+        assert_eq!(fields[1].offset, 8);
+    }
+
+    #[test]
+    fn parse_structure_with_nested_structure_field_then_creates_nested_structure() {
+        let program = "
+TYPE
+    Inner : STRUCT
+        flag : BOOL;
+        value : DINT;
+    END_STRUCT;
+    Outer : STRUCT
+        first : BOOL;
+        plain : Inner;
+        initialized : Inner := (value := 1);
+    END_STRUCT;
+END_TYPE
+        ";
+        let env = parse_and_apply(program);
+
+        let struct_type = env.get(&TypeName::from("Outer")).unwrap();
+        let fields = match &struct_type.representation {
+            IntermediateType::Structure { fields } => fields,
+            _ => panic!(
+                "Expected Structure type, got {:?}",
+                struct_type.representation
+            ),
+        };
+
+        assert_eq!(fields.len(), 3);
+        assert!(fields[1].field_type.is_structure());
+        assert!(fields[2].field_type.is_structure());
+        // Inner is aligned to DINT and has size 8
+        assert_eq!(fields[1].offset, 4);
+        assert_eq!(fields[2].offset, 12);
+        assert_eq!(
+            struct_type
+                .representation
+                .get_field_path_offset(&[Id::from("initialized"), Id::from("value")]),
+            Some(16)
+        );
+    }
+
+    #[test]
+    fn parse_structure_with_nested_structure_field_not_declared_then_error() {
+        let program = "
+TYPE
+    Outer : STRUCT
+        initialized : MISSING_STRUCT := (value := 1);
+    END_STRUCT;
+END_TYPE
+        ";
+        let errors = parse_and_expect_error(program);
+
+        assert_eq!(Problem::StructFieldTypeNotDeclared.code(), errors[0].code);
+    }
+
     #[test]
     fn parse_structure_with_missing_field_type_then_error() {
         let program = "
@@ -4,6 +4,7 @@
 //! of the base type.
 
 use crate::intermediate_type::IntermediateType;
+use crate::intermediates::bound_value;
 use crate::type_environment::{TypeAttributes, TypeEnvironment};
 use ironplc_dsl::common::*;
 use ironplc_dsl::core::Located;
@@ -47,15 +48,17 @@ pub fn try_from(
             }
 
             // Extract min and max values from the subrange
-            let min_value = if spec.subrange.start.is_neg {
-                -(spec.subrange.start.value.value as i128)
+            let start = bound_value(&spec.subrange.start)?;
+            let end = bound_value(&spec.subrange.end)?;
+            let min_value = if start.is_neg {
+                -(start.value.value as i128)
             } else {
-                spec.subrange.start.value.value as i128
+                start.value.value as i128
             };
-            let max_value = if spec.subrange.end.is_neg {
-                -(spec.subrange.end.value.value as i128)
+            let max_value = if end.is_neg {
+                -(end.value.value as i128)
             } else {
-                spec.subrange.end.value.value as i128
+                end.value.value as i128
             };
 
             // Validate range
@@ -65,11 +68,11 @@ pub fn try_from(
                     Label::span(node_name.span(), "Subrange declaration"),
                 )
                 .with_secondary(Label::span(
-                    spec.subrange.start.value.span(),
+                    start.value.span(),
                     format!("Minimum value: {}", min_value),
                 ))
                 .with_secondary(Label::span(
-                    spec.subrange.end.value.span(),
+                    end.value.span(),
                     format!("Maximum value: {}", max_value),
                 )));
             }
@@ -271,14 +274,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 100,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
         });
 
@@ -362,14 +367,16 @@ mod tests {
                         value: 100,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
         });
 
@@ -396,14 +403,16 @@ mod tests {
                         value: 200,
                     },
                     is_neg: true,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 200,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
         });
 
@@ -427,14 +436,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 100,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
         });
 
@@ -468,14 +479,16 @@ mod tests {
                         value: 1,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span: SourceSpan::default(),
                         value: 100,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
         });
 
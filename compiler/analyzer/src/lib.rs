@@ -4,28 +4,38 @@
 extern crate ironplc_dsl;
 extern crate ironplc_parser;
 
+mod const_eval;
+mod expr_type;
 mod intermediate_type;
 mod result;
 mod rule_decl_struct_element_unique_names;
 mod rule_decl_subrange_limits;
+mod rule_deref_requires_reference;
 mod rule_enumeration_values_unique;
+mod rule_expr_type_compatible;
 mod rule_function_block_invocation;
+mod rule_function_invocation;
 mod rule_pou_hierarchy;
+mod rule_pou_inheritance;
 mod rule_program_task_definition_exists;
-mod rule_unsupported_stdlib_type;
+mod rule_reference_assignment_compatible;
 mod rule_use_declared_enumerated_value;
+mod rule_use_declared_struct_field;
 mod rule_use_declared_symbolic_var;
 mod rule_var_decl_const_initialized;
 mod rule_var_decl_const_not_fb;
 mod rule_var_decl_global_const_requires_external_const;
+pub mod rules;
 mod scoped_table;
 pub mod stages;
-mod stdlib;
+pub mod stdlib;
 mod symbol_environment;
+pub mod symbol_index;
 mod type_attributes;
 mod type_category;
 mod type_environment;
 mod type_table;
+mod xform_resolve_constant_expressions;
 mod xform_resolve_late_bound_expr_kind;
 mod xform_resolve_late_bound_type_initializer;
 mod xform_resolve_symbol_environment;
@@ -37,6 +47,7 @@ mod xform_toposort_declarations;
 mod intermediates;
 
 // Re-export public types for external use
+pub use intermediate_type::{ByteSized, IntermediateType};
 pub use type_attributes::TypeAttributes;
 pub use type_category::TypeCategory;
 pub use type_environment::{TypeEnvironment, TypeEnvironmentBuilder, UsageContext};
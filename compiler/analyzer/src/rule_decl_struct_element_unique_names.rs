@@ -25,7 +25,7 @@
 use ironplc_dsl::{
     common::*,
     core::*,
-    diagnostic::{Diagnostic, Label},
+    diagnostic::{Diagnostic, Fix, Label, TextEdit},
     visitor::Visitor,
 };
 use ironplc_problems::Problem;
@@ -65,7 +65,7 @@ impl Visitor<Diagnostic> for RuleStructElementNamesUnique {
     ) -> Result<Self::Value, Diagnostic> {
         let mut element_names: HashSet<&Id> = HashSet::new();
 
-        for element in &node.elements {
+        for (index, element) in node.elements.iter().enumerate() {
             let seen = element_names.get(&element.name);
             match seen {
                 Some(first) => {
@@ -77,7 +77,8 @@ impl Visitor<Diagnostic> for RuleStructElementNamesUnique {
                         .with_context_type("structure", &node.type_name)
                         .with_context_id("element", &element.name)
                         .with_secondary(Label::span(first.span(), "First use of name"))
-                        .with_secondary(Label::span(element.name.span(), "Second use of name")),
+                        .with_secondary(Label::span(element.name.span(), "Second use of name"))
+                        .with_fix(remove_duplicate(node, index, first)),
                     );
                 }
                 None => {
@@ -91,6 +92,35 @@ impl Visitor<Diagnostic> for RuleStructElementNamesUnique {
     }
 }
 
+/// Creates a fix that removes one of the declarations of a duplicated name.
+///
+/// Elements do not know where they end, so the fix removes text up to the
+/// start of the following element. The last element has no following
+/// element, so the fix then removes the first declaration instead.
+fn remove_duplicate(node: &StructureDeclaration, index: usize, first: &Id) -> Fix {
+    let (start, end) = match node.elements.get(index + 1) {
+        Some(next) => (node.elements[index].name.span(), next.name.span()),
+        None => {
+            let first_index = node
+                .elements
+                .iter()
+                .position(|element| element.name.span().start == first.span().start)
+                .unwrap_or(index);
+            (first.span(), node.elements[first_index + 1].name.span())
+        }
+    };
+
+    let span = SourceSpan {
+        start: start.start,
+        end: end.start,
+        file_id: start.file_id,
+    };
+    Fix::new(
+        format!("Remove duplicate element {}", first),
+        TextEdit::replace(&span, ""),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test_helpers::parse_and_resolve_types;
@@ -131,4 +161,51 @@ END_TYPE";
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn apply_when_structure_has_duplicated_names_then_fix_removes_duplicate() {
+        let program = "
+TYPE
+    CUSTOM_STRUCT : STRUCT
+        NAME: BOOL;
+        NAME: BOOL;
+        OTHER: BOOL;
+    END_STRUCT;
+END_TYPE";
+
+        let library = parse_and_resolve_types(program);
+        let type_env = TypeEnvironment::new();
+        let symbol_env = SymbolEnvironment::new();
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let diagnostics = result.unwrap_err();
+        let edit = &diagnostics[0].fixes[0].edits[0];
+        assert_eq!("", edit.new_text);
+        assert_eq!(
+            "NAME: BOOL;\n        ",
+            &program[edit.location.start..edit.location.end]
+        );
+        assert_eq!(program.rfind("NAME").unwrap(), edit.location.start);
+    }
+
+    #[test]
+    fn apply_when_structure_has_duplicated_last_name_then_fix_removes_first() {
+        let program = "
+TYPE
+    CUSTOM_STRUCT : STRUCT
+        NAME: BOOL;
+        NAME: BOOL;
+    END_STRUCT;
+END_TYPE";
+
+        let library = parse_and_resolve_types(program);
+        let type_env = TypeEnvironment::new();
+        let symbol_env = SymbolEnvironment::new();
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let diagnostics = result.unwrap_err();
+        let edit = &diagnostics[0].fixes[0].edits[0];
+        assert_eq!(program.find("NAME").unwrap(), edit.location.start);
+        assert_eq!(program.rfind("NAME").unwrap(), edit.location.end);
+    }
 }
@@ -54,18 +54,25 @@ impl Visitor<Diagnostic> for RuleDeclSubrangeLimits {
     type Value = ();
 
     fn visit_subrange(&mut self, node: &Subrange) -> Result<(), Diagnostic> {
-        let minimum: i128 = node.start.clone().try_into().expect("Value in range i128");
-        let maximum: i128 = node.end.clone().try_into().expect("Value in range i128");
+        // Constant expressions are evaluated when resolving types so the
+        // bounds are integer literals.
+        let (Some(start), Some(end)) =
+            (node.start.as_signed_integer(), node.end.as_signed_integer())
+        else {
+            return Err(Diagnostic::internal_error(file!(), line!()));
+        };
+        let minimum: i128 = start.clone().try_into().expect("Value in range i128");
+        let maximum: i128 = end.clone().try_into().expect("Value in range i128");
 
         if minimum >= maximum {
             self.diagnostics.push(
                 Diagnostic::problem(
                     Problem::SubrangeMinStrictlyLessMax,
-                    Label::span(node.start.value.span(), "Expected smaller value"),
+                    Label::span(start.value.span(), "Expected smaller value"),
                 )
-                .with_context("minimum", &node.start.to_string())
-                .with_context("maximum", &node.end.to_string())
-                .with_secondary(Label::span(node.end.value.span(), "Expected greater value")),
+                .with_context("minimum", &start.to_string())
+                .with_context("maximum", &end.to_string())
+                .with_secondary(Label::span(end.value.span(), "Expected greater value")),
             );
         }
         Ok(())
@@ -0,0 +1,212 @@
+//! Semantic rule that the dereference operator is only applied to
+//! variables that are references.
+//!
+//! ## Passes
+//!
+//! ```ignore
+//! PROGRAM main
+//!    VAR
+//!       Value : INT;
+//!       ValueRef : REF_TO INT;
+//!    END_VAR
+//!    ValueRef := REF(Value);
+//!    Value := ValueRef^;
+//! END_PROGRAM
+//! ```
+//!
+//! ## Fails
+//!
+//! ```ignore
+//! PROGRAM main
+//!    VAR
+//!       Value : INT;
+//!       Result : INT;
+//!    END_VAR
+//!    Result := Value^;
+//! END_PROGRAM
+//! ```
+use ironplc_dsl::{
+    common::*,
+    core::{Id, Located},
+    diagnostic::{Diagnostic, Label},
+    textual::{DerefVariable, SymbolicVariableKind},
+    visitor::Visitor,
+};
+use ironplc_problems::Problem;
+
+use crate::{
+    result::SemanticResult,
+    scoped_table::{ScopedTable, Value},
+    symbol_environment::SymbolEnvironment,
+    type_environment::TypeEnvironment,
+};
+
+pub fn apply(
+    lib: &Library,
+    type_environment: &TypeEnvironment,
+    _symbol_environment: &SymbolEnvironment,
+) -> SemanticResult {
+    let mut visitor = RuleDerefRequiresReference {
+        scope: ScopedTable::new(),
+        type_environment,
+        diagnostics: vec![],
+    };
+
+    visitor.walk(lib).map_err(|e| vec![e])?;
+
+    if !visitor.diagnostics.is_empty() {
+        return Err(visitor.diagnostics);
+    }
+    Ok(())
+}
+
+/// Whether a declared variable is a reference.
+#[derive(Debug)]
+struct IsReference(bool);
+impl Value for IsReference {}
+
+struct RuleDerefRequiresReference<'a> {
+    scope: ScopedTable<'a, Id, IsReference>,
+    type_environment: &'a TypeEnvironment,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl RuleDerefRequiresReference<'_> {
+    fn is_reference(&self, init: &InitialValueAssignmentKind) -> bool {
+        match init {
+            InitialValueAssignmentKind::Reference(_) => true,
+            InitialValueAssignmentKind::Simple(simple) => self
+                .type_environment
+                .get(&simple.type_name)
+                .is_some_and(|attrs| attrs.representation.is_reference()),
+            _ => false,
+        }
+    }
+}
+
+impl Visitor<Diagnostic> for RuleDerefRequiresReference<'_> {
+    type Value = ();
+
+    fn visit_function_declaration(&mut self, node: &FunctionDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_program_declaration(&mut self, node: &ProgramDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_function_block_declaration(
+        &mut self,
+        node: &FunctionBlockDeclaration,
+    ) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_method_declaration(&mut self, node: &MethodDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_var_decl(&mut self, node: &VarDecl) -> Result<(), Diagnostic> {
+        let is_reference = IsReference(self.is_reference(&node.initializer));
+        self.scope
+            .add_if(node.identifier.symbolic_id(), is_reference);
+        node.recurse_visit(self)
+    }
+
+    fn visit_deref_variable(&mut self, node: &DerefVariable) -> Result<(), Diagnostic> {
+        if let SymbolicVariableKind::Named(named) = node.reference.as_ref() {
+            // Unknown variables (such as inherited variables) are
+            // checked by other rules.
+            if let Some(IsReference(false)) = self.scope.find(&named.name) {
+                self.diagnostics.push(
+                    Diagnostic::problem(
+                        Problem::DereferenceNotReference,
+                        Label::span(node.span(), "Dereference"),
+                    )
+                    .with_context_id("variable", &named.name),
+                );
+            }
+        }
+        node.recurse_visit(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::parse_and_resolve_environments;
+
+    use super::*;
+
+    #[test]
+    fn apply_when_deref_reference_then_ok() {
+        let program = "
+PROGRAM main
+VAR
+Value : INT;
+ValueRef : REF_TO INT;
+END_VAR
+ValueRef := REF(Value);
+Value := ValueRef^;
+END_PROGRAM";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_deref_reference_type_alias_then_ok() {
+        let program = "
+TYPE
+INT_REF : REF_TO INT;
+END_TYPE
+
+PROGRAM main
+VAR
+Value : INT;
+ValueRef : INT_REF;
+END_VAR
+ValueRef^ := Value;
+END_PROGRAM";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_deref_not_reference_then_error() {
+        let program = "
+PROGRAM main
+VAR
+Value : INT;
+Result : INT;
+END_VAR
+Result := Value^;
+END_PROGRAM";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_err());
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::DereferenceNotReference.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+}
@@ -0,0 +1,1109 @@
+//! Semantic rule that expressions have types that are compatible with the
+//! operators and statements that use the expressions. The rule resolves the
+//! type of each expression and checks that:
+//!
+//! * operands of an operator have types that the operator accepts
+//! * conditions of `IF`, `ELSIF`, `WHILE` and `REPEAT` have type `BOOL`
+//! * assigned values have a type that implicitly converts to the type of
+//!   the assignment target
+//! * arguments of function invocations have a type that implicitly
+//!   converts to the type of the input (or is a member of the generic
+//!   type family for standard functions)
+//!
+//! Implicit conversions follow the widening rules of IEC 61131-3 (for
+//! example, `INT` converts to `DINT` but `DINT` does not convert to `INT`).
+//! Expressions whose type is not known (for example, calls to functions
+//! that are not declared) are not checked.
+//!
+//! See 2.3.1 and 3.3.1.
+//!
+//! ## Passes
+//!
+//! ```ignore
+//! PROGRAM main
+//!    VAR
+//!       myInt : INT;
+//!       myReal : REAL;
+//!       myBool : BOOL;
+//!    END_VAR
+//!    myReal := myInt + 1;
+//!    myBool := myReal > 1.0;
+//!    IF myBool THEN
+//!       myInt := 0;
+//!    END_IF;
+//! END_PROGRAM
+//! ```
+//!
+//! ## Fails
+//!
+//! ```ignore
+//! PROGRAM main
+//!    VAR
+//!       myReal : REAL;
+//!       myBool : BOOL;
+//!    END_VAR
+//!    myBool := myReal + 'text';
+//! END_PROGRAM
+//! ```
+use std::collections::HashMap;
+
+use ironplc_dsl::{
+    common::*,
+    core::{Id, Located, SourceSpan},
+    diagnostic::{Diagnostic, Label},
+    textual::*,
+    visitor::Visitor,
+};
+use ironplc_problems::Problem;
+
+use crate::{
+    expr_type::{ExprType, ExpressionTypes},
+    intermediate_type::IntermediateType,
+    result::SemanticResult,
+    scoped_table::{ScopedTable, Value},
+    stdlib::functions::{
+        find_standard_function, FunctionSignature, GenericType, InputType, ReturnType,
+    },
+    symbol_environment::SymbolEnvironment,
+    type_environment::TypeEnvironment,
+};
+
+pub fn apply(
+    lib: &Library,
+    type_environment: &TypeEnvironment,
+    _symbol_environment: &SymbolEnvironment,
+) -> SemanticResult {
+    let (_, diagnostics) = resolve_expression_types(lib, type_environment)?;
+
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+    Ok(())
+}
+
+/// Resolves the type of expressions in the library. Returns the types of
+/// the expressions (where the type is known) and diagnostics for
+/// expressions with incompatible types.
+pub(crate) fn resolve_expression_types(
+    lib: &Library,
+    type_environment: &TypeEnvironment,
+) -> Result<(ExpressionTypes, Vec<Diagnostic>), Vec<Diagnostic>> {
+    let functions = lib
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            LibraryElementKind::FunctionDeclaration(func) => {
+                Some((func.name.clone(), FunctionSignature::from_declaration(func)))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let function_blocks = lib
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            LibraryElementKind::FunctionBlockDeclaration(fb) => Some((fb.name.clone(), fb)),
+            _ => None,
+        })
+        .collect();
+
+    let mut visitor = RuleExprTypeCompatible {
+        scope: ScopedTable::new(),
+        type_environment,
+        functions,
+        function_blocks,
+        context: None,
+        types: ExpressionTypes::default(),
+        diagnostics: vec![],
+    };
+
+    visitor.walk(lib).map_err(|e| vec![e])?;
+
+    Ok((visitor.types, visitor.diagnostics))
+}
+
+/// The declared type of a variable.
+#[derive(Debug)]
+enum DeclaredType {
+    /// The variable has the named type.
+    Named(TypeName),
+    /// The variable has a type that is declared as part of the variable.
+    Expr(ExprType),
+    /// The variable is an array (declared as part of the variable) having
+    /// elements of the named type.
+    ArrayOf(TypeName),
+    /// The variable is an instance of the named function block.
+    FunctionBlock(TypeName),
+    /// The variable has a type that this rule does not check (such as a
+    /// reference).
+    Unknown,
+}
+impl Value for DeclaredType {}
+
+struct RuleExprTypeCompatible<'a> {
+    scope: ScopedTable<'a, Id, DeclaredType>,
+    type_environment: &'a TypeEnvironment,
+    /// Signatures of functions declared in the library.
+    functions: HashMap<Id, FunctionSignature>,
+    /// Function blocks declared in the library.
+    function_blocks: HashMap<TypeName, &'a FunctionBlockDeclaration>,
+    /// Location to use for diagnostics when the expression itself does not
+    /// have a location.
+    context: Option<SourceSpan>,
+    types: ExpressionTypes,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> RuleExprTypeCompatible<'a> {
+    /// Returns the expression type for the named type or `None` if the type
+    /// is not known.
+    fn resolve(&self, type_name: &TypeName) -> Option<ExprType> {
+        if let Some(elementary) = ExprType::from_elementary_name(type_name) {
+            return Some(elementary);
+        }
+        let attrs = self.type_environment.get(type_name)?;
+        ExprType::from_representation(Some(type_name), &attrs.representation)
+    }
+
+    /// Returns the representation of the type of the variable or `None` if
+    /// the type is not known.
+    fn find_representation(
+        &mut self,
+        variable: &SymbolicVariableKind,
+    ) -> Option<&'a IntermediateType> {
+        let type_environment = self.type_environment;
+        match variable {
+            SymbolicVariableKind::Named(named) => match self.scope.find(&named.name)? {
+                DeclaredType::Named(type_name) => type_environment
+                    .get(type_name)
+                    .map(|attrs| &attrs.representation),
+                _ => None,
+            },
+            SymbolicVariableKind::Array(array) => {
+                match self.find_representation(&array.subscripted_variable)? {
+                    IntermediateType::Array { element_type, .. } => Some(element_type),
+                    _ => None,
+                }
+            }
+            SymbolicVariableKind::Structured(structured) => self
+                .find_representation(&structured.record)?
+                .get_field(&structured.field)
+                .map(|field| &field.field_type),
+            SymbolicVariableKind::Deref(deref) => {
+                match self.find_representation(&deref.reference)? {
+                    IntermediateType::Reference {
+                        referenced_type_name,
+                    } => type_environment
+                        .get(referenced_type_name)
+                        .map(|attrs| &attrs.representation),
+                    _ => None,
+                }
+            }
+            SymbolicVariableKind::This(_) | SymbolicVariableKind::Super(_) => None,
+        }
+    }
+
+    /// Returns the declared type of the member of a function block instance
+    /// or `None` if the record is not a function block instance that
+    /// declares the member.
+    fn member_type(&mut self, structured: &StructuredVariable) -> Option<DeclaredType> {
+        let SymbolicVariableKind::Named(named) = structured.record.as_ref() else {
+            return None;
+        };
+        let DeclaredType::FunctionBlock(type_name) = self.scope.find(&named.name)? else {
+            return None;
+        };
+        let function_block = self.function_blocks.get(type_name)?;
+        if function_block
+            .edge_variables
+            .iter()
+            .any(|var| var.identifier == structured.field)
+        {
+            return Some(DeclaredType::Expr(ExprType::Elementary(
+                ElementaryTypeName::BOOL,
+            )));
+        }
+        function_block
+            .variables
+            .iter()
+            .find(|var| var.identifier.symbolic_id() == Some(&structured.field))
+            .map(|var| declared_type(&var.initializer))
+    }
+
+    /// Returns the type of the variable or `None` if the type is not known.
+    fn variable_type(&mut self, variable: &SymbolicVariableKind) -> Option<ExprType> {
+        match variable {
+            SymbolicVariableKind::Named(named) => match self.scope.find(&named.name)? {
+                DeclaredType::Named(type_name) => {
+                    let type_name = type_name.clone();
+                    self.resolve(&type_name)
+                }
+                DeclaredType::Expr(expr_type) => Some(expr_type.clone()),
+                DeclaredType::ArrayOf(_)
+                | DeclaredType::FunctionBlock(_)
+                | DeclaredType::Unknown => None,
+            },
+            SymbolicVariableKind::Array(array) => {
+                if let SymbolicVariableKind::Named(named) = array.subscripted_variable.as_ref() {
+                    if let Some(DeclaredType::ArrayOf(type_name)) = self.scope.find(&named.name) {
+                        let type_name = type_name.clone();
+                        return self.resolve(&type_name);
+                    }
+                }
+                let representation = self.find_representation(variable)?;
+                ExprType::from_representation(None, representation)
+            }
+            SymbolicVariableKind::Deref(deref) => {
+                match self.find_representation(&deref.reference)? {
+                    IntermediateType::Reference {
+                        referenced_type_name,
+                    } => self.resolve(referenced_type_name),
+                    _ => None,
+                }
+            }
+            SymbolicVariableKind::Structured(structured) => {
+                if let Some(member_type) = self.member_type(structured) {
+                    return match member_type {
+                        DeclaredType::Named(type_name) => self.resolve(&type_name),
+                        DeclaredType::Expr(expr_type) => Some(expr_type),
+                        DeclaredType::ArrayOf(_)
+                        | DeclaredType::FunctionBlock(_)
+                        | DeclaredType::Unknown => None,
+                    };
+                }
+                let representation = self.find_representation(variable)?;
+                ExprType::from_representation(None, representation)
+            }
+            SymbolicVariableKind::This(_) | SymbolicVariableKind::Super(_) => None,
+        }
+    }
+
+    /// Returns the type of the expression or `None` if the type is not known
+    /// or the expression has incompatible types.
+    ///
+    /// Records the type of the expression and adds diagnostics for operators
+    /// that are applied to incompatible types.
+    fn type_of(&mut self, expr: &ExprKind) -> Option<ExprType> {
+        let expr_type = match expr {
+            ExprKind::Compare(compare) => {
+                let left = self.type_of(&compare.left);
+                let right = self.type_of(&compare.right);
+                let (left, right) = (left?, right?);
+                let result = match compare.op {
+                    CompareOp::Or | CompareOp::Xor | CompareOp::And => {
+                        ExprType::logical(&left, &right)
+                    }
+                    CompareOp::Eq
+                    | CompareOp::Ne
+                    | CompareOp::Lt
+                    | CompareOp::Gt
+                    | CompareOp::LtEq
+                    | CompareOp::GtEq => ExprType::is_comparable(&left, &right)
+                        .then_some(ExprType::Elementary(ElementaryTypeName::BOOL)),
+                };
+                if result.is_none() {
+                    self.operands_incompatible(expr, &left, &right);
+                }
+                result
+            }
+            ExprKind::BinaryOp(binary) => {
+                let left = self.type_of(&binary.left);
+                let right = self.type_of(&binary.right);
+                let (left, right) = (left?, right?);
+                let result = ExprType::arithmetic(&binary.op, &left, &right);
+                if result.is_none() {
+                    self.operands_incompatible(expr, &left, &right);
+                }
+                result
+            }
+            ExprKind::UnaryOp(unary) => {
+                let term = self.type_of(&unary.term)?;
+                let result = match unary.op {
+                    UnaryOp::Neg => ExprType::negate(&term),
+                    UnaryOp::Not => ExprType::complement(&term),
+                };
+                if result.is_none() {
+                    let span = self.span_of(expr);
+                    if let Some(span) = span {
+                        self.diagnostics.push(
+                            Diagnostic::problem(
+                                Problem::OperandTypesIncompatible,
+                                Label::span(span, "Expression"),
+                            )
+                            .with_context("operand", &term.to_string()),
+                        );
+                    }
+                }
+                result
+            }
+            ExprKind::Expression(inner) => self.type_of(inner),
+            ExprKind::Const(constant) => Some(constant_type(constant)),
+            ExprKind::EnumeratedValue(value) => {
+                Some(ExprType::Enumeration(value.type_name.clone()))
+            }
+            ExprKind::Variable(Variable::Symbolic(variable)) => {
+                self.visit_subscripts(variable);
+                self.variable_type(variable)
+            }
+            ExprKind::Variable(Variable::Direct(_)) => None,
+            ExprKind::Function(function) => self.invocation_type(function),
+            ExprKind::MethodCall(method) => {
+                self.visit_params(&method.params);
+                None
+            }
+            ExprKind::Ref(_) | ExprKind::Null(_) | ExprKind::LateBound(_) => None,
+        };
+
+        if let Some(expr_type) = &expr_type {
+            if let (Some(span), Some(type_name)) = (self.span_of(expr), expr_type.type_name()) {
+                self.types.insert(&span, type_name);
+            }
+        }
+
+        expr_type
+    }
+
+    /// Returns the type that the function invocation returns or `None` if
+    /// the type is not known.
+    ///
+    /// Adds diagnostics for arguments that are not compatible with the
+    /// inputs of the function.
+    fn invocation_type(&mut self, function: &Function) -> Option<ExprType> {
+        // Functions declared in the library take precedence over standard
+        // functions.
+        let signature = self
+            .functions
+            .get(&function.name)
+            .cloned()
+            .or_else(|| find_standard_function(&function.name));
+        let Some(signature) = signature else {
+            self.visit_params(&function.param_assignment);
+            return None;
+        };
+
+        // The common type of the arguments for inputs having the generic type
+        // of the function. The common type is only known when the type of
+        // every such argument is known.
+        let mut generic: Option<ExprType> = None;
+        let mut is_generic_known = true;
+        let mut position = 0;
+        for param in &function.param_assignment {
+            let (input, expr) = match param {
+                ParamAssignmentKind::PositionalInput(positional) => {
+                    position += 1;
+                    (signature.positional_input(position - 1), &positional.expr)
+                }
+                ParamAssignmentKind::NamedInput(named) => {
+                    (signature.named_input(&named.name), &named.expr)
+                }
+                ParamAssignmentKind::Output(_) => continue,
+            };
+            let arg_type = self.type_of(expr);
+
+            // Inputs that the function does not have are reported by other
+            // rules.
+            let Some(input) = input else {
+                continue;
+            };
+            let Some(arg_type) = arg_type else {
+                if matches!(input.input_type, InputType::Generic(_)) {
+                    is_generic_known = false;
+                }
+                continue;
+            };
+
+            let expected = match &input.input_type {
+                InputType::Generic(family) => {
+                    let common = match &generic {
+                        Some(current) => current.unify(&arg_type),
+                        None => Some(arg_type.clone()),
+                    };
+                    match common {
+                        Some(common) if family.contains(&arg_type) => {
+                            generic = Some(common);
+                            None
+                        }
+                        _ => Some(match &generic {
+                            Some(current) => current.to_string(),
+                            None => family.name().to_string(),
+                        }),
+                    }
+                }
+                InputType::Independent(family) => {
+                    (!family.contains(&arg_type)).then(|| family.name().to_string())
+                }
+                InputType::Elementary(elementary) => {
+                    let input_type = ExprType::Elementary(elementary.clone());
+                    (!arg_type.is_assignable_to(&input_type)).then(|| input_type.to_string())
+                }
+                InputType::Named(type_name) => self
+                    .resolve(type_name)
+                    .filter(|input_type| !arg_type.is_assignable_to(input_type))
+                    .map(|input_type| input_type.to_string()),
+                InputType::Unknown => None,
+            };
+
+            if let (Some(expected), Some(span)) = (expected, self.span_of(expr)) {
+                self.diagnostics.push(
+                    Diagnostic::problem(
+                        Problem::FunctionArgumentTypeIncompatible,
+                        Label::span(span, "Argument"),
+                    )
+                    .with_context_id("function", &signature.name)
+                    .with_context_id("input", &input.name)
+                    .with_context("expected type", &expected)
+                    .with_context("argument type", &arg_type.to_string()),
+                );
+            }
+        }
+
+        match &signature.return_type {
+            ReturnType::Generic => generic.filter(|_| is_generic_known),
+            ReturnType::Family(GenericType::AnyInt) => Some(ExprType::AnyInt),
+            ReturnType::Family(GenericType::AnyReal) => Some(ExprType::AnyReal),
+            ReturnType::Family(_) => None,
+            ReturnType::Elementary(elementary) => Some(ExprType::Elementary(elementary.clone())),
+            ReturnType::Named(type_name) => self.resolve(type_name),
+        }
+    }
+
+    /// Resolves types of the expressions in the parameters of an invocation.
+    fn visit_params(&mut self, params: &[ParamAssignmentKind]) {
+        for param in params {
+            match param {
+                ParamAssignmentKind::PositionalInput(input) => {
+                    self.type_of(&input.expr);
+                }
+                ParamAssignmentKind::NamedInput(input) => {
+                    self.type_of(&input.expr);
+                }
+                ParamAssignmentKind::Output(_) => {}
+            }
+        }
+    }
+
+    /// Resolves types of the expressions in array subscripts of a variable.
+    fn visit_subscripts(&mut self, variable: &SymbolicVariableKind) {
+        match variable {
+            SymbolicVariableKind::Array(array) => {
+                self.visit_subscripts(&array.subscripted_variable);
+                for subscript in &array.subscripts {
+                    self.type_of(subscript);
+                }
+            }
+            SymbolicVariableKind::Structured(structured) => {
+                self.visit_subscripts(&structured.record)
+            }
+            SymbolicVariableKind::Deref(deref) => self.visit_subscripts(&deref.reference),
+            _ => {}
+        }
+    }
+
+    /// Returns the location of the expression or `None` if the expression
+    /// (or the context of the expression) does not have a location.
+    fn span_of(&self, expr: &ExprKind) -> Option<SourceSpan> {
+        expr_span(expr).or_else(|| self.context.clone())
+    }
+
+    fn operands_incompatible(&mut self, expr: &ExprKind, left: &ExprType, right: &ExprType) {
+        if let Some(span) = self.span_of(expr) {
+            self.diagnostics.push(
+                Diagnostic::problem(
+                    Problem::OperandTypesIncompatible,
+                    Label::span(span, "Expression"),
+                )
+                .with_context("left", &left.to_string())
+                .with_context("right", &right.to_string()),
+            );
+        }
+    }
+
+    /// Checks that the condition of a selection or iteration statement has
+    /// type `BOOL`.
+    fn check_condition(&mut self, condition: &ExprKind) {
+        if let Some(condition_type) = self.type_of(condition) {
+            if !condition_type.is_bool() {
+                if let Some(span) = self.span_of(condition) {
+                    self.diagnostics.push(
+                        Diagnostic::problem(
+                            Problem::ConditionNotBoolean,
+                            Label::span(span, "Condition"),
+                        )
+                        .with_context("type", &condition_type.to_string()),
+                    );
+                }
+            }
+        }
+    }
+
+    fn visit_body(&mut self, body: &[StmtKind]) -> Result<(), Diagnostic> {
+        for stmt in body {
+            self.visit_stmt_kind(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn enter_pou(&mut self, name: &Id, return_type: Option<&TypeName>) {
+        self.scope.enter();
+        self.context = Some(name.span());
+        if let Some(return_type) = return_type {
+            // The return value is assigned to a variable having the name of
+            // the function or method.
+            self.scope
+                .add(name, DeclaredType::Named(return_type.clone()));
+        }
+    }
+}
+
+impl Visitor<Diagnostic> for RuleExprTypeCompatible<'_> {
+    type Value = ();
+
+    fn visit_function_declaration(&mut self, node: &FunctionDeclaration) -> Result<(), Diagnostic> {
+        self.enter_pou(&node.name, Some(&node.return_type));
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_program_declaration(&mut self, node: &ProgramDeclaration) -> Result<(), Diagnostic> {
+        self.enter_pou(&node.name, None);
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_function_block_declaration(
+        &mut self,
+        node: &FunctionBlockDeclaration,
+    ) -> Result<(), Diagnostic> {
+        self.enter_pou(&node.name.name, None);
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_method_declaration(&mut self, node: &MethodDeclaration) -> Result<(), Diagnostic> {
+        self.enter_pou(&node.name, node.return_type.as_ref());
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_var_decl(&mut self, node: &VarDecl) -> Result<(), Diagnostic> {
+        let declared_type = declared_type(&node.initializer);
+        self.scope
+            .add_if(node.identifier.symbolic_id(), declared_type);
+        node.recurse_visit(self)
+    }
+
+    fn visit_assignment(&mut self, node: &Assignment) -> Result<(), Diagnostic> {
+        let Variable::Symbolic(target) = &node.target else {
+            self.type_of(&node.value);
+            return Ok(());
+        };
+
+        self.context = Some(target.span());
+        self.visit_subscripts(target);
+        let target_type = self.variable_type(target);
+        let value_type = self.type_of(&node.value);
+
+        if let (Some(target_type), Some(value_type)) = (target_type, value_type) {
+            if !value_type.is_assignable_to(&target_type) {
+                let span = expr_span(&node.value).unwrap_or_else(|| target.span());
+                self.diagnostics.push(
+                    Diagnostic::problem(
+                        Problem::AssignmentTypeIncompatible,
+                        Label::span(span, "Assigned value"),
+                    )
+                    .with_context("target type", &target_type.to_string())
+                    .with_context("value type", &value_type.to_string()),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_if(&mut self, node: &If) -> Result<(), Diagnostic> {
+        self.check_condition(&node.expr);
+        self.visit_body(&node.body)?;
+        for else_if in &node.else_ifs {
+            self.visit_else_if(else_if)?;
+        }
+        self.visit_body(&node.else_body)
+    }
+
+    fn visit_else_if(&mut self, node: &ElseIf) -> Result<(), Diagnostic> {
+        self.check_condition(&node.expr);
+        self.visit_body(&node.body)
+    }
+
+    fn visit_while(&mut self, node: &While) -> Result<(), Diagnostic> {
+        self.check_condition(&node.condition);
+        self.visit_body(&node.body)
+    }
+
+    fn visit_repeat(&mut self, node: &Repeat) -> Result<(), Diagnostic> {
+        self.visit_body(&node.body)?;
+        self.check_condition(&node.until);
+        Ok(())
+    }
+
+    fn visit_expr_kind(&mut self, node: &ExprKind) -> Result<(), Diagnostic> {
+        // Resolving the type visits nested expressions so there is no need
+        // to recurse.
+        self.type_of(node);
+        Ok(())
+    }
+}
+
+/// Returns the declared type of a variable having the initializer.
+fn declared_type(initializer: &InitialValueAssignmentKind) -> DeclaredType {
+    match initializer {
+        InitialValueAssignmentKind::Simple(simple) => DeclaredType::Named(simple.type_name.clone()),
+        InitialValueAssignmentKind::String(string) => {
+            DeclaredType::Expr(ExprType::Elementary(match string.width {
+                StringType::String => ElementaryTypeName::STRING,
+                StringType::WString => ElementaryTypeName::WSTRING,
+            }))
+        }
+        InitialValueAssignmentKind::EnumeratedValues(_) => {
+            DeclaredType::Expr(ExprType::Enumeration(None))
+        }
+        InitialValueAssignmentKind::EnumeratedType(enumerated) => {
+            DeclaredType::Named(enumerated.type_name.clone())
+        }
+        InitialValueAssignmentKind::Subrange(SubrangeSpecificationKind::Specification(spec)) => {
+            DeclaredType::Expr(ExprType::Elementary(spec.type_name.clone()))
+        }
+        InitialValueAssignmentKind::Subrange(SubrangeSpecificationKind::Type(type_name)) => {
+            DeclaredType::Named(type_name.clone())
+        }
+        InitialValueAssignmentKind::Structure(structure) => {
+            DeclaredType::Named(structure.type_name.clone())
+        }
+        InitialValueAssignmentKind::Array(array) => match &array.spec {
+            ArraySpecificationKind::Type(type_name) => DeclaredType::Named(type_name.clone()),
+            ArraySpecificationKind::Subranges(subranges) => {
+                DeclaredType::ArrayOf(subranges.type_name.clone())
+            }
+        },
+        InitialValueAssignmentKind::LateResolvedType(type_name) => {
+            DeclaredType::Named(type_name.clone())
+        }
+        InitialValueAssignmentKind::FunctionBlock(fb) => {
+            DeclaredType::FunctionBlock(fb.type_name.clone())
+        }
+        InitialValueAssignmentKind::None(_) | InitialValueAssignmentKind::Reference(_) => {
+            DeclaredType::Unknown
+        }
+    }
+}
+
+/// Returns the type of a constant.
+fn constant_type(constant: &ConstantKind) -> ExprType {
+    match constant {
+        ConstantKind::IntegerLiteral(literal) => match &literal.data_type {
+            Some(data_type) => ExprType::Elementary(data_type.clone()),
+            None => ExprType::AnyInt,
+        },
+        ConstantKind::RealLiteral(literal) => match &literal.data_type {
+            Some(data_type) => ExprType::Elementary(data_type.clone()),
+            None => ExprType::AnyReal,
+        },
+        ConstantKind::Boolean(_) => ExprType::Elementary(ElementaryTypeName::BOOL),
+        ConstantKind::CharacterString(_) => ExprType::AnyString,
+        ConstantKind::Duration(_) => ExprType::Elementary(ElementaryTypeName::TIME),
+        ConstantKind::TimeOfDay(_) => ExprType::Elementary(ElementaryTypeName::TimeOfDay),
+        ConstantKind::Date(_) => ExprType::Elementary(ElementaryTypeName::DATE),
+        ConstantKind::DateAndTime(_) => ExprType::Elementary(ElementaryTypeName::DateAndTime),
+        ConstantKind::BitStringLiteral(literal) => match &literal.data_type {
+            Some(data_type) => ExprType::Elementary(data_type.clone()),
+            None => ExprType::AnyInt,
+        },
+    }
+}
+
+/// Returns the location of the expression or `None` if no part of the
+/// expression has a location.
+fn expr_span(expr: &ExprKind) -> Option<SourceSpan> {
+    match expr {
+        ExprKind::Compare(compare) => join(expr_span(&compare.left), expr_span(&compare.right)),
+        ExprKind::BinaryOp(binary) => join(expr_span(&binary.left), expr_span(&binary.right)),
+        ExprKind::UnaryOp(unary) => expr_span(&unary.term),
+        ExprKind::Expression(inner) => expr_span(inner),
+        // Literals do not have a reliable location.
+        ExprKind::Const(_) => None,
+        ExprKind::EnumeratedValue(value) => Some(value.span()),
+        ExprKind::Variable(Variable::Symbolic(variable)) => Some(variable.span()),
+        ExprKind::Variable(Variable::Direct(_)) => None,
+        ExprKind::Function(function) => Some(function.name.span()),
+        ExprKind::MethodCall(method) => Some(method.span()),
+        ExprKind::Ref(reference) => Some(reference.span()),
+        ExprKind::Null(null) => Some(null.span()),
+        ExprKind::LateBound(late_bound) => Some(late_bound.value.span()),
+    }
+}
+
+fn join(left: Option<SourceSpan>, right: Option<SourceSpan>) -> Option<SourceSpan> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(SourceSpan::join(&left, &right)),
+        (left, right) => left.or(right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::parse_and_resolve_environments;
+
+    use super::*;
+
+    fn apply_program(program: &str) -> SemanticResult {
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        apply(&library, &type_env, &symbol_env)
+    }
+
+    fn assert_single_problem(result: SemanticResult, problem: Problem) {
+        let errors = result.unwrap_err();
+        assert_eq!(1, errors.len(), "{errors:?}");
+        assert_eq!(problem.code().to_string(), errors.first().unwrap().code);
+    }
+
+    #[test]
+    fn apply_when_compatible_types_then_ok() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+myDint : DINT;
+myReal : REAL;
+myBool : BOOL;
+myTime : TIME;
+myWord : WORD;
+END_VAR
+myDint := myInt + 1;
+myReal := myInt * 2.5;
+myBool := myReal > 1.0 AND myInt <> 0;
+myTime := myTime + T#1s;
+myWord := myWord AND 16#FF;
+myInt := -myInt MOD 3;
+IF myBool THEN
+myInt := 0;
+ELSIF NOT myBool THEN
+myInt := 1;
+END_IF;
+WHILE myInt < 10 DO
+myInt := myInt + 1;
+END_WHILE;
+REPEAT
+myInt := myInt - 1;
+UNTIL myInt = 0
+END_REPEAT;
+END_PROGRAM",
+        );
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn apply_when_real_plus_string_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myReal : REAL;
+myBool : BOOL;
+END_VAR
+myBool := myReal + 'text';
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::OperandTypesIncompatible);
+    }
+
+    #[test]
+    fn apply_when_assign_narrowing_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+myDint : DINT;
+END_VAR
+myInt := myDint;
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::AssignmentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_assign_real_literal_to_integer_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+END_VAR
+myInt := 1.5;
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::AssignmentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_if_condition_not_bool_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+END_VAR
+IF myInt + 1 THEN
+myInt := 0;
+END_IF;
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::ConditionNotBoolean);
+    }
+
+    #[test]
+    fn apply_when_while_condition_not_bool_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+END_VAR
+WHILE myInt DO
+myInt := 0;
+END_WHILE;
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::ConditionNotBoolean);
+    }
+
+    #[test]
+    fn apply_when_repeat_condition_not_bool_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myReal : REAL;
+END_VAR
+REPEAT
+myReal := 0.0;
+UNTIL myReal
+END_REPEAT;
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::ConditionNotBoolean);
+    }
+
+    #[test]
+    fn apply_when_user_function_return_type_then_checks_assignment() {
+        let result = apply_program(
+            "
+FUNCTION square : LREAL
+VAR_INPUT
+x : LREAL;
+END_VAR
+square := x * x;
+END_FUNCTION
+
+PROGRAM main
+VAR
+myReal : REAL;
+END_VAR
+myReal := square(x := 2.0);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::AssignmentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_standard_function_generic_inputs_compatible_then_ok() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+myDint : DINT;
+myReal : REAL;
+myWord : WORD;
+myText : STRING;
+END_VAR
+myDint := LIMIT(0, myInt, myDint);
+myReal := SQRT(myReal) + INT_TO_REAL(myInt);
+myWord := SHL(myWord, 2);
+myInt := LEN(CONCAT(myText, 'suffix'));
+myReal := MAX(myInt, 1.5);
+END_PROGRAM",
+        );
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn apply_when_standard_function_generic_inputs_no_common_type_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+myText : STRING;
+END_VAR
+myInt := LIMIT(0, myText, 10);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::FunctionArgumentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_standard_function_input_not_in_family_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+myReal : REAL;
+END_VAR
+myReal := SQRT(myInt);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::FunctionArgumentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_standard_function_return_type_narrowing_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+myInt : INT;
+myDint : DINT;
+END_VAR
+myInt := MAX(myInt, myDint);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::AssignmentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_user_function_argument_incompatible_then_error() {
+        let result = apply_program(
+            "
+FUNCTION Half : INT
+VAR_INPUT
+Value : INT;
+END_VAR
+Half := Value / 2;
+END_FUNCTION
+
+PROGRAM main
+VAR
+myInt : INT;
+myReal : REAL;
+END_VAR
+myInt := Half(Value := myReal);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::FunctionArgumentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_structure_field_and_array_element_then_checks_types() {
+        let result = apply_program(
+            "
+TYPE
+Point : STRUCT
+x : INT;
+END_STRUCT;
+END_TYPE
+
+PROGRAM main
+VAR
+p : Point;
+values : ARRAY[1..10] OF BOOL;
+END_VAR
+p.x := 1;
+values[p.x] := p.x > 0;
+values[2] := p.x;
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::AssignmentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_function_block_outputs_then_checks_types() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+t : TON;
+c : CTU_DINT;
+elapsed : TIME;
+count : DINT;
+done : BOOL;
+END_VAR
+done := t.Q AND c.Q;
+elapsed := t.ET;
+count := c.CV + 1;
+count := t.Q;
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::AssignmentTypeIncompatible);
+    }
+
+    #[test]
+    fn apply_when_enumeration_compared_with_integer_then_error() {
+        let result = apply_program(
+            "
+TYPE
+Color : (Red, Green) := Red;
+END_TYPE
+
+PROGRAM main
+VAR
+c : Color;
+b : BOOL;
+END_VAR
+b := c = Red;
+b := c = 1;
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::OperandTypesIncompatible);
+    }
+
+    #[test]
+    fn resolve_expression_types_when_variable_then_annotates_type() {
+        let program = "
+PROGRAM main
+VAR
+myInt : INT;
+myDint : DINT;
+END_VAR
+myDint := myInt;
+END_PROGRAM";
+        let (library, type_env, _) = parse_and_resolve_environments(program);
+        let (types, diagnostics) = resolve_expression_types(&library, &type_env).unwrap();
+        assert!(diagnostics.is_empty());
+
+        let start = program.rfind("myInt").unwrap();
+        let span = SourceSpan::range(start, start + "myInt".len());
+        assert_eq!(Some(&TypeName::from("INT")), types.get(&span));
+    }
+}
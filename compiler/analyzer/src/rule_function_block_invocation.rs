@@ -42,7 +42,8 @@ use ironplc_problems::Problem;
 use std::collections::HashMap;
 
 use crate::{
-    result::SemanticResult, symbol_environment::SymbolEnvironment,
+    result::SemanticResult,
+    symbol_environment::{ScopeKind, SymbolEnvironment, SymbolKind},
     type_environment::TypeEnvironment,
 };
 
@@ -89,7 +90,7 @@ fn find_output_type<'a>(owner: &'a dyn HasVariables, name: &'a Id) -> Option<&'a
 pub fn apply(
     lib: &Library,
     _type_environment: &TypeEnvironment,
-    _symbol_environment: &SymbolEnvironment,
+    symbol_environment: &SymbolEnvironment,
 ) -> SemanticResult {
     // Collect the names from the library into a map so that
     // we can quickly look up invocations
@@ -101,7 +102,7 @@ pub fn apply(
     }
 
     // Walk the library to find all references to function blocks
-    let mut visitor = RuleFunctionBlockUse::new(&function_blocks);
+    let mut visitor = RuleFunctionBlockUse::new(&function_blocks, symbol_environment);
     visitor.walk(lib).map_err(|e| vec![e])
 }
 
@@ -110,17 +111,67 @@ struct RuleFunctionBlockUse<'a> {
     // declaration itself.
     function_blocks: &'a HashMap<TypeName, &'a FunctionBlockDeclaration>,
 
+    symbol_environment: &'a SymbolEnvironment,
+
     // Map of variable name to the function block name that is the implementation
     var_to_fb: HashMap<Id, TypeName>,
+
+    // The function block declaration that we are currently visiting (if any).
+    // This is the owner of methods that are invoked without a receiver.
+    current_function_block: Option<Id>,
 }
 impl<'a> RuleFunctionBlockUse<'a> {
-    fn new(decls: &'a HashMap<TypeName, &'a FunctionBlockDeclaration>) -> Self {
+    fn new(
+        decls: &'a HashMap<TypeName, &'a FunctionBlockDeclaration>,
+        symbol_environment: &'a SymbolEnvironment,
+    ) -> Self {
         Self {
             function_blocks: decls,
+            symbol_environment,
             var_to_fb: HashMap::new(),
+            current_function_block: None,
         }
     }
 
+    /// Returns true if the name is a method of the current function block
+    /// (including inherited methods).
+    fn is_own_method(&self, name: &Id) -> bool {
+        self.current_function_block
+            .as_ref()
+            .is_some_and(|fb| self.symbol_environment.find_method(fb, name).is_some())
+    }
+
+    /// Returns the function block or interface that owns the methods that
+    /// are invoked on the receiver or `None` if the owner is not known.
+    fn receiver_type(&self, receiver: &SymbolicVariableKind) -> Option<Id> {
+        let owner = match receiver {
+            SymbolicVariableKind::Named(named) => {
+                self.var_to_fb.get(&named.name).map(|ty| ty.name.clone())
+            }
+            SymbolicVariableKind::This(_) => self.current_function_block.clone(),
+            SymbolicVariableKind::Super(_) => self
+                .current_function_block
+                .as_ref()
+                .and_then(|fb| self.symbol_environment.get_base_types(fb).first().cloned()),
+            SymbolicVariableKind::Array(_)
+            | SymbolicVariableKind::Structured(_)
+            | SymbolicVariableKind::Deref(_) => None,
+        }?;
+
+        // Only check types that we know about so that we don't report errors
+        // for types that are not declared (those are reported elsewhere).
+        let is_known = self
+            .symbol_environment
+            .find(&owner, &ScopeKind::Global)
+            .is_some_and(|symbol| {
+                matches!(
+                    symbol.kind,
+                    SymbolKind::FunctionBlock | SymbolKind::Interface
+                )
+            });
+        is_known.then_some(owner)
+    }
+
     fn check_assignments(
         function_block: &FunctionBlockDeclaration,
         fb_call: &FbCall,
@@ -220,10 +271,23 @@ impl Visitor<Diagnostic> for RuleFunctionBlockUse<'_> {
         &mut self,
         node: &FunctionBlockDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.current_function_block = Some(node.name.name.clone());
         let res = node.recurse_visit(self);
 
         // Remove all items from var init decl since we have left this context
         self.var_to_fb.clear();
+        self.current_function_block = None;
+        res
+    }
+
+    fn visit_method_declaration(
+        &mut self,
+        node: &MethodDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        // Variables of the method are only visible within the method
+        let outer = self.var_to_fb.clone();
+        let res = node.recurse_visit(self);
+        self.var_to_fb = outer;
         res
     }
 
@@ -277,6 +341,9 @@ impl Visitor<Diagnostic> for RuleFunctionBlockUse<'_> {
                     }
                 }
             }
+            // Within a function block, the invocation may be of a method
+            // without the THIS qualifier.
+            None if self.is_own_method(&fb_call.var_name) => Ok(()),
             None => Err(Diagnostic::problem(
                 Problem::FunctionBlockNotInScope,
                 Label::span(fb_call.span(), "Function block invocation"),
@@ -284,11 +351,29 @@ impl Visitor<Diagnostic> for RuleFunctionBlockUse<'_> {
             .with_context_id("invocation", &fb_call.var_name)),
         }
     }
+
+    fn visit_method_call(&mut self, node: &MethodCall) -> Result<Self::Value, Diagnostic> {
+        if let Some(owner) = self.receiver_type(&node.receiver) {
+            if self
+                .symbol_environment
+                .find_method(&owner, &node.name)
+                .is_none()
+            {
+                return Err(Diagnostic::problem(
+                    Problem::MethodNotDeclared,
+                    Label::span(node.span(), "Method invocation"),
+                )
+                .with_context_id("type", &owner)
+                .with_context_id("method", &node.name));
+            }
+        }
+        node.recurse_visit(self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test_helpers::parse_and_resolve_types;
+    use crate::test_helpers::{parse_and_resolve_environments, parse_and_resolve_types};
 
     use super::*;
 
@@ -600,4 +685,112 @@ END_PROGRAM";
 
         assert!(result.is_ok())
     }
+
+    #[test]
+    fn apply_when_method_call_declared_then_ok() {
+        let program = "
+FUNCTION_BLOCK Callee
+METHOD Run
+END_METHOD
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK Caller
+VAR
+FB_INSTANCE : Callee;
+END_VAR
+FB_INSTANCE.Run();
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn apply_when_method_call_inherited_then_ok() {
+        let program = "
+FUNCTION_BLOCK Base
+METHOD Run
+END_METHOD
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK Derived EXTENDS Base
+SUPER.Run();
+THIS.Run();
+Run();
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn apply_when_method_call_undeclared_then_error() {
+        let program = "
+FUNCTION_BLOCK Callee
+METHOD Run
+END_METHOD
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK Caller
+VAR
+FB_INSTANCE : Callee;
+END_VAR
+FB_INSTANCE.Stop();
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_err());
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::MethodNotDeclared.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn apply_when_standard_function_blocks_invoked_then_ok() {
+        let program = "
+PROGRAM main
+VAR
+Start : BOOL;
+Timer : TON;
+Counter : CTU_DINT;
+Down : CTD;
+END_VAR
+Timer(IN := Start, PT := T#5s);
+Counter(CU := Timer.Q, R := FALSE, PV := 10);
+Down(CD := Start, LD := Timer.Q, PV := 5);
+END_PROGRAM";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn apply_when_standard_function_block_input_not_declared_then_error() {
+        let program = "
+PROGRAM main
+VAR
+Timer : TON;
+END_VAR
+Timer(INPUT := TRUE, PT := T#5s);
+END_PROGRAM";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::FunctionInvocationMissingInput.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
 }
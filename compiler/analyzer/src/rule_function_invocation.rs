@@ -0,0 +1,361 @@
+//! Semantic rule that invocations of functions (both functions declared in
+//! the library and standard functions) assign inputs and outputs that the
+//! function declares and, for non-formal invocations, assign every input.
+//!
+//! Standard functions that are extensible accept any number of additional
+//! inputs, for example `ADD(IN1 := 1, IN2 := 2, IN3 := 3)`.
+//!
+//! See 2.5.1.1.
+//!
+//! ## Passes
+//!
+//! ```ignore
+//! FUNCTION Scale : REAL
+//!    VAR_INPUT
+//!       Value : REAL;
+//!       Factor : REAL;
+//!    END_VAR
+//!    Scale := Value * Factor;
+//! END_FUNCTION
+//!
+//! PROGRAM main
+//!    VAR
+//!       Result : REAL;
+//!    END_VAR
+//!    Result := Scale(1.0, 2.0);
+//!    Result := LIMIT(MN := 0.0, IN := Result, MX := 10.0);
+//! END_PROGRAM
+//! ```
+//!
+//! ## Fails
+//!
+//! ```ignore
+//! PROGRAM main
+//!    VAR
+//!       Result : REAL;
+//!    END_VAR
+//!    Result := LIMIT(0.0, Result);
+//! END_PROGRAM
+//! ```
+use std::collections::HashMap;
+
+use ironplc_dsl::{
+    common::*,
+    core::{Id, Located},
+    diagnostic::{Diagnostic, Label},
+    textual::*,
+    visitor::Visitor,
+};
+use ironplc_problems::Problem;
+
+use crate::{
+    result::SemanticResult,
+    stdlib::functions::{find_standard_function, FunctionSignature},
+    symbol_environment::SymbolEnvironment,
+    type_environment::TypeEnvironment,
+};
+
+pub fn apply(
+    lib: &Library,
+    _type_environment: &TypeEnvironment,
+    _symbol_environment: &SymbolEnvironment,
+) -> SemanticResult {
+    let functions = lib
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            LibraryElementKind::FunctionDeclaration(func) => {
+                Some((func.name.clone(), FunctionSignature::from_declaration(func)))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut visitor = RuleFunctionInvocation {
+        functions,
+        diagnostics: vec![],
+    };
+
+    visitor.walk(lib).map_err(|e| vec![e])?;
+
+    if !visitor.diagnostics.is_empty() {
+        return Err(visitor.diagnostics);
+    }
+    Ok(())
+}
+
+struct RuleFunctionInvocation {
+    // Map of the name of a function declaration to the signature of the
+    // function.
+    functions: HashMap<Id, FunctionSignature>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl RuleFunctionInvocation {
+    fn check_invocation(&mut self, signature: &FunctionSignature, node: &Function) {
+        let mut formal: Vec<&NamedInput> = vec![];
+        let mut non_formal: Vec<&PositionalInput> = vec![];
+        let mut outputs: Vec<&Output> = vec![];
+        for param in node.param_assignment.iter() {
+            match param {
+                ParamAssignmentKind::NamedInput(n) => formal.push(n),
+                ParamAssignmentKind::PositionalInput(p) => non_formal.push(p),
+                ParamAssignmentKind::Output(o) => outputs.push(o),
+            }
+        }
+
+        if !formal.is_empty() && !non_formal.is_empty() {
+            self.diagnostics.push(
+                Diagnostic::problem(
+                    Problem::FunctionCallMixedArgTypes,
+                    Label::span(node.name.span(), "Function invocation"),
+                )
+                .with_context_id("function", &signature.name),
+            );
+            return;
+        }
+
+        for input in formal {
+            if signature.named_input(&input.name).is_some() {
+                continue;
+            }
+            self.diagnostics.push(
+                Diagnostic::problem(
+                    Problem::FunctionInvocationMissingInput,
+                    Label::span(input.name.span(), "Function invocation"),
+                )
+                .with_context_id("invocation", &signature.name)
+                .with_context_id("undefined input", &input.name),
+            );
+        }
+
+        if !non_formal.is_empty() {
+            let required = signature.inputs.len();
+            let is_valid_count = match signature.extensible {
+                true => non_formal.len() >= required,
+                false => non_formal.len() == required,
+            };
+            if !is_valid_count {
+                self.diagnostics.push(
+                    Diagnostic::problem(
+                        Problem::FunctionInvocationRequiresFormal,
+                        Label::span(node.name.span(), "Function invocation"),
+                    )
+                    .with_context_id("invocation", &signature.name)
+                    .with_context("required", &format!("{required}"))
+                    .with_context("actual", &format!("{}", non_formal.len())),
+                );
+            }
+        }
+
+        for output in outputs {
+            if signature.has_output(&output.src) {
+                continue;
+            }
+            self.diagnostics.push(
+                Diagnostic::problem(
+                    Problem::FunctionInvocationUndefinedOutput,
+                    Label::span(output.src.span(), "Function invocation"),
+                )
+                .with_context_id("invocation", &signature.name)
+                .with_context_id("source", &output.src)
+                .with_context("target", &output.tgt.to_string()),
+            );
+        }
+    }
+}
+
+impl Visitor<Diagnostic> for RuleFunctionInvocation {
+    type Value = ();
+
+    fn visit_function(&mut self, node: &Function) -> Result<(), Diagnostic> {
+        // Functions declared in the library take precedence over standard
+        // functions. Functions that are not known are reported by other rules.
+        let signature = self
+            .functions
+            .get(&node.name)
+            .cloned()
+            .or_else(|| find_standard_function(&node.name));
+        if let Some(signature) = signature {
+            self.check_invocation(&signature, node);
+        }
+        node.recurse_visit(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::parse_and_resolve_environments;
+
+    use super::*;
+
+    fn apply_program(program: &str) -> SemanticResult {
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        apply(&library, &type_env, &symbol_env)
+    }
+
+    fn assert_single_problem(result: SemanticResult, problem: Problem) {
+        let errors = result.unwrap_err();
+        assert_eq!(1, errors.len(), "{errors:?}");
+        assert_eq!(problem.code().to_string(), errors.first().unwrap().code);
+    }
+
+    #[test]
+    fn apply_when_standard_functions_valid_then_ok() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+x : INT;
+r : REAL;
+s : STRING;
+END_VAR
+x := LIMIT(0, x, 10);
+x := LIMIT(MN := 0, IN := x, MX := 10);
+x := SEL(TRUE, x, 1);
+x := MUX(1, x, 2, 3, 4);
+x := MAX(1, 2, 3, 4);
+x := ADD(IN1 := 1, IN2 := 2, IN3 := 3);
+r := INT_TO_REAL(x);
+x := LEN(s);
+s := CONCAT(s, 'a', 'b');
+x := ABS(IN := x);
+END_PROGRAM",
+        );
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn apply_when_standard_function_too_few_inputs_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+x : INT;
+END_VAR
+x := LIMIT(0, x);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::FunctionInvocationRequiresFormal);
+    }
+
+    #[test]
+    fn apply_when_standard_function_too_many_inputs_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+r : REAL;
+END_VAR
+r := SQRT(r, r);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::FunctionInvocationRequiresFormal);
+    }
+
+    #[test]
+    fn apply_when_standard_function_undefined_formal_input_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+x : INT;
+END_VAR
+x := LIMIT(MN := 0, IN := x, MAX := 10);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::FunctionInvocationMissingInput);
+    }
+
+    #[test]
+    fn apply_when_standard_function_mixed_inputs_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+x : INT;
+END_VAR
+x := LIMIT(0, IN := x, MX := 10);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::FunctionCallMixedArgTypes);
+    }
+
+    #[test]
+    fn apply_when_user_function_valid_then_ok() {
+        let result = apply_program(
+            "
+FUNCTION Scale : REAL
+VAR_INPUT
+Value : REAL;
+Factor : REAL;
+END_VAR
+VAR_OUTPUT
+Clamped : BOOL;
+END_VAR
+Scale := Value * Factor;
+END_FUNCTION
+
+PROGRAM main
+VAR
+r : REAL;
+b : BOOL;
+END_VAR
+r := Scale(1.0, 2.0);
+r := Scale(Value := 1.0, Clamped => b);
+END_PROGRAM",
+        );
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn apply_when_user_function_undefined_output_then_error() {
+        let result = apply_program(
+            "
+FUNCTION Scale : REAL
+VAR_INPUT
+Value : REAL;
+END_VAR
+Scale := Value;
+END_FUNCTION
+
+PROGRAM main
+VAR
+r : REAL;
+b : BOOL;
+END_VAR
+r := Scale(Value := 1.0, Clamped => b);
+END_PROGRAM",
+        );
+
+        assert_single_problem(result, Problem::FunctionInvocationUndefinedOutput);
+    }
+
+    #[test]
+    fn apply_when_user_function_same_name_as_standard_then_uses_declaration() {
+        let result = apply_program(
+            "
+FUNCTION LIMIT : INT
+VAR_INPUT
+IN : INT;
+END_VAR
+LIMIT := IN;
+END_FUNCTION
+
+PROGRAM main
+VAR
+x : INT;
+END_VAR
+x := LIMIT(x);
+END_PROGRAM",
+        );
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+}
@@ -171,27 +171,6 @@ impl Visitor<Diagnostic> for HierarchyVisitor {
 
         node.recurse_visit(self)
     }
-
-    fn visit_function_block_initial_value_assignment(
-        &mut self,
-        node: &ironplc_dsl::common::FunctionBlockInitialValueAssignment,
-    ) -> Result<Self::Value, Diagnostic> {
-        if let Some(existing) = self.pou_types.insert(
-            node.type_name.name.clone(),
-            (PouKind::FunctionBlock, node.type_name.name.span()),
-        ) {
-            self.problems.push(
-                Diagnostic::problem(
-                    Problem::PouDeclNameDuplicated,
-                    Label::span(node.type_name.name.span(), "POU"),
-                )
-                .with_secondary(Label::span(existing.1, "POU")),
-            );
-        }
-        self.context_type = Some((PouKind::Config, node.type_name.name.span()));
-
-        node.recurse_visit(self)
-    }
 }
 
 #[cfg(test)]
@@ -226,4 +205,29 @@ mod tests {
         // TODO
         // assert!(result.is_ok());
     }
+
+    #[test]
+    fn apply_when_program_has_function_block_instances_then_ok() {
+        let program = "
+        FUNCTION_BLOCK Callee
+            VAR
+               IN1: BOOL;
+            END_VAR
+            IN1 := TRUE;
+        END_FUNCTION_BLOCK
+
+        PROGRAM Caller
+            VAR
+                CalleeInstance1 : Callee;
+                CalleeInstance2 : Callee;
+            END_VAR
+            CalleeInstance1();
+        END_PROGRAM";
+
+        let library = parse_and_resolve_types(program);
+        let type_env = TypeEnvironment::new();
+        let symbol_env = SymbolEnvironment::new();
+        let result = apply(&library, &type_env, &symbol_env);
+        assert!(result.is_ok());
+    }
 }
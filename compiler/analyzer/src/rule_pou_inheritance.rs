@@ -0,0 +1,284 @@
+//! Semantic rule that the types that a function block or interface
+//! inherits from are declared and that a function block implements
+//! every method of the interfaces that the function block claims to
+//! implement.
+//!
+//! ## Passes
+//!
+//! ```ignore
+//! INTERFACE IStartable
+//!    METHOD Start : BOOL
+//!    END_METHOD
+//! END_INTERFACE
+//!
+//! FUNCTION_BLOCK Base
+//!    METHOD Start : BOOL
+//!    END_METHOD
+//! END_FUNCTION_BLOCK
+//!
+//! FUNCTION_BLOCK Motor EXTENDS Base IMPLEMENTS IStartable
+//! END_FUNCTION_BLOCK
+//! ```
+//!
+//! ## Fails
+//!
+//! ```ignore
+//! INTERFACE IStartable
+//!    METHOD Start : BOOL
+//!    END_METHOD
+//! END_INTERFACE
+//!
+//! FUNCTION_BLOCK Motor IMPLEMENTS IStartable
+//! END_FUNCTION_BLOCK
+//! ```
+use ironplc_dsl::{
+    common::*,
+    core::{Id, Located},
+    diagnostic::{Diagnostic, Label},
+    visitor::Visitor,
+};
+use ironplc_problems::Problem;
+
+use crate::{
+    result::SemanticResult,
+    symbol_environment::{ScopeKind, SymbolEnvironment, SymbolKind},
+    type_environment::TypeEnvironment,
+};
+
+pub fn apply(
+    lib: &Library,
+    _type_environment: &TypeEnvironment,
+    symbol_environment: &SymbolEnvironment,
+) -> SemanticResult {
+    let mut visitor = RulePouInheritance {
+        symbol_environment,
+        diagnostics: Vec::new(),
+    };
+    visitor.walk(lib).map_err(|e| vec![e])?;
+
+    if !visitor.diagnostics.is_empty() {
+        return Err(visitor.diagnostics);
+    }
+    Ok(())
+}
+
+struct RulePouInheritance<'a> {
+    symbol_environment: &'a SymbolEnvironment,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl RulePouInheritance<'_> {
+    /// Checks that the name refers to a declaration of the expected kind
+    /// and returns true if it does.
+    fn check_declared(&mut self, owner: &Id, name: &TypeName, expected: SymbolKind) -> bool {
+        let is_declared = self
+            .symbol_environment
+            .find(&name.name, &ScopeKind::Global)
+            .is_some_and(|symbol| symbol.kind == expected);
+        if !is_declared {
+            self.diagnostics.push(
+                Diagnostic::problem(
+                    Problem::InheritedTypeNotDeclared,
+                    Label::span(name.span(), "Inherited type"),
+                )
+                .with_context_id("declaration", owner)
+                .with_context_type("inherited type", name),
+            );
+        }
+        is_declared
+    }
+}
+
+impl Visitor<Diagnostic> for RulePouInheritance<'_> {
+    type Value = ();
+
+    fn visit_function_block_declaration(
+        &mut self,
+        node: &FunctionBlockDeclaration,
+    ) -> Result<(), Diagnostic> {
+        if let Some(base) = &node.extends {
+            self.check_declared(&node.name.name, base, SymbolKind::FunctionBlock);
+        }
+
+        for interface in &node.implements {
+            if !self.check_declared(&node.name.name, interface, SymbolKind::Interface) {
+                continue;
+            }
+
+            // An abstract function block leaves the implementation to
+            // the function blocks that extend it.
+            if node.modifier == Some(PouModifier::Abstract) {
+                continue;
+            }
+
+            let mut missing: Vec<&Id> = self
+                .symbol_environment
+                .get_methods(&interface.name)
+                .into_iter()
+                .filter(|(method, _)| {
+                    self.symbol_environment
+                        .find_method(&node.name.name, method)
+                        .is_none()
+                })
+                .map(|(method, _)| method)
+                .collect();
+            missing.sort_by_key(|method| method.lower_case().clone());
+
+            for method in missing {
+                self.diagnostics.push(
+                    Diagnostic::problem(
+                        Problem::InterfaceMethodNotImplemented,
+                        Label::span(interface.span(), "Implemented interface"),
+                    )
+                    .with_context_type("function block", &node.name)
+                    .with_context_id("method", method)
+                    .with_secondary(Label::span(method.span(), "Interface method")),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit_interface_declaration(
+        &mut self,
+        node: &InterfaceDeclaration,
+    ) -> Result<(), Diagnostic> {
+        for base in &node.extends {
+            self.check_declared(&node.name.name, base, SymbolKind::Interface);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::parse_and_resolve_environments;
+
+    use super::*;
+
+    #[test]
+    fn apply_when_function_block_implements_all_methods_then_ok() {
+        let program = "
+INTERFACE IStartable
+   METHOD Start : BOOL
+   END_METHOD
+END_INTERFACE
+
+FUNCTION_BLOCK Motor IMPLEMENTS IStartable
+   METHOD Start : BOOL
+      Start := TRUE;
+   END_METHOD
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_method_inherited_from_base_then_ok() {
+        let program = "
+INTERFACE IStartable
+   METHOD Start : BOOL
+   END_METHOD
+END_INTERFACE
+
+FUNCTION_BLOCK Base
+   METHOD Start : BOOL
+      Start := TRUE;
+   END_METHOD
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK Motor EXTENDS Base IMPLEMENTS IStartable
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_method_missing_then_error() {
+        let program = "
+INTERFACE IStartable
+   METHOD Start : BOOL
+   END_METHOD
+END_INTERFACE
+
+INTERFACE IStoppable EXTENDS IStartable
+   METHOD Stop : BOOL
+   END_METHOD
+END_INTERFACE
+
+FUNCTION_BLOCK Motor IMPLEMENTS IStoppable
+   METHOD Stop : BOOL
+      Stop := TRUE;
+   END_METHOD
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let errors = result.unwrap_err();
+        assert_eq!(1, errors.len());
+        assert_eq!(
+            Problem::InterfaceMethodNotImplemented.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn apply_when_function_block_is_abstract_then_ok() {
+        let program = "
+INTERFACE IStartable
+   METHOD Start : BOOL
+   END_METHOD
+END_INTERFACE
+
+FUNCTION_BLOCK ABSTRACT Motor IMPLEMENTS IStartable
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_implements_function_block_then_error() {
+        let program = "
+FUNCTION_BLOCK Base
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK Motor IMPLEMENTS Base
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::InheritedTypeNotDeclared.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn apply_when_extends_undeclared_then_error() {
+        let program = "
+FUNCTION_BLOCK Motor EXTENDS Base
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::InheritedTypeNotDeclared.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+}
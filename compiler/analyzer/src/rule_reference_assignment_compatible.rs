@@ -0,0 +1,365 @@
+//! Semantic rule that assignments involving references have compatible
+//! types. A reference may be assigned `NULL`, `REF()` of a variable having
+//! the referenced type or another reference to the same type. `REF()` and
+//! `NULL` may only be assigned to a reference.
+//!
+//! ## Passes
+//!
+//! ```ignore
+//! PROGRAM main
+//!    VAR
+//!       Value : INT;
+//!       ValueRef : REF_TO INT;
+//!    END_VAR
+//!    ValueRef := REF(Value);
+//!    ValueRef := NULL;
+//! END_PROGRAM
+//! ```
+//!
+//! ## Fails
+//!
+//! ```ignore
+//! PROGRAM main
+//!    VAR
+//!       Value : REAL;
+//!       ValueRef : REF_TO INT;
+//!    END_VAR
+//!    ValueRef := REF(Value);
+//! END_PROGRAM
+//! ```
+use ironplc_dsl::{
+    common::*,
+    core::{Id, Located},
+    diagnostic::{Diagnostic, Label},
+    textual::{Assignment, ExprKind, SymbolicVariableKind, Variable},
+    visitor::Visitor,
+};
+use ironplc_problems::Problem;
+
+use crate::{
+    result::SemanticResult,
+    scoped_table::{ScopedTable, Value},
+    symbol_environment::SymbolEnvironment,
+    type_environment::TypeEnvironment,
+};
+
+pub fn apply(
+    lib: &Library,
+    type_environment: &TypeEnvironment,
+    _symbol_environment: &SymbolEnvironment,
+) -> SemanticResult {
+    let mut visitor = RuleReferenceAssignmentCompatible {
+        scope: ScopedTable::new(),
+        type_environment,
+        diagnostics: vec![],
+    };
+
+    visitor.walk(lib).map_err(|e| vec![e])?;
+
+    if !visitor.diagnostics.is_empty() {
+        return Err(visitor.diagnostics);
+    }
+    Ok(())
+}
+
+/// The declared type of a variable as relevant to references.
+#[derive(Debug, Clone)]
+enum DeclaredType {
+    /// The variable is a reference to the named type.
+    Reference(TypeName),
+    /// The variable is not a reference and has the type (if the type is named).
+    Value(Option<TypeName>),
+}
+impl Value for DeclaredType {}
+
+/// The type of the right hand side of an assignment as relevant to references.
+enum AssignedType {
+    Null,
+    /// A reference to the named type (if the type is known).
+    Reference(Option<TypeName>),
+    Value,
+}
+
+struct RuleReferenceAssignmentCompatible<'a> {
+    scope: ScopedTable<'a, Id, DeclaredType>,
+    type_environment: &'a TypeEnvironment,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl RuleReferenceAssignmentCompatible<'_> {
+    fn declared_type(&self, init: &InitialValueAssignmentKind) -> DeclaredType {
+        let type_name = match init {
+            InitialValueAssignmentKind::Reference(reference) => {
+                return DeclaredType::Reference(reference.referenced_type_name.clone())
+            }
+            InitialValueAssignmentKind::Simple(simple) => Some(simple.type_name.clone()),
+            InitialValueAssignmentKind::EnumeratedType(enumerated) => {
+                Some(enumerated.type_name.clone())
+            }
+            InitialValueAssignmentKind::FunctionBlock(fb) => Some(fb.type_name.clone()),
+            InitialValueAssignmentKind::Structure(structure) => Some(structure.type_name.clone()),
+            InitialValueAssignmentKind::Subrange(SubrangeSpecificationKind::Type(type_name)) => {
+                Some(type_name.clone())
+            }
+            _ => None,
+        };
+
+        // A variable may be a reference through a named reference type.
+        if let Some(name) = &type_name {
+            if let Some(attrs) = self.type_environment.get(name) {
+                if let crate::intermediate_type::IntermediateType::Reference {
+                    referenced_type_name,
+                } = &attrs.representation
+                {
+                    return DeclaredType::Reference(referenced_type_name.clone());
+                }
+            }
+        }
+
+        DeclaredType::Value(type_name)
+    }
+
+    /// Returns the declared type of a variable if the variable is a
+    /// named variable in the current scope.
+    fn find_variable(&mut self, variable: &SymbolicVariableKind) -> Option<DeclaredType> {
+        match variable {
+            SymbolicVariableKind::Named(named) => self.scope.find(&named.name).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of the expression or `None` if the expression
+    /// type is not known by this rule.
+    fn assigned_type(&mut self, expr: &ExprKind) -> Option<AssignedType> {
+        match expr {
+            ExprKind::Expression(inner) => self.assigned_type(inner),
+            ExprKind::Null(_) => Some(AssignedType::Null),
+            ExprKind::Ref(reference) => match self.find_variable(&reference.variable) {
+                Some(DeclaredType::Value(type_name)) => Some(AssignedType::Reference(type_name)),
+                _ => Some(AssignedType::Reference(None)),
+            },
+            ExprKind::Variable(Variable::Symbolic(variable)) => {
+                match self.find_variable(variable)? {
+                    DeclaredType::Reference(type_name) => {
+                        Some(AssignedType::Reference(Some(type_name)))
+                    }
+                    DeclaredType::Value(_) => Some(AssignedType::Value),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if the types are the same type. Types are the same
+    /// when they have the same name after resolving aliases; distinct
+    /// types with the same layout are different types.
+    fn is_same_type(&self, left: &TypeName, right: &TypeName) -> bool {
+        self.type_environment.resolve_alias(left) == self.type_environment.resolve_alias(right)
+    }
+}
+
+impl Visitor<Diagnostic> for RuleReferenceAssignmentCompatible<'_> {
+    type Value = ();
+
+    fn visit_function_declaration(&mut self, node: &FunctionDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_program_declaration(&mut self, node: &ProgramDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_function_block_declaration(
+        &mut self,
+        node: &FunctionBlockDeclaration,
+    ) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_method_declaration(&mut self, node: &MethodDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_var_decl(&mut self, node: &VarDecl) -> Result<(), Diagnostic> {
+        let declared_type = self.declared_type(&node.initializer);
+        self.scope
+            .add_if(node.identifier.symbolic_id(), declared_type);
+        node.recurse_visit(self)
+    }
+
+    fn visit_assignment(&mut self, node: &Assignment) -> Result<(), Diagnostic> {
+        let Variable::Symbolic(target) = &node.target else {
+            return node.recurse_visit(self);
+        };
+        let (Some(target_type), Some(value_type)) =
+            (self.find_variable(target), self.assigned_type(&node.value))
+        else {
+            return node.recurse_visit(self);
+        };
+
+        let is_compatible = match (&target_type, &value_type) {
+            (DeclaredType::Reference(_), AssignedType::Null) => true,
+            (DeclaredType::Reference(target), AssignedType::Reference(Some(value))) => {
+                self.is_same_type(target, value)
+            }
+            // The type that the value refers to is not known
+            (DeclaredType::Reference(_), AssignedType::Reference(None)) => true,
+            (DeclaredType::Reference(_), AssignedType::Value) => false,
+            (DeclaredType::Value(_), AssignedType::Null | AssignedType::Reference(_)) => false,
+            (DeclaredType::Value(_), AssignedType::Value) => true,
+        };
+
+        if !is_compatible {
+            self.diagnostics.push(
+                Diagnostic::problem(
+                    Problem::ReferenceAssignmentIncompatible,
+                    Label::span(target.span(), "Assignment target"),
+                )
+                .with_context("target", &target.to_string()),
+            );
+        }
+
+        node.recurse_visit(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::parse_and_resolve_environments;
+
+    use super::*;
+
+    fn assert_error(program: &str) {
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_err());
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::ReferenceAssignmentIncompatible.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn apply_when_assign_ref_and_null_then_ok() {
+        let program = "
+TYPE
+INT_REF : REF_TO INT;
+END_TYPE
+
+PROGRAM main
+VAR
+Value : INT;
+ValueRef : REF_TO INT;
+OtherRef : INT_REF;
+END_VAR
+ValueRef := REF(Value);
+OtherRef := ValueRef;
+ValueRef := NULL;
+Value := ValueRef^;
+END_PROGRAM";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_assign_ref_of_alias_type_then_ok() {
+        let program = "
+TYPE
+COUNT : INT := 0;
+END_TYPE
+
+PROGRAM main
+VAR
+Value : COUNT;
+ValueRef : REF_TO INT;
+END_VAR
+ValueRef := REF(Value);
+END_PROGRAM";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_assign_ref_of_struct_with_same_fields_then_error() {
+        assert_error(
+            "
+TYPE
+A : STRUCT
+x : INT;
+END_STRUCT;
+B : STRUCT
+x : INT;
+END_STRUCT;
+END_TYPE
+
+PROGRAM main
+VAR
+Value : B;
+ValueRef : REF_TO A;
+END_VAR
+ValueRef := REF(Value);
+END_PROGRAM",
+        );
+    }
+
+    #[test]
+    fn apply_when_assign_ref_of_different_type_then_error() {
+        assert_error(
+            "
+PROGRAM main
+VAR
+Value : REAL;
+ValueRef : REF_TO INT;
+END_VAR
+ValueRef := REF(Value);
+END_PROGRAM",
+        );
+    }
+
+    #[test]
+    fn apply_when_assign_value_to_reference_then_error() {
+        assert_error(
+            "
+PROGRAM main
+VAR
+Value : INT;
+ValueRef : REF_TO INT;
+END_VAR
+ValueRef := Value;
+END_PROGRAM",
+        );
+    }
+
+    #[test]
+    fn apply_when_assign_null_to_value_then_error() {
+        assert_error(
+            "
+PROGRAM main
+VAR
+Value : INT;
+END_VAR
+Value := NULL;
+END_PROGRAM",
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! Semantic rule that field access to structured variables and structure
+//! initializers only use fields that are declared in the structure,
+//! including fields of nested structures. Access to a member of a function
+//! block instance must name an input, output or property of the function
+//! block (or a function block that it extends).
+//!
+//! See 2.4.1.2 and 2.5.2.1.
+//!
+//! ## Passes
+//!
+//! ```ignore
+//! TYPE
+//!    Point : STRUCT
+//!       x : INT;
+//!    END_STRUCT;
+//!    Line : STRUCT
+//!       start : Point;
+//!    END_STRUCT;
+//! END_TYPE
+//!
+//! PROGRAM main
+//!    VAR
+//!       l : Line := (start := (x := 1));
+//!    END_VAR
+//!    l.start.x := 2;
+//! END_PROGRAM
+//! ```
+//!
+//! ## Fails
+//!
+//! ```ignore
+//! TYPE
+//!    Point : STRUCT
+//!       x : INT;
+//!    END_STRUCT;
+//!    Line : STRUCT
+//!       start : Point;
+//!    END_STRUCT;
+//! END_TYPE
+//!
+//! PROGRAM main
+//!    VAR
+//!       l : Line;
+//!    END_VAR
+//!    l.start.z := 2;
+//! END_PROGRAM
+//! ```
+use std::collections::HashMap;
+
+use ironplc_dsl::{
+    common::*,
+    core::{Id, Located},
+    diagnostic::{Diagnostic, Label},
+    textual::{StructuredVariable, SymbolicVariableKind},
+    visitor::Visitor,
+};
+use ironplc_problems::Problem;
+
+use crate::{
+    intermediate_type::IntermediateType,
+    result::SemanticResult,
+    scoped_table::{ScopedTable, Value},
+    symbol_environment::SymbolEnvironment,
+    type_environment::TypeEnvironment,
+};
+
+pub fn apply(
+    lib: &Library,
+    type_environment: &TypeEnvironment,
+    _symbol_environment: &SymbolEnvironment,
+) -> SemanticResult {
+    let function_blocks = lib
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            LibraryElementKind::FunctionBlockDeclaration(fb) => Some((fb.name.clone(), fb)),
+            _ => None,
+        })
+        .collect();
+
+    let mut visitor = RuleUseDeclaredStructField {
+        scope: ScopedTable::new(),
+        type_environment,
+        function_blocks,
+        diagnostics: vec![],
+    };
+
+    visitor.walk(lib).map_err(|e| vec![e])?;
+
+    if !visitor.diagnostics.is_empty() {
+        return Err(visitor.diagnostics);
+    }
+    Ok(())
+}
+
+/// The name of the type of a declared variable.
+#[derive(Debug)]
+struct DeclaredType(TypeName);
+impl Value for DeclaredType {}
+
+struct RuleUseDeclaredStructField<'a> {
+    scope: ScopedTable<'a, Id, DeclaredType>,
+    type_environment: &'a TypeEnvironment,
+    // Map of the name of a function block declaration to the declaration.
+    function_blocks: HashMap<TypeName, &'a FunctionBlockDeclaration>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> RuleUseDeclaredStructField<'a> {
+    /// Returns the type of the variable or `None` if the type is not known.
+    fn find_type(&mut self, variable: &SymbolicVariableKind) -> Option<&'a IntermediateType> {
+        match variable {
+            SymbolicVariableKind::Named(named) => {
+                let DeclaredType(type_name) = self.scope.find(&named.name)?;
+                let type_environment = self.type_environment;
+                type_environment
+                    .get(type_name)
+                    .map(|attrs| &attrs.representation)
+            }
+            SymbolicVariableKind::Structured(structured) => self
+                .find_type(&structured.record)?
+                .get_field(&structured.field)
+                .map(|field| &field.field_type),
+            _ => None,
+        }
+    }
+
+    /// Returns the function block declaration that is the type of the
+    /// variable or `None` if the variable is not a function block instance.
+    fn find_function_block(
+        &mut self,
+        variable: &SymbolicVariableKind,
+    ) -> Option<&'a FunctionBlockDeclaration> {
+        match variable {
+            SymbolicVariableKind::Named(named) => {
+                let DeclaredType(type_name) = self.scope.find(&named.name)?;
+                self.function_blocks.get(type_name).copied()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns true if the function block (or a function block that it
+    /// extends) declares an input, output or property with the name.
+    fn declares_member(&self, function_block: &FunctionBlockDeclaration, name: &Id) -> bool {
+        let mut current = Some(function_block);
+        // Bound the search so that inheritance cycles (reported by other
+        // rules) terminate.
+        for _ in 0..=self.function_blocks.len() {
+            let Some(fb) = current else {
+                return false;
+            };
+            let is_variable = fb.variables.iter().any(|var| {
+                matches!(var.var_type, VariableType::Input | VariableType::Output)
+                    && var.identifier.symbolic_id() == Some(name)
+            });
+            let is_edge_variable = fb.edge_variables.iter().any(|var| var.identifier == *name);
+            let is_property = fb.properties.iter().any(|prop| prop.name == *name);
+            if is_variable || is_edge_variable || is_property {
+                return true;
+            }
+            current = fb
+                .extends
+                .as_ref()
+                .and_then(|base| self.function_blocks.get(base).copied());
+        }
+        false
+    }
+
+    /// Checks that the initializer only assigns fields of the structure type.
+    fn check_initializer(
+        &mut self,
+        structure_type: &IntermediateType,
+        type_name: &TypeName,
+        elements: &[StructureElementInit],
+    ) {
+        if !structure_type.is_structure() {
+            return;
+        }
+
+        for element in elements {
+            match structure_type.get_field(&element.name) {
+                Some(field) => {
+                    if let StructInitialValueAssignmentKind::Structure(nested) = &element.init {
+                        self.check_initializer(&field.field_type, type_name, nested);
+                    }
+                }
+                None => self.diagnostics.push(
+                    Diagnostic::problem(
+                        Problem::StructFieldNotDeclared,
+                        Label::span(element.name.span(), "Field"),
+                    )
+                    .with_context_type("structure", type_name)
+                    .with_context_id("field", &element.name),
+                ),
+            }
+        }
+    }
+}
+
+impl Visitor<Diagnostic> for RuleUseDeclaredStructField<'_> {
+    type Value = ();
+
+    fn visit_function_declaration(&mut self, node: &FunctionDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_program_declaration(&mut self, node: &ProgramDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_function_block_declaration(
+        &mut self,
+        node: &FunctionBlockDeclaration,
+    ) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_method_declaration(&mut self, node: &MethodDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        let ret = node.recurse_visit(self);
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_var_decl(&mut self, node: &VarDecl) -> Result<(), Diagnostic> {
+        let type_name = match &node.initializer {
+            InitialValueAssignmentKind::Simple(simple) => Some(&simple.type_name),
+            InitialValueAssignmentKind::EnumeratedType(enumerated) => Some(&enumerated.type_name),
+            InitialValueAssignmentKind::Structure(structure) => Some(&structure.type_name),
+            InitialValueAssignmentKind::LateResolvedType(type_name) => Some(type_name),
+            InitialValueAssignmentKind::FunctionBlock(fb) => Some(&fb.type_name),
+            _ => None,
+        };
+        if let Some(type_name) = type_name {
+            self.scope.add_if(
+                node.identifier.symbolic_id(),
+                DeclaredType(type_name.clone()),
+            );
+        }
+        node.recurse_visit(self)
+    }
+
+    fn visit_structure_initialization_declaration(
+        &mut self,
+        node: &StructureInitializationDeclaration,
+    ) -> Result<(), Diagnostic> {
+        let type_environment = self.type_environment;
+        if let Some(attrs) = type_environment.get(&node.type_name) {
+            self.check_initializer(&attrs.representation, &node.type_name, &node.elements_init);
+        }
+        node.recurse_visit(self)
+    }
+
+    fn visit_structured_variable(&mut self, node: &StructuredVariable) -> Result<(), Diagnostic> {
+        if let Some(function_block) = self.find_function_block(&node.record) {
+            if !self.declares_member(function_block, &node.field) {
+                self.diagnostics.push(
+                    Diagnostic::problem(
+                        Problem::FunctionBlockMemberNotDeclared,
+                        Label::span(node.field.span(), "Member"),
+                    )
+                    .with_context_type("function block", &function_block.name)
+                    .with_context_id("member", &node.field),
+                );
+            }
+            return node.recurse_visit(self);
+        }
+
+        // Unknown variables are checked by other rules.
+        match self.find_type(&node.record) {
+            Some(record_type @ IntermediateType::Structure { .. }) => {
+                if record_type.get_field(&node.field).is_none() {
+                    self.diagnostics.push(
+                        Diagnostic::problem(
+                            Problem::StructFieldNotDeclared,
+                            Label::span(node.field.span(), "Field"),
+                        )
+                        .with_context_id("field", &node.field),
+                    );
+                }
+            }
+            Some(IntermediateType::FunctionBlock { .. })
+            | Some(IntermediateType::Reference { .. })
+            | None => {}
+            Some(_) => {
+                self.diagnostics.push(
+                    Diagnostic::problem(
+                        Problem::FieldAccessNotStructure,
+                        Label::span(node.span(), "Field access"),
+                    )
+                    .with_context_id("field", &node.field),
+                );
+            }
+        }
+        node.recurse_visit(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::parse_and_resolve_environments;
+
+    use super::*;
+
+    const TYPES: &str = "
+TYPE
+Color : (Red, Green) := Red;
+Point : STRUCT
+   x : INT;
+   c : Color := Red;
+END_STRUCT;
+Line : STRUCT
+   start : Point;
+   finish : Point := (x := 1);
+END_STRUCT;
+END_TYPE
+";
+
+    fn apply_program(program: &str) -> SemanticResult {
+        let program = format!("{TYPES}{program}");
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(&program);
+        apply(&library, &type_env, &symbol_env)
+    }
+
+    #[test]
+    fn apply_when_nested_field_declared_then_ok() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+l : Line;
+v : INT;
+END_VAR
+l.start.x := 1;
+v := l.finish.x;
+l.start.c := Green;
+END_PROGRAM",
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_nested_field_not_declared_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+l : Line;
+END_VAR
+l.start.z := 1;
+END_PROGRAM",
+        );
+
+        let errors = result.unwrap_err();
+        assert_eq!(1, errors.len());
+        assert_eq!(
+            Problem::StructFieldNotDeclared.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn apply_when_field_of_elementary_type_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+l : Line;
+END_VAR
+l.start.x.y := 1;
+END_PROGRAM",
+        );
+
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::FieldAccessNotStructure.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn apply_when_nested_initializer_declared_then_ok() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+l : Line := (start := (x := 1, c := Green), finish := (x := 2));
+END_VAR
+END_PROGRAM",
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_nested_initializer_not_declared_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+l : Line := (start := (z := 1));
+END_VAR
+END_PROGRAM",
+        );
+
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::StructFieldNotDeclared.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn apply_when_function_block_member_declared_then_ok() {
+        let result = apply_program(
+            "
+FUNCTION_BLOCK Base
+VAR_INPUT
+Enable : BOOL;
+END_VAR
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK Derived EXTENDS Base
+VAR_OUTPUT
+Done : BOOL;
+END_VAR
+END_FUNCTION_BLOCK
+
+PROGRAM main
+VAR
+d : Derived;
+t : TON;
+b : BOOL;
+END_VAR
+d.Enable := TRUE;
+b := d.Done;
+b := t.Q;
+END_PROGRAM",
+        );
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn apply_when_function_block_member_not_declared_then_error() {
+        let result = apply_program(
+            "
+PROGRAM main
+VAR
+t : TON;
+b : BOOL;
+END_VAR
+b := t.Done;
+END_PROGRAM",
+        );
+
+        let errors = result.unwrap_err();
+        assert_eq!(1, errors.len());
+        assert_eq!(
+            Problem::FunctionBlockMemberNotDeclared.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn apply_when_function_block_internal_variable_then_error() {
+        let result = apply_program(
+            "
+FUNCTION_BLOCK Callee
+VAR
+Internal : BOOL;
+END_VAR
+END_FUNCTION_BLOCK
+
+PROGRAM main
+VAR
+c : Callee;
+b : BOOL;
+END_VAR
+b := c.Internal;
+END_PROGRAM",
+        );
+
+        let errors = result.unwrap_err();
+        assert_eq!(
+            Problem::FunctionBlockMemberNotDeclared.code().to_string(),
+            errors.first().unwrap().code
+        );
+    }
+}
@@ -40,15 +40,15 @@
 //! ```
 use ironplc_dsl::{
     common::*,
-    core::{Id, Located},
-    diagnostic::{Diagnostic, Label},
+    core::{Id, Located, SourceSpan},
+    diagnostic::{Diagnostic, Fix, Label, TextEdit},
     visitor::Visitor,
 };
 use ironplc_problems::Problem;
 
 use crate::{
     result::SemanticResult,
-    scoped_table::{self, Key, ScopedTable, Value},
+    scoped_table::{Key, ScopedTable, Value},
     symbol_environment::SymbolEnvironment,
     type_environment::TypeEnvironment,
 };
@@ -56,9 +56,9 @@ use crate::{
 pub fn apply(
     lib: &Library,
     _type_environment: &TypeEnvironment,
-    _symbol_environment: &SymbolEnvironment,
+    symbol_environment: &SymbolEnvironment,
 ) -> SemanticResult {
-    let mut visitor: ScopedTable<Id, DummyNode> = scoped_table::ScopedTable::new();
+    let mut visitor = RuleUseDeclaredSymbolicVar::new(symbol_environment);
 
     visitor.walk(lib).map_err(|e| vec![e])
 }
@@ -70,23 +70,78 @@ impl Value for DummyNode {}
 impl Key for Id {}
 impl Key for TypeName {}
 
-impl Visitor<Diagnostic> for ScopedTable<'_, Id, DummyNode> {
+struct RuleUseDeclaredSymbolicVar<'a> {
+    scope: ScopedTable<'a, Id, DummyNode>,
+    symbol_environment: &'a SymbolEnvironment,
+
+    // The function block declaration that we are currently visiting (if any).
+    // Variables declared in base function blocks are visible in the
+    // function block.
+    current_function_block: Option<Id>,
+
+    // The position after which a variable block can be inserted in the
+    // declaration that we are currently visiting (if known).
+    declarations_start: Option<SourceSpan>,
+}
+
+impl<'a> RuleUseDeclaredSymbolicVar<'a> {
+    fn new(symbol_environment: &'a SymbolEnvironment) -> Self {
+        Self {
+            scope: ScopedTable::new(),
+            symbol_environment,
+            current_function_block: None,
+            declarations_start: None,
+        }
+    }
+
+    /// Visits the declaration with the position where variable blocks
+    /// can be inserted.
+    fn with_declarations_start<F>(
+        &mut self,
+        span: Option<SourceSpan>,
+        visit: F,
+    ) -> Result<(), Diagnostic>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Diagnostic>,
+    {
+        // Positions not from source text have no end.
+        let span = span.filter(|span| span.end > 0);
+        let prior = std::mem::replace(&mut self.declarations_start, span);
+        let ret = visit(self);
+        self.declarations_start = prior;
+        ret
+    }
+
+    /// Returns true if the name is declared by a base of the current
+    /// function block.
+    fn is_inherited(&self, name: &Id) -> bool {
+        self.current_function_block.as_ref().is_some_and(|fb| {
+            self.symbol_environment
+                .get_base_types(fb)
+                .iter()
+                .any(|base| self.symbol_environment.find_inherited(name, base).is_some())
+        })
+    }
+}
+
+impl Visitor<Diagnostic> for RuleUseDeclaredSymbolicVar<'_> {
     type Value = ();
 
     fn visit_function_declaration(&mut self, node: &FunctionDeclaration) -> Result<(), Diagnostic> {
-        self.enter();
+        self.scope.enter();
 
-        self.add(&node.name, DummyNode {});
-        let ret = node.recurse_visit(self);
-        self.exit();
+        self.scope.add(&node.name, DummyNode {});
+        let ret =
+            self.with_declarations_start(Some(node.return_type.span()), |v| node.recurse_visit(v));
+        self.scope.exit();
         ret
     }
 
     fn visit_program_declaration(&mut self, node: &ProgramDeclaration) -> Result<(), Diagnostic> {
-        self.enter();
-        self.add(&node.name, DummyNode {});
-        let ret = node.recurse_visit(self);
-        self.exit();
+        self.scope.enter();
+        self.scope.add(&node.name, DummyNode {});
+        let ret = self.with_declarations_start(Some(node.name.span()), |v| node.recurse_visit(v));
+        self.scope.exit();
         ret
     }
 
@@ -94,15 +149,45 @@ impl Visitor<Diagnostic> for ScopedTable<'_, Id, DummyNode> {
         &mut self,
         node: &FunctionBlockDeclaration,
     ) -> Result<(), Diagnostic> {
-        self.enter();
-        self.add(&node.name.name, DummyNode {});
-        let ret = node.recurse_visit(self);
-        self.exit();
+        self.scope.enter();
+        self.current_function_block = Some(node.name.name.clone());
+        self.scope.add(&node.name.name, DummyNode {});
+        let header_end = node
+            .implements
+            .last()
+            .or(node.extends.as_ref())
+            .unwrap_or(&node.name)
+            .span();
+        let ret = self.with_declarations_start(Some(header_end), |v| node.recurse_visit(v));
+        self.current_function_block = None;
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_method_declaration(&mut self, node: &MethodDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        self.scope.add(&node.name, DummyNode {});
+        let header_end = node
+            .return_type
+            .as_ref()
+            .map(|return_type| return_type.span())
+            .unwrap_or_else(|| node.name.span());
+        let ret = self.with_declarations_start(Some(header_end), |v| node.recurse_visit(v));
+        self.scope.exit();
+        ret
+    }
+
+    fn visit_property_declaration(&mut self, node: &PropertyDeclaration) -> Result<(), Diagnostic> {
+        self.scope.enter();
+        self.scope.add(&node.name, DummyNode {});
+        let ret = self.with_declarations_start(None, |v| node.recurse_visit(v));
+        self.scope.exit();
         ret
     }
 
     fn visit_var_decl(&mut self, node: &VarDecl) -> Result<Self::Value, Diagnostic> {
-        self.add_if(node.identifier.symbolic_id(), DummyNode {});
+        self.scope
+            .add_if(node.identifier.symbolic_id(), DummyNode {});
         node.recurse_visit(self)
     }
 
@@ -110,23 +195,30 @@ impl Visitor<Diagnostic> for ScopedTable<'_, Id, DummyNode> {
         &mut self,
         node: &ironplc_dsl::textual::NamedVariable,
     ) -> Result<(), Diagnostic> {
-        match self.find(&node.name) {
-            Some(_) => {
-                // We found the variable being referred to
-                Ok(())
-            }
-            None => Err(Diagnostic::problem(
-                Problem::VariableUndefined,
-                Label::span(node.name.span(), "Undefined variable"),
-            )
-            .with_context_id("variable", &node.name)),
+        if self.scope.find(&node.name).is_some() || self.is_inherited(&node.name) {
+            // We found the variable being referred to
+            return Ok(());
+        }
+        let mut diagnostic = Diagnostic::problem(
+            Problem::VariableUndefined,
+            Label::span(node.name.span(), "Undefined variable"),
+        )
+        .with_context_id("variable", &node.name);
+        if let Some(span) = &self.declarations_start {
+            // The type is not known, so the declaration uses a type that
+            // the user is expected to change.
+            diagnostic = diagnostic.with_fix(Fix::new(
+                format!("Declare variable {}", node.name),
+                TextEdit::insert_after(span, format!("\nVAR\n    {} : INT;\nEND_VAR", node.name)),
+            ));
         }
+        Err(diagnostic)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test_helpers::parse_and_resolve_types;
+    use crate::test_helpers::{parse_and_resolve_environments, parse_and_resolve_types};
 
     use super::*;
 
@@ -155,6 +247,31 @@ END_FUNCTION_BLOCK";
             .contains(&"variable=TRIG".to_owned()))
     }
 
+    #[test]
+    fn apply_when_function_block_undeclared_symbol_then_fix_declares_variable() {
+        let program = "
+FUNCTION_BLOCK LOGGER
+VAR
+TRIG0 : BOOL;
+END_VAR
+
+TRIG := TRIG0;
+END_FUNCTION_BLOCK";
+
+        let library = parse_and_resolve_types(program);
+        let type_env = TypeEnvironment::new();
+        let symbol_env = SymbolEnvironment::new();
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let diagnostics = result.unwrap_err();
+        let edit = &diagnostics[0].fixes[0].edits[0];
+        assert_eq!("\nVAR\n    TRIG : INT;\nEND_VAR", edit.new_text);
+        assert_eq!(
+            program.find("LOGGER").unwrap() + "LOGGER".len(),
+            edit.location.start
+        );
+    }
+
     #[test]
     fn apply_when_function_block_all_symbol_declared_then_ok() {
         let program = "
@@ -236,4 +353,51 @@ END_FUNCTION_BLOCK";
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn apply_when_method_uses_own_and_inherited_variables_then_ok() {
+        let program = "
+FUNCTION_BLOCK Base
+VAR
+Value : INT;
+END_VAR
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK Derived EXTENDS Base
+VAR
+Limit : INT;
+END_VAR
+METHOD Add : INT
+VAR_INPUT
+Amount : INT;
+END_VAR
+Value := Value + Amount;
+Add := Limit;
+END_METHOD
+Value := 0;
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn apply_when_method_variable_used_outside_method_then_error() {
+        let program = "
+FUNCTION_BLOCK Counter
+METHOD Add
+VAR_INPUT
+Amount : INT;
+END_VAR
+END_METHOD
+Amount := 0;
+END_FUNCTION_BLOCK";
+
+        let (library, type_env, symbol_env) = parse_and_resolve_environments(program);
+        let result = apply(&library, &type_env, &symbol_env);
+
+        assert!(result.is_err());
+    }
 }
@@ -30,8 +30,8 @@
 //! references the value (and still be constant).
 use ironplc_dsl::{
     common::*,
-    core::Located,
-    diagnostic::{Diagnostic, Label},
+    core::{Located, SourceSpan},
+    diagnostic::{Diagnostic, Fix, Label, TextEdit},
     visitor::Visitor,
 };
 use ironplc_problems::Problem;
@@ -61,6 +61,44 @@ struct RuleConstantVarsInitialized {
     diagnostics: Vec<Diagnostic>,
 }
 
+impl RuleConstantVarsInitialized {
+    fn missing_initializer(&mut self, node: &VarDecl, message: &str, fix: Option<Fix>) {
+        let mut diagnostic = Diagnostic::problem(
+            Problem::ConstantMustHaveInitializer,
+            Label::span(node.span(), message),
+        )
+        .with_context("variable", &node.identifier.to_string());
+        if let Some(fix) = fix {
+            diagnostic = diagnostic.with_fix(fix);
+        }
+        self.diagnostics.push(diagnostic);
+    }
+}
+
+/// Creates a fix that adds the initial value after the span (normally the
+/// end of the type in the declaration).
+fn add_initializer(span: &SourceSpan, value: &str) -> Fix {
+    Fix::new(
+        format!("Initialize with {value}"),
+        TextEdit::insert_after(span, format!(" := {value}")),
+    )
+}
+
+/// Returns the default initial value for elementary types.
+fn default_value(type_name: &TypeName) -> Option<&'static str> {
+    match type_name.name.lower_case().as_str() {
+        "bool" => Some("FALSE"),
+        "sint" | "int" | "dint" | "lint" | "usint" | "uint" | "udint" | "ulint" => Some("0"),
+        "byte" | "word" | "dword" | "lword" => Some("0"),
+        "real" | "lreal" => Some("0.0"),
+        "time" => Some("T#0s"),
+        "date" => Some("D#1970-01-01"),
+        "time_of_day" | "tod" => Some("TOD#00:00:00"),
+        "date_and_time" | "dt" => Some("DT#1970-01-01-00:00:00"),
+        _ => None,
+    }
+}
+
 impl Visitor<Diagnostic> for RuleConstantVarsInitialized {
     type Value = ();
 
@@ -79,49 +117,40 @@ impl Visitor<Diagnostic> for RuleConstantVarsInitialized {
                 InitialValueAssignmentKind::Simple(si) => match si.initial_value {
                     Some(_) => {}
                     None => {
-                        self.diagnostics.push(
-                            Diagnostic::problem(
-                                Problem::ConstantMustHaveInitializer,
-                                Label::span(node.span(), "Variable"),
-                            )
-                            .with_context("variable", &node.identifier.to_string()),
-                        );
+                        let fix = default_value(&si.type_name)
+                            .map(|value| add_initializer(&si.type_name.span(), value));
+                        self.missing_initializer(node, "Variable", fix);
                     }
                 },
                 InitialValueAssignmentKind::String(str) => match str.initial_value {
                     Some(_) => {}
                     None => {
-                        self.diagnostics.push(
-                            Diagnostic::problem(
-                                Problem::ConstantMustHaveInitializer,
-                                Label::span(node.span(), "Variable declaration"),
-                            )
-                            .with_context("variable", &node.identifier.to_string()),
-                        );
+                        // The span is the keyword so only insert after the
+                        // keyword when there is no length that follows.
+                        let fix = match (&str.length, &str.width) {
+                            (None, StringType::String) => {
+                                Some(add_initializer(&str.keyword_span, "''"))
+                            }
+                            (None, StringType::WString) => {
+                                Some(add_initializer(&str.keyword_span, "\"\""))
+                            }
+                            _ => None,
+                        };
+                        self.missing_initializer(node, "Variable declaration", fix);
                     }
                 },
                 InitialValueAssignmentKind::EnumeratedValues(spec) => match spec.initial_value {
                     Some(_) => {}
                     None => {
-                        self.diagnostics.push(
-                            Diagnostic::problem(
-                                Problem::ConstantMustHaveInitializer,
-                                Label::span(node.span(), "Variable declaration"),
-                            )
-                            .with_context("variable", &node.identifier.to_string()),
-                        );
+                        // The span of the values excludes the closing
+                        // parenthesis so there is no position for a fix.
+                        self.missing_initializer(node, "Variable declaration", None);
                     }
                 },
                 InitialValueAssignmentKind::EnumeratedType(type_init) => {
                     match type_init.initial_value {
                         Some(_) => {}
-                        None => self.diagnostics.push(
-                            Diagnostic::problem(
-                                Problem::ConstantMustHaveInitializer,
-                                Label::span(node.span(), "Variable declaration"),
-                            )
-                            .with_context("variable", &node.identifier.to_string()),
-                        ),
+                        None => self.missing_initializer(node, "Variable declaration", None),
                     }
                 }
                 InitialValueAssignmentKind::FunctionBlock(_) => {
@@ -136,6 +165,13 @@ impl Visitor<Diagnostic> for RuleConstantVarsInitialized {
                 InitialValueAssignmentKind::Array(_) => {
                     return Err(Diagnostic::todo(file!(), line!()))
                 }
+                InitialValueAssignmentKind::Reference(init) => match init.initial_value {
+                    Some(_) => {}
+                    None => {
+                        let fix = add_initializer(&init.referenced_type_name.span(), "NULL");
+                        self.missing_initializer(node, "Variable declaration", Some(fix));
+                    }
+                },
                 InitialValueAssignmentKind::LateResolvedType(_) => {
                     return Err(Diagnostic::todo(file!(), line!()))
                 }
@@ -174,6 +210,28 @@ END_FUNCTION_BLOCK";
         assert!(result.is_err())
     }
 
+    #[test]
+    fn apply_when_const_simple_type_missing_initializer_then_fix_adds_default() {
+        let program = "
+FUNCTION_BLOCK LOGGER
+VAR CONSTANT
+ResetCounterValue : INT;
+END_VAR
+
+END_FUNCTION_BLOCK";
+
+        let library = parse_and_resolve_types(program);
+        let type_env = TypeEnvironment::new();
+        let symbol_env = SymbolEnvironment::new();
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let diagnostics = result.unwrap_err();
+        let edit = &diagnostics[0].fixes[0].edits[0];
+        assert_eq!(" := 0", edit.new_text);
+        assert_eq!(program.find("INT;").unwrap() + 3, edit.location.start);
+        assert_eq!(edit.location.start, edit.location.end);
+    }
+
     #[test]
     fn apply_when_const_enum_type_missing_initializer_then_error() {
         let program = "
@@ -39,7 +39,7 @@ use std::collections::HashSet;
 use ironplc_dsl::{
     common::*,
     core::{Id, Located},
-    diagnostic::{Diagnostic, Label},
+    diagnostic::{Diagnostic, Fix, Label, TextEdit},
     visitor::Visitor,
 };
 use ironplc_problems::Problem;
@@ -99,12 +99,22 @@ impl Visitor<Diagnostic> for RuleExternalGlobalConst<'_> {
         {
             if let Some(name) = node.identifier.symbolic_id() {
                 if let Some(global) = self.global_consts.get(name) {
-                    return Err(Diagnostic::problem(
+                    let mut diagnostic = Diagnostic::problem(
                         Problem::VariableMustBeConst,
                         Label::span(node.identifier.span(), "Reference to global variable"),
                     )
                     .with_context("variable", &node.identifier.to_string())
-                    .with_secondary(Label::span(global.span(), "Constant global variable")));
+                    .with_secondary(Label::span(global.span(), "Constant global variable"));
+
+                    // The qualifier applies to the block so the fix is only
+                    // possible when we know where the block starts.
+                    if node.keyword_span.end > node.keyword_span.start {
+                        diagnostic = diagnostic.with_fix(Fix::new(
+                            "Add CONSTANT qualifier",
+                            TextEdit::insert_after(&node.keyword_span, " CONSTANT"),
+                        ));
+                    }
+                    return Err(diagnostic);
                 }
             }
         }
@@ -146,6 +156,39 @@ END_FUNCTION_BLOCK";
         assert!(result.is_err())
     }
 
+    #[test]
+    fn apply_when_global_const_external_not_const_then_fix_adds_constant() {
+        let program = "
+CONFIGURATION config
+    VAR_GLOBAL CONSTANT
+        ResetCounterValue : INT := 17;
+    END_VAR
+    RESOURCE resource1 ON PLC
+        TASK plc_task(INTERVAL := T#100ms,PRIORITY := 1);
+        PROGRAM plc_task_instance WITH plc_task : plc_prg;
+    END_RESOURCE
+END_CONFIGURATION
+
+FUNCTION_BLOCK func
+    VAR_EXTERNAL
+        ResetCounterValue : INT;
+    END_VAR
+END_FUNCTION_BLOCK";
+
+        let library = parse_and_resolve_types(program);
+        let type_env = TypeEnvironment::new();
+        let symbol_env = SymbolEnvironment::new();
+        let result = apply(&library, &type_env, &symbol_env);
+
+        let diagnostics = result.unwrap_err();
+        let edit = &diagnostics[0].fixes[0].edits[0];
+        assert_eq!(" CONSTANT", edit.new_text);
+        assert_eq!(
+            program.find("VAR_EXTERNAL").unwrap() + "VAR_EXTERNAL".len(),
+            edit.location.start
+        );
+    }
+
     #[test]
     fn apply_when_global_const_external_const_then_ok() {
         let program = "
@@ -0,0 +1,156 @@
+//! Implements the configurable rule set. The rule set changes the severity
+//! of problems (or turns off problems) so that some problems are advisory
+//! rather than preventing a successful build.
+
+use std::{collections::HashMap, str::FromStr};
+
+use ironplc_dsl::diagnostic::Diagnostic;
+use ironplc_problems::{Problem, Severity};
+
+/// The configured level for a problem code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleLevel {
+    /// Report the problem as an error.
+    Error,
+    /// Report the problem as a warning.
+    Warning,
+    /// Do not report the problem.
+    Off,
+}
+
+impl FromStr for RuleLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(RuleLevel::Error),
+            "warning" => Ok(RuleLevel::Warning),
+            "off" => Ok(RuleLevel::Off),
+            _ => Err(format!(
+                "Rule level '{s}' is not valid. Valid levels are error, warning and off"
+            )),
+        }
+    }
+}
+
+/// A set of overrides for the default severity of problems.
+#[derive(Clone, Debug, Default)]
+pub struct RuleSet {
+    levels: HashMap<String, RuleLevel>,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level for the problem code. Returns an error if the code
+    /// is not a known problem code.
+    pub fn set(&mut self, code: &str, level: RuleLevel) -> Result<(), String> {
+        let problem = Problem::from_code(code)
+            .ok_or_else(|| format!("Problem code '{code}' is not a known problem code"))?;
+        self.levels.insert(problem.code().to_string(), level);
+        Ok(())
+    }
+
+    /// Sets the level for a problem code from text of the form `CODE=LEVEL`,
+    /// for example `P4007=warning`.
+    pub fn set_from_str(&mut self, text: &str) -> Result<(), String> {
+        let (code, level) = text
+            .split_once('=')
+            .ok_or_else(|| format!("Rule '{text}' must have the form CODE=LEVEL"))?;
+        self.set(code.trim(), level.trim().parse()?)
+    }
+
+    /// Returns true if the rule set does not change any problem.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Adds the levels from the other rule set to this rule set. The levels
+    /// in the other rule set replace the levels in this rule set.
+    pub fn extend(&mut self, other: &RuleSet) {
+        self.levels
+            .extend(other.levels.iter().map(|(k, v)| (k.clone(), *v)));
+    }
+
+    /// Applies the rule set to the diagnostics, changing the severity of
+    /// diagnostics and removing diagnostics that are off.
+    pub fn apply(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|diagnostic| match self.levels.get(&diagnostic.code) {
+                Some(RuleLevel::Error) => Some(diagnostic.with_severity(Severity::Error)),
+                Some(RuleLevel::Warning) => Some(diagnostic.with_severity(Severity::Warning)),
+                Some(RuleLevel::Off) => None,
+                None => Some(diagnostic),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_dsl::{
+        core::SourceSpan,
+        diagnostic::{Diagnostic, Label},
+    };
+    use ironplc_problems::{Problem, Severity};
+
+    use super::{RuleLevel, RuleSet};
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        vec![
+            Diagnostic::problem(
+                Problem::VariableUndefined,
+                Label::span(SourceSpan::default(), "Variable"),
+            ),
+            Diagnostic::problem(
+                Problem::SyntaxError,
+                Label::span(SourceSpan::default(), "Syntax"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn apply_when_no_levels_then_unchanged() {
+        let result = RuleSet::new().apply(diagnostics());
+
+        assert_eq!(2, result.len());
+        assert!(result.iter().all(|d| d.is_error()));
+    }
+
+    #[test]
+    fn apply_when_warning_then_changes_severity() {
+        let mut rules = RuleSet::new();
+        rules.set_from_str("P4007=warning").unwrap();
+
+        let result = rules.apply(diagnostics());
+
+        assert_eq!(2, result.len());
+        assert_eq!(Severity::Warning, result[0].severity);
+        assert_eq!(Severity::Error, result[1].severity);
+    }
+
+    #[test]
+    fn apply_when_off_then_removes_diagnostic() {
+        let mut rules = RuleSet::new();
+        rules.set("P4007", RuleLevel::Off).unwrap();
+
+        let result = rules.apply(diagnostics());
+
+        assert_eq!(1, result.len());
+        assert_eq!("P0002", result[0].code);
+    }
+
+    #[test]
+    fn set_when_unknown_code_then_err() {
+        assert!(RuleSet::new().set("P0000", RuleLevel::Off).is_err());
+    }
+
+    #[test]
+    fn set_from_str_when_invalid_level_then_err() {
+        assert!(RuleSet::new().set_from_str("P4007=loud").is_err());
+        assert!(RuleSet::new().set_from_str("P4007").is_err());
+    }
+}
@@ -13,16 +13,20 @@ use crate::{
     ironplc_dsl::common::Library,
     result::SemanticResult,
     rule_decl_struct_element_unique_names, rule_decl_subrange_limits,
-    rule_enumeration_values_unique, rule_function_block_invocation, rule_pou_hierarchy,
-    rule_program_task_definition_exists, rule_unsupported_stdlib_type,
-    rule_use_declared_enumerated_value, rule_use_declared_symbolic_var,
+    rule_deref_requires_reference, rule_enumeration_values_unique, rule_expr_type_compatible,
+    rule_function_block_invocation, rule_function_invocation, rule_pou_hierarchy,
+    rule_pou_inheritance, rule_program_task_definition_exists,
+    rule_reference_assignment_compatible, rule_use_declared_enumerated_value,
+    rule_use_declared_struct_field, rule_use_declared_symbolic_var,
     rule_var_decl_const_initialized, rule_var_decl_const_not_fb,
     rule_var_decl_global_const_requires_external_const,
+    rules::RuleSet,
+    stdlib,
     symbol_environment::SymbolEnvironment,
     type_environment::{TypeEnvironment, TypeEnvironmentBuilder},
-    type_table, xform_resolve_late_bound_expr_kind, xform_resolve_late_bound_type_initializer,
-    xform_resolve_symbol_environment, xform_resolve_type_aliases,
-    xform_resolve_type_decl_environment, xform_toposort_declarations,
+    type_table, xform_resolve_constant_expressions, xform_resolve_late_bound_expr_kind,
+    xform_resolve_late_bound_type_initializer, xform_resolve_symbol_environment,
+    xform_resolve_type_aliases, xform_resolve_type_decl_environment, xform_toposort_declarations,
 };
 
 /// Analyze runs semantic analysis on the set of files as a self-contained and complete unit.
@@ -31,34 +35,119 @@ use crate::{
 /// the merge of the inputs.
 /// Returns `Err(Diagnostic)` if analysis did not succeed.
 pub fn analyze(sources: &[&Library]) -> Result<(), Vec<Diagnostic>> {
+    analyze_with_rules(sources, &RuleSet::new())
+}
+
+/// Analyze runs semantic analysis on the set of files with the rule set
+/// applied to the problems that each stage finds.
+///
+/// Analysis stops at the first stage that has errors after applying the rule
+/// set, so problems that the rule set demotes to warnings (or turns off) do
+/// not hide problems found by later stages.
+///
+/// Returns `Err(Diagnostic)` with the diagnostics after applying the rule set
+/// if analysis found any problem.
+pub fn analyze_with_rules(sources: &[&Library], rules: &RuleSet) -> Result<(), Vec<Diagnostic>> {
     if sources.is_empty() {
         let span = SourceSpan::range(0, 0).with_file_id(&FileId::default());
-        return Err(vec![Diagnostic::problem(
+        return Err(rules.apply(vec![Diagnostic::problem(
             Problem::NoContent,
             Label::span(span, "First location"),
-        )]);
+        )]));
+    }
+    let (library, type_environment, symbol_environment, mut diagnostics) =
+        resolve_types_with_rules(sources, rules)?;
+    if let Err(found) = semantic(&library, &type_environment, &symbol_environment) {
+        // Resolving types and the semantic rules check some of the same
+        // problems, so only keep problems that were not already found.
+        let found: Vec<_> = rules
+            .apply(found)
+            .into_iter()
+            .filter(|diagnostic| !is_reported(&diagnostics, diagnostic))
+            .collect();
+        diagnostics.extend(found);
     }
-    let (library, type_environment, symbol_environment) = resolve_types(sources)?;
-    let result = semantic(&library, &type_environment, &symbol_environment);
 
     // TODO this is currently in progress. It isn't clear to me yet how this will influence
     // semantic analysis, but it should because the type table should influence rule checking.
     // For now, this is just after the rules as they were originally written.
-    let type_table_result = type_table::apply(&library)?;
-    debug!("{type_table_result:?}");
+    match type_table::apply(&library) {
+        Ok(type_table_result) => debug!("{type_table_result:?}"),
+        Err(found) => diagnostics.extend(rules.apply(found)),
+    }
 
-    result
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+    Ok(())
 }
 
+/// Returns true if one of the diagnostics reports the same problem at the
+/// primary location of the diagnostic.
+fn is_reported(diagnostics: &[Diagnostic], diagnostic: &Diagnostic) -> bool {
+    diagnostics.iter().any(|reported| {
+        reported.code == diagnostic.code
+            && std::iter::once(&reported.primary)
+                .chain(reported.secondary.iter())
+                .any(|label| {
+                    label.location.start == diagnostic.primary.location.start
+                        && label.location.end == diagnostic.primary.location.end
+                        && label.file_id == diagnostic.primary.file_id
+                })
+    })
+}
+
+/// Resolves the data type declarations in the sources into a type environment.
+///
+/// This is the part of [`analyze`] that resolves types and is for tools that
+/// need information about types, such as the memory size of a type.
+/// Returns `Err(Diagnostic)` if the data type declarations are not valid.
+pub fn resolve_type_environment(sources: &[&Library]) -> Result<TypeEnvironment, Vec<Diagnostic>> {
+    let mut library = Library::new();
+    for x in sources {
+        library = library.extend((*x).clone());
+    }
+    let library = stdlib::with_standard_function_blocks(library)?;
+
+    let mut type_environment = TypeEnvironmentBuilder::new()
+        .with_elementary_types()
+        .build()
+        .map_err(|err| vec![err])?;
+
+    let library = xform_toposort_declarations::apply(library)?;
+    let library = xform_resolve_constant_expressions::apply(library, &mut type_environment)?;
+    xform_resolve_type_decl_environment::apply(library, &mut type_environment)?;
+
+    Ok(type_environment)
+}
+
+#[cfg(test)]
 pub(crate) fn resolve_types(
     sources: &[&Library],
 ) -> Result<(Library, TypeEnvironment, SymbolEnvironment), Vec<Diagnostic>> {
+    // Without rules, every problem is an error so there are never
+    // diagnostics from a successful resolution.
+    resolve_types_with_rules(sources, &RuleSet::new()).map(
+        |(library, type_environment, symbol_environment, _)| {
+            (library, type_environment, symbol_environment)
+        },
+    )
+}
+
+/// Resolves types with the rule set applied to the problems found by each
+/// transformation. Returns the diagnostics that do not stop analysis along
+/// with the resolved library and environments.
+fn resolve_types_with_rules(
+    sources: &[&Library],
+    rules: &RuleSet,
+) -> Result<(Library, TypeEnvironment, SymbolEnvironment, Vec<Diagnostic>), Vec<Diagnostic>> {
     // We want to analyze this as a complete set, so we need to join the items together
     // into a single library. Extend owns the item so after this we are free to modify
     let mut library = Library::new();
     for x in sources {
         library = library.extend((*x).clone());
     }
+    let library = stdlib::with_standard_function_blocks(library)?;
 
     let mut type_environment = TypeEnvironmentBuilder::new()
         .with_elementary_types()
@@ -70,25 +159,47 @@ pub(crate) fn resolve_types(
     let mut library = xform_toposort_declarations::apply(library)?;
 
     let xforms: Vec<fn(Library, &mut TypeEnvironment) -> Result<Library, Vec<Diagnostic>>> = vec![
+        xform_resolve_constant_expressions::apply,
         xform_resolve_type_decl_environment::apply,
         xform_resolve_late_bound_expr_kind::apply,
         xform_resolve_late_bound_type_initializer::apply,
     ];
 
+    let mut diagnostics = vec![];
     for xform in xforms {
-        library = xform(library, &mut type_environment)?
+        // A transformation consumes the library, so keep the input when
+        // the rule set might demote the problems that the transformation
+        // finds and continue with the input in that case.
+        let input = (!rules.is_empty()).then(|| library.clone());
+        library = match xform(library, &mut type_environment) {
+            Ok(library) => library,
+            Err(found) => {
+                diagnostics.extend(rules.apply(found));
+                match input {
+                    Some(input) if !diagnostics.iter().any(|d| d.is_error()) => input,
+                    _ => return Err(diagnostics),
+                }
+            }
+        };
     }
 
     // Resolve symbols after types are resolved
-    library = xform_resolve_symbol_environment::apply(
+    let library = xform_resolve_symbol_environment::apply(
         library,
         &type_environment,
         &mut symbol_environment,
-    )?;
-
-    // Resolve type aliases by duplicating values
-    library =
-        xform_resolve_type_aliases::apply(library, &type_environment, &mut symbol_environment)?;
+    )
+    .and_then(|library| {
+        // Resolve type aliases by duplicating values
+        xform_resolve_type_aliases::apply(library, &type_environment, &mut symbol_environment)
+    });
+    let library = match library {
+        Ok(library) => library,
+        Err(found) => {
+            diagnostics.extend(rules.apply(found));
+            return Err(diagnostics);
+        }
+    };
 
     // Generate and display useful symbol table information
     debug!("Type Environment:");
@@ -97,7 +208,7 @@ pub(crate) fn resolve_types(
     debug!("Symbol Environment:");
     debug!("{symbol_environment:?}");
 
-    Ok((library, type_environment, symbol_environment))
+    Ok((library, type_environment, symbol_environment, diagnostics))
 }
 
 /// Semantic implements semantic analysis (stage 3).
@@ -112,16 +223,21 @@ pub(crate) fn semantic(
     let functions: Vec<fn(&Library, &TypeEnvironment, &SymbolEnvironment) -> SemanticResult> = vec![
         rule_decl_struct_element_unique_names::apply,
         rule_decl_subrange_limits::apply,
+        rule_deref_requires_reference::apply,
         rule_enumeration_values_unique::apply,
+        rule_expr_type_compatible::apply,
         rule_function_block_invocation::apply,
+        rule_function_invocation::apply,
         rule_program_task_definition_exists::apply,
+        rule_reference_assignment_compatible::apply,
         rule_use_declared_enumerated_value::apply,
+        rule_use_declared_struct_field::apply,
         rule_use_declared_symbolic_var::apply,
-        rule_unsupported_stdlib_type::apply,
         rule_var_decl_const_initialized::apply,
         rule_var_decl_const_not_fb::apply,
         rule_var_decl_global_const_requires_external_const::apply,
         rule_pou_hierarchy::apply,
+        rule_pou_inheritance::apply,
     ];
 
     let mut all_diagnostics = vec![];
@@ -145,8 +261,10 @@ pub(crate) fn semantic(
 
 #[cfg(test)]
 mod tests {
-    use crate::stages::analyze;
-    use ironplc_dsl::common::Library;
+    use crate::stages::{analyze, resolve_type_environment, resolve_types};
+    use ironplc_dsl::common::{
+        DataTypeDeclarationKind, HasAttributes, Library, LibraryElementKind, TypeName,
+    };
     use ironplc_dsl::core::FileId;
     use ironplc_parser::options::ParseOptions;
     use ironplc_parser::parse_program;
@@ -166,6 +284,25 @@ mod tests {
         assert!(res.is_err())
     }
 
+    #[test]
+    fn resolve_type_environment_when_structure_then_has_memory_size() {
+        let program = "
+TYPE
+POINT : STRUCT
+  x : INT;
+  y : DINT;
+END_STRUCT;
+END_TYPE";
+        let library = parse_program(program, &FileId::default(), &ParseOptions::default()).unwrap();
+
+        let env = resolve_type_environment(&[&library]).unwrap();
+
+        assert_eq!(
+            Some(8),
+            env.get_memory_size(&TypeName::from("POINT")).unwrap()
+        );
+    }
+
     #[test]
     fn analyze_2() {
         let lib = parse_shared_library("main.st");
@@ -197,6 +334,86 @@ END_FUNCTION_BLOCK";
         assert!(result.is_ok())
     }
 
+    #[test]
+    fn analyze_when_method_variable_shadows_function_block_variable_then_ok() {
+        let program = "
+TYPE
+Mode : (STOP, RUN);
+END_TYPE
+
+FUNCTION_BLOCK Machine
+VAR
+state : Mode := STOP;
+END_VAR
+
+METHOD Count : INT
+VAR
+state : INT;
+END_VAR
+state := 1;
+Count := state;
+END_METHOD
+
+state := RUN;
+END_FUNCTION_BLOCK";
+        let library = parse_program(program, &FileId::default(), &ParseOptions::default()).unwrap();
+
+        let res = analyze(&[&library]);
+        assert!(res.is_ok(), "{res:?}");
+    }
+
+    #[test]
+    fn resolve_types_when_pragma_then_attributes_are_queryable() {
+        let lib = parse_shared_library("pragma.st");
+        let (lib, _, _) = resolve_types(&[&lib]).unwrap();
+
+        // Resolving types sorts the declarations so find the types by kind
+        let types: Vec<_> = lib
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                LibraryElementKind::DataTypeDeclaration(decl) => Some(decl),
+                _ => None,
+            })
+            .collect();
+        let level = types
+            .iter()
+            .find(|decl| matches!(decl, DataTypeDeclarationKind::Enumeration(_)))
+            .unwrap();
+        assert!(level.has_attribute("qualified_only"));
+        let point = types
+            .iter()
+            .find(|decl| matches!(decl, DataTypeDeclarationKind::Structure(_)))
+            .unwrap();
+        assert_eq!(
+            Some("1".to_string()),
+            point.find_attribute("pack_mode").unwrap().value
+        );
+
+        let fb = lib
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                LibraryElementKind::FunctionBlockDeclaration(fb)
+                    if fb.name == TypeName::from("Motor") =>
+                {
+                    Some(fb)
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert!(fb.has_attribute("reflection"));
+        assert!(fb.variables[0].has_attribute("hmi"));
+        assert!(!fb.variables[1].has_attribute("hmi"));
+    }
+
+    #[test]
+    fn analyze_when_nested_structure_then_ok() {
+        let lib = parse_shared_library("nested_structure.st");
+        let res = analyze(&[&lib]);
+        assert!(res.is_ok(), "{res:?}");
+    }
+
     fn parse_shared_library(name: &'static str) -> Library {
         let src = read_shared_resource(name);
         parse_program(&src, &FileId::default(), &ParseOptions::default()).unwrap()
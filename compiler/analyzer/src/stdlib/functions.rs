@@ -0,0 +1,496 @@
+//! Signatures of functions, including the standard functions.
+//!
+//! Standard functions are generic: the inputs of a standard function accept
+//! a family of types (such as `ANY_NUM`) rather than a single type. Inputs
+//! that share the generic type of the function must have a common type and
+//! that type is also the return type of the function.
+//!
+//! See 2.5.1.5.
+use ironplc_dsl::{
+    common::{
+        ElementaryTypeName, FunctionDeclaration, InitialValueAssignmentKind, StringType,
+        SubrangeSpecificationKind, TypeName, VariableType,
+    },
+    core::Id,
+};
+
+use crate::expr_type::ExprType;
+
+/// Families of types that inputs of standard functions accept.
+///
+/// See 2.3.2 (figure 5).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenericType {
+    Any,
+    AnyElementary,
+    AnyNum,
+    AnyReal,
+    AnyInt,
+    AnyBit,
+    AnyString,
+}
+
+impl GenericType {
+    /// Returns true if the type is a member of the family.
+    pub fn contains(&self, expr_type: &ExprType) -> bool {
+        match self {
+            GenericType::Any => true,
+            GenericType::AnyElementary => expr_type.is_elementary(),
+            GenericType::AnyNum => expr_type.is_numeric(),
+            GenericType::AnyReal => expr_type.is_real(),
+            GenericType::AnyInt => expr_type.is_integer(),
+            GenericType::AnyBit => expr_type.is_bit_string(),
+            GenericType::AnyString => expr_type.is_string(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GenericType::Any => "ANY",
+            GenericType::AnyElementary => "ANY_ELEMENTARY",
+            GenericType::AnyNum => "ANY_NUM",
+            GenericType::AnyReal => "ANY_REAL",
+            GenericType::AnyInt => "ANY_INT",
+            GenericType::AnyBit => "ANY_BIT",
+            GenericType::AnyString => "ANY_STRING",
+        }
+    }
+}
+
+/// The type of an input to a function.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputType {
+    /// The input has the generic type of the function. All inputs having
+    /// the generic type of the function must have a common type.
+    Generic(GenericType),
+    /// The input has any type in the family independent of other inputs.
+    Independent(GenericType),
+    Elementary(ElementaryTypeName),
+    Named(TypeName),
+    /// The type of the input is not checked.
+    Unknown,
+}
+
+/// The type that a function returns.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReturnType {
+    /// The function returns the generic type of the function.
+    Generic,
+    /// The function returns a type in the family but the specific type
+    /// is not known.
+    Family(GenericType),
+    Elementary(ElementaryTypeName),
+    Named(TypeName),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionInput {
+    pub name: Id,
+    pub input_type: InputType,
+}
+
+/// The signature of a function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionSignature {
+    pub name: Id,
+    pub inputs: Vec<FunctionInput>,
+    /// The names of the outputs of the function (other than the return
+    /// value).
+    pub outputs: Vec<Id>,
+    /// True if the function accepts any number of additional inputs having
+    /// the type of the last input. For example, `ADD(IN1, IN2, IN3)`.
+    pub extensible: bool,
+    pub return_type: ReturnType,
+}
+
+impl FunctionSignature {
+    fn new(name: &str, inputs: &[(&str, InputType)], return_type: ReturnType) -> Self {
+        FunctionSignature {
+            name: Id::from(name),
+            inputs: inputs
+                .iter()
+                .map(|(name, input_type)| FunctionInput {
+                    name: Id::from(name),
+                    input_type: input_type.clone(),
+                })
+                .collect(),
+            outputs: vec![],
+            extensible: false,
+            return_type,
+        }
+    }
+
+    fn extensible(mut self) -> Self {
+        self.extensible = true;
+        self
+    }
+
+    /// Creates the signature for a function declared in the library.
+    pub fn from_declaration(decl: &FunctionDeclaration) -> Self {
+        let inputs = decl
+            .variables
+            .iter()
+            .filter(|var| matches!(var.var_type, VariableType::Input | VariableType::InOut))
+            .filter_map(|var| {
+                Some(FunctionInput {
+                    name: var.identifier.symbolic_id()?.clone(),
+                    input_type: declared_input_type(&var.initializer),
+                })
+            })
+            .collect();
+        let outputs = decl
+            .variables
+            .iter()
+            .filter(|var| var.var_type == VariableType::Output)
+            .filter_map(|var| var.identifier.symbolic_id().cloned())
+            .collect();
+
+        FunctionSignature {
+            name: decl.name.clone(),
+            inputs,
+            outputs,
+            extensible: false,
+            return_type: ReturnType::Named(decl.return_type.clone()),
+        }
+    }
+
+    /// Returns the input that receives the positional argument at the index
+    /// or `None` if the function does not have the input.
+    pub fn positional_input(&self, index: usize) -> Option<&FunctionInput> {
+        match self.inputs.get(index) {
+            Some(input) => Some(input),
+            None if self.extensible => self.inputs.last(),
+            None => None,
+        }
+    }
+
+    /// Returns the input having the name or `None` if the function does not
+    /// have the input.
+    pub fn named_input(&self, name: &Id) -> Option<&FunctionInput> {
+        if let Some(input) = self.inputs.iter().find(|input| input.name == *name) {
+            return Some(input);
+        }
+        // Additional inputs of extensible functions are named by the
+        // position, for example IN3.
+        let last = self.inputs.last()?;
+        let prefix = last
+            .name
+            .lower_case()
+            .trim_end_matches(|c: char| c.is_ascii_digit());
+        let suffix = name.lower_case().strip_prefix(prefix)?;
+        let is_extension =
+            self.extensible && !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit());
+        is_extension.then_some(last)
+    }
+
+    /// Returns true if the name is an output of the function.
+    pub fn has_output(&self, name: &Id) -> bool {
+        self.outputs.contains(name)
+    }
+}
+
+/// Returns the type of a declared input variable.
+fn declared_input_type(init: &InitialValueAssignmentKind) -> InputType {
+    match init {
+        InitialValueAssignmentKind::Simple(simple) => InputType::Named(simple.type_name.clone()),
+        InitialValueAssignmentKind::String(string) => InputType::Elementary(match string.width {
+            StringType::String => ElementaryTypeName::STRING,
+            StringType::WString => ElementaryTypeName::WSTRING,
+        }),
+        InitialValueAssignmentKind::EnumeratedType(enumerated) => {
+            InputType::Named(enumerated.type_name.clone())
+        }
+        InitialValueAssignmentKind::Subrange(SubrangeSpecificationKind::Specification(spec)) => {
+            InputType::Elementary(spec.type_name.clone())
+        }
+        InitialValueAssignmentKind::Subrange(SubrangeSpecificationKind::Type(type_name)) => {
+            InputType::Named(type_name.clone())
+        }
+        InitialValueAssignmentKind::Structure(structure) => {
+            InputType::Named(structure.type_name.clone())
+        }
+        InitialValueAssignmentKind::LateResolvedType(type_name) => {
+            InputType::Named(type_name.clone())
+        }
+        _ => InputType::Unknown,
+    }
+}
+
+/// Returns the signature of the standard function having the name or
+/// `None` if the name is not the name of a standard function.
+pub fn find_standard_function(name: &Id) -> Option<FunctionSignature> {
+    use ElementaryTypeName::*;
+    use GenericType::*;
+    use InputType::{Elementary as E, Generic as G, Independent as I};
+
+    let binary = |generic: GenericType, return_type: ReturnType| {
+        FunctionSignature::new(
+            name.original(),
+            &[("IN1", G(generic)), ("IN2", G(generic))],
+            return_type,
+        )
+    };
+    let unary = |generic: GenericType| {
+        FunctionSignature::new(name.original(), &[("IN", G(generic))], ReturnType::Generic)
+    };
+    let time = |in1: ElementaryTypeName, in2: InputType, ret: ElementaryTypeName| {
+        FunctionSignature::new(
+            name.original(),
+            &[("IN1", E(in1)), ("IN2", in2)],
+            ReturnType::Elementary(ret),
+        )
+    };
+    let bool_type = ReturnType::Elementary(BOOL);
+
+    let signature = match name.lower_case().as_str() {
+        // Numerical functions (table 23)
+        "abs" => unary(AnyNum),
+        "sqrt" | "ln" | "log" | "exp" | "sin" | "cos" | "tan" | "asin" | "acos" | "atan" => {
+            unary(AnyReal)
+        }
+        // Arithmetic functions (table 24)
+        "add" | "mul" => binary(AnyNum, ReturnType::Generic).extensible(),
+        "sub" | "div" => binary(AnyNum, ReturnType::Generic),
+        "mod" => binary(AnyInt, ReturnType::Generic),
+        "expt" => FunctionSignature::new(
+            name.original(),
+            &[("IN1", G(AnyReal)), ("IN2", I(AnyNum))],
+            ReturnType::Generic,
+        ),
+        "move" => unary(Any),
+        // Bit shift functions (table 25)
+        "shl" | "shr" | "ror" | "rol" => FunctionSignature::new(
+            name.original(),
+            &[("IN", G(AnyBit)), ("N", I(AnyInt))],
+            ReturnType::Generic,
+        ),
+        // Bitwise Boolean functions (table 26)
+        "and" | "or" | "xor" => binary(AnyBit, ReturnType::Generic).extensible(),
+        "not" => unary(AnyBit),
+        // Selection functions (table 27)
+        "sel" => FunctionSignature::new(
+            name.original(),
+            &[("G", E(BOOL)), ("IN0", G(Any)), ("IN1", G(Any))],
+            ReturnType::Generic,
+        ),
+        "max" | "min" => binary(AnyElementary, ReturnType::Generic).extensible(),
+        "limit" => FunctionSignature::new(
+            name.original(),
+            &[
+                ("MN", G(AnyElementary)),
+                ("IN", G(AnyElementary)),
+                ("MX", G(AnyElementary)),
+            ],
+            ReturnType::Generic,
+        ),
+        "mux" => FunctionSignature::new(
+            name.original(),
+            &[("K", I(AnyInt)), ("IN0", G(Any)), ("IN1", G(Any))],
+            ReturnType::Generic,
+        )
+        .extensible(),
+        // Comparison functions (table 28)
+        "gt" | "ge" | "le" | "lt" => binary(AnyElementary, bool_type).extensible(),
+        "eq" => binary(Any, bool_type).extensible(),
+        "ne" => binary(Any, bool_type),
+        // Character string functions (table 29)
+        "len" => FunctionSignature::new(
+            name.original(),
+            &[("IN", I(AnyString))],
+            ReturnType::Elementary(INT),
+        ),
+        "left" | "right" => FunctionSignature::new(
+            name.original(),
+            &[("IN", G(AnyString)), ("L", I(AnyInt))],
+            ReturnType::Generic,
+        ),
+        "mid" => FunctionSignature::new(
+            name.original(),
+            &[("IN", G(AnyString)), ("L", I(AnyInt)), ("P", I(AnyInt))],
+            ReturnType::Generic,
+        ),
+        "concat" => binary(AnyString, ReturnType::Generic).extensible(),
+        "insert" => FunctionSignature::new(
+            name.original(),
+            &[
+                ("IN1", G(AnyString)),
+                ("IN2", G(AnyString)),
+                ("P", I(AnyInt)),
+            ],
+            ReturnType::Generic,
+        ),
+        "delete" => FunctionSignature::new(
+            name.original(),
+            &[("IN", G(AnyString)), ("L", I(AnyInt)), ("P", I(AnyInt))],
+            ReturnType::Generic,
+        ),
+        "replace" => FunctionSignature::new(
+            name.original(),
+            &[
+                ("IN1", G(AnyString)),
+                ("IN2", G(AnyString)),
+                ("L", I(AnyInt)),
+                ("P", I(AnyInt)),
+            ],
+            ReturnType::Generic,
+        ),
+        "find" => FunctionSignature::new(
+            name.original(),
+            &[("IN1", G(AnyString)), ("IN2", G(AnyString))],
+            ReturnType::Elementary(INT),
+        ),
+        // Functions of time data types (table 30)
+        "add_time" => time(TIME, E(TIME), TIME),
+        "add_tod_time" => time(TimeOfDay, E(TIME), TimeOfDay),
+        "add_dt_time" => time(DateAndTime, E(TIME), DateAndTime),
+        "sub_time" => time(TIME, E(TIME), TIME),
+        "sub_date_date" => time(DATE, E(DATE), TIME),
+        "sub_tod_time" => time(TimeOfDay, E(TIME), TimeOfDay),
+        "sub_tod_tod" => time(TimeOfDay, E(TimeOfDay), TIME),
+        "sub_dt_time" => time(DateAndTime, E(TIME), DateAndTime),
+        "sub_dt_dt" => time(DateAndTime, E(DateAndTime), TIME),
+        "mul_time" | "div_time" => time(TIME, I(AnyNum), TIME),
+        "concat_date_tod" => time(DATE, E(TimeOfDay), DateAndTime),
+        // Type conversion functions (table 22)
+        "trunc" => FunctionSignature::new(
+            name.original(),
+            &[("IN", I(AnyReal))],
+            ReturnType::Family(AnyInt),
+        ),
+        _ => return find_conversion_function(name),
+    };
+    Some(signature)
+}
+
+/// Returns the signature of a type conversion function having the name, for
+/// example `INT_TO_REAL`, `TO_REAL` or `WORD_BCD_TO_INT`.
+///
+/// See 2.5.1.5.1.
+fn find_conversion_function(name: &Id) -> Option<FunctionSignature> {
+    let lower_case = name.lower_case();
+    let (from, to) = match lower_case.strip_prefix("to_") {
+        Some(to) => (None, to),
+        None => {
+            let (from, to) = lower_case.split_once("_to_")?;
+            (Some(from), to)
+        }
+    };
+
+    let (input_type, return_type) = match (from, to) {
+        // Binary coded decimal conversions
+        (Some(from), to) if from.ends_with("_bcd") => (
+            bit_type(from.trim_end_matches("_bcd"))?,
+            ReturnType::Elementary(int_type(to)?),
+        ),
+        (Some(from), to) if to.starts_with("bcd_") => (
+            InputType::Elementary(int_type(from)?),
+            ReturnType::Elementary(bit_type_name(to.trim_start_matches("bcd_"))?),
+        ),
+        (Some(from), to) => (
+            InputType::Elementary(elementary_type(from)?),
+            ReturnType::Elementary(elementary_type(to)?),
+        ),
+        (None, to) => (
+            InputType::Independent(GenericType::AnyElementary),
+            ReturnType::Elementary(elementary_type(to)?),
+        ),
+    };
+
+    Some(FunctionSignature::new(
+        name.original(),
+        &[("IN", input_type)],
+        return_type,
+    ))
+}
+
+fn elementary_type(name: &str) -> Option<ElementaryTypeName> {
+    match ExprType::from_elementary_name(&TypeName::from(name))? {
+        ExprType::Elementary(elementary) => Some(elementary),
+        _ => None,
+    }
+}
+
+fn int_type(name: &str) -> Option<ElementaryTypeName> {
+    elementary_type(name).filter(|elementary| ExprType::Elementary(elementary.clone()).is_integer())
+}
+
+fn bit_type_name(name: &str) -> Option<ElementaryTypeName> {
+    elementary_type(name).filter(|elementary| {
+        ExprType::Elementary(elementary.clone()).is_bit_string()
+            && *elementary != ElementaryTypeName::BOOL
+    })
+}
+
+fn bit_type(name: &str) -> Option<InputType> {
+    bit_type_name(name).map(InputType::Elementary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_standard_function_when_limit_then_three_generic_inputs() {
+        let signature = find_standard_function(&Id::from("LIMIT")).unwrap();
+        assert_eq!(3, signature.inputs.len());
+        assert_eq!(ReturnType::Generic, signature.return_type);
+        assert!(!signature.extensible);
+    }
+
+    #[test]
+    fn find_standard_function_when_conversion_then_input_and_return_types() {
+        let signature = find_standard_function(&Id::from("int_to_real")).unwrap();
+        assert_eq!(
+            InputType::Elementary(ElementaryTypeName::INT),
+            signature.inputs[0].input_type
+        );
+        assert_eq!(
+            ReturnType::Elementary(ElementaryTypeName::REAL),
+            signature.return_type
+        );
+    }
+
+    #[test]
+    fn find_standard_function_when_bcd_conversion_then_signature() {
+        let signature = find_standard_function(&Id::from("WORD_BCD_TO_INT")).unwrap();
+        assert_eq!(
+            InputType::Elementary(ElementaryTypeName::WORD),
+            signature.inputs[0].input_type
+        );
+        assert!(find_standard_function(&Id::from("REAL_BCD_TO_INT")).is_none());
+    }
+
+    #[test]
+    fn find_standard_function_when_not_standard_then_none() {
+        assert!(find_standard_function(&Id::from("FOO")).is_none());
+        assert!(find_standard_function(&Id::from("FOO_TO_BAR")).is_none());
+    }
+
+    #[test]
+    fn named_input_when_extensible_then_matches_additional_inputs() {
+        let signature = find_standard_function(&Id::from("ADD")).unwrap();
+        assert!(signature.named_input(&Id::from("IN3")).is_some());
+        assert!(signature.named_input(&Id::from("IN")).is_none());
+        assert!(signature.named_input(&Id::from("K")).is_none());
+
+        let signature = find_standard_function(&Id::from("MUX")).unwrap();
+        assert!(signature.named_input(&Id::from("IN5")).is_some());
+    }
+
+    #[test]
+    fn positional_input_when_extensible_then_last_input() {
+        let signature = find_standard_function(&Id::from("MAX")).unwrap();
+        assert_eq!(Id::from("IN2"), signature.positional_input(4).unwrap().name);
+
+        let signature = find_standard_function(&Id::from("SUB")).unwrap();
+        assert!(signature.positional_input(2).is_none());
+    }
+
+    #[test]
+    fn contains_when_literal_then_in_numeric_family() {
+        assert!(GenericType::AnyNum.contains(&ExprType::AnyInt));
+        assert!(GenericType::AnyReal.contains(&ExprType::AnyInt));
+        assert!(!GenericType::AnyInt.contains(&ExprType::AnyReal));
+        assert!(!GenericType::AnyElementary.contains(&ExprType::Enumeration(None)));
+    }
+}
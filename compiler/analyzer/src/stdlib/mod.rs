@@ -0,0 +1,99 @@
+//! The standard library of functions and function blocks.
+//!
+//! Standard functions are generic and so are described by signatures (see
+//! [`functions`]). Standard function blocks have a fixed interface and so
+//! are declared in IEC 61131-3 and added to the library for analysis.
+use ironplc_dsl::{
+    common::{Library, LibraryElementKind},
+    core::FileId,
+    diagnostic::Diagnostic,
+};
+use ironplc_parser::{options::ParseOptions, parse_program};
+
+pub mod functions;
+
+/// Declarations of the standard function blocks.
+const FUNCTION_BLOCKS: &str = include_str!("function_blocks.st");
+
+/// Returns the library that declares the standard function blocks.
+pub fn standard_function_blocks() -> Result<Library, Diagnostic> {
+    parse_program(
+        FUNCTION_BLOCKS,
+        &FileId::from_string("stdlib/function_blocks.st"),
+        &ParseOptions::default(),
+    )
+}
+
+/// Returns the library extended with the declarations of the standard
+/// function blocks. Function blocks declared in the library take precedence
+/// over standard function blocks with the same name.
+pub(crate) fn with_standard_function_blocks(library: Library) -> Result<Library, Vec<Diagnostic>> {
+    let standard = standard_function_blocks().map_err(|err| vec![err])?;
+
+    let declared: Vec<_> = library
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            LibraryElementKind::FunctionBlockDeclaration(fb) => Some(&fb.name),
+            _ => None,
+        })
+        .collect();
+
+    let elements = standard
+        .elements
+        .into_iter()
+        .filter(|element| match element {
+            LibraryElementKind::FunctionBlockDeclaration(fb) => !declared.contains(&&fb.name),
+            _ => true,
+        })
+        .collect();
+
+    Ok(library.extend(Library { elements }))
+}
+
+#[cfg(test)]
+mod tests {
+    use ironplc_dsl::common::TypeName;
+
+    use super::*;
+
+    fn function_block_names(library: &Library) -> Vec<TypeName> {
+        library
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                LibraryElementKind::FunctionBlockDeclaration(fb) => Some(fb.name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn with_standard_function_blocks_when_empty_then_has_timers_and_counters() {
+        let library = with_standard_function_blocks(Library::new()).unwrap();
+        let names = function_block_names(&library);
+
+        assert!(names.contains(&TypeName::from("TON")));
+        assert!(names.contains(&TypeName::from("ctu_dint")));
+        assert!(names.contains(&TypeName::from("R_TRIG")));
+    }
+
+    #[test]
+    fn with_standard_function_blocks_when_declared_then_keeps_declaration() {
+        let program = "
+FUNCTION_BLOCK TON
+END_FUNCTION_BLOCK";
+        let library = parse_program(program, &FileId::default(), &ParseOptions::default()).unwrap();
+
+        let library = with_standard_function_blocks(library).unwrap();
+        let names = function_block_names(&library);
+
+        assert_eq!(
+            1,
+            names
+                .iter()
+                .filter(|name| **name == TypeName::from("TON"))
+                .count()
+        );
+    }
+}
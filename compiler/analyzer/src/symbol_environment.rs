@@ -1,7 +1,7 @@
 use ironplc_dsl::common::TypeName;
 use ironplc_dsl::core::{Id, Located};
 use ironplc_dsl::diagnostic::Diagnostic;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Represents the kind of scope a symbol belongs to
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -40,6 +40,12 @@ pub enum SymbolKind {
     StructureElement,
     /// Edge variable (rising/falling edge)
     EdgeVariable,
+    /// Interface declaration
+    Interface,
+    /// Method of a function block or interface
+    Method,
+    /// Property of a function block or interface
+    Property,
 }
 
 /// Metadata associated with a symbol
@@ -118,6 +124,8 @@ pub struct SymbolEnvironment {
     scoped_symbols: HashMap<ScopeKind, HashMap<Id, SymbolInfo>>,
     /// Symbol resolution cache for performance
     resolution_cache: HashMap<(Id, ScopeKind), Option<&'static SymbolInfo>>,
+    /// The types that a function block or interface inherits from (EXTENDS)
+    base_types: HashMap<Id, Vec<Id>>,
 }
 
 impl SymbolEnvironment {
@@ -126,9 +134,94 @@ impl SymbolEnvironment {
             global_symbols: HashMap::new(),
             scoped_symbols: HashMap::new(),
             resolution_cache: HashMap::new(),
+            base_types: HashMap::new(),
         }
     }
 
+    /// Returns the name of the scope for the symbols declared within a
+    /// method or property.
+    ///
+    /// Methods of different function blocks can have the same name, so
+    /// the scope is qualified by the name of the owner.
+    pub fn member_scope_name(owner: &Id, member: &Id) -> Id {
+        Id::from(format!("{}.{}", owner.original(), member.original()).as_str())
+            .with_position(member.span())
+    }
+
+    /// Records that the derived function block or interface inherits from
+    /// the base type.
+    pub fn insert_base_type(&mut self, derived: &Id, base: &Id) {
+        self.base_types
+            .entry(derived.clone())
+            .or_default()
+            .push(base.clone());
+    }
+
+    /// Get the types that the function block or interface directly
+    /// inherits from.
+    pub fn get_base_types(&self, name: &Id) -> &[Id] {
+        self.base_types
+            .get(name)
+            .map(|bases| bases.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Find a symbol declared in the scope of the function block or
+    /// interface, searching through the inheritance chain.
+    ///
+    /// The nearest declaration wins so that a symbol in a derived type
+    /// hides the symbol in the base type.
+    pub fn find_inherited(&self, name: &Id, owner: &Id) -> Option<&SymbolInfo> {
+        let mut visited = HashSet::new();
+        let mut pending = vec![owner];
+        while let Some(current) = pending.pop() {
+            // Guard against cycles in erroneous declarations
+            if !visited.insert(current) {
+                continue;
+            }
+            let found = self
+                .scoped_symbols
+                .get(&ScopeKind::Named(current.clone()))
+                .and_then(|symbols| symbols.get(name));
+            if found.is_some() {
+                return found;
+            }
+            pending.extend(self.get_base_types(current).iter().rev());
+        }
+        None
+    }
+
+    /// Find a method of the function block or interface, searching through
+    /// the inheritance chain.
+    pub fn find_method(&self, owner: &Id, method: &Id) -> Option<&SymbolInfo> {
+        self.find_inherited(method, owner)
+            .filter(|symbol| symbol.kind == SymbolKind::Method)
+    }
+
+    /// Get all methods of the function block or interface including
+    /// inherited methods.
+    pub fn get_methods(&self, owner: &Id) -> Vec<(&Id, &SymbolInfo)> {
+        let mut methods: Vec<(&Id, &SymbolInfo)> = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![owner];
+        while let Some(current) = pending.pop() {
+            if !visited.insert(current) {
+                continue;
+            }
+            if let Some(symbols) = self.scoped_symbols.get(&ScopeKind::Named(current.clone())) {
+                for (name, symbol) in symbols {
+                    if symbol.kind == SymbolKind::Method
+                        && !methods.iter().any(|(existing, _)| *existing == name)
+                    {
+                        methods.push((name, symbol));
+                    }
+                }
+            }
+            pending.extend(self.get_base_types(current).iter().rev());
+        }
+        methods
+    }
+
     /// Insert a symbol into the environment
     pub fn insert(
         &mut self,
@@ -999,4 +1092,46 @@ mod tests {
         assert!(!symbol_info.is_external);
         assert!(symbol_info.data_type.is_none());
     }
+
+    #[test]
+    fn find_method_when_method_in_base_then_returns_method() {
+        let mut env = SymbolEnvironment::new();
+        let base = Id::from("BASE");
+        let derived = Id::from("DERIVED");
+        let base_scope = ScopeKind::Named(base.clone());
+        let derived_scope = ScopeKind::Named(derived.clone());
+
+        env.insert(&Id::from("RUN"), SymbolKind::Method, &base_scope)
+            .unwrap();
+        env.insert(&Id::from("VALUE"), SymbolKind::Variable, &base_scope)
+            .unwrap();
+        env.insert(&Id::from("STOP"), SymbolKind::Method, &derived_scope)
+            .unwrap();
+        env.insert_base_type(&derived, &base);
+
+        assert!(env.find_method(&derived, &Id::from("RUN")).is_some());
+        assert!(env.find_method(&derived, &Id::from("VALUE")).is_none());
+        assert!(env.find_inherited(&Id::from("VALUE"), &derived).is_some());
+        assert!(env.find_method(&base, &Id::from("STOP")).is_none());
+
+        let mut methods: Vec<String> = env
+            .get_methods(&derived)
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        methods.sort();
+        assert_eq!(vec!["RUN", "STOP"], methods);
+    }
+
+    #[test]
+    fn find_method_when_inheritance_cycle_then_terminates() {
+        let mut env = SymbolEnvironment::new();
+        let a = Id::from("A");
+        let b = Id::from("B");
+        env.insert_base_type(&a, &b);
+        env.insert_base_type(&b, &a);
+
+        assert!(env.find_method(&a, &Id::from("RUN")).is_none());
+        assert!(env.get_methods(&a).is_empty());
+    }
 }
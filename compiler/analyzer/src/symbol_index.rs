@@ -0,0 +1,1139 @@
+//! Index of the declarations in a set of libraries and of the references
+//! to those declarations.
+//!
+//! The index is for interactive tools such as the language server. Unlike
+//! the semantic analysis stages, the index depends only on the syntax of
+//! the libraries. The index is therefore available for libraries that have
+//! errors, including the partial libraries from sources with syntax errors.
+//!
+//! Names resolve in the innermost scope that declares the name:
+//!
+//! 1. the method, function, function block or program that contains the
+//!    reference (including variables inherited from base function blocks)
+//! 2. the configuration and resource that contains the reference
+//! 3. global variables
+//! 4. types, POUs and configurations
+//! 5. enumeration values
+//!
+//! A reference to an external variable resolves to the global variable
+//! that the external variable refers to.
+use std::collections::HashMap;
+
+use ironplc_dsl::{
+    common::*,
+    configuration::*,
+    core::{FileId, Id, SourceSpan},
+    textual::*,
+    visitor::Visitor,
+};
+
+/// Limit on following type references so that cyclic declarations terminate.
+const MAX_TYPE_DEPTH: usize = 16;
+
+/// Identifies a definition in the index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DefinitionId(usize);
+
+/// The kinds of declarations that the index contains.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DefinitionKind {
+    /// A variable declared in a POU, configuration or resource.
+    Variable(VariableType),
+    StructureElement,
+    EnumerationValue,
+    /// A data type declaration.
+    Type,
+    Function,
+    FunctionBlock,
+    Program,
+    Interface,
+    Method,
+    Property,
+    Configuration,
+    Resource,
+    Task,
+    /// A program configuration in a resource (an instance of a program).
+    ProgramInstance,
+}
+
+/// A declaration in the index.
+#[derive(Clone, Debug)]
+pub struct Definition {
+    /// The declared name. The span of the name is the location of the
+    /// declaration.
+    pub name: Id,
+    pub kind: DefinitionKind,
+    /// The definition that contains this definition, for example, the
+    /// function block that declares a variable.
+    pub container: Option<DefinitionId>,
+    /// The type associated with the definition:
+    ///
+    /// * variables, structure elements and properties: the declared type
+    ///   (the element type for arrays and the referenced type for references)
+    /// * program instances: the program
+    /// * functions and methods: the return type
+    /// * function blocks: the function block that this extends
+    /// * types: the type that this type derives from, if any
+    pub type_name: Option<TypeName>,
+    /// The declaration for variables, structure elements and types.
+    pub declaration: Option<Declaration>,
+}
+
+/// The declaration (syntax) of a definition.
+#[derive(Clone, Debug)]
+pub enum Declaration {
+    Variable(VarDecl),
+    StructureElement(StructureElementDeclaration),
+    DataType(DataTypeDeclarationKind),
+}
+
+/// A use of a name that refers to a definition.
+#[derive(Clone, Debug)]
+pub struct Reference {
+    pub span: SourceSpan,
+    pub definition: DefinitionId,
+}
+
+/// Index of declarations and references.
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    definitions: Vec<Definition>,
+    references: Vec<Reference>,
+    /// Types, POUs and configurations by name.
+    top_level: HashMap<Id, DefinitionId>,
+    /// Global variables by name.
+    globals: HashMap<Id, DefinitionId>,
+    /// The definitions that each definition contains in declaration order.
+    members: HashMap<DefinitionId, Vec<DefinitionId>>,
+    enumeration_values: Vec<DefinitionId>,
+    /// The definition for the location of the name in a declaration.
+    declarations: HashMap<(FileId, usize), DefinitionId>,
+}
+
+impl SymbolIndex {
+    /// Creates the index for the libraries. The libraries are the complete
+    /// set of libraries so that references can be between libraries.
+    pub fn new(libraries: &[&Library]) -> Self {
+        let mut index = SymbolIndex::default();
+        for library in libraries {
+            for element in &library.elements {
+                index.declare_element(element);
+            }
+        }
+
+        let mut resolver = ReferenceResolver {
+            index: &mut index,
+            scopes: vec![],
+        };
+        for library in libraries {
+            // The resolver does not fail.
+            let _ = resolver.walk(library);
+        }
+
+        index
+    }
+
+    /// Returns the definition that is declared or referenced at the
+    /// offset in the file.
+    pub fn find(&self, file_id: &FileId, offset: usize) -> Option<DefinitionId> {
+        self.find_name(file_id, offset).map(|(id, _)| id)
+    }
+
+    /// Returns the definition that is declared or referenced at the
+    /// offset in the file and the location of the name at the offset.
+    pub fn find_name(
+        &self,
+        file_id: &FileId,
+        offset: usize,
+    ) -> Option<(DefinitionId, &SourceSpan)> {
+        self.references
+            .iter()
+            .find(|reference| contains(&reference.span, file_id, offset))
+            .map(|reference| (reference.definition, &reference.span))
+            .or_else(|| {
+                self.definitions
+                    .iter()
+                    .position(|definition| contains(&definition.name.span, file_id, offset))
+                    .map(|id| (DefinitionId(id), &self.definitions[id].name.span))
+            })
+    }
+
+    /// Returns the definition for the identifier.
+    pub fn definition(&self, id: DefinitionId) -> &Definition {
+        &self.definitions[id.0]
+    }
+
+    /// Returns the locations that refer to the definition. The locations do
+    /// not include the declaration.
+    pub fn references(&self, id: DefinitionId) -> Vec<&SourceSpan> {
+        self.references
+            .iter()
+            .filter(|reference| reference.definition == id)
+            .map(|reference| &reference.span)
+            .collect()
+    }
+
+    /// Returns the definitions that the definition contains in declaration
+    /// order. The members do not include inherited members.
+    pub fn members(&self, id: DefinitionId) -> &[DefinitionId] {
+        self.members.get(&id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns all definitions in the index in declaration order.
+    pub fn definitions(&self) -> impl Iterator<Item = DefinitionId> {
+        (0..self.definitions.len()).map(DefinitionId)
+    }
+
+    /// Returns the member of the definition with the name. Members of
+    /// variables are the members of the type of the variable and members
+    /// of function blocks include the members of base function blocks.
+    pub fn member(&self, id: DefinitionId, name: &Id) -> Option<DefinitionId> {
+        self.member_containers(id)
+            .into_iter()
+            .find_map(|container| {
+                self.members(container)
+                    .iter()
+                    .find(|member| self.definition(**member).name == *name)
+                    .copied()
+            })
+    }
+
+    /// Returns the members that are accessible through the definition,
+    /// that is, the members that [`SymbolIndex::member`] finds. A member
+    /// hides an inherited member with the same name.
+    pub fn visible_members(&self, id: DefinitionId) -> Vec<DefinitionId> {
+        let mut visible: Vec<DefinitionId> = vec![];
+        for container in self.member_containers(id) {
+            for member in self.members(container) {
+                let name = &self.definition(*member).name;
+                if !visible.iter().any(|v| self.definition(*v).name == *name) {
+                    visible.push(*member);
+                }
+            }
+        }
+        visible
+    }
+
+    /// Returns the definition for the name as seen from the scope. The
+    /// scope is the innermost definition (such as a method or program)
+    /// that contains the name and the name resolves in the scope and then
+    /// in the containers of the scope.
+    pub fn lookup(&self, scope: Option<DefinitionId>, name: &Id) -> Option<DefinitionId> {
+        let mut current = scope;
+        while let Some(id) = current {
+            if let Some(member) = self.member(id, name) {
+                return Some(self.global_for_external(member));
+            }
+            current = self.definition(id).container;
+        }
+        self.globals
+            .get(name)
+            .or_else(|| self.top_level.get(name))
+            .copied()
+            .or_else(|| self.enumeration_value(None, name))
+    }
+
+    /// Returns a definition that has the name in the scope of the
+    /// definition, that is, a definition that would conflict with the
+    /// definition if the definition had the name. For global variables, the
+    /// scope includes the POUs that declare the global variable as external.
+    pub fn conflict(&self, id: DefinitionId, name: &Id) -> Option<DefinitionId> {
+        let definition = self.definition(id);
+        let mut scopes = vec![definition.container];
+        if definition.kind == DefinitionKind::Variable(VariableType::Global) {
+            scopes.extend(
+                self.definitions()
+                    .filter(|other| {
+                        let other = self.definition(*other);
+                        other.kind == DefinitionKind::Variable(VariableType::External)
+                            && other.name == definition.name
+                    })
+                    .map(|external| self.definition(external).container),
+            );
+        }
+
+        scopes
+            .into_iter()
+            .filter_map(|scope| match scope {
+                Some(scope) => self.member(scope, name),
+                None => self.top_level.get(name).copied(),
+            })
+            .find(|other| *other != id && self.global_for_external(*other) != id)
+    }
+
+    /// Returns the definitions that declare the members accessible through
+    /// the definition, following the type of variables and the base of
+    /// types and function blocks.
+    fn member_containers(&self, id: DefinitionId) -> Vec<DefinitionId> {
+        let mut containers = vec![];
+        let mut current = Some(id);
+        for _ in 0..MAX_TYPE_DEPTH {
+            let Some(id) = current else {
+                break;
+            };
+            let definition = self.definition(id);
+            match definition.kind {
+                DefinitionKind::Variable(_)
+                | DefinitionKind::StructureElement
+                | DefinitionKind::Property
+                | DefinitionKind::ProgramInstance => {}
+                _ => containers.push(id),
+            }
+            current = match definition.kind {
+                DefinitionKind::Function
+                | DefinitionKind::Method
+                | DefinitionKind::EnumerationValue => None,
+                _ => definition
+                    .type_name
+                    .as_ref()
+                    .and_then(|type_name| self.type_definition(type_name)),
+            };
+        }
+        containers
+    }
+
+    /// Returns the global variable for an external variable. Otherwise
+    /// returns the definition.
+    fn global_for_external(&self, id: DefinitionId) -> DefinitionId {
+        let definition = self.definition(id);
+        match definition.kind {
+            DefinitionKind::Variable(VariableType::External) => {
+                self.globals.get(&definition.name).copied().unwrap_or(id)
+            }
+            _ => id,
+        }
+    }
+
+    /// Returns the definition of the type, POU or configuration with the name.
+    pub fn type_definition(&self, type_name: &TypeName) -> Option<DefinitionId> {
+        self.top_level.get(&type_name.name).copied()
+    }
+
+    /// Returns the enumeration value with the name. If the type name is
+    /// specified, then the value must be declared by that type.
+    fn enumeration_value(&self, type_name: Option<&TypeName>, value: &Id) -> Option<DefinitionId> {
+        self.enumeration_values.iter().copied().find(|id| {
+            let definition = self.definition(*id);
+            definition.name == *value
+                && type_name.is_none_or(|type_name| {
+                    definition
+                        .container
+                        .is_some_and(|container| self.definition(container).name == type_name.name)
+                })
+        })
+    }
+
+    /// Returns the definition declared by the name (at the location of the name).
+    fn declaration(&self, name: &Id) -> Option<DefinitionId> {
+        self.declarations
+            .get(&(name.span.file_id.clone(), name.span.start))
+            .copied()
+    }
+
+    fn declare(
+        &mut self,
+        name: &Id,
+        kind: DefinitionKind,
+        container: Option<DefinitionId>,
+        type_name: Option<TypeName>,
+    ) -> DefinitionId {
+        let id = DefinitionId(self.definitions.len());
+        self.declarations
+            .insert((name.span.file_id.clone(), name.span.start), id);
+        if let Some(container) = container {
+            self.members.entry(container).or_default().push(id);
+        }
+        self.definitions.push(Definition {
+            name: name.clone(),
+            kind,
+            container,
+            type_name,
+            declaration: None,
+        });
+        id
+    }
+
+    fn set_declaration(&mut self, id: DefinitionId, declaration: Declaration) {
+        self.definitions[id.0].declaration = Some(declaration);
+    }
+
+    fn declare_top_level(
+        &mut self,
+        name: &Id,
+        kind: DefinitionKind,
+        type_name: Option<TypeName>,
+    ) -> DefinitionId {
+        let id = self.declare(name, kind, None, type_name);
+        self.top_level.entry(name.clone()).or_insert(id);
+        id
+    }
+
+    fn declare_enumeration_value(&mut self, container: DefinitionId, value: &Id) {
+        let id = self.declare(
+            value,
+            DefinitionKind::EnumerationValue,
+            Some(container),
+            None,
+        );
+        self.enumeration_values.push(id);
+    }
+
+    fn declare_element(&mut self, element: &LibraryElementKind) {
+        match element {
+            LibraryElementKind::DataTypeDeclaration(decl) => self.declare_data_type(decl),
+            LibraryElementKind::FunctionDeclaration(decl) => {
+                let id = self.declare_top_level(
+                    &decl.name,
+                    DefinitionKind::Function,
+                    Some(decl.return_type.clone()),
+                );
+                self.declare_variables(id, &decl.variables);
+                self.declare_edge_variables(id, &decl.edge_variables);
+            }
+            LibraryElementKind::FunctionBlockDeclaration(decl) => {
+                let id = self.declare_top_level(
+                    &decl.name.name,
+                    DefinitionKind::FunctionBlock,
+                    decl.extends.clone(),
+                );
+                self.declare_variables(id, &decl.variables);
+                self.declare_edge_variables(id, &decl.edge_variables);
+                self.declare_methods(id, &decl.methods);
+                self.declare_properties(id, &decl.properties);
+            }
+            LibraryElementKind::ProgramDeclaration(decl) => {
+                let id = self.declare_top_level(&decl.name, DefinitionKind::Program, None);
+                self.declare_variables(id, &decl.variables);
+            }
+            LibraryElementKind::ConfigurationDeclaration(decl) => {
+                let id = self.declare_top_level(&decl.name, DefinitionKind::Configuration, None);
+                self.declare_variables(id, &decl.global_var);
+                for resource in &decl.resource_decl {
+                    let resource_id =
+                        self.declare(&resource.name, DefinitionKind::Resource, Some(id), None);
+                    self.declare_variables(resource_id, &resource.global_vars);
+                    for task in &resource.tasks {
+                        self.declare(&task.name, DefinitionKind::Task, Some(resource_id), None);
+                    }
+                    for program in &resource.programs {
+                        self.declare(
+                            &program.name,
+                            DefinitionKind::ProgramInstance,
+                            Some(resource_id),
+                            Some(TypeName::from_id(&program.type_name)),
+                        );
+                    }
+                }
+            }
+            LibraryElementKind::InterfaceDeclaration(decl) => {
+                let id = self.declare_top_level(&decl.name.name, DefinitionKind::Interface, None);
+                self.declare_methods(id, &decl.methods);
+                self.declare_properties(id, &decl.properties);
+            }
+        }
+    }
+
+    fn declare_data_type(&mut self, decl: &DataTypeDeclarationKind) {
+        let (type_name, base) = match decl {
+            DataTypeDeclarationKind::Enumeration(decl) => (
+                &decl.type_name,
+                match &decl.spec_init.spec {
+                    EnumeratedSpecificationKind::TypeName(base) => Some(base.clone()),
+                    EnumeratedSpecificationKind::Values(_) => None,
+                },
+            ),
+            DataTypeDeclarationKind::Subrange(decl) => (
+                &decl.type_name,
+                match &decl.spec {
+                    SubrangeSpecificationKind::Type(base) => Some(base.clone()),
+                    SubrangeSpecificationKind::Specification(_) => None,
+                },
+            ),
+            DataTypeDeclarationKind::Simple(decl) => {
+                (&decl.type_name, initializer_type_name(&decl.spec_and_init))
+            }
+            DataTypeDeclarationKind::Array(decl) => {
+                (&decl.type_name, array_element_type_name(&decl.spec))
+            }
+            DataTypeDeclarationKind::Structure(decl) => (&decl.type_name, None),
+            DataTypeDeclarationKind::StructureInitialization(decl) => (&decl.type_name, None),
+            DataTypeDeclarationKind::String(decl) => (&decl.type_name, None),
+            DataTypeDeclarationKind::Reference(decl) => (
+                &decl.type_name,
+                Some(decl.spec_init.referenced_type_name.clone()),
+            ),
+            DataTypeDeclarationKind::LateBound(decl) => {
+                (&decl.data_type_name, Some(decl.base_type_name.clone()))
+            }
+        };
+
+        let id = self.declare_top_level(&type_name.name, DefinitionKind::Type, base);
+        self.set_declaration(id, Declaration::DataType(decl.clone()));
+
+        match decl {
+            DataTypeDeclarationKind::Enumeration(decl) => {
+                if let EnumeratedSpecificationKind::Values(values) = &decl.spec_init.spec {
+                    for value in &values.values {
+                        self.declare_enumeration_value(id, &value.value);
+                    }
+                }
+            }
+            DataTypeDeclarationKind::Structure(decl) => {
+                for element in &decl.elements {
+                    let element_id = self.declare(
+                        &element.name,
+                        DefinitionKind::StructureElement,
+                        Some(id),
+                        initializer_type_name(&element.init),
+                    );
+                    self.set_declaration(
+                        element_id,
+                        Declaration::StructureElement(element.clone()),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn declare_variables(&mut self, container: DefinitionId, variables: &[VarDecl]) {
+        for variable in variables {
+            if let Some(name) = variable.identifier.symbolic_id() {
+                let id = self.declare(
+                    name,
+                    DefinitionKind::Variable(variable.var_type.clone()),
+                    Some(container),
+                    initializer_type_name(&variable.initializer),
+                );
+                self.set_declaration(id, Declaration::Variable(variable.clone()));
+                if variable.var_type == VariableType::Global {
+                    self.globals.entry(name.clone()).or_insert(id);
+                }
+                if let InitialValueAssignmentKind::EnumeratedValues(init) = &variable.initializer {
+                    for value in &init.values {
+                        self.declare_enumeration_value(id, &value.value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn declare_edge_variables(&mut self, container: DefinitionId, variables: &[EdgeVarDecl]) {
+        for variable in variables {
+            self.declare(
+                &variable.identifier,
+                DefinitionKind::Variable(VariableType::Input),
+                Some(container),
+                Some(TypeName::from("BOOL")),
+            );
+        }
+    }
+
+    fn declare_methods(&mut self, container: DefinitionId, methods: &[MethodDeclaration]) {
+        for method in methods {
+            let id = self.declare(
+                &method.name,
+                DefinitionKind::Method,
+                Some(container),
+                method.return_type.clone(),
+            );
+            self.declare_variables(id, &method.variables);
+        }
+    }
+
+    fn declare_properties(&mut self, container: DefinitionId, properties: &[PropertyDeclaration]) {
+        for property in properties {
+            self.declare(
+                &property.name,
+                DefinitionKind::Property,
+                Some(container),
+                Some(property.property_type.clone()),
+            );
+        }
+    }
+}
+
+/// Returns the name of the type of a variable declared with the initializer.
+fn initializer_type_name(init: &InitialValueAssignmentKind) -> Option<TypeName> {
+    match init {
+        InitialValueAssignmentKind::None(_) => None,
+        InitialValueAssignmentKind::Simple(init) => Some(init.type_name.clone()),
+        InitialValueAssignmentKind::String(init) => Some(init.type_name()),
+        InitialValueAssignmentKind::EnumeratedValues(_) => None,
+        InitialValueAssignmentKind::EnumeratedType(init) => Some(init.type_name.clone()),
+        InitialValueAssignmentKind::FunctionBlock(init) => Some(init.type_name.clone()),
+        InitialValueAssignmentKind::Subrange(spec) => match spec {
+            SubrangeSpecificationKind::Type(type_name) => Some(type_name.clone()),
+            SubrangeSpecificationKind::Specification(_) => None,
+        },
+        InitialValueAssignmentKind::Structure(init) => Some(init.type_name.clone()),
+        InitialValueAssignmentKind::Array(init) => array_element_type_name(&init.spec),
+        InitialValueAssignmentKind::Reference(init) => Some(init.referenced_type_name.clone()),
+        InitialValueAssignmentKind::LateResolvedType(type_name) => Some(type_name.clone()),
+    }
+}
+
+fn array_element_type_name(spec: &ArraySpecificationKind) -> Option<TypeName> {
+    match spec {
+        ArraySpecificationKind::Type(type_name) => Some(type_name.clone()),
+        ArraySpecificationKind::Subranges(subranges) => Some(subranges.type_name.clone()),
+    }
+}
+
+/// Returns true if the span is in the file and contains the offset. The
+/// span contains the offset that is immediately after the span so that a
+/// position at the end of a name is in the name.
+fn contains(span: &SourceSpan, file_id: &FileId, offset: usize) -> bool {
+    span.file_id == *file_id && span.start <= offset && offset <= span.end
+}
+
+/// Visits the libraries to find the definition for each reference.
+struct ReferenceResolver<'a> {
+    index: &'a mut SymbolIndex,
+    /// The definitions that contain the node being visited, innermost last.
+    scopes: Vec<DefinitionId>,
+}
+
+impl ReferenceResolver<'_> {
+    /// Visits the node with the definition declared by the name as the
+    /// innermost scope.
+    fn in_scope<F>(&mut self, name: &Id, f: F) -> Result<(), ()>
+    where
+        F: FnOnce(&mut Self) -> Result<(), ()>,
+    {
+        match self.index.declaration(name) {
+            Some(id) => {
+                self.scopes.push(id);
+                let result = f(self);
+                self.scopes.pop();
+                result
+            }
+            None => f(self),
+        }
+    }
+
+    /// Returns the definition for the name in the current scope.
+    fn lookup(&self, name: &Id) -> Option<DefinitionId> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(id) = self.index.member(*scope, name) {
+                return Some(self.index.global_for_external(id));
+            }
+        }
+        self.index
+            .globals
+            .get(name)
+            .or_else(|| self.index.top_level.get(name))
+            .copied()
+    }
+
+    fn current_function_block(&self) -> Option<DefinitionId> {
+        self.scopes
+            .iter()
+            .rev()
+            .find(|id| self.index.definition(**id).kind == DefinitionKind::FunctionBlock)
+            .copied()
+    }
+
+    /// Records the reference unless the name is the declaration of the
+    /// definition.
+    fn refer(&mut self, name: &Id, definition: DefinitionId) {
+        if self.index.declaration(name) == Some(definition) {
+            return;
+        }
+        self.index.references.push(Reference {
+            span: name.span.clone(),
+            definition,
+        });
+    }
+
+    fn refer_name(&mut self, name: &Id) {
+        if let Some(id) = self.lookup(name) {
+            self.refer(name, id);
+        }
+    }
+
+    fn refer_member(&mut self, container: Option<DefinitionId>, name: &Id) {
+        if let Some(id) = container.and_then(|container| self.index.member(container, name)) {
+            self.refer(name, id);
+        }
+    }
+
+    /// Records the references in the variable and returns the definition
+    /// that the variable refers to.
+    fn resolve_variable(
+        &mut self,
+        node: &SymbolicVariableKind,
+    ) -> Result<Option<DefinitionId>, ()> {
+        match node {
+            SymbolicVariableKind::Named(node) => {
+                let id = self.lookup(&node.name);
+                if let Some(id) = id {
+                    self.refer(&node.name, id);
+                }
+                Ok(id)
+            }
+            SymbolicVariableKind::Array(node) => {
+                let id = self.resolve_variable(&node.subscripted_variable)?;
+                for subscript in &node.subscripts {
+                    self.visit_expr_kind(subscript)?;
+                }
+                Ok(id)
+            }
+            SymbolicVariableKind::Structured(node) => {
+                let record = self.resolve_variable(&node.record)?;
+                let id = record.and_then(|record| self.index.member(record, &node.field));
+                if let Some(id) = id {
+                    self.refer(&node.field, id);
+                }
+                Ok(id)
+            }
+            SymbolicVariableKind::This(_) => Ok(self.current_function_block()),
+            SymbolicVariableKind::Super(_) => Ok(self.current_function_block().and_then(|id| {
+                self.index
+                    .definition(id)
+                    .type_name
+                    .as_ref()
+                    .and_then(|base| self.index.type_definition(base))
+            })),
+            SymbolicVariableKind::Deref(node) => self.resolve_variable(&node.reference),
+        }
+    }
+
+    /// Records the references in the parameters of a call to the target.
+    fn visit_params(
+        &mut self,
+        target: Option<DefinitionId>,
+        params: &[ParamAssignmentKind],
+    ) -> Result<(), ()> {
+        for param in params {
+            match param {
+                ParamAssignmentKind::PositionalInput(input) => self.visit_expr_kind(&input.expr)?,
+                ParamAssignmentKind::NamedInput(input) => {
+                    self.refer_member(target, &input.name);
+                    self.visit_expr_kind(&input.expr)?;
+                }
+                ParamAssignmentKind::Output(output) => {
+                    self.refer_member(target, &output.src);
+                    self.visit_variable(&output.tgt)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Visitor<()> for ReferenceResolver<'_> {
+    type Value = ();
+
+    fn visit_function_declaration(&mut self, node: &FunctionDeclaration) -> Result<(), ()> {
+        self.in_scope(&node.name, |v| node.recurse_visit(v))
+    }
+
+    fn visit_function_block_declaration(
+        &mut self,
+        node: &FunctionBlockDeclaration,
+    ) -> Result<(), ()> {
+        self.in_scope(&node.name.name, |v| node.recurse_visit(v))
+    }
+
+    fn visit_method_declaration(&mut self, node: &MethodDeclaration) -> Result<(), ()> {
+        self.in_scope(&node.name, |v| node.recurse_visit(v))
+    }
+
+    fn visit_program_declaration(&mut self, node: &ProgramDeclaration) -> Result<(), ()> {
+        self.in_scope(&node.name, |v| node.recurse_visit(v))
+    }
+
+    fn visit_interface_declaration(&mut self, node: &InterfaceDeclaration) -> Result<(), ()> {
+        self.in_scope(&node.name.name, |v| node.recurse_visit(v))
+    }
+
+    fn visit_configuration_declaration(
+        &mut self,
+        node: &ConfigurationDeclaration,
+    ) -> Result<(), ()> {
+        self.in_scope(&node.name, |v| node.recurse_visit(v))
+    }
+
+    fn visit_resource_declaration(&mut self, node: &ResourceDeclaration) -> Result<(), ()> {
+        self.in_scope(&node.name, |v| node.recurse_visit(v))
+    }
+
+    fn visit_var_decl(&mut self, node: &VarDecl) -> Result<(), ()> {
+        if node.var_type == VariableType::External {
+            if let Some(name) = node.identifier.symbolic_id() {
+                if let Some(global) = self.index.globals.get(name).copied() {
+                    self.refer(name, global);
+                }
+            }
+        }
+        node.recurse_visit(self)
+    }
+
+    fn visit_type_name(&mut self, node: &TypeName) -> Result<(), ()> {
+        if let Some(id) = self.index.type_definition(node) {
+            self.refer(&node.name, id);
+        }
+        Ok(())
+    }
+
+    fn visit_enumerated_specification_values(
+        &mut self,
+        _node: &EnumeratedSpecificationValues,
+    ) -> Result<(), ()> {
+        // The values are declarations.
+        Ok(())
+    }
+
+    fn visit_enumerated_values_initializer(
+        &mut self,
+        node: &EnumeratedValuesInitializer,
+    ) -> Result<(), ()> {
+        // The values are declarations and only the initial value is a reference.
+        match &node.initial_value {
+            Some(value) => self.visit_enumerated_value(value),
+            None => Ok(()),
+        }
+    }
+
+    fn visit_enumerated_value(&mut self, node: &EnumeratedValue) -> Result<(), ()> {
+        if let Some(type_name) = &node.type_name {
+            self.visit_type_name(type_name)?;
+        }
+        if let Some(id) = self
+            .index
+            .enumeration_value(node.type_name.as_ref(), &node.value)
+        {
+            self.refer(&node.value, id);
+        }
+        Ok(())
+    }
+
+    fn visit_late_bound(&mut self, node: &LateBound) -> Result<(), ()> {
+        let id = self
+            .lookup(&node.value)
+            .or_else(|| self.index.enumeration_value(None, &node.value));
+        if let Some(id) = id {
+            self.refer(&node.value, id);
+        }
+        Ok(())
+    }
+
+    fn visit_symbolic_variable_kind(&mut self, node: &SymbolicVariableKind) -> Result<(), ()> {
+        self.resolve_variable(node).map(|_| ())
+    }
+
+    fn visit_fb_call(&mut self, node: &FbCall) -> Result<(), ()> {
+        let instance = self.lookup(&node.var_name);
+        if let Some(instance) = instance {
+            self.refer(&node.var_name, instance);
+        }
+        self.visit_params(instance, &node.params)
+    }
+
+    fn visit_function(&mut self, node: &Function) -> Result<(), ()> {
+        let function = self.index.top_level.get(&node.name).copied();
+        if let Some(function) = function {
+            self.refer(&node.name, function);
+        }
+        self.visit_params(function, &node.param_assignment)
+    }
+
+    fn visit_method_call(&mut self, node: &MethodCall) -> Result<(), ()> {
+        let receiver = self.resolve_variable(&node.receiver)?;
+        let method = receiver.and_then(|receiver| self.index.member(receiver, &node.name));
+        if let Some(method) = method {
+            self.refer(&node.name, method);
+        }
+        self.visit_params(method, &node.params)
+    }
+
+    fn visit_for(&mut self, node: &For) -> Result<(), ()> {
+        self.refer_name(&node.control);
+        node.recurse_visit(self)
+    }
+
+    fn visit_program_configuration(&mut self, node: &ProgramConfiguration) -> Result<(), ()> {
+        let program = self.index.top_level.get(&node.type_name).copied();
+        if let Some(program) = program {
+            self.refer(&node.type_name, program);
+        }
+        if let Some(task_name) = &node.task_name {
+            self.refer_name(task_name);
+        }
+        for fb_task in &node.fb_tasks {
+            self.refer_member(program, &fb_task.fb_name);
+            self.refer_name(&fb_task.task_name);
+        }
+
+        // The connections are to variables in the program.
+        if let Some(program) = program {
+            self.scopes.push(program);
+        }
+        for source in &node.sources {
+            self.resolve_variable(&source.dst)?;
+        }
+        for sink in &node.sinks {
+            self.resolve_variable(&sink.src)?;
+        }
+        if program.is_some() {
+            self.scopes.pop();
+        }
+
+        for source in &node.sources {
+            self.visit_program_connection_source_kind(&source.src)?;
+        }
+        for sink in &node.sinks {
+            self.visit_program_connection_sink_kind(&sink.dst)?;
+        }
+        Ok(())
+    }
+
+    fn visit_global_var_reference(&mut self, node: &GlobalVarReference) -> Result<(), ()> {
+        self.refer_name(&node.global_var_name);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ironplc_dsl::{
+        common::Library,
+        core::{FileId, Id},
+    };
+    use ironplc_parser::{options::ParseOptions, parse_program};
+
+    use super::{DefinitionKind, SymbolIndex};
+
+    fn parse(program: &str, file: &str) -> Library {
+        parse_program(
+            program,
+            &FileId::from_string(file),
+            &ParseOptions::default(),
+        )
+        .unwrap()
+    }
+
+    /// Returns the offset of the nth (0-based) occurrence of the text.
+    fn offset(program: &str, text: &str, nth: usize) -> usize {
+        program.match_indices(text).nth(nth).unwrap().0
+    }
+
+    /// Returns the name of the definition at the nth occurrence of the
+    /// text and the offsets of the references to the definition.
+    fn find(index: &SymbolIndex, program: &str, text: &str, nth: usize) -> (Id, Vec<usize>) {
+        let id = index
+            .find(&FileId::from_string("main.st"), offset(program, text, nth))
+            .unwrap();
+        let references = index.references(id).iter().map(|s| s.start).collect();
+        (index.definition(id).name.clone(), references)
+    }
+
+    #[test]
+    fn find_when_variable_reference_then_declaration_and_references() {
+        let program = "
+PROGRAM main
+VAR
+   counter : INT;
+END_VAR
+   counter := counter + 1;
+END_PROGRAM";
+        let library = parse(program, "main.st");
+        let index = SymbolIndex::new(&[&library]);
+
+        let (name, references) = find(&index, program, "counter", 2);
+
+        assert_eq!("counter", name.original());
+        assert_eq!(offset(program, "counter", 0), name.span.start);
+        assert_eq!(
+            vec![offset(program, "counter", 1), offset(program, "counter", 2)],
+            references
+        );
+    }
+
+    #[test]
+    fn find_when_same_name_in_other_pou_then_not_reference() {
+        let program = "
+FUNCTION_BLOCK fb
+VAR
+   x : INT;
+END_VAR
+   x := 1;
+END_FUNCTION_BLOCK
+PROGRAM main
+VAR
+   x : INT;
+END_VAR
+   x := 2;
+END_PROGRAM";
+        let library = parse(program, "main.st");
+        let index = SymbolIndex::new(&[&library]);
+
+        let (name, references) = find(&index, program, "x", 3);
+
+        assert_eq!(offset(program, "x", 2), name.span.start);
+        assert_eq!(vec![offset(program, "x", 3)], references);
+    }
+
+    #[test]
+    fn find_when_enumeration_value_then_value_declaration() {
+        let program = "
+TYPE
+   LEVEL : (LOW, HIGH) := LOW;
+END_TYPE
+PROGRAM main
+VAR
+   level_value : LEVEL := HIGH;
+END_VAR
+   level_value := LOW;
+END_PROGRAM";
+        let library = parse(program, "main.st");
+        let index = SymbolIndex::new(&[&library]);
+
+        let (name, references) = find(&index, program, "LOW", 2);
+        assert_eq!(offset(program, "LOW", 0), name.span.start);
+        assert_eq!(
+            vec![offset(program, "LOW", 1), offset(program, "LOW", 2)],
+            references
+        );
+
+        let (name, references) = find(&index, program, "LEVEL", 1);
+        assert_eq!(offset(program, "LEVEL", 0), name.span.start);
+        assert_eq!(vec![offset(program, "LEVEL", 1)], references);
+    }
+
+    #[test]
+    fn find_when_structure_field_then_element_declaration() {
+        let program = "
+TYPE
+   POINT : STRUCT
+      x : INT;
+      y : INT;
+   END_STRUCT;
+END_TYPE
+PROGRAM main
+VAR
+   p : POINT;
+END_VAR
+   p.y := 1;
+END_PROGRAM";
+        let library = parse(program, "main.st");
+        let index = SymbolIndex::new(&[&library]);
+
+        let (name, _) = find(&index, program, "y", 1);
+        assert_eq!(offset(program, "y", 0), name.span.start);
+    }
+
+    #[test]
+    fn find_when_function_block_in_other_file_then_declaration_in_other_file() {
+        let fb = "
+FUNCTION_BLOCK counter
+VAR_INPUT
+   reset : BOOL;
+END_VAR
+END_FUNCTION_BLOCK";
+        let program = "
+PROGRAM main
+VAR
+   instance : counter;
+END_VAR
+   instance(reset := TRUE);
+END_PROGRAM";
+        let fb_library = parse(fb, "fb.st");
+        let library = parse(program, "main.st");
+        let index = SymbolIndex::new(&[&fb_library, &library]);
+
+        let id = index
+            .find(
+                &FileId::from_string("main.st"),
+                offset(program, "counter", 0),
+            )
+            .unwrap();
+        let definition = index.definition(id);
+        assert_eq!(DefinitionKind::FunctionBlock, definition.kind);
+        assert_eq!(FileId::from_string("fb.st"), definition.name.span.file_id);
+
+        let (name, references) = find(&index, program, "reset", 0);
+        assert_eq!(offset(fb, "reset", 0), name.span.start);
+        assert_eq!(vec![offset(program, "reset", 0)], references);
+
+        let (_, references) = find(&index, program, "instance", 0);
+        assert_eq!(vec![offset(program, "instance", 1)], references);
+    }
+
+    #[test]
+    fn find_when_external_variable_then_global_variable() {
+        let program = "
+PROGRAM main
+VAR_EXTERNAL
+   shared : INT;
+END_VAR
+   shared := 1;
+END_PROGRAM
+CONFIGURATION config
+VAR_GLOBAL
+   shared : INT;
+END_VAR
+RESOURCE res ON PLC
+   TASK plc_task(INTERVAL := T#100ms, PRIORITY := 1);
+   PROGRAM instance WITH plc_task : main;
+END_RESOURCE
+END_CONFIGURATION";
+        let library = parse(program, "main.st");
+        let index = SymbolIndex::new(&[&library]);
+
+        let (name, references) = find(&index, program, "shared", 1);
+        assert_eq!(offset(program, "shared", 2), name.span.start);
+        assert_eq!(
+            vec![offset(program, "shared", 0), offset(program, "shared", 1)],
+            references
+        );
+
+        let (name, _) = find(&index, program, "main", 1);
+        assert_eq!(offset(program, "main", 0), name.span.start);
+
+        let (name, _) = find(&index, program, "plc_task", 1);
+        assert_eq!(offset(program, "plc_task", 0), name.span.start);
+    }
+
+    #[test]
+    fn conflict_when_name_in_scope_then_conflicting_definition() {
+        let program = "
+PROGRAM main
+VAR_EXTERNAL
+   shared : INT;
+END_VAR
+VAR
+   local : INT;
+   other : INT;
+END_VAR
+END_PROGRAM
+CONFIGURATION config
+VAR_GLOBAL
+   shared : INT;
+END_VAR
+RESOURCE res ON PLC
+   TASK plc_task(INTERVAL := T#100ms, PRIORITY := 1);
+   PROGRAM instance WITH plc_task : main;
+END_RESOURCE
+END_CONFIGURATION";
+        let library = parse(program, "main.st");
+        let index = SymbolIndex::new(&[&library]);
+
+        let local = index
+            .find(&FileId::from_string("main.st"), offset(program, "local", 0))
+            .unwrap();
+        let global = index
+            .find(
+                &FileId::from_string("main.st"),
+                offset(program, "shared", 1),
+            )
+            .unwrap();
+
+        let conflict = index.conflict(local, &Id::from("OTHER")).unwrap();
+        assert_eq!("other", index.definition(conflict).name.original());
+        assert!(index.conflict(local, &Id::from("config")).is_none());
+        assert!(index.conflict(global, &Id::from("local")).is_some());
+        assert!(index.conflict(global, &Id::from("unused")).is_none());
+    }
+}
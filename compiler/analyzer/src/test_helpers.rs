@@ -59,3 +59,17 @@ pub fn parse_and_resolve_types(program: &str) -> Library {
     let (library, _type_environment, _symbol_environment) = resolve_types(&[&library]).unwrap();
     library
 }
+
+#[cfg(test)]
+pub fn parse_and_resolve_environments(
+    program: &str,
+) -> (
+    Library,
+    crate::type_environment::TypeEnvironment,
+    crate::symbol_environment::SymbolEnvironment,
+) {
+    use ironplc_parser::{options::ParseOptions, parse_program};
+
+    let library = parse_program(program, &FileId::default(), &ParseOptions::default()).unwrap();
+    resolve_types(&[&library]).unwrap()
+}
@@ -31,9 +31,9 @@ impl TypeCategory {
             IntermediateType::Structure { .. } | IntermediateType::Enumeration { .. } => {
                 TypeCategory::UserDefined
             }
-            IntermediateType::Subrange { .. } | IntermediateType::Array { .. } => {
-                TypeCategory::Derived
-            }
+            IntermediateType::Subrange { .. }
+            | IntermediateType::Array { .. }
+            | IntermediateType::Reference { .. } => TypeCategory::Derived,
             IntermediateType::FunctionBlock { .. } | IntermediateType::Function { .. } => {
                 TypeCategory::UserDefined
             }
@@ -138,6 +138,8 @@ static ELEMENTARY_TYPES_LOWER_CASE: [(&str, IntermediateType); 23] = [
 #[derive(Debug)]
 pub struct TypeEnvironment {
     table: HashMap<TypeName, crate::type_attributes::TypeAttributes>,
+    // Defines the base type name for each type that is an alias
+    aliases: HashMap<TypeName, TypeName>,
 }
 
 impl TypeEnvironment {
@@ -145,6 +147,7 @@ impl TypeEnvironment {
     pub fn new() -> Self {
         Self {
             table: HashMap::new(),
+            aliases: HashMap::new(),
         }
     }
 
@@ -189,7 +192,21 @@ impl TypeEnvironment {
             .with_secondary(Label::span(base_type_name.span(), "Base type"))
         })?;
 
-        self.insert_type(type_name, base_intermediate_type.clone())
+        self.insert_type(type_name, base_intermediate_type.clone())?;
+        self.aliases
+            .insert(type_name.clone(), base_type_name.clone());
+        Ok(())
+    }
+
+    /// Returns the name of the type that the type is an alias of, following
+    /// aliases of aliases. Returns the type name when the type is not an
+    /// alias.
+    pub fn resolve_alias<'a>(&'a self, type_name: &'a TypeName) -> &'a TypeName {
+        let mut type_name = type_name;
+        while let Some(base_type_name) = self.aliases.get(type_name) {
+            type_name = base_type_name;
+        }
+        type_name
     }
 
     /// Gets the type from the environment.
@@ -219,7 +236,6 @@ impl TypeEnvironment {
     /// Returns `Ok(Some(size))` if the type exists and has a known size,
     /// `Ok(None)` if the type exists but has unknown size (e.g., dynamic arrays),
     /// or `Err` if the type is not declared.
-    #[allow(dead_code)]
     pub fn get_memory_size(&self, type_name: &TypeName) -> Result<Option<u32>, Diagnostic> {
         self.table
             .get(type_name)
@@ -402,6 +418,32 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn resolve_alias_when_alias_of_alias_then_base_type_name() {
+        let mut env = TypeEnvironment::new();
+        env.insert_type(
+            &TypeName::from("TYPE"),
+            TypeAttributes::new(SourceSpan::default(), IntermediateType::Bool),
+        )
+        .unwrap();
+        env.insert_alias(&TypeName::from("TYPE_ALIAS"), &TypeName::from("TYPE"))
+            .unwrap();
+        env.insert_alias(
+            &TypeName::from("TYPE_ALIAS_ALIAS"),
+            &TypeName::from("TYPE_ALIAS"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            &TypeName::from("TYPE"),
+            env.resolve_alias(&TypeName::from("TYPE_ALIAS_ALIAS"))
+        );
+        assert_eq!(
+            &TypeName::from("TYPE"),
+            env.resolve_alias(&TypeName::from("TYPE"))
+        );
+    }
+
     #[test]
     fn insert_alias_when_type_doesnt_exist_then_error() {
         let mut env = TypeEnvironment::new();
@@ -0,0 +1,409 @@
+//! Transform that evaluates the constant expressions of array bounds,
+//! subrange limits and string lengths into integer literals.
+//!
+//! Constant expressions may refer to variables that are declared
+//! `CONSTANT`, for example
+//!
+//! ```ignore
+//! CONFIGURATION config
+//!    VAR_GLOBAL CONSTANT
+//!       MAX_AXES : INT := 8;
+//!    END_VAR
+//!    ...
+//! END_CONFIGURATION
+//!
+//! PROGRAM main
+//!    VAR
+//!       positions : ARRAY[0..MAX_AXES-1] OF REAL;
+//!    END_VAR
+//! END_PROGRAM
+//! ```
+//!
+//! Global constants are visible in every declaration. Constants declared
+//! in a program organization unit are visible within the unit. After this
+//! transform, later stages only need to handle integer literals.
+use ironplc_dsl::{
+    common::*,
+    configuration::ConfigurationDeclaration,
+    core::{Id, Located, SourceSpan},
+    diagnostic::Diagnostic,
+    fold::Fold,
+    textual::ExprKind,
+};
+
+use crate::{
+    const_eval::{evaluate, ConstantValue},
+    scoped_table::ScopedTable,
+    type_environment::TypeEnvironment,
+};
+
+pub fn apply(
+    lib: Library,
+    _type_environment: &mut TypeEnvironment,
+) -> Result<Library, Vec<Diagnostic>> {
+    let mut resolver = ConstantExpressionResolver {
+        constants: ScopedTable::new(),
+        context: SourceSpan::default(),
+        diagnostics: vec![],
+    };
+
+    // Global constants are visible everywhere (including in type
+    // declarations) so find them before resolving any expression.
+    for element in lib.elements.iter() {
+        if let LibraryElementKind::ConfigurationDeclaration(config) = element {
+            resolver.add_global_constants(config);
+        }
+    }
+
+    let result = resolver.fold_library(lib).map_err(|e| vec![e]);
+
+    if !resolver.diagnostics.is_empty() {
+        return Err(resolver.diagnostics);
+    }
+
+    result
+}
+
+struct ConstantExpressionResolver<'a> {
+    constants: ScopedTable<'a, Id, ConstantValue>,
+    /// Location of the declaration that contains the expression that is
+    /// being evaluated.
+    context: SourceSpan,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ConstantExpressionResolver<'_> {
+    fn add_global_constants(&mut self, config: &ConfigurationDeclaration) {
+        self.add_constants(&config.global_var);
+        for resource in config.resource_decl.iter() {
+            self.add_constants(&resource.global_vars);
+        }
+    }
+
+    /// Adds the variables that are declared constant and have a value.
+    fn add_constants(&mut self, variables: &[VarDecl]) {
+        for var in variables {
+            if var.qualifier != DeclarationQualifier::Constant {
+                continue;
+            }
+            if let Some(value) = ConstantValue::from_initializer(&var.initializer) {
+                self.constants.add_if(var.identifier.symbolic_id(), value);
+            }
+        }
+    }
+
+    /// Evaluates the expression or adds a diagnostic if the expression
+    /// cannot be evaluated.
+    fn evaluate(&mut self, expr: &ExprKind) -> Option<ConstantValue> {
+        let constants = &mut self.constants;
+        match evaluate(
+            expr,
+            &mut |name| constants.find(name).cloned(),
+            &self.context,
+        ) {
+            Ok(value) => Some(value),
+            Err(diagnostic) => {
+                self.diagnostics.push(diagnostic);
+                None
+            }
+        }
+    }
+
+    /// Folds a declaration that has variables in a new scope where the
+    /// constants of the declaration are visible.
+    fn fold_in_scope<T>(
+        &mut self,
+        name: SourceSpan,
+        variables: &[VarDecl],
+        node: T,
+        fold: impl FnOnce(T, &mut Self) -> Result<T, Diagnostic>,
+    ) -> Result<T, Diagnostic> {
+        self.constants.enter();
+        self.add_constants(variables);
+        self.context = name;
+        let result = fold(node, self);
+        self.constants.exit();
+        result
+    }
+}
+
+impl Fold<Diagnostic> for ConstantExpressionResolver<'_> {
+    fn fold_function_declaration(
+        &mut self,
+        node: FunctionDeclaration,
+    ) -> Result<FunctionDeclaration, Diagnostic> {
+        let variables = node.variables.clone();
+        self.fold_in_scope(node.name.span(), &variables, node, |node, f| {
+            node.recurse_fold(f)
+        })
+    }
+
+    fn fold_function_block_declaration(
+        &mut self,
+        node: FunctionBlockDeclaration,
+    ) -> Result<FunctionBlockDeclaration, Diagnostic> {
+        let variables = node.variables.clone();
+        self.fold_in_scope(node.name.span(), &variables, node, |node, f| {
+            node.recurse_fold(f)
+        })
+    }
+
+    fn fold_program_declaration(
+        &mut self,
+        node: ProgramDeclaration,
+    ) -> Result<ProgramDeclaration, Diagnostic> {
+        let variables = node.variables.clone();
+        self.fold_in_scope(node.name.span(), &variables, node, |node, f| {
+            node.recurse_fold(f)
+        })
+    }
+
+    fn fold_method_declaration(
+        &mut self,
+        node: MethodDeclaration,
+    ) -> Result<MethodDeclaration, Diagnostic> {
+        let variables = node.variables.clone();
+        self.fold_in_scope(node.name.span(), &variables, node, |node, f| {
+            node.recurse_fold(f)
+        })
+    }
+
+    fn fold_var_decl(&mut self, node: VarDecl) -> Result<VarDecl, Diagnostic> {
+        self.context = node.span();
+        node.recurse_fold(self)
+    }
+
+    fn fold_array_declaration(
+        &mut self,
+        node: ArrayDeclaration,
+    ) -> Result<ArrayDeclaration, Diagnostic> {
+        self.context = node.type_name.span();
+        node.recurse_fold(self)
+    }
+
+    fn fold_subrange_declaration(
+        &mut self,
+        node: SubrangeDeclaration,
+    ) -> Result<SubrangeDeclaration, Diagnostic> {
+        self.context = node.type_name.span();
+        node.recurse_fold(self)
+    }
+
+    fn fold_string_declaration(
+        &mut self,
+        node: StringDeclaration,
+    ) -> Result<StringDeclaration, Diagnostic> {
+        self.context = node.type_name.span();
+        node.recurse_fold(self)
+    }
+
+    fn fold_structure_element_declaration(
+        &mut self,
+        node: StructureElementDeclaration,
+    ) -> Result<StructureElementDeclaration, Diagnostic> {
+        self.context = node.name.span();
+        node.recurse_fold(self)
+    }
+
+    fn fold_subrange_bound_kind(
+        &mut self,
+        node: SubrangeBoundKind,
+    ) -> Result<SubrangeBoundKind, Diagnostic> {
+        let SubrangeBoundKind::Expression(expr) = &node else {
+            return Ok(node);
+        };
+        let Some(value) = self.evaluate(expr) else {
+            return Ok(node);
+        };
+        Ok(SubrangeBoundKind::SignedInteger(SignedInteger {
+            value: Integer {
+                span: self.context.clone(),
+                value: value.value.unsigned_abs(),
+            },
+            is_neg: value.value < 0,
+        }))
+    }
+
+    fn fold_string_length_kind(
+        &mut self,
+        node: StringLengthKind,
+    ) -> Result<StringLengthKind, Diagnostic> {
+        let StringLengthKind::Expression(expr) = &node else {
+            return Ok(node);
+        };
+        let Some(value) = self.evaluate(expr) else {
+            return Ok(node);
+        };
+        match u128::try_from(value.value) {
+            Ok(length) => Ok(StringLengthKind::Integer(Integer {
+                span: self.context.clone(),
+                value: length,
+            })),
+            Err(_) => {
+                self.diagnostics.push(
+                    Diagnostic::problem(
+                        ironplc_problems::Problem::ConstantExpressionInvalid,
+                        ironplc_dsl::diagnostic::Label::span(self.context.clone(), "String length"),
+                    )
+                    .with_context("reason", &String::from("length is negative")),
+                );
+                Ok(node)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ironplc_dsl::visitor::Visitor;
+    use ironplc_problems::Problem;
+
+    use crate::test_helpers::parse_and_resolve_types;
+
+    use super::*;
+
+    fn resolve(program: &str) -> Result<Library, Vec<Diagnostic>> {
+        let library = ironplc_parser::parse_program(
+            program,
+            &ironplc_dsl::core::FileId::default(),
+            &ironplc_parser::options::ParseOptions::default(),
+        )
+        .unwrap();
+        apply(library, &mut TypeEnvironment::default())
+    }
+
+    fn first_array_ranges(library: &Library) -> Vec<(i128, i128)> {
+        struct Finder(Vec<(i128, i128)>);
+        impl Visitor<()> for Finder {
+            type Value = ();
+            fn visit_subrange(&mut self, node: &Subrange) -> Result<(), ()> {
+                let start = node.start.as_signed_integer().unwrap().clone();
+                let end = node.end.as_signed_integer().unwrap().clone();
+                self.0
+                    .push((start.try_into().unwrap(), end.try_into().unwrap()));
+                Ok(())
+            }
+        }
+        let mut finder = Finder(vec![]);
+        finder.walk(library).unwrap();
+        finder.0
+    }
+
+    #[test]
+    fn apply_when_global_constant_in_array_bound_then_folds() {
+        let library = resolve(
+            "
+CONFIGURATION config
+VAR_GLOBAL CONSTANT
+MAX_AXES : INT := 4;
+END_VAR
+RESOURCE res ON PLC
+PROGRAM instance : main;
+END_RESOURCE
+END_CONFIGURATION
+
+PROGRAM main
+VAR
+positions : ARRAY[0..MAX_AXES-1] OF REAL;
+END_VAR
+END_PROGRAM",
+        )
+        .unwrap();
+
+        assert_eq!(vec![(0, 3)], first_array_ranges(&library));
+    }
+
+    #[test]
+    fn apply_when_local_constant_shadows_global_then_uses_local() {
+        let library = resolve(
+            "
+TYPE
+Axes : ARRAY[1..MAX_AXES * 2] OF INT;
+END_TYPE
+
+CONFIGURATION config
+VAR_GLOBAL CONSTANT
+MAX_AXES : INT := 4;
+END_VAR
+RESOURCE res ON PLC
+PROGRAM instance : main;
+END_RESOURCE
+END_CONFIGURATION
+
+PROGRAM main
+VAR CONSTANT
+MAX_AXES : INT := 2;
+END_VAR
+VAR
+limits : ARRAY[-MAX_AXES..MAX_AXES] OF INT;
+END_VAR
+END_PROGRAM",
+        )
+        .unwrap();
+
+        assert_eq!(vec![(1, 8), (-2, 2)], first_array_ranges(&library));
+    }
+
+    #[test]
+    fn apply_when_string_length_expression_then_folds() {
+        let library = resolve(
+            "
+TYPE
+Name : STRING[NAME_LENGTH + 1];
+END_TYPE
+
+CONFIGURATION config
+VAR_GLOBAL CONSTANT
+NAME_LENGTH : UINT := 16#1F;
+END_VAR
+RESOURCE res ON PLC
+PROGRAM instance : main;
+END_RESOURCE
+END_CONFIGURATION",
+        )
+        .unwrap();
+
+        let LibraryElementKind::DataTypeDeclaration(DataTypeDeclarationKind::String(decl)) =
+            &library.elements[0]
+        else {
+            panic!("expected string declaration");
+        };
+        assert_eq!(32, decl.length.as_integer().unwrap().value);
+    }
+
+    #[test]
+    fn apply_when_variable_not_constant_then_error() {
+        let result = resolve(
+            "
+PROGRAM main
+VAR
+count : INT := 4;
+values : ARRAY[1..count] OF INT;
+END_VAR
+END_PROGRAM",
+        );
+
+        let errors = result.unwrap_err();
+        assert_eq!(1, errors.len());
+        assert_eq!(
+            Problem::ConstantExpressionRequired.code(),
+            errors.first().unwrap().code
+        );
+    }
+
+    #[test]
+    fn parse_and_resolve_types_when_constant_array_bound_then_array_has_size() {
+        let program = "
+PROGRAM main
+VAR CONSTANT
+SIZE : INT := 5;
+END_VAR
+VAR
+values : ARRAY[1..SIZE] OF INT;
+END_VAR
+END_PROGRAM";
+        let library = parse_and_resolve_types(program);
+
+        assert_eq!(vec![(1, 5)], first_array_ranges(&library));
+    }
+}
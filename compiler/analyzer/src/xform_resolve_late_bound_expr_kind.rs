@@ -13,15 +13,18 @@ use ironplc_dsl::textual::*;
 use ironplc_dsl::{common::*, core::Id};
 use std::collections::HashMap;
 
+use crate::intermediate_type::IntermediateType;
 use crate::type_environment::TypeEnvironment;
 
 pub fn apply(
     lib: Library,
-    _type_environment: &mut TypeEnvironment,
+    type_environment: &mut TypeEnvironment,
 ) -> Result<Library, Vec<Diagnostic>> {
     // Resolve the types. This is a single fold of the library
     let mut resolver = DeclarationResolver {
+        type_environment,
         names_to_types: HashMap::new(),
+        names_to_type_names: HashMap::new(),
         current_type: VariableType::None,
         diagnostics: Vec::new(),
     };
@@ -45,19 +48,24 @@ enum VariableType {
     Subrange,
     Structure,
     Array,
+    Reference,
     LateResolvedType,
 }
 
-struct DeclarationResolver {
+struct DeclarationResolver<'a> {
+    type_environment: &'a TypeEnvironment,
     // Defines the desired type for each identifier
     names_to_types: HashMap<Id, VariableType>,
+    // Defines the declared type name for each identifier (when the
+    // declaration names a type)
+    names_to_type_names: HashMap<Id, TypeName>,
     current_type: VariableType,
     diagnostics: Vec<Diagnostic>,
 }
 
-impl DeclarationResolver {
+impl DeclarationResolver<'_> {
     fn insert(&mut self, node: &VarDecl) {
-        let var_type = match node.initializer {
+        let var_type = match &node.initializer {
             InitialValueAssignmentKind::None(_) => VariableType::None,
             InitialValueAssignmentKind::Simple(_) => VariableType::Simple,
             InitialValueAssignmentKind::String(_) => VariableType::String,
@@ -67,17 +75,67 @@ impl DeclarationResolver {
             InitialValueAssignmentKind::Subrange(_) => VariableType::Subrange,
             InitialValueAssignmentKind::Structure(_) => VariableType::Structure,
             InitialValueAssignmentKind::Array(_) => VariableType::Array,
-            InitialValueAssignmentKind::LateResolvedType(_) => VariableType::LateResolvedType,
+            InitialValueAssignmentKind::Reference(_) => VariableType::Reference,
+            InitialValueAssignmentKind::LateResolvedType(type_name) => {
+                // Declared reference and structure types are already known
+                match self.type_environment.get(type_name) {
+                    Some(attrs) if attrs.representation.is_reference() => VariableType::Reference,
+                    Some(attrs) if attrs.representation.is_structure() => VariableType::Structure,
+                    Some(_) => VariableType::LateResolvedType,
+                    // The type is not declared (for example, because the
+                    // declaration has a syntax error) so there are no
+                    // enumerated values and the identifier is a variable.
+                    None => VariableType::None,
+                }
+            }
         };
-        match &node.identifier {
-            VariableIdentifier::Symbol(id) => {
-                self.names_to_types.insert(id.clone(), var_type);
+        let type_name = match &node.initializer {
+            InitialValueAssignmentKind::Simple(simple) => Some(&simple.type_name),
+            InitialValueAssignmentKind::EnumeratedType(enumerated) => Some(&enumerated.type_name),
+            InitialValueAssignmentKind::Structure(structure) => Some(&structure.type_name),
+            InitialValueAssignmentKind::LateResolvedType(type_name) => Some(type_name),
+            _ => None,
+        };
+
+        let name = match &node.identifier {
+            VariableIdentifier::Symbol(id) => Some(id),
+            VariableIdentifier::Direct(direct) => direct.name.as_ref(),
+        };
+        if let Some(name) = name {
+            self.names_to_types.insert(name.clone(), var_type);
+            match type_name {
+                Some(type_name) => {
+                    self.names_to_type_names
+                        .insert(name.clone(), type_name.clone());
+                }
+                None => {
+                    self.names_to_type_names.remove(name);
+                }
             }
-            VariableIdentifier::Direct(direct) => {
-                if let Some(name) = &direct.name {
-                    self.names_to_types.insert(name.clone(), var_type);
+        }
+    }
+
+    /// Returns the type of the variable when the variable is a named
+    /// variable, an element of an array or a (possibly nested) field of a
+    /// structure.
+    fn find_variable_type(&self, variable: &SymbolicVariableKind) -> Option<&IntermediateType> {
+        match variable {
+            SymbolicVariableKind::Named(named) => self
+                .names_to_type_names
+                .get(&named.name)
+                .and_then(|type_name| self.type_environment.get(type_name))
+                .map(|attrs| &attrs.representation),
+            SymbolicVariableKind::Structured(structured) => self
+                .find_variable_type(&structured.record)?
+                .get_field(&structured.field)
+                .map(|field| &field.field_type),
+            SymbolicVariableKind::Array(array) => {
+                match self.find_variable_type(&array.subscripted_variable)? {
+                    IntermediateType::Array { element_type, .. } => Some(element_type),
+                    _ => None,
                 }
             }
+            _ => None,
         }
     }
 
@@ -86,7 +144,7 @@ impl DeclarationResolver {
     }
 }
 
-impl Fold<Diagnostic> for DeclarationResolver {
+impl Fold<Diagnostic> for DeclarationResolver<'_> {
     fn fold_function_declaration(
         &mut self,
         node: FunctionDeclaration,
@@ -106,6 +164,21 @@ impl Fold<Diagnostic> for DeclarationResolver {
         self.names_to_types.clear();
         result
     }
+    fn fold_method_declaration(
+        &mut self,
+        node: MethodDeclaration,
+    ) -> Result<MethodDeclaration, Diagnostic> {
+        // Method variables are in scope together with the variables of the
+        // containing function block and may shadow them, so restore the
+        // function block variables when leaving the method.
+        let names_to_types = self.names_to_types.clone();
+        let names_to_type_names = self.names_to_type_names.clone();
+        node.variables.iter().for_each(|v| self.insert(v));
+        let result = node.recurse_fold(self);
+        self.names_to_types = names_to_types;
+        self.names_to_type_names = names_to_type_names;
+        result
+    }
     fn fold_program_declaration(
         &mut self,
         node: ProgramDeclaration,
@@ -130,11 +203,30 @@ impl Fold<Diagnostic> for DeclarationResolver {
                         // that we should have found earlier to identify the type
                         self.current_type = self.find_type(&named.name).clone();
                     }
-                    SymbolicVariableKind::Array(arr) => {
-                        Err(Diagnostic::todo_with_span(arr.span(), file!(), line!()))?
+                    SymbolicVariableKind::Array(_) | SymbolicVariableKind::Structured(_) => {
+                        // An assignment to an element of an array or a field of
+                        // a structure. The type is the type of the innermost
+                        // element or field. Fields that are not known are
+                        // reported by other rules.
+                        self.current_type = match self.find_variable_type(symbolic_kind) {
+                            Some(IntermediateType::Enumeration { .. }) => {
+                                VariableType::EnumeratedType
+                            }
+                            Some(IntermediateType::Reference { .. }) => VariableType::Reference,
+                            Some(IntermediateType::Structure { .. }) => VariableType::Structure,
+                            Some(_) => VariableType::Simple,
+                            None => VariableType::None,
+                        };
+                    }
+                    SymbolicVariableKind::This(this) => {
+                        Err(Diagnostic::todo_with_span(this.span(), file!(), line!()))?
                     }
-                    SymbolicVariableKind::Structured(st) => {
-                        Err(Diagnostic::todo_with_span(st.span(), file!(), line!()))?
+                    SymbolicVariableKind::Super(sup) => {
+                        Err(Diagnostic::todo_with_span(sup.span(), file!(), line!()))?
+                    }
+                    SymbolicVariableKind::Deref(_) => {
+                        // TODO the type of the referenced instance is not known here
+                        self.current_type = VariableType::None;
                     }
                 }
             }
@@ -178,6 +270,13 @@ impl Fold<Diagnostic> for DeclarationResolver {
             ExprKind::Function(node) => {
                 node.recurse_fold(self).map(|v| Ok(ExprKind::Function(v)))?
             }
+            ExprKind::MethodCall(node) => node
+                .recurse_fold(self)
+                .map(|v| Ok(ExprKind::MethodCall(Box::new(v))))?,
+            ExprKind::Ref(node) => node
+                .recurse_fold(self)
+                .map(|v| Ok(ExprKind::Ref(Box::new(v))))?,
+            ExprKind::Null(node) => Ok(ExprKind::Null(node)),
             ExprKind::LateBound(node) => match self.current_type {
                 VariableType::None => {
                     // TODO this is likely not right in all cases
@@ -185,21 +284,24 @@ impl Fold<Diagnostic> for DeclarationResolver {
                         SymbolicVariableKind::Named(NamedVariable { name: node.value }),
                     )))
                 }
-                VariableType::Simple => Ok(ExprKind::Variable(Variable::Symbolic(
-                    SymbolicVariableKind::Named(NamedVariable { name: node.value }),
-                ))),
-                VariableType::String => Err(Diagnostic::todo(file!(), line!())),
+                // Strings and subranges do not have enumerated values so the
+                // identifier must be a variable.
+                VariableType::Simple | VariableType::String | VariableType::Subrange => {
+                    Ok(ExprKind::Variable(Variable::Symbolic(
+                        SymbolicVariableKind::Named(NamedVariable { name: node.value }),
+                    )))
+                }
                 VariableType::EnumeratedValues => Err(Diagnostic::todo(file!(), line!())),
                 VariableType::EnumeratedType => Ok(ExprKind::EnumeratedValue(EnumeratedValue {
                     type_name: None,
                     value: node.value,
                 })),
                 VariableType::FunctionBlock => Err(Diagnostic::todo(file!(), line!())),
-                VariableType::Subrange => Err(Diagnostic::todo(file!(), line!())),
-                VariableType::Structure => Err(Diagnostic::todo(file!(), line!())),
-                VariableType::Array => Ok(ExprKind::Variable(Variable::Symbolic(
-                    SymbolicVariableKind::Named(NamedVariable { name: node.value }),
-                ))),
+                VariableType::Structure | VariableType::Array | VariableType::Reference => {
+                    Ok(ExprKind::Variable(Variable::Symbolic(
+                        SymbolicVariableKind::Named(NamedVariable { name: node.value }),
+                    )))
+                }
                 VariableType::LateResolvedType => Err(Diagnostic::todo(file!(), line!())),
             },
         }
@@ -13,7 +13,6 @@ use ironplc_problems::Problem;
 use log::trace;
 
 use crate::scoped_table::{ScopedTable, Value};
-use crate::stdlib::is_unsupported_standard_type;
 use crate::type_environment::TypeEnvironment;
 
 /// Derived data types declared.
@@ -28,8 +27,11 @@ enum TypeDefinitionKind {
     Array(ArraySpecificationKind),
     Structure,
     StructureInitialization,
-    String(StringType, Integer),
+    String(StringType, StringLengthKind),
+    /// Defines a reference to the named type.
+    Reference(TypeName),
     FunctionBlock,
+    Interface,
 }
 
 impl Value for TypeDefinitionKind {}
@@ -111,6 +113,10 @@ impl Visitor<Diagnostic> for ScopedTable<'_, TypeName, TypeDefinitionKind> {
                 &node.type_name,
                 TypeDefinitionKind::String(node.width.clone(), node.length.clone()),
             ),
+            DataTypeDeclarationKind::Reference(node) => self.add_if_new(
+                &node.type_name,
+                TypeDefinitionKind::Reference(node.spec_init.referenced_type_name.clone()),
+            ),
             DataTypeDeclarationKind::LateBound(_) => Ok(()),
         }
     }
@@ -123,6 +129,13 @@ impl Visitor<Diagnostic> for ScopedTable<'_, TypeName, TypeDefinitionKind> {
         // actually an identifier, so treat identifier and type as equivalent in this context.
         self.add_if_new(&node.name, TypeDefinitionKind::FunctionBlock)
     }
+
+    fn visit_interface_declaration(
+        &mut self,
+        node: &InterfaceDeclaration,
+    ) -> Result<(), Diagnostic> {
+        self.add_if_new(&node.name, TypeDefinitionKind::Interface)
+    }
 }
 
 struct TypeResolver<'a> {
@@ -149,17 +162,6 @@ impl Fold<Diagnostic> for TypeResolver<'_> {
                     }
                 }
 
-                // Unsupported standard types resolve to a known type that we will detect later.
-                // This allows passing the transformation stage to show other errors.
-                if is_unsupported_standard_type(&name) {
-                    return Ok(InitialValueAssignmentKind::FunctionBlock(
-                        FunctionBlockInitialValueAssignment {
-                            type_name: name,
-                            init: vec![],
-                        },
-                    ));
-                }
-
                 // TODO error handling
                 let maybe_type_kind = self.types.find(&name);
                 match maybe_type_kind {
@@ -172,7 +174,9 @@ impl Fold<Diagnostic> for TypeResolver<'_> {
                                 },
                             ))
                         }
-                        TypeDefinitionKind::FunctionBlock => {
+                        // A variable having an interface type refers to a function
+                        // block instance that implements the interface.
+                        TypeDefinitionKind::FunctionBlock | TypeDefinitionKind::Interface => {
                             Ok(InitialValueAssignmentKind::FunctionBlock(
                                 FunctionBlockInitialValueAssignment {
                                     type_name: name,
@@ -184,6 +188,7 @@ impl Fold<Diagnostic> for TypeResolver<'_> {
                             StructureInitializationDeclaration {
                                 type_name: name,
                                 elements_init: vec![],
+                                attributes: vec![],
                             },
                         )),
                         TypeDefinitionKind::String(width, length) => {
@@ -200,6 +205,14 @@ impl Fold<Diagnostic> for TypeResolver<'_> {
                                 initial_values: vec![],
                             },
                         )),
+                        TypeDefinitionKind::Reference(referenced_type_name) => {
+                            Ok(InitialValueAssignmentKind::Reference(
+                                ReferenceInitialValueAssignment {
+                                    referenced_type_name: referenced_type_name.clone(),
+                                    initial_value: None,
+                                },
+                            ))
+                        }
                         _ => Err(Diagnostic::todo_with_type(&name, file!(), line!())),
                     },
                     None => {
@@ -215,9 +228,44 @@ impl Fold<Diagnostic> for TypeResolver<'_> {
                     }
                 }
             }
+            InitialValueAssignmentKind::Reference(init) => {
+                Ok(InitialValueAssignmentKind::Reference(
+                    self.fold_reference_initial_value_assignment(init)?,
+                ))
+            }
             _ => Ok(node),
         }
     }
+
+    fn fold_reference_initial_value_assignment(
+        &mut self,
+        node: ReferenceInitialValueAssignment,
+    ) -> Result<ReferenceInitialValueAssignment, Diagnostic> {
+        // The referenced type may be declared anywhere (including after this
+        // declaration) but must be declared.
+        let name = &node.referenced_type_name;
+        if self.type_environment.get(name).is_none() && self.types.find(name).is_none() {
+            self.diagnostics.push(
+                Diagnostic::problem(
+                    Problem::UndeclaredUnknownType,
+                    Label::span(name.span(), "Referenced type"),
+                )
+                .with_context_type("identifier", name),
+            );
+        }
+        Ok(node)
+    }
+
+    fn fold_reference_declaration(
+        &mut self,
+        node: ReferenceDeclaration,
+    ) -> Result<ReferenceDeclaration, Diagnostic> {
+        Ok(ReferenceDeclaration {
+            type_name: node.type_name,
+            spec_init: self.fold_reference_initial_value_assignment(node.spec_init)?,
+            attributes: node.attributes,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -256,17 +304,29 @@ END_FUNCTION_BLOCK
             elements: vec![
                 LibraryElementKind::FunctionBlockDeclaration(FunctionBlockDeclaration {
                     name: TypeName::from("called"),
+                    modifier: None,
+                    extends: None,
+                    implements: vec![],
                     variables: vec![],
                     edge_variables: vec![],
+                    methods: vec![],
+                    properties: vec![],
                     body: FunctionBlockBodyKind::empty(),
                     span: SourceSpan::default(),
+                    attributes: vec![],
                 }),
                 LibraryElementKind::FunctionBlockDeclaration(FunctionBlockDeclaration {
                     name: TypeName::from("caller"),
+                    modifier: None,
+                    extends: None,
+                    implements: vec![],
                     variables: vec![VarDecl::function_block("fb_var", "called")],
                     edge_variables: vec![],
+                    methods: vec![],
+                    properties: vec![],
                     body: FunctionBlockBodyKind::empty(),
                     span: SourceSpan::default(),
+                    attributes: vec![],
                 }),
             ],
         };
@@ -306,15 +366,23 @@ END_FUNCTION_BLOCK
                             init: InitialValueAssignmentKind::simple_uninitialized(TypeName::from(
                                 "BOOL",
                             )),
+                            attributes: vec![],
                         }],
+                        attributes: vec![],
                     },
                 )),
                 LibraryElementKind::FunctionBlockDeclaration(FunctionBlockDeclaration {
                     name: TypeName::from("caller"),
+                    modifier: None,
+                    extends: None,
+                    implements: vec![],
                     variables: vec![VarDecl::structure("the_var", "the_struct")],
                     edge_variables: vec![],
+                    methods: vec![],
+                    properties: vec![],
                     body: FunctionBlockBodyKind::empty(),
                     span: SourceSpan::default(),
+                    attributes: vec![],
                 }),
             ],
         };
@@ -353,14 +421,21 @@ END_FUNCTION_BLOCK
                             ]),
                             default: None,
                         },
+                        attributes: vec![],
                     },
                 )),
                 LibraryElementKind::FunctionBlockDeclaration(FunctionBlockDeclaration {
                     name: TypeName::from("caller"),
+                    modifier: None,
+                    extends: None,
+                    implements: vec![],
                     variables: vec![VarDecl::uninitialized_enumerated("the_var", "values")],
                     edge_variables: vec![],
+                    methods: vec![],
+                    properties: vec![],
                     body: FunctionBlockBodyKind::empty(),
                     span: SourceSpan::default(),
+                    attributes: vec![],
                 }),
             ],
         };
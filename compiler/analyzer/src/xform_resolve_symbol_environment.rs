@@ -136,12 +136,67 @@ impl<'a> Visitor<Diagnostic> for SymbolEnvironmentResolver<'a> {
             SymbolKind::FunctionBlock,
             &ScopeKind::Global,
         )?;
+        if let Some(base) = &node.extends {
+            self.env.insert_base_type(&node.name.name, &base.name);
+        }
+        let result = node.recurse_visit(self);
+        self.scope = None;
+
+        result
+    }
+
+    fn visit_interface_declaration(
+        &mut self,
+        node: &ironplc_dsl::common::InterfaceDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.scope = Some(node.name.name.clone());
+        self.env
+            .insert(&node.name.name, SymbolKind::Interface, &ScopeKind::Global)?;
+        for base in &node.extends {
+            self.env.insert_base_type(&node.name.name, &base.name);
+        }
         let result = node.recurse_visit(self);
         self.scope = None;
 
         result
     }
 
+    fn visit_method_declaration(
+        &mut self,
+        node: &ironplc_dsl::common::MethodDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        let owner = self.scope.clone();
+        self.env
+            .insert(&node.name, SymbolKind::Method, &self.current_scope())?;
+
+        // Variables of the method are in their own scope
+        if let Some(owner) = &owner {
+            self.scope = Some(SymbolEnvironment::member_scope_name(owner, &node.name));
+        }
+        let result = node.recurse_visit(self);
+        self.scope = owner;
+
+        result
+    }
+
+    fn visit_property_declaration(
+        &mut self,
+        node: &ironplc_dsl::common::PropertyDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        let owner = self.scope.clone();
+        self.env
+            .insert(&node.name, SymbolKind::Property, &self.current_scope())?;
+
+        // Variables of the accessors are in their own scope
+        if let Some(owner) = &owner {
+            self.scope = Some(SymbolEnvironment::member_scope_name(owner, &node.name));
+        }
+        let result = node.recurse_visit(self);
+        self.scope = owner;
+
+        result
+    }
+
     fn visit_program_declaration(
         &mut self,
         node: &ironplc_dsl::common::ProgramDeclaration,
@@ -184,6 +239,10 @@ impl<'a> Visitor<Diagnostic> for SymbolEnvironmentResolver<'a> {
                 self.env
                     .insert(&decl.type_name.name, SymbolKind::Type, &ScopeKind::Global)?;
             }
+            ironplc_dsl::common::DataTypeDeclarationKind::Reference(decl) => {
+                self.env
+                    .insert(&decl.type_name.name, SymbolKind::Type, &ScopeKind::Global)?;
+            }
             ironplc_dsl::common::DataTypeDeclarationKind::LateBound(_) => {
                 // Skip late-bound types for now
             }
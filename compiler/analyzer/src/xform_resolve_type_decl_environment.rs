@@ -44,6 +44,7 @@ impl TypeEnvironment {
                     type_name: node.base_type_name,
                     initial_value: None,
                 }),
+                attributes: node.attributes,
             }))
         } else {
             match existing.representation {
@@ -54,13 +55,18 @@ impl TypeEnvironment {
                             spec: EnumeratedSpecificationKind::TypeName(node.base_type_name),
                             default: None,
                         },
+                        attributes: node.attributes,
                     }),
                 ),
                 IntermediateType::Structure { fields: _ } => {
+                    // The structure initialization does not name the base
+                    // type, so the alias is added here.
+                    self.insert_alias(&node.data_type_name, &node.base_type_name)?;
                     Ok(DataTypeDeclarationKind::StructureInitialization(
                         StructureInitializationDeclaration {
                             type_name: node.data_type_name,
                             elements_init: vec![],
+                            attributes: node.attributes,
                         },
                     ))
                 }
@@ -71,14 +77,26 @@ impl TypeEnvironment {
                     type_name: node.data_type_name,
                     spec: ArraySpecificationKind::Type(node.base_type_name),
                     init: vec![],
+                    attributes: node.attributes,
                 })),
                 IntermediateType::Subrange { .. } => {
                     Ok(DataTypeDeclarationKind::Subrange(SubrangeDeclaration {
                         type_name: node.data_type_name,
                         spec: SubrangeSpecificationKind::Type(node.base_type_name),
                         default: None,
+                        attributes: node.attributes,
                     }))
                 }
+                IntermediateType::Reference {
+                    ref referenced_type_name,
+                } => Ok(DataTypeDeclarationKind::Reference(ReferenceDeclaration {
+                    type_name: node.data_type_name,
+                    spec_init: ReferenceInitialValueAssignment {
+                        referenced_type_name: referenced_type_name.clone(),
+                        initial_value: None,
+                    },
+                    attributes: node.attributes,
+                })),
                 // FunctionBlock and Function types are POUs (Program Organization Units),
                 // not TYPE declarations, so they should never appear in the type environment.
                 // If we reach this branch, it indicates a bug in the compiler.
@@ -154,6 +172,9 @@ impl Fold<Diagnostic> for TypeEnvironment {
             InitialValueAssignmentKind::Array(_array_initial_value_assignment) => {
                 // TODO: Handle array initializers
             }
+            InitialValueAssignmentKind::Reference(reference_initializer) => {
+                self.insert_type(&node.type_name, reference::from(reference_initializer))?;
+            }
             InitialValueAssignmentKind::LateResolvedType(_type_name) => {
                 return Err(Diagnostic::internal_error(file!(), line!()));
             }
@@ -198,6 +219,14 @@ impl Fold<Diagnostic> for TypeEnvironment {
         Ok(node)
     }
 
+    fn fold_reference_declaration(
+        &mut self,
+        node: ReferenceDeclaration,
+    ) -> Result<ReferenceDeclaration, Diagnostic> {
+        self.insert_type(&node.type_name, reference::from_decl(&node))?;
+        Ok(node)
+    }
+
     fn fold_structure_declaration(
         &mut self,
         node: StructureDeclaration,
@@ -307,6 +336,7 @@ END_TYPE
                             vec!["CRITICAL"],
                             "CRITICAL",
                         ),
+                        attributes: vec![],
                     },
                 )),
                 LibraryElementKind::DataTypeDeclaration(DataTypeDeclarationKind::Enumeration(
@@ -316,6 +346,7 @@ END_TYPE
                             spec: EnumeratedSpecificationKind::TypeName(TypeName::from("LEVEL")),
                             default: None,
                         },
+                        attributes: vec![],
                     },
                 )),
             ],
@@ -653,6 +684,25 @@ END_TYPE
         assert_eq!(base_type.representation, alias_type.representation);
     }
 
+    #[test]
+    fn apply_when_structure_type_alias_then_creates_alias() {
+        let program = "
+TYPE
+BASE_STRUCT : STRUCT
+   MEMBER : INT;
+END_STRUCT;
+ALIAS_STRUCT : BASE_STRUCT;
+END_TYPE
+        ";
+        let (result, env) = parse_and_apply_with_elementary_types(program);
+        assert!(result.is_ok());
+
+        // Both should resolve to the same structure type
+        let base_type = env.get(&TypeName::from("BASE_STRUCT")).unwrap();
+        let alias_type = env.get(&TypeName::from("ALIAS_STRUCT")).unwrap();
+        assert_eq!(base_type.representation, alias_type.representation);
+    }
+
     #[test]
     fn apply_when_array_of_user_defined_type_then_resolves_correctly() {
         let program = "
@@ -116,6 +116,12 @@ pub fn apply(lib: Library) -> Result<Library, Vec<Diagnostic>> {
                             DataTypeDeclarationKind::String(decl),
                         );
                     }
+                    DataTypeDeclarationKind::Reference(decl) => {
+                        types_by_name.insert(
+                            decl.type_name.name.clone(),
+                            DataTypeDeclarationKind::Reference(decl),
+                        );
+                    }
                     DataTypeDeclarationKind::LateBound(decl) => {
                         types_by_name.insert(
                             decl.data_type_name.name.clone(),
@@ -148,6 +154,12 @@ pub fn apply(lib: Library) -> Result<Library, Vec<Diagnostic>> {
                     LibraryElementKind::ConfigurationDeclaration(decl),
                 );
             }
+            LibraryElementKind::InterfaceDeclaration(decl) => {
+                elems_by_name.insert(
+                    decl.name.name.clone(),
+                    LibraryElementKind::InterfaceDeclaration(decl),
+                );
+            }
         }
     }
 
@@ -358,6 +370,16 @@ impl Visitor<Diagnostic> for RuleGraphReferenceableElements {
         res
     }
 
+    fn visit_reference_declaration(
+        &mut self,
+        node: &ReferenceDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        // A reference does not depend on the layout of the referenced
+        // type, so there are no dependencies.
+        self.declarations.add_node(&node.type_name.name);
+        Ok(())
+    }
+
     // POU declarations
 
     fn visit_function_declaration(
@@ -376,7 +398,30 @@ impl Visitor<Diagnostic> for RuleGraphReferenceableElements {
         node: &FunctionBlockDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
         self.current_from = Some(node.name.name.clone());
-        self.declarations.add_node(&node.name.name);
+        let from = self.declarations.add_node(&node.name.name);
+
+        // The base function block and the interfaces must be declared
+        // before this function block.
+        for base in node.extends.iter().chain(node.implements.iter()) {
+            let to = self.declarations.add_node(&base.name);
+            self.declarations.graph.add_edge(to, from, ());
+        }
+
+        let res = node.recurse_visit(self);
+        self.current_from = None;
+        res
+    }
+
+    fn visit_interface_declaration(
+        &mut self,
+        node: &InterfaceDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.current_from = Some(node.name.name.clone());
+        let from = self.declarations.add_node(&node.name.name);
+        for base in node.extends.iter() {
+            let to = self.declarations.add_node(&base.name);
+            self.declarations.graph.add_edge(to, from, ());
+        }
         let res = node.recurse_visit(self);
         self.current_from = None;
         res
@@ -429,10 +474,20 @@ impl Visitor<Diagnostic> for RuleGraphReferenceableElements {
             Some(from) => {
                 match node {
                     InitialValueAssignmentKind::None(_) => {}
-                    InitialValueAssignmentKind::Simple(_) => {}
+                    InitialValueAssignmentKind::Simple(simple) => {
+                        // The type must be declared before a structure
+                        // that contains a field of the type.
+                        let from = self.declarations.add_node(from);
+                        let to = self.declarations.add_node(&simple.type_name.name);
+                        self.declarations.graph.add_edge(to, from, ());
+                    }
                     InitialValueAssignmentKind::String(_) => {}
                     InitialValueAssignmentKind::EnumeratedValues(_) => {}
-                    InitialValueAssignmentKind::EnumeratedType(_) => {}
+                    InitialValueAssignmentKind::EnumeratedType(enumerated) => {
+                        let from = self.declarations.add_node(from);
+                        let to = self.declarations.add_node(&enumerated.type_name.name);
+                        self.declarations.graph.add_edge(to, from, ());
+                    }
                     InitialValueAssignmentKind::FunctionBlock(fb) => {
                         // We only care about these because these may be references to a function block
                         let from = self.declarations.add_node(from);
@@ -440,8 +495,21 @@ impl Visitor<Diagnostic> for RuleGraphReferenceableElements {
                         self.declarations.graph.add_edge(from, to, ());
                     }
                     InitialValueAssignmentKind::Subrange(_) => {}
-                    InitialValueAssignmentKind::Structure(_) => {}
+                    InitialValueAssignmentKind::Structure(structure) => {
+                        // Nested structures depend on the layout of the
+                        // nested structure.
+                        let from = self.declarations.add_node(from);
+                        let to = self.declarations.add_node(&structure.type_name.name);
+                        self.declarations.graph.add_edge(to, from, ());
+                        // The initializer is not a declaration of the
+                        // structure, so there is nothing more to visit.
+                        return Ok(());
+                    }
                     InitialValueAssignmentKind::Array(_) => {}
+                    // A reference does not depend on the layout of the referenced
+                    // type and so may refer to types that are declared later
+                    // (including to itself).
+                    InitialValueAssignmentKind::Reference(_) => {}
                     InitialValueAssignmentKind::LateResolvedType(lrt) => {
                         // We only care about these because these may be references to a function block
                         let from = self.declarations.add_node(from);
@@ -635,6 +703,42 @@ END_STRUCT;
 
 ENUM_TYPE : (A, B, C);
 
+END_TYPE";
+
+        let library = parse_only(program);
+        let library = apply(library).unwrap();
+
+        let decl = library.elements.first().unwrap();
+        let decl = cast!(decl, LibraryElementKind::DataTypeDeclaration);
+        let decl = cast!(decl, DataTypeDeclarationKind::Enumeration);
+        assert_eq!(decl.type_name, TypeName::from("ENUM_TYPE"));
+
+        let decl = library.elements.get(1).unwrap();
+        let decl = cast!(decl, LibraryElementKind::DataTypeDeclaration);
+        let decl = cast!(decl, DataTypeDeclarationKind::Structure);
+        assert_eq!(decl.type_name, TypeName::from("INNER_STRUCT"));
+
+        let decl = library.elements.get(2).unwrap();
+        let decl = cast!(decl, LibraryElementKind::DataTypeDeclaration);
+        let decl = cast!(decl, DataTypeDeclarationKind::Structure);
+        assert_eq!(decl.type_name, TypeName::from("OUTER_STRUCT"));
+    }
+
+    #[test]
+    fn apply_when_nested_structure_types_with_initializers() {
+        let program = "
+TYPE
+
+OUTER_STRUCT : STRUCT
+   MEMBER : INNER_STRUCT := (MEMBER := B);
+END_STRUCT;
+
+INNER_STRUCT: STRUCT
+   MEMBER : ENUM_TYPE := A;
+END_STRUCT;
+
+ENUM_TYPE : (A, B, C);
+
 END_TYPE";
 
         let library = parse_only(program);
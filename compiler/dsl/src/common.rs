@@ -12,6 +12,7 @@ use dsl_macro_derive::Recurse;
 use crate::configuration::{ConfigurationDeclaration, Direction};
 use crate::core::{Id, Located, SourceSpan};
 use crate::fold::Fold;
+use crate::il::{IlInstruction, InstructionList};
 use crate::sfc::{Network, Sfc};
 use crate::textual::*;
 use crate::time::*;
@@ -640,6 +641,8 @@ pub enum DataTypeDeclarationKind {
     Structure(StructureDeclaration),
     StructureInitialization(StructureInitializationDeclaration),
     String(StringDeclaration),
+    /// Derived data type that refers to an instance of another type.
+    Reference(ReferenceDeclaration),
     /// Data declaration that is ambiguous at parse time and must be
     /// resolved to a data type declaration after parsing all types.
     LateBound(LateBoundDeclaration),
@@ -660,6 +663,7 @@ pub struct LateBoundDeclaration {
     /// For example, if this is an alias then this is the underlying
     /// type.
     pub base_type_name: TypeName,
+    pub attributes: Vec<Attribute>,
 }
 
 impl Located for LateBoundDeclaration {
@@ -674,6 +678,7 @@ pub struct EnumerationDeclaration {
     pub type_name: TypeName,
     // TODO need to understand when the context name matters in the definition
     pub spec_init: EnumeratedSpecificationInit,
+    pub attributes: Vec<Attribute>,
 }
 
 /// The specification of an enumeration with a possible default value.
@@ -789,6 +794,7 @@ pub struct SubrangeDeclaration {
     pub type_name: TypeName,
     pub spec: SubrangeSpecificationKind,
     pub default: Option<SignedInteger>,
+    pub attributes: Vec<Attribute>,
 }
 
 /// Subranges can be specified by either providing a direct specification
@@ -821,6 +827,7 @@ pub struct SubrangeSpecification {
 pub struct SimpleDeclaration {
     pub type_name: TypeName,
     pub spec_and_init: InitialValueAssignmentKind,
+    pub attributes: Vec<Attribute>,
 }
 
 /// Derived data type that
@@ -829,6 +836,7 @@ pub struct ArrayDeclaration {
     pub type_name: TypeName,
     pub spec: ArraySpecificationKind,
     pub init: Vec<ArrayInitialElementKind>,
+    pub attributes: Vec<Attribute>,
 }
 
 #[derive(Clone, Debug, PartialEq, Recurse)]
@@ -890,6 +898,7 @@ pub struct StructureDeclaration {
     pub type_name: TypeName,
     /// The elements (components) of the structure declaration.
     pub elements: Vec<StructureElementDeclaration>,
+    pub attributes: Vec<Attribute>,
 }
 
 /// Declares an element contained within a structure.
@@ -899,6 +908,7 @@ pub struct StructureDeclaration {
 pub struct StructureElementDeclaration {
     pub name: Id,
     pub init: InitialValueAssignmentKind,
+    pub attributes: Vec<Attribute>,
 }
 
 /// See section 2.3.3.1.
@@ -906,6 +916,7 @@ pub struct StructureElementDeclaration {
 pub struct StructureInitializationDeclaration {
     pub type_name: TypeName,
     pub elements_init: Vec<StructureElementInit>,
+    pub attributes: Vec<Attribute>,
 }
 
 /// Initializes a particular element in a structured type.
@@ -932,12 +943,51 @@ pub enum StringType {
 #[derive(Clone, Debug, PartialEq, Recurse)]
 pub struct StringDeclaration {
     pub type_name: TypeName,
-    pub length: Integer,
+    pub length: StringLengthKind,
     /// The size of a single 'character'
     #[recurse(ignore)]
     pub width: StringType,
     #[recurse(ignore)]
     pub init: Option<String>,
+    pub attributes: Vec<Attribute>,
+}
+
+/// The maximum length of a string.
+///
+/// A length is either an integer literal or a constant expression, such as
+/// `MAX_NAME + 1`. Analysis evaluates constant expressions so that resolved
+/// libraries only contain integer literals.
+#[derive(Clone, Debug, PartialEq, Recurse)]
+pub enum StringLengthKind {
+    Integer(Integer),
+    Expression(Box<ExprKind>),
+}
+
+impl StringLengthKind {
+    /// Returns the length if the length is an integer literal.
+    pub fn as_integer(&self) -> Option<&Integer> {
+        match self {
+            StringLengthKind::Integer(value) => Some(value),
+            StringLengthKind::Expression(_) => None,
+        }
+    }
+}
+
+impl From<Integer> for StringLengthKind {
+    fn from(value: Integer) -> Self {
+        StringLengthKind::Integer(value)
+    }
+}
+
+/// Declares a reference type, that is, a type whose values refer to
+/// instances of another type.
+///
+/// Reference types are from the third edition of IEC 61131-3.
+#[derive(Clone, Debug, PartialEq, Recurse)]
+pub struct ReferenceDeclaration {
+    pub type_name: TypeName,
+    pub spec_init: ReferenceInitialValueAssignment,
+    pub attributes: Vec<Attribute>,
 }
 
 /// Location prefix for directly represented variables.
@@ -1041,8 +1091,142 @@ pub struct ArraySubranges {
 /// See section 2.4.2.1.
 #[derive(Clone, Debug, PartialEq, Recurse)]
 pub struct Subrange {
-    pub start: SignedInteger,
-    pub end: SignedInteger,
+    pub start: SubrangeBoundKind,
+    pub end: SubrangeBoundKind,
+}
+
+/// The minimum or maximum value of a subrange.
+///
+/// A bound is either an integer literal or a constant expression, such as
+/// `MAX_AXES - 1`. Analysis evaluates constant expressions so that resolved
+/// libraries only contain integer literals.
+#[derive(Clone, Debug, PartialEq, Recurse)]
+pub enum SubrangeBoundKind {
+    SignedInteger(SignedInteger),
+    Expression(Box<ExprKind>),
+}
+
+impl SubrangeBoundKind {
+    /// Returns the value of the bound if the bound is an integer literal.
+    pub fn as_signed_integer(&self) -> Option<&SignedInteger> {
+        match self {
+            SubrangeBoundKind::SignedInteger(value) => Some(value),
+            SubrangeBoundKind::Expression(_) => None,
+        }
+    }
+}
+
+impl From<SignedInteger> for SubrangeBoundKind {
+    fn from(value: SignedInteger) -> Self {
+        SubrangeBoundKind::SignedInteger(value)
+    }
+}
+
+/// Attribute given by a pragma that precedes a declaration, variable or
+/// program organization unit, such as `{attribute 'qualified_only'}`.
+///
+/// Pragmas are implementation specific (see section 2.1.5) so the
+/// attribute retains the text of the pragma in addition to the parts
+/// that are common to most implementations: `{kind 'name' := 'value'}`.
+#[derive(Clone, Debug, PartialEq, Recurse)]
+pub struct Attribute {
+    /// The leading word of the pragma, such as `attribute` or `warning`.
+    pub kind: Id,
+    /// The first quoted string in the pragma (without quotes).
+    #[recurse(ignore)]
+    pub name: Option<String>,
+    /// The quoted string assigned to the name (without quotes).
+    #[recurse(ignore)]
+    pub value: Option<String>,
+    /// The text of the pragma, including the braces.
+    #[recurse(ignore)]
+    pub text: String,
+    pub span: SourceSpan,
+}
+
+impl Attribute {
+    /// Returns true if this is `{attribute 'name' ...}` for the name.
+    pub fn is_attribute(&self, name: &str) -> bool {
+        self.kind == Id::from("attribute")
+            && self
+                .name
+                .as_ref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Located for Attribute {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
+/// Container for structures that have attributes.
+///
+/// Implementing this trait allows querying the attributes of declarations,
+/// variables and program organization units in a common way.
+pub trait HasAttributes {
+    fn attributes(&self) -> &Vec<Attribute>;
+
+    /// Returns the attribute `{attribute 'name'}` if the item has the attribute.
+    fn find_attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes()
+            .iter()
+            .find(|attr| attr.is_attribute(name))
+    }
+
+    /// Returns true if the item has the attribute `{attribute 'name'}`.
+    fn has_attribute(&self, name: &str) -> bool {
+        self.find_attribute(name).is_some()
+    }
+}
+
+macro_rules! impl_has_attributes {
+    ($($ty:ty),*) => {
+        $(
+            impl HasAttributes for $ty {
+                fn attributes(&self) -> &Vec<Attribute> {
+                    &self.attributes
+                }
+            }
+        )*
+    };
+}
+
+impl_has_attributes!(
+    VarDecl,
+    FunctionDeclaration,
+    FunctionBlockDeclaration,
+    ProgramDeclaration,
+    InterfaceDeclaration,
+    MethodDeclaration,
+    PropertyDeclaration,
+    StructureElementDeclaration,
+    EnumerationDeclaration,
+    SubrangeDeclaration,
+    SimpleDeclaration,
+    ArrayDeclaration,
+    StructureDeclaration,
+    StructureInitializationDeclaration,
+    StringDeclaration,
+    ReferenceDeclaration,
+    LateBoundDeclaration
+);
+
+impl HasAttributes for DataTypeDeclarationKind {
+    fn attributes(&self) -> &Vec<Attribute> {
+        match self {
+            DataTypeDeclarationKind::Enumeration(decl) => decl.attributes(),
+            DataTypeDeclarationKind::Subrange(decl) => decl.attributes(),
+            DataTypeDeclarationKind::Simple(decl) => decl.attributes(),
+            DataTypeDeclarationKind::Array(decl) => decl.attributes(),
+            DataTypeDeclarationKind::Structure(decl) => decl.attributes(),
+            DataTypeDeclarationKind::StructureInitialization(decl) => decl.attributes(),
+            DataTypeDeclarationKind::String(decl) => decl.attributes(),
+            DataTypeDeclarationKind::Reference(decl) => decl.attributes(),
+            DataTypeDeclarationKind::LateBound(decl) => decl.attributes(),
+        }
+    }
 }
 
 /// Container for structures that have variables.
@@ -1074,6 +1258,10 @@ pub struct VarDecl {
     #[recurse(ignore)]
     pub qualifier: DeclarationQualifier,
     pub initializer: InitialValueAssignmentKind,
+    pub attributes: Vec<Attribute>,
+    /// The keyword that starts the block containing the declaration. The
+    /// parser sets this for `VAR_GLOBAL` and `VAR_EXTERNAL` blocks.
+    pub keyword_span: SourceSpan,
 }
 
 impl Located for VarDecl {
@@ -1093,6 +1281,8 @@ impl VarDecl {
             initializer: InitialValueAssignmentKind::simple_uninitialized(TypeName::from(
                 type_name,
             )),
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 
@@ -1107,6 +1297,8 @@ impl VarDecl {
                 initial_value: None,
                 keyword_span: SourceSpan::default(),
             }),
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 
@@ -1123,6 +1315,8 @@ impl VarDecl {
                     initial_value: None,
                 },
             ),
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 
@@ -1142,6 +1336,8 @@ impl VarDecl {
                     }),
                 },
             ),
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 
@@ -1158,6 +1354,8 @@ impl VarDecl {
                     init: vec![],
                 },
             ),
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 
@@ -1171,8 +1369,11 @@ impl VarDecl {
                 StructureInitializationDeclaration {
                     type_name: TypeName::from(type_name),
                     elements_init: vec![],
+                    attributes: vec![],
                 },
             ),
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 
@@ -1187,6 +1388,8 @@ impl VarDecl {
             var_type: VariableType::Var,
             qualifier: DeclarationQualifier::Unspecified,
             initializer: InitialValueAssignmentKind::LateResolvedType(TypeName::from(type_name)),
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 
@@ -1228,6 +1431,8 @@ impl VarDecl {
             }
             InitialValueAssignmentKind::Structure(_structure_initialization_declaration) => todo!(),
             InitialValueAssignmentKind::Array(_array_initial_value_assignment) => todo!(),
+            // TODO should generate a type name for these anonymous types
+            InitialValueAssignmentKind::Reference(_reference_initial_value_assignment) => None,
             InitialValueAssignmentKind::LateResolvedType(type_name) => Some(type_name.clone()),
         }
     }
@@ -1484,6 +1689,7 @@ pub enum InitialValueAssignmentKind {
     Subrange(SubrangeSpecificationKind),
     Structure(StructureInitializationDeclaration),
     Array(ArrayInitialValueAssignment),
+    Reference(ReferenceInitialValueAssignment),
     /// Type that is ambiguous until have discovered type
     /// definitions. Value is the name of the type.
     LateResolvedType(TypeName),
@@ -1553,7 +1759,7 @@ pub struct SimpleInitializer {
 #[derive(Clone, PartialEq, Debug, Recurse)]
 pub struct StringInitializer {
     /// Maximum length of the string.
-    pub length: Option<Integer>,
+    pub length: Option<StringLengthKind>,
     /// The size of a single 'character'
     #[recurse(ignore)]
     pub width: StringType,
@@ -1620,6 +1826,16 @@ pub struct FunctionBlockInitialValueAssignment {
     pub init: Vec<StructureElementInit>,
 }
 
+/// Specification of a reference (`REF_TO`) with an optional initial value.
+///
+/// The initial value is either `NULL` or a `REF()` of a variable.
+#[derive(Clone, PartialEq, Debug, Recurse)]
+pub struct ReferenceInitialValueAssignment {
+    /// The type of the instance that the reference refers to.
+    pub referenced_type_name: TypeName,
+    pub initial_value: Option<ExprKind>,
+}
+
 /// See section 2.4.3.2. #6
 #[derive(Clone, PartialEq, Debug, Recurse)]
 pub struct ArrayInitialValueAssignment {
@@ -1644,7 +1860,7 @@ pub enum VariableSpecificationKind {
 pub struct StringSpecification {
     #[recurse(ignore)]
     pub width: StringType,
-    pub length: Option<Integer>,
+    pub length: Option<StringLengthKind>,
     pub keyword_span: SourceSpan,
 }
 
@@ -1666,6 +1882,7 @@ pub enum LibraryElementKind {
     FunctionBlockDeclaration(FunctionBlockDeclaration),
     ProgramDeclaration(ProgramDeclaration),
     ConfigurationDeclaration(ConfigurationDeclaration),
+    InterfaceDeclaration(InterfaceDeclaration),
 }
 
 ///Function Program Organization Unit Declaration
@@ -1681,7 +1898,8 @@ pub struct FunctionDeclaration {
     pub return_type: TypeName,
     pub variables: Vec<VarDecl>,
     pub edge_variables: Vec<EdgeVarDecl>,
-    pub body: Vec<StmtKind>,
+    pub body: FunctionBlockBodyKind,
+    pub attributes: Vec<Attribute>,
 }
 
 impl HasVariables for FunctionDeclaration {
@@ -1697,12 +1915,23 @@ impl HasVariables for FunctionDeclaration {
 /// and variables retain values between invocations.
 ///
 /// See section 2.5.2.
+/// The object oriented items (EXTENDS, IMPLEMENTS, methods and properties)
+/// are from the third edition of IEC 61131-3.
 #[derive(Clone, Debug, PartialEq, Recurse)]
 pub struct FunctionBlockDeclaration {
     pub name: TypeName,
+    #[recurse(ignore)]
+    pub modifier: Option<PouModifier>,
+    /// The function block that this function block inherits from.
+    pub extends: Option<TypeName>,
+    /// The interfaces that this function block claims to implement.
+    pub implements: Vec<TypeName>,
     pub variables: Vec<VarDecl>,
     pub edge_variables: Vec<EdgeVarDecl>,
+    pub methods: Vec<MethodDeclaration>,
+    pub properties: Vec<PropertyDeclaration>,
     pub body: FunctionBlockBodyKind,
+    pub attributes: Vec<Attribute>,
     pub span: SourceSpan,
 }
 
@@ -1718,6 +1947,126 @@ impl Located for FunctionBlockDeclaration {
     }
 }
 
+/// Access specifier for a method or property.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AccessSpecifier {
+    Public,
+    Protected,
+    Private,
+    Internal,
+}
+
+impl fmt::Display for AccessSpecifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AccessSpecifier::Public => "PUBLIC",
+            AccessSpecifier::Protected => "PROTECTED",
+            AccessSpecifier::Private => "PRIVATE",
+            AccessSpecifier::Internal => "INTERNAL",
+        })
+    }
+}
+
+/// Modifier that constrains how a function block or method participates
+/// in inheritance.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PouModifier {
+    Abstract,
+    Final,
+    Override,
+}
+
+impl fmt::Display for PouModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PouModifier::Abstract => "ABSTRACT",
+            PouModifier::Final => "FINAL",
+            PouModifier::Override => "OVERRIDE",
+        })
+    }
+}
+
+/// Method declaration.
+///
+/// A method is a function that belongs to a function block (or interface)
+/// and that has access to the variables of the function block instance.
+/// Methods declared in an interface are prototypes and have an empty body.
+#[derive(Clone, Debug, PartialEq, Recurse)]
+pub struct MethodDeclaration {
+    pub name: Id,
+    #[recurse(ignore)]
+    pub access: Option<AccessSpecifier>,
+    #[recurse(ignore)]
+    pub modifier: Option<PouModifier>,
+    pub return_type: Option<TypeName>,
+    pub variables: Vec<VarDecl>,
+    pub body: FunctionBlockBodyKind,
+    pub attributes: Vec<Attribute>,
+    pub span: SourceSpan,
+}
+
+impl HasVariables for MethodDeclaration {
+    fn variables(&self) -> &Vec<VarDecl> {
+        &self.variables
+    }
+}
+
+impl Located for MethodDeclaration {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
+/// Property declaration.
+///
+/// A property is accessed like a variable but reading and writing the
+/// property executes the getter and setter respectively.
+#[derive(Clone, Debug, PartialEq, Recurse)]
+pub struct PropertyDeclaration {
+    pub name: Id,
+    #[recurse(ignore)]
+    pub access: Option<AccessSpecifier>,
+    pub property_type: TypeName,
+    pub getter: Option<PropertyAccessor>,
+    pub setter: Option<PropertyAccessor>,
+    pub attributes: Vec<Attribute>,
+    pub span: SourceSpan,
+}
+
+impl Located for PropertyDeclaration {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
+/// The getter (GET) or setter (SET) of a property.
+#[derive(Clone, Debug, PartialEq, Recurse)]
+pub struct PropertyAccessor {
+    pub variables: Vec<VarDecl>,
+    pub body: FunctionBlockBodyKind,
+}
+
+/// Interface declaration.
+///
+/// An interface defines a set of method prototypes that a function block
+/// that implements the interface must define.
+#[derive(Clone, Debug, PartialEq, Recurse)]
+pub struct InterfaceDeclaration {
+    pub name: TypeName,
+    /// The interfaces that this interface inherits from.
+    pub extends: Vec<TypeName>,
+    pub methods: Vec<MethodDeclaration>,
+    pub properties: Vec<PropertyDeclaration>,
+    pub attributes: Vec<Attribute>,
+    pub span: SourceSpan,
+}
+
+impl Located for InterfaceDeclaration {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
 /// "Program" Program Organization Unit Declaration Declaration
 ///
 /// Programs assembled the units into a whole that embodies a measurement
@@ -1730,6 +2079,7 @@ pub struct ProgramDeclaration {
     pub variables: Vec<VarDecl>,
     pub access_variables: Vec<ProgramAccessDecl>,
     pub body: FunctionBlockBodyKind,
+    pub attributes: Vec<Attribute>,
 }
 
 impl HasVariables for ProgramDeclaration {
@@ -1746,6 +2096,7 @@ impl HasVariables for ProgramDeclaration {
 pub enum FunctionBlockBodyKind {
     Sfc(Sfc),
     Statements(Statements),
+    InstructionList(InstructionList),
     /// A function block that has no body (and is therefore no known type).
     ///
     /// This type is not strictly valid, but highly useful and can be detected
@@ -1765,6 +2116,11 @@ impl FunctionBlockBodyKind {
         FunctionBlockBodyKind::Sfc(Sfc { networks })
     }
 
+    /// Creates a function body that is composed of an instruction list.
+    pub fn il(instructions: Vec<IlInstruction>) -> FunctionBlockBodyKind {
+        FunctionBlockBodyKind::InstructionList(InstructionList { body: instructions })
+    }
+
     /// Creates an empty function body.
     pub fn empty() -> FunctionBlockBodyKind {
         FunctionBlockBodyKind::Empty
@@ -1940,6 +2296,7 @@ mod tests {
         let enum_decl = EnumerationDeclaration {
             type_name: TypeName::from("ENUM"),
             spec_init: EnumeratedSpecificationInit::values_and_default(vec!["A", "B"], "A"),
+            attributes: vec![],
         };
         let d1 = DataTypeDeclarationKind::Enumeration(enum_decl.clone());
         let d2 = d1.clone();
@@ -1947,6 +2304,7 @@ mod tests {
         let d3 = DataTypeDeclarationKind::Enumeration(EnumerationDeclaration {
             type_name: TypeName::from("ENUM"),
             spec_init: EnumeratedSpecificationInit::values_and_default(vec!["A", "B"], "B"),
+            attributes: vec![],
         });
         assert_ne!(d1, d3);
     }
@@ -1961,6 +2319,7 @@ mod tests {
                 DataTypeDeclarationKind::Enumeration(EnumerationDeclaration {
                     type_name: TypeName::from("ENUM"),
                     spec_init: EnumeratedSpecificationInit::values_and_default(vec!["A"], "A"),
+                    attributes: vec![],
                 }),
             )],
         };
@@ -8,12 +8,13 @@
 //! 2. Primary label points to the main error location
 //! 3. Secondary labels point to different, related locations
 //! 4. Labels describe what's at each location, not how to fix issues
+//! 5. Fixes describe how to fix issues as edits to the source text
 //!
 //! There exist crates that make this easy, but we need different information
 //! for different integrations and there is no one crate that does it all
 //! (especially one that works for both command line and language server
 //! protocol).
-use ironplc_problems::Problem;
+use ironplc_problems::{Problem, Severity};
 use std::collections::HashSet;
 
 use crate::common::TypeName;
@@ -52,7 +53,7 @@ impl Label {
     /// explanations or fix guidance.
     ///
     /// # Examples
-    /// ```rust
+    /// ```ignore
     /// Label::span(name.span(), "Type declaration")     // ✅ What's there
     /// Label::span(base.span(), "Base type")           // ✅ What's there
     /// Label::span(name.span(), "Fix by adding type")  // ❌ Fix guidance
@@ -79,6 +80,80 @@ impl Label {
     }
 }
 
+/// A change to the text of a file that replaces the range with new text.
+///
+/// An empty range inserts the text and empty text removes the range.
+#[derive(Debug, Clone)]
+pub struct TextEdit {
+    /// The range of text to replace.
+    pub location: Location,
+
+    /// Identifier for the file.
+    pub file_id: FileId,
+
+    /// The text that replaces the range.
+    pub new_text: String,
+}
+
+impl TextEdit {
+    /// Creates an edit that replaces the text in the source span.
+    pub fn replace(span: &SourceSpan, new_text: impl Into<String>) -> Self {
+        Self {
+            location: Location {
+                start: span.start,
+                end: span.end,
+            },
+            file_id: span.file_id.clone(),
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Creates an edit that inserts text at the end of the source span.
+    pub fn insert_after(span: &SourceSpan, new_text: impl Into<String>) -> Self {
+        Self {
+            location: Location {
+                start: span.end,
+                end: span.end,
+            },
+            file_id: span.file_id.clone(),
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Creates an edit that inserts text at the start of the source span.
+    pub fn insert_before(span: &SourceSpan, new_text: impl Into<String>) -> Self {
+        Self {
+            location: Location {
+                start: span.start,
+                end: span.start,
+            },
+            file_id: span.file_id.clone(),
+            new_text: new_text.into(),
+        }
+    }
+}
+
+/// A suggested fix for a diagnostic. Applying all of the edits together
+/// is intended to resolve the problem.
+#[derive(Debug, Clone)]
+pub struct Fix {
+    /// A short description of the fix such as "Add CONSTANT qualifier".
+    pub title: String,
+
+    /// The edits that make up the fix.
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    /// Creates a fix consisting of a single edit.
+    pub fn new(title: impl Into<String>, edit: TextEdit) -> Self {
+        Self {
+            title: title.into(),
+            edits: vec![edit],
+        }
+    }
+}
+
 /// A diagnostic. Diagnostic have a code that is indicative of the category,
 /// a primary location and possibly non-zero set of secondary location.
 #[derive(Debug, Clone)]
@@ -86,6 +161,10 @@ pub struct Diagnostic {
     /// A normally unique value describing the type of diagnostic.
     pub code: String,
 
+    /// The severity of the diagnostic. This is normally the severity of
+    /// the problem but project configuration can change the severity.
+    pub severity: Severity,
+
     description: String,
 
     /// The primary or first diagnostic.
@@ -96,6 +175,9 @@ pub struct Diagnostic {
 
     /// Additional information about the diagnostic.
     pub secondary: Vec<Label>,
+
+    /// Suggested fixes for the diagnostic.
+    pub fixes: Vec<Fix>,
 }
 
 impl Diagnostic {
@@ -106,10 +188,12 @@ impl Diagnostic {
     pub fn problem(problem: Problem, primary: Label) -> Self {
         Self {
             code: problem.code().to_string(),
+            severity: problem.severity(),
             description: problem.message().to_string(),
             primary,
             described: vec![],
             secondary: vec![],
+            fixes: vec![],
         }
     }
 
@@ -203,13 +287,25 @@ impl Diagnostic {
         self
     }
 
+    /// Changes the severity of the diagnostic.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Returns true if the diagnostic is an error (and so prevents a
+    /// successful build).
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
     /// Adds a secondary label pointing to a related location.
     ///
     /// Secondary labels must point to **different spans** than the primary label
     /// and should describe what is located at those spans.
     ///
     /// # Examples
-    /// ```rust
+    /// ```ignore
     /// // ✅ Correct: Different spans, describes locations
     /// Diagnostic::problem(Problem::ParentTypeNotDeclared,
     ///     Label::span(decl_span, "Type declaration"))
@@ -223,6 +319,14 @@ impl Diagnostic {
         self
     }
 
+    /// Adds a suggested fix for the problem.
+    ///
+    /// Unlike labels, fixes describe how to resolve the problem.
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
     /// Returns the description for the diagnostic. This may add in other
     /// data in addition that is part of the diagnostic.
     pub fn description(&self) -> String {
@@ -10,6 +10,7 @@
 use crate::common::*;
 use crate::configuration::*;
 use crate::core::*;
+use crate::il::*;
 use crate::sfc::*;
 use crate::textual::*;
 use crate::time::*;
@@ -149,6 +150,8 @@ pub trait Fold<E> {
 
     // 2.3.3.1
     dispatch!(StringDeclaration);
+    dispatch!(StringLengthKind);
+    dispatch!(ReferenceDeclaration);
 
     dispatch!(ArraySpecificationKind);
 
@@ -156,6 +159,7 @@ pub trait Fold<E> {
 
     // 2.4.2.1
     dispatch!(Subrange);
+    dispatch!(SubrangeBoundKind);
 
     dispatch!(ProgramAccessDecl);
 
@@ -181,6 +185,7 @@ pub trait Fold<E> {
 
     // 2.4.3.2.
     dispatch!(ArrayInitialValueAssignment);
+    dispatch!(ReferenceInitialValueAssignment);
 
     dispatch!(VariableSpecificationKind);
 
@@ -197,6 +202,9 @@ pub trait Fold<E> {
 
     dispatch!(LibraryElementKind);
 
+    // 2.1.5
+    dispatch!(Attribute);
+
     // 2.5.1
     dispatch!(FunctionDeclaration);
 
@@ -205,6 +213,14 @@ pub trait Fold<E> {
 
     dispatch!(FunctionBlockBodyKind);
 
+    dispatch!(MethodDeclaration);
+
+    dispatch!(PropertyDeclaration);
+
+    dispatch!(PropertyAccessor);
+
+    dispatch!(InterfaceDeclaration);
+
     // 2.5.3
     dispatch!(ProgramDeclaration);
 
@@ -276,6 +292,59 @@ pub trait Fold<E> {
 
     dispatch!(LocatedVarInit);
 
+    // Declarations from Instruction List
+
+    // 3.2
+    dispatch!(InstructionList);
+
+    // 3.2.1
+    dispatch!(IlInstruction);
+
+    // 3.2.1
+    dispatch!(IlInstructionKind);
+
+    // 3.2.1
+    dispatch!(IlSimpleInstructionKind);
+
+    // 3.2.1
+    dispatch!(IlOperand);
+
+    // 3.2.2
+    dispatch!(IlSimpleOperation);
+
+    // 3.2.2
+    dispatch!(IlExpression);
+
+    // 3.2.2
+    dispatch!(IlJumpOperation);
+
+    // 3.2.2
+    dispatch!(IlReturnOperation);
+
+    // 3.2.3
+    dispatch!(IlFunctionCall);
+
+    // 3.2.3
+    dispatch!(IlFormalFunctionCall);
+
+    // 3.2.3
+    dispatch!(IlFbCall);
+
+    // 3.2.3
+    dispatch!(IlParamAssignmentKind);
+
+    // 3.2.3
+    dispatch!(IlInputParamAssignment);
+
+    // 3.2.3
+    dispatch!(IlParamValueKind);
+
+    // 3.2.3
+    dispatch!(IlSimpleInstructionList);
+
+    // 3.2.3
+    dispatch!(IlOutputParamAssignment);
+
     // Declarations from Textual
 
     // 3
@@ -288,6 +357,10 @@ pub trait Fold<E> {
     // 3.2.3
     dispatch!(FbCall);
 
+    dispatch!(MethodCall);
+    dispatch!(RefExpr);
+    dispatch!(NullLiteral);
+
     // 3.2.3
     dispatch!(PositionalInput);
 
@@ -339,9 +412,16 @@ pub trait Fold<E> {
 
     dispatch!(Repeat);
 
+    dispatch!(ErrorStatement);
+
     dispatch!(NamedVariable);
 
     dispatch!(ArrayVariable);
 
     dispatch!(StructuredVariable);
+
+    dispatch!(ThisVariable);
+
+    dispatch!(SuperVariable);
+    dispatch!(DerefVariable);
 }
@@ -0,0 +1,363 @@
+//! Provides definitions specific to instruction list (IL) elements.
+//!
+//! See section 3.2.
+use core::fmt;
+
+use crate::common::{ConstantKind, EnumeratedValue};
+use crate::core::{Id, Located, SourceSpan};
+use crate::textual::Variable;
+
+use crate::fold::Fold;
+use crate::visitor::Visitor;
+use dsl_macro_derive::Recurse;
+
+/// A body of a function block that is a sequence of instructions.
+///
+/// See section 3.2.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct InstructionList {
+    pub body: Vec<IlInstruction>,
+}
+
+/// A single line in an instruction list. The instruction may have
+/// a label so that it is the target of a jump.
+///
+/// See section 3.2.1.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlInstruction {
+    pub label: Option<Id>,
+    pub kind: IlInstructionKind,
+    pub span: SourceSpan,
+}
+
+impl Located for IlInstruction {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
+/// The operation that an instruction performs.
+///
+/// See section 3.2.1.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub enum IlInstructionKind {
+    /// An instruction with only a label.
+    #[recurse(ignore)]
+    Nil,
+    SimpleOperation(IlSimpleOperation),
+    FunctionCall(IlFunctionCall),
+    Expression(IlExpression),
+    FormalFunctionCall(IlFormalFunctionCall),
+    Jump(IlJumpOperation),
+    FbCall(IlFbCall),
+    Return(IlReturnOperation),
+}
+
+/// The subset of instructions that are permitted within a parenthesized
+/// expression or as a parameter value.
+///
+/// See section 3.2.1.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub enum IlSimpleInstructionKind {
+    SimpleOperation(IlSimpleOperation),
+    FunctionCall(IlFunctionCall),
+    Expression(IlExpression),
+    FormalFunctionCall(IlFormalFunctionCall),
+}
+
+impl From<IlSimpleInstructionKind> for IlInstructionKind {
+    fn from(item: IlSimpleInstructionKind) -> Self {
+        match item {
+            IlSimpleInstructionKind::SimpleOperation(op) => IlInstructionKind::SimpleOperation(op),
+            IlSimpleInstructionKind::FunctionCall(call) => IlInstructionKind::FunctionCall(call),
+            IlSimpleInstructionKind::Expression(expr) => IlInstructionKind::Expression(expr),
+            IlSimpleInstructionKind::FormalFunctionCall(call) => {
+                IlInstructionKind::FormalFunctionCall(call)
+            }
+        }
+    }
+}
+
+/// An operator applied to the current result and an optional operand.
+///
+/// See section 3.2.2.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlSimpleOperation {
+    #[recurse(ignore)]
+    pub operator: IlSimpleOperator,
+    pub operand: Option<IlOperand>,
+}
+
+/// A function invocation where the current result is the first input
+/// and the operands are the remaining inputs (non-formal call).
+///
+/// See section 3.2.3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlFunctionCall {
+    pub name: Id,
+    pub operands: Vec<IlOperand>,
+}
+
+/// A deferred operation where the operator is applied to the current
+/// result and the result of evaluating the nested instructions.
+///
+/// See section 3.2.2.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlExpression {
+    #[recurse(ignore)]
+    pub operator: IlExprOperator,
+    pub operand: Option<IlOperand>,
+    pub body: Vec<IlSimpleInstructionKind>,
+}
+
+/// A function invocation with named parameters (formal call).
+///
+/// See section 3.2.3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlFormalFunctionCall {
+    pub name: Id,
+    pub params: Vec<IlParamAssignmentKind>,
+}
+
+/// A (possibly conditional) jump to a labeled instruction.
+///
+/// See section 3.2.2.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlJumpOperation {
+    #[recurse(ignore)]
+    pub operator: IlJumpOperator,
+    pub target: Id,
+}
+
+/// A (possibly conditional) invocation of a function block instance.
+///
+/// See section 3.2.3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlFbCall {
+    #[recurse(ignore)]
+    pub operator: IlCallOperator,
+    pub name: Id,
+    pub params: Vec<IlParamAssignmentKind>,
+}
+
+/// A (possibly conditional) return from the function or function block.
+///
+/// See section 3.2.2.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlReturnOperation {
+    #[recurse(ignore)]
+    pub operator: IlReturnOperator,
+}
+
+/// An argument to an instruction list function or function block call.
+///
+/// See section 3.2.3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub enum IlParamAssignmentKind {
+    Positional(IlOperand),
+    Input(IlInputParamAssignment),
+    Output(IlOutputParamAssignment),
+}
+
+/// An input argument that is mapped to the input by name.
+///
+/// See section 3.2.3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlInputParamAssignment {
+    pub name: Id,
+    pub value: IlParamValueKind,
+}
+
+/// The value of a named input argument.
+///
+/// See section 3.2.3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub enum IlParamValueKind {
+    Operand(IlOperand),
+    Instructions(IlSimpleInstructionList),
+}
+
+/// A sequence of instructions that produce a value for an input
+/// argument.
+///
+/// See section 3.2.3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlSimpleInstructionList {
+    pub body: Vec<IlSimpleInstructionKind>,
+}
+
+/// An output argument captured by name from a call.
+///
+/// See section 3.2.3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct IlOutputParamAssignment {
+    #[recurse(ignore)]
+    pub not: bool,
+    pub src: Id,
+    pub tgt: Variable,
+}
+
+/// The operand of an instruction.
+///
+/// See section 3.2.1.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub enum IlOperand {
+    Constant(ConstantKind),
+    EnumeratedValue(EnumeratedValue),
+    Variable(Variable),
+}
+
+/// Operators that take at most one operand.
+///
+/// See section 3.2.2, especially table 52.
+#[derive(Debug, PartialEq, Clone)]
+pub enum IlSimpleOperator {
+    Ld,
+    Ldn,
+    St,
+    Stn,
+    Not,
+    S,
+    R,
+    S1,
+    R1,
+    Clk,
+    Cu,
+    Cd,
+    Pv,
+    In,
+    Pt,
+    Expr(IlExprOperator),
+}
+
+impl fmt::Display for IlSimpleOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            IlSimpleOperator::Ld => "LD",
+            IlSimpleOperator::Ldn => "LDN",
+            IlSimpleOperator::St => "ST",
+            IlSimpleOperator::Stn => "STN",
+            IlSimpleOperator::Not => "NOT",
+            IlSimpleOperator::S => "S",
+            IlSimpleOperator::R => "R",
+            IlSimpleOperator::S1 => "S1",
+            IlSimpleOperator::R1 => "R1",
+            IlSimpleOperator::Clk => "CLK",
+            IlSimpleOperator::Cu => "CU",
+            IlSimpleOperator::Cd => "CD",
+            IlSimpleOperator::Pv => "PV",
+            IlSimpleOperator::In => "IN",
+            IlSimpleOperator::Pt => "PT",
+            IlSimpleOperator::Expr(op) => return op.fmt(f),
+        };
+        f.write_str(op)
+    }
+}
+
+/// Operators that can defer evaluation with a parenthesized expression.
+///
+/// See section 3.2.2, especially table 52.
+#[derive(Debug, PartialEq, Clone)]
+pub enum IlExprOperator {
+    And,
+    Or,
+    Xor,
+    Andn,
+    Orn,
+    Xorn,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Gt,
+    Ge,
+    Eq,
+    Lt,
+    Le,
+    Ne,
+}
+
+impl fmt::Display for IlExprOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IlExprOperator::And => "AND",
+            IlExprOperator::Or => "OR",
+            IlExprOperator::Xor => "XOR",
+            IlExprOperator::Andn => "ANDN",
+            IlExprOperator::Orn => "ORN",
+            IlExprOperator::Xorn => "XORN",
+            IlExprOperator::Add => "ADD",
+            IlExprOperator::Sub => "SUB",
+            IlExprOperator::Mul => "MUL",
+            IlExprOperator::Div => "DIV",
+            IlExprOperator::Mod => "MOD",
+            IlExprOperator::Gt => "GT",
+            IlExprOperator::Ge => "GE",
+            IlExprOperator::Eq => "EQ",
+            IlExprOperator::Lt => "LT",
+            IlExprOperator::Le => "LE",
+            IlExprOperator::Ne => "NE",
+        })
+    }
+}
+
+/// Jump operators.
+///
+/// See section 3.2.2, especially table 52.
+#[derive(Debug, PartialEq, Clone)]
+pub enum IlJumpOperator {
+    Jmp,
+    Jmpc,
+    Jmpcn,
+}
+
+impl fmt::Display for IlJumpOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IlJumpOperator::Jmp => "JMP",
+            IlJumpOperator::Jmpc => "JMPC",
+            IlJumpOperator::Jmpcn => "JMPCN",
+        })
+    }
+}
+
+/// Function block call operators.
+///
+/// See section 3.2.2, especially table 52.
+#[derive(Debug, PartialEq, Clone)]
+pub enum IlCallOperator {
+    Cal,
+    Calc,
+    Calcn,
+}
+
+impl fmt::Display for IlCallOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IlCallOperator::Cal => "CAL",
+            IlCallOperator::Calc => "CALC",
+            IlCallOperator::Calcn => "CALCN",
+        })
+    }
+}
+
+/// Return operators.
+///
+/// See section 3.2.2, especially table 52.
+#[derive(Debug, PartialEq, Clone)]
+pub enum IlReturnOperator {
+    Ret,
+    Retc,
+    Retcn,
+}
+
+impl fmt::Display for IlReturnOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IlReturnOperator::Ret => "RET",
+            IlReturnOperator::Retc => "RETC",
+            IlReturnOperator::Retcn => "RETCN",
+        })
+    }
+}
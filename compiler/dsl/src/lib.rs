@@ -6,6 +6,7 @@ pub mod configuration;
 pub mod core;
 pub mod diagnostic;
 pub mod fold;
+pub mod il;
 pub mod sfc;
 pub mod textual;
 pub mod time;
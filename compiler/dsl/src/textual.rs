@@ -42,6 +42,15 @@ impl From<SymbolicVariableKind> for Variable {
             SymbolicVariableKind::Structured(structured) => {
                 Variable::Symbolic(SymbolicVariableKind::Structured(structured))
             }
+            SymbolicVariableKind::This(this) => {
+                Variable::Symbolic(SymbolicVariableKind::This(this))
+            }
+            SymbolicVariableKind::Super(sup) => {
+                Variable::Symbolic(SymbolicVariableKind::Super(sup))
+            }
+            SymbolicVariableKind::Deref(deref) => {
+                Variable::Symbolic(SymbolicVariableKind::Deref(deref))
+            }
         }
     }
 }
@@ -60,6 +69,9 @@ pub enum SymbolicVariableKind {
     Named(NamedVariable),
     Array(ArrayVariable),
     Structured(StructuredVariable),
+    This(ThisVariable),
+    Super(SuperVariable),
+    Deref(DerefVariable),
 }
 
 impl fmt::Display for SymbolicVariableKind {
@@ -70,6 +82,9 @@ impl fmt::Display for SymbolicVariableKind {
             SymbolicVariableKind::Structured(structured) => {
                 f.write_fmt(format_args!("{structured}"))
             }
+            SymbolicVariableKind::This(this) => f.write_fmt(format_args!("{this}")),
+            SymbolicVariableKind::Super(sup) => f.write_fmt(format_args!("{sup}")),
+            SymbolicVariableKind::Deref(deref) => f.write_fmt(format_args!("{deref}")),
         }
     }
 }
@@ -80,6 +95,9 @@ impl Located for SymbolicVariableKind {
             SymbolicVariableKind::Named(named) => named.span(),
             SymbolicVariableKind::Array(array) => array.span(),
             SymbolicVariableKind::Structured(structured) => structured.span(),
+            SymbolicVariableKind::This(this) => this.span(),
+            SymbolicVariableKind::Super(sup) => sup.span(),
+            SymbolicVariableKind::Deref(deref) => deref.span(),
         }
     }
 }
@@ -161,6 +179,66 @@ impl Located for StructuredVariable {
     }
 }
 
+/// Reference to the function block instance that contains the method
+/// (THIS).
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct ThisVariable {
+    pub span: SourceSpan,
+}
+
+impl fmt::Display for ThisVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("THIS")
+    }
+}
+
+impl Located for ThisVariable {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
+/// Reference to the function block instance as the type that the
+/// function block extends (SUPER).
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct SuperVariable {
+    pub span: SourceSpan,
+}
+
+impl fmt::Display for SuperVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SUPER")
+    }
+}
+
+impl Located for SuperVariable {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
+/// Access to the instance that a reference refers to (`ref^`).
+///
+/// Dereference is from the third edition of IEC 61131-3.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct DerefVariable {
+    /// The variable that holds the reference.
+    pub reference: Box<SymbolicVariableKind>,
+    pub span: SourceSpan,
+}
+
+impl fmt::Display for DerefVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("{}^", self.reference.as_ref()))
+    }
+}
+
+impl Located for DerefVariable {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
 /// Function block invocation.
 ///
 /// See section 3.2.3.
@@ -179,6 +257,24 @@ impl Located for FbCall {
     }
 }
 
+/// Method invocation on a function block instance, either as a statement
+/// or as part of an expression.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct MethodCall {
+    /// The function block instance (including THIS or SUPER) that owns
+    /// the method.
+    pub receiver: SymbolicVariableKind,
+    pub name: Id,
+    pub params: Vec<ParamAssignmentKind>,
+    pub position: SourceSpan,
+}
+
+impl Located for MethodCall {
+    fn span(&self) -> SourceSpan {
+        self.position.clone()
+    }
+}
+
 /// A binary expression that produces a Boolean result by comparing operands.
 ///
 /// See section 3.3.1.
@@ -219,6 +315,32 @@ pub struct Function {
     pub param_assignment: Vec<ParamAssignmentKind>,
 }
 
+/// Expression that yields a reference to a variable (`REF(var)`).
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct RefExpr {
+    /// The variable that the reference refers to.
+    pub variable: SymbolicVariableKind,
+    pub span: SourceSpan,
+}
+
+impl Located for RefExpr {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
+/// The reference that does not refer to any instance (`NULL`).
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct NullLiteral {
+    pub span: SourceSpan,
+}
+
+impl Located for NullLiteral {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Recurse)]
 pub struct LateBound {
     pub value: Id,
@@ -235,6 +357,9 @@ pub enum ExprKind {
     EnumeratedValue(EnumeratedValue),
     Variable(Variable),
     Function(Function),
+    MethodCall(Box<MethodCall>),
+    Ref(Box<RefExpr>),
+    Null(NullLiteral),
     LateBound(LateBound),
 }
 
@@ -369,6 +494,7 @@ pub enum StmtKind {
     Assignment(Assignment),
     // Function and function block control
     FbCall(FbCall),
+    MethodCall(MethodCall),
     // Selection statements
     If(If),
     Case(Case),
@@ -381,6 +507,8 @@ pub enum StmtKind {
     // Exit statement.
     #[recurse(ignore)]
     Exit,
+    // Statement that is not valid syntax.
+    Error(ErrorStatement),
 }
 
 impl StmtKind {
@@ -545,6 +673,21 @@ pub struct While {
     pub body: Vec<StmtKind>,
 }
 
+/// A statement that could not be parsed.
+///
+/// The parser creates the statement when recovering from a syntax error
+/// so that the statements around the error are still available.
+#[derive(Debug, PartialEq, Clone, Recurse)]
+pub struct ErrorStatement {
+    pub span: SourceSpan,
+}
+
+impl Located for ErrorStatement {
+    fn span(&self) -> SourceSpan {
+        self.span.clone()
+    }
+}
+
 /// The repeat loop statement.
 ///
 /// See section 3.3.2.4.
@@ -35,6 +35,7 @@
 use crate::common::*;
 use crate::configuration::*;
 use crate::core::{Id, SourceSpan};
+use crate::il::*;
 use crate::sfc::*;
 use crate::textual::*;
 use crate::time::*;
@@ -200,6 +201,8 @@ pub trait Visitor<E> {
 
     // 2.3.3.1
     dispatch!(StringDeclaration);
+    dispatch!(StringLengthKind);
+    dispatch!(ReferenceDeclaration);
 
     dispatch!(ArraySpecificationKind);
 
@@ -207,6 +210,7 @@ pub trait Visitor<E> {
 
     // 2.4.2.1
     dispatch!(Subrange);
+    dispatch!(SubrangeBoundKind);
 
     dispatch!(ProgramAccessDecl);
 
@@ -232,6 +236,7 @@ pub trait Visitor<E> {
 
     // 2.4.3.2
     dispatch!(ArrayInitialValueAssignment);
+    dispatch!(ReferenceInitialValueAssignment);
 
     dispatch!(VariableSpecificationKind);
 
@@ -248,6 +253,9 @@ pub trait Visitor<E> {
 
     dispatch!(LibraryElementKind);
 
+    // 2.1.5
+    dispatch!(Attribute);
+
     // 2.5.1
     dispatch!(FunctionDeclaration);
 
@@ -256,6 +264,14 @@ pub trait Visitor<E> {
 
     dispatch!(FunctionBlockBodyKind);
 
+    dispatch!(MethodDeclaration);
+
+    dispatch!(PropertyDeclaration);
+
+    dispatch!(PropertyAccessor);
+
+    dispatch!(InterfaceDeclaration);
+
     // 2.5.3
     dispatch!(ProgramDeclaration);
 
@@ -327,6 +343,59 @@ pub trait Visitor<E> {
 
     dispatch!(LocatedVarInit);
 
+    // Declarations from Instruction List
+
+    // 3.2
+    dispatch!(InstructionList);
+
+    // 3.2.1
+    dispatch!(IlInstruction);
+
+    // 3.2.1
+    dispatch!(IlInstructionKind);
+
+    // 3.2.1
+    dispatch!(IlSimpleInstructionKind);
+
+    // 3.2.1
+    dispatch!(IlOperand);
+
+    // 3.2.2
+    dispatch!(IlSimpleOperation);
+
+    // 3.2.2
+    dispatch!(IlExpression);
+
+    // 3.2.2
+    dispatch!(IlJumpOperation);
+
+    // 3.2.2
+    dispatch!(IlReturnOperation);
+
+    // 3.2.3
+    dispatch!(IlFunctionCall);
+
+    // 3.2.3
+    dispatch!(IlFormalFunctionCall);
+
+    // 3.2.3
+    dispatch!(IlFbCall);
+
+    // 3.2.3
+    dispatch!(IlParamAssignmentKind);
+
+    // 3.2.3
+    dispatch!(IlInputParamAssignment);
+
+    // 3.2.3
+    dispatch!(IlParamValueKind);
+
+    // 3.2.3
+    dispatch!(IlSimpleInstructionList);
+
+    // 3.2.3
+    dispatch!(IlOutputParamAssignment);
+
     // Declarations from Textual
 
     // 3
@@ -339,6 +408,10 @@ pub trait Visitor<E> {
     // 3.2.3
     dispatch!(FbCall);
 
+    dispatch!(MethodCall);
+    dispatch!(RefExpr);
+    dispatch!(NullLiteral);
+
     // 3.2.3
     dispatch!(PositionalInput);
 
@@ -390,11 +463,18 @@ pub trait Visitor<E> {
 
     dispatch!(Repeat);
 
+    dispatch!(ErrorStatement);
+
     dispatch!(NamedVariable);
 
     dispatch!(ArrayVariable);
 
     dispatch!(StructuredVariable);
+
+    dispatch!(ThisVariable);
+
+    dispatch!(SuperVariable);
+    dispatch!(DerefVariable);
 }
 
 #[cfg(test)]
@@ -454,6 +534,7 @@ mod test {
                     vec!["Cnt1", "Cnt2"],
                     "_TMP_AverageVal17_OUT",
                 )]),
+                attributes: vec![],
             })],
         };
 
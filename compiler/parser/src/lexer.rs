@@ -2,7 +2,7 @@
 //! text into tokens (tokens are the input to the parser).
 //!
 //! This lexer makes some simplifying assumptions:
-//! * there are no pragmas
+//! * pragmas do not contain a closing brace
 use dsl::{
     core::{FileId, SourceSpan},
     diagnostic::{Diagnostic, Label},
@@ -55,8 +55,8 @@ pub fn tokenize(
                         line += 1;
                         col = 0;
                     }
-                    TokenType::Comment => {
-                        // Comments can have new lines embedded
+                    TokenType::Comment | TokenType::Pragma => {
+                        // Comments and pragmas can have new lines embedded
                         for c in lexer.slice().chars() {
                             match c {
                                 '\n' => {
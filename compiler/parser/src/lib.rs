@@ -6,17 +6,20 @@ mod lexer;
 pub mod options;
 mod parser;
 mod preprocessor;
+mod recovery;
 mod rule_token_no_c_style_comment;
 mod vars;
 mod xform_assign_file_id;
+mod xform_attach_attributes;
 mod xform_tokens;
 
-use crate::parser::{parse_library, parse_statements};
+use crate::parser::{parse_library_with_recovery, parse_statements};
 use dsl::{core::FileId, diagnostic::Diagnostic};
 use ironplc_dsl::common::Library;
 use ironplc_dsl::textual::StmtKind;
 use lexer::tokenize;
 use options::ParseOptions;
+pub use preprocessor::has_conditional_directives;
 use preprocessor::preprocess;
 use token::Token;
 use xform_tokens::insert_keyword_statement_terminators;
@@ -42,8 +45,15 @@ pub fn tokenize_program(
     line_offset: usize,
     col_offset: usize,
 ) -> (Vec<Token>, Vec<Diagnostic>) {
-    let source = preprocess(source);
-    let (tokens, mut errors) = tokenize(&source, file_id, line_offset, col_offset);
+    let mut errors = vec![];
+    // If the directives are not valid, then continue with the original source
+    // (the directives are pragmas) so that we can still report other problems.
+    let source = preprocess(source, file_id, options).unwrap_or_else(|err| {
+        errors.push(err);
+        source.to_string()
+    });
+    let (tokens, mut token_errors) = tokenize(&source, file_id, line_offset, col_offset);
+    errors.append(&mut token_errors);
 
     let tokens = insert_keyword_statement_terminators(tokens, file_id);
     let result = check_tokens(&tokens, options);
@@ -81,16 +91,44 @@ pub fn parse_program(
     file_id: &FileId,
     options: &ParseOptions,
 ) -> Result<Library, Diagnostic> {
-    let mut result = tokenize_program(source, file_id, options, 0, 0);
-    if !result.1.is_empty() {
-        return Err(result.1.remove(0));
+    let (library, mut diagnostics) = parse_program_with_recovery(source, file_id, options);
+    match diagnostics.is_empty() {
+        true => Ok(library),
+        false => Err(diagnostics.remove(0)),
     }
+}
 
-    let library = parse_library(result.0).map(|elements| Library { elements })?;
+/// Parse a full IEC 61131 program, recovering from syntax errors.
+///
+/// Returns the library containing the elements that could be parsed and
+/// all of the diagnostics. The library is partial when there are
+/// diagnostics: statements that have a syntax error are error statements
+/// and library elements with a syntax error outside of the body are omitted.
+pub fn parse_program_with_recovery(
+    source: &str,
+    file_id: &FileId,
+    options: &ParseOptions,
+) -> (Library, Vec<Diagnostic>) {
+    let (tokens, mut diagnostics) = tokenize_program(source, file_id, options, 0, 0);
+
+    // Pragmas are not part of the grammar, so find the pragmas before
+    // parsing and attach them to the parsed declarations.
+    let attributes = xform_attach_attributes::collect(&tokens);
+    let (elements, mut syntax_diagnostics) = parse_library_with_recovery(&tokens);
+    diagnostics.append(&mut syntax_diagnostics);
 
     // The parser does not know how to assign the file identifier, so transform the input as
     // a post-processing step.
-    xform_assign_file_id::apply(library, file_id)
+    let library = xform_attach_attributes::apply(Library { elements }, attributes)
+        .and_then(|library| xform_assign_file_id::apply(library, file_id));
+
+    match library {
+        Ok(library) => (library, diagnostics),
+        Err(diagnostic) => {
+            diagnostics.push(diagnostic);
+            (Library::new(), diagnostics)
+        }
+    }
 }
 
 /// Parse ST (Structured Text) body content into statements.
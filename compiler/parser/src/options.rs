@@ -3,7 +3,10 @@
 //!
 //!
 
-#[derive(Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ParseOptions {
     pub allow_c_style_comments: bool,
+    /// Names that are defined for conditional compilation, for example
+    /// `{IF defined(NAME)}`.
+    pub defines: Vec<String>,
 }
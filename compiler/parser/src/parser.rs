@@ -2,7 +2,8 @@
 //! a list of tokens into objects.
 //!
 //! This parser makes some simplifying assumptions:
-//! * there are no pragmas
+//! * pragmas are not language elements (they are attached to declarations
+//!   after parsing)
 //! * there are no ladder diagram or function block diagrams
 //!
 //! These assumptions just mean an earlier stage needs to remove/apply these
@@ -15,20 +16,23 @@
 //! * parser rule names in all capital letters are not production rules
 extern crate peg;
 
-use dsl::core::SourceSpan;
+use dsl::core::{Located, SourceSpan};
 use dsl::diagnostic::Diagnostic;
 use dsl::diagnostic::Label;
 use ironplc_problems::Problem;
+use peg::error::ExpectedSet;
 use peg::parser;
 use peg::Parse;
 use peg::ParseElem;
 use peg::RuleResult;
 
+use crate::recovery::Recovery;
 use crate::token::{Token, TokenType};
 use crate::vars::*;
 use ironplc_dsl::common::*;
 use ironplc_dsl::configuration::*;
 use ironplc_dsl::core::Id;
+use ironplc_dsl::il::*;
 use ironplc_dsl::sfc::*;
 use ironplc_dsl::textual::*;
 use ironplc_dsl::time::*;
@@ -36,27 +40,39 @@ use ironplc_dsl::time::*;
 // Don't use std::time::Duration because it does not allow negative values.
 use time::{Date, Month, PrimitiveDateTime, Time};
 
-/// Parses a IEC 61131-3 library into object form.
-pub fn parse_library(tokens: Vec<Token>) -> Result<Vec<LibraryElementKind>, Diagnostic> {
-    plc_parser::library(&SliceByRef(&tokens[..])).map_err(|e| {
-        let token_index = e.location;
-
-        let expected = Vec::from_iter(e.expected.tokens()).join(" | ");
-        let actual = tokens.get(token_index - 1).unwrap();
-
-        Diagnostic::problem(
-            Problem::SyntaxError,
-            Label::span(
-                actual.span.clone(),
-                format!(
-                    "Expected {}. Found text '{}' that matched token {}",
-                    expected,
-                    actual.text.replace('\n', "\\n").replace('\r', "\\r"),
-                    actual.token_type.describe()
-                ),
-            ),
-        )
-    })
+/// The most syntax errors that parsing a library reports. Each syntax error
+/// parses the library again, so the limit bounds the time to parse a library
+/// that has many errors.
+const MAX_SYNTAX_ERRORS: usize = 50;
+
+/// Parses a IEC 61131-3 library into object form, recovering from syntax
+/// errors.
+///
+/// Returns the library elements that could be parsed (statements with
+/// errors are error statements) and a diagnostic for each syntax error.
+pub fn parse_library_with_recovery(tokens: &[Token]) -> (Vec<LibraryElementKind>, Vec<Diagnostic>) {
+    let mut recovery = Recovery::default();
+    let mut diagnostics = vec![];
+
+    loop {
+        match plc_parser::library(&SliceByRef(tokens), &recovery) {
+            Ok(elements) => return (elements, diagnostics),
+            Err(e) => {
+                let token_index = e.location.saturating_sub(1);
+                diagnostics.push(syntax_error(tokens, token_index, &e.expected));
+
+                if diagnostics.len() >= MAX_SYNTAX_ERRORS || !recovery.recover(tokens, token_index)
+                {
+                    // Keep the elements before the error by skipping the
+                    // remainder of the library.
+                    recovery.skip_remainder(tokens, token_index);
+                    let elements =
+                        plc_parser::library(&SliceByRef(tokens), &recovery).unwrap_or_default();
+                    return (elements, diagnostics);
+                }
+            }
+        }
+    }
 }
 
 /// Parses a list of IEC 61131-3 statements into object form.
@@ -68,25 +84,26 @@ pub fn parse_statements(tokens: Vec<Token>) -> Result<Vec<StmtKind>, Diagnostic>
         return Ok(vec![]);
     }
 
-    plc_parser::statement_list(&SliceByRef(&tokens[..])).map_err(|e| {
-        let token_index = e.location;
-
-        let expected = Vec::from_iter(e.expected.tokens()).join(" | ");
-        let actual = tokens.get(token_index.saturating_sub(1)).unwrap();
+    plc_parser::statement_list(&SliceByRef(&tokens[..]), &Recovery::default())
+        .map_err(|e| syntax_error(&tokens, e.location.saturating_sub(1), &e.expected))
+}
 
-        Diagnostic::problem(
-            Problem::SyntaxError,
-            Label::span(
-                actual.span.clone(),
-                format!(
-                    "Expected {}. Found text '{}' that matched token {}",
-                    expected,
-                    actual.text.replace('\n', "\\n").replace('\r', "\\r"),
-                    actual.token_type.describe()
-                ),
+fn syntax_error(tokens: &[Token], token_index: usize, expected: &ExpectedSet) -> Diagnostic {
+    let expected = Vec::from_iter(expected.tokens()).join(" | ");
+    let actual = tokens.get(token_index).unwrap();
+
+    Diagnostic::problem(
+        Problem::SyntaxError,
+        Label::span(
+            actual.span.clone(),
+            format!(
+                "Expected {}. Found text '{}' that matched token {}",
+                expected,
+                actual.text.replace('\n', "\\n").replace('\r', "\\r"),
+                actual.token_type.describe()
             ),
-        )
-    })
+        ),
+    )
 }
 
 enum StatementsOrEmpty {
@@ -108,6 +125,28 @@ fn flatten_statements(mut items: Vec<StatementsOrEmpty>) -> Vec<StmtKind> {
 enum Element {
     StructSelector(Id),
     ArraySelector(Vec<ExprKind>),
+    Deref(SourceSpan),
+}
+
+enum PouMemberKind {
+    Method(MethodDeclaration),
+    Property(PropertyDeclaration),
+}
+
+/// Splits the members of a function block or interface into the methods
+/// and the properties.
+fn split_pou_members(
+    items: Vec<PouMemberKind>,
+) -> (Vec<MethodDeclaration>, Vec<PropertyDeclaration>) {
+    let mut methods = Vec::new();
+    let mut properties = Vec::new();
+    for item in items {
+        match item {
+            PouMemberKind::Method(method) => methods.push(method),
+            PouMemberKind::Property(property) => properties.push(property),
+        }
+    }
+    (methods, properties)
 }
 
 enum InstanceInitKind {
@@ -153,7 +192,7 @@ impl<'a, T: 'a> ParseElem<'a> for SliceByRef<'a, T> {
 }
 
 parser! {
-  grammar plc_parser<'a>() for SliceByRef<'a, Token> {
+  grammar plc_parser<'a>(recovery: &Recovery) for SliceByRef<'a, Token> {
 
     /// Rule to enable optional tracing rule for pegviz markers that makes
     /// working with the parser easier in the terminal.
@@ -187,6 +226,8 @@ parser! {
 
     /// Helper rule to match an Identifier with the specified text
     rule id_eq(val: &str) -> &'input Token = [t if t.token_type == TokenType::Identifier && t.text.as_str() == val]
+    // Matches an identifier that is a keyword only in some contexts.
+    rule id_eq_ignore_case(val: &str) -> &'input Token = [t if t.token_type == TokenType::Identifier && t.text.eq_ignore_ascii_case(val)]
 
     // peg rules for making the grammar easier to work with. These produce
     // output on matching with the name of the item
@@ -196,7 +237,8 @@ parser! {
     rule whitespace() -> () = tok(TokenType::Whitespace) {} / tok(TokenType::Newline) {}
 
     rule comment() -> () = tok(TokenType::Comment) ()
-    rule _ = (whitespace() / comment())*
+    rule pragma() -> () = tok(TokenType::Pragma) ()
+    rule _ = (whitespace() / comment() / pragma())*
 
     // Lists of separated items with required ending separator
     rule periodsep<T>(x: rule<T>) -> Vec<T> = v:(x() ** (_ period() _)) _ period() {v}
@@ -218,6 +260,19 @@ parser! {
       / fbd:function_block_declaration() { vec![LibraryElementKind::FunctionBlockDeclaration(fbd)] }
       / pd:program_declaration() { vec![LibraryElementKind::ProgramDeclaration(pd)] }
       / cd:configuration_declaration() { vec![LibraryElementKind::ConfigurationDeclaration(cd)] }
+      / id:interface_declaration() { vec![LibraryElementKind::InterfaceDeclaration(id)] }
+      / error_element() { vec![] }
+
+    // Library element that has a syntax error and is skipped (see recovery).
+    rule error_element() = start:position!() end:recovered_end(recovery.element_end(start)) recovered_token(end)+
+    // Statement that has a syntax error (see recovery).
+    rule error_statement() -> StmtKind = start:position!() end:recovered_end(recovery.statement_end(start)) tokens:recovered_token(end)+ {
+      StmtKind::Error(ErrorStatement {
+        span: SourceSpan::join(&tokens[0].span, &tokens[tokens.len() - 1].span),
+      })
+    }
+    rule recovered_end(end: Option<usize>) -> usize = {? end.ok_or("recovered region") }
+    rule recovered_token(end: usize) -> &'input Token = pos:position!() token:[t] {? if pos < end { Ok(token) } else { Err("recovered token") } }
 
     // B.1.1 Letters, digits and identifier
     rule identifier() -> Id = i:tok(TokenType::Identifier) {
@@ -400,7 +455,8 @@ parser! {
     /// the type_declaration also bring in from single_element_type_declaration so that we can match in an order
     /// that identifies the type
     rule type_declaration() -> DataTypeDeclarationKind =
-      s:string_type_declaration() { DataTypeDeclarationKind::String(s) }
+      r:reference_type_declaration() { DataTypeDeclarationKind::Reference(r) }
+      / s:string_type_declaration() { DataTypeDeclarationKind::String(s) }
       / s:string_type_declaration__parenthesis() { DataTypeDeclarationKind::String(s) }
       / a:array_type_declaration() { DataTypeDeclarationKind::Array(a) }
       / subrange:subrange_type_declaration__with_range() { DataTypeDeclarationKind::Subrange(subrange) }
@@ -419,12 +475,14 @@ parser! {
       LateBoundDeclaration {
         data_type_name,
         base_type_name,
+        attributes: vec![],
       }
     }
     rule simple_type_declaration__with_constant() -> SimpleDeclaration = type_name:simple_type_name() _ tok(TokenType::Colon) _ spec_and_init:simple_spec_init__with_constant() {
       SimpleDeclaration {
         type_name,
         spec_and_init,
+        attributes: vec![],
       }
     }
     rule simple_spec_init() -> InitialValueAssignmentKind = type_name:simple_specification() _ constant:(tok(TokenType::Assignment) _ c:constant() { c })? {
@@ -440,12 +498,17 @@ parser! {
         initial_value: Some(constant),
       })
     }
-    rule simple_specification() -> TypeName = et:elementary_type_name() { et.into() } / simple_type_name()
+    rule simple_specification() -> TypeName = elementary_type_specification() / simple_type_name()
+    rule elementary_type_specification() -> TypeName = token:&[t] et:elementary_type_name() {
+      let type_name: TypeName = et.into();
+      TypeName { name: type_name.name.with_position(token.span.clone()) }
+    }
     rule subrange_type_declaration__with_range() -> SubrangeDeclaration = type_name:subrange_type_name() _ tok(TokenType::Colon) _ spec:subrange_spec_init__with_range() {
       SubrangeDeclaration {
         type_name,
         spec: spec.0,
         default: spec.1,
+        attributes: vec![],
       }
     }
     rule subrange_spec_init__with_range() -> (SubrangeSpecificationKind, Option<SignedInteger>) = spec:subrange_specification__with_range() _ default:(tok(TokenType::Assignment) _ def:signed_integer() { def })? {
@@ -453,7 +516,11 @@ parser! {
     }
     rule subrange_specification__with_range() -> SubrangeSpecificationKind
       = type_name:integer_type_name() _ tok(TokenType::LeftParen) _ subrange:subrange() _ tok(TokenType::RightParen) { SubrangeSpecificationKind::Specification(SubrangeSpecification{ type_name, subrange }) }
-    rule subrange() -> Subrange = start:signed_integer() tok(TokenType::Range) end:signed_integer() { Subrange{start, end} }
+    rule subrange() -> Subrange = start:subrange_bound() _ tok(TokenType::Range) _ end:subrange_bound() { Subrange{start, end} }
+    // Bounds are usually integer literals but may be constant expressions such as MAX_AXES-1
+    rule subrange_bound() -> SubrangeBoundKind =
+      i:signed_integer() &(_ (tok(TokenType::Range) / tok(TokenType::RightBracket) / tok(TokenType::RightParen) / tok(TokenType::Comma) / tok(TokenType::Colon))) { SubrangeBoundKind::SignedInteger(i) }
+      / e:expression() { SubrangeBoundKind::Expression(Box::new(e)) }
 
     rule enumerated_type_declaration__with_value() -> EnumerationDeclaration =
       type_name:enumerated_type_name() _ tok(TokenType::Colon) _ spec_init:enumerated_spec_init__with_value() {
@@ -466,6 +533,7 @@ parser! {
             spec,
             default: Some(init),
           },
+          attributes: vec![],
         }
       }
       / type_name:enumerated_type_name() _ tok(TokenType::Colon) _ spec_init:enumerated_spec_init__with_values() {
@@ -478,6 +546,7 @@ parser! {
             spec,
             default: init,
           },
+          attributes: vec![],
         }
       }
     rule enumerated_spec_init__with_value() -> (EnumeratedSpecificationKind, EnumeratedValue) = spec:enumerated_specification() _ tok(TokenType::Assignment) _ def:enumerated_value() {
@@ -503,6 +572,7 @@ parser! {
         type_name,
         spec: spec_and_init.spec,
         init: spec_and_init.initial_values,
+        attributes: vec![],
       }
     }
     rule array_spec_init() -> ArrayInitialValueAssignment = spec:array_specification() _ init:(tok(TokenType::Assignment) _ a:array_initialization() { a })? {
@@ -523,6 +593,7 @@ parser! {
         DataTypeDeclarationKind::Structure(StructureDeclaration {
           type_name,
           elements: decl.elements,
+          attributes: vec![],
         })
       }
       / type_name:structure_type_name() _ tok(TokenType::Colon) _ init:initialized_structure__without_ambiguous() {
@@ -530,6 +601,7 @@ parser! {
           // TODO there is something off with having two type names
           type_name,
           elements_init: init.elements_init,
+          attributes: vec![],
         })
       }
     // structure_specification - covered in structure_type_declaration because that avoids
@@ -538,6 +610,7 @@ parser! {
       StructureInitializationDeclaration {
         type_name,
         elements_init: init.unwrap_or_default(),
+        attributes: vec![],
       }
     }
     /// Same as initialized_structure but requires an initializer. Without the
@@ -547,6 +620,7 @@ parser! {
       StructureInitializationDeclaration {
         type_name,
         elements_init: init,
+        attributes: vec![],
       }
     }
     rule structure_declaration() -> StructureDeclaration = tok(TokenType::Struct) _ elements:semisep_oneplus(<structure_element_declaration()>) _ tok(TokenType::EndStruct) {
@@ -554,10 +628,12 @@ parser! {
         // Requires a value but we don't know the name until one level up
         type_name: TypeName::from(""),
         elements,
+        attributes: vec![],
       }
     }
     rule structure_element_declaration() -> StructureElementDeclaration = name:structure_element_name() _ tok(TokenType::Colon) _ init:(
       arr:array_spec_init() { InitialValueAssignmentKind::Array(arr) }
+      / r:ref_spec_init() { InitialValueAssignmentKind::Reference(r) }
       // handle the initial value
       / subrange:subrange_spec_init__with_range() { InitialValueAssignmentKind::Subrange(subrange.0) }
       / i:initialized_structure__without_ambiguous() { InitialValueAssignmentKind::Structure(i) }
@@ -587,6 +663,7 @@ parser! {
         StructureElementDeclaration {
           name,
           init,
+          attributes: vec![],
         }
     }
     rule structure_element_name() ->Id = identifier()
@@ -636,11 +713,11 @@ parser! {
         values,
         initial_value: init,
       })
-    } / et:elementary_type_name() {
+    } / type_name:elementary_type_specification() {
       // An identifier that is an elementary_type_name s unambiguous because these are
       // reserved keywords
       InitialValueAssignmentKind::Simple(SimpleInitializer {
-        type_name: et.into(),
+        type_name,
         initial_value: None,
       })
     }/ i:type_name() {
@@ -649,20 +726,49 @@ parser! {
       InitialValueAssignmentKind::LateResolvedType(i)
     }
     rule string_type_name() -> TypeName = type_name()
-    rule string_type_declaration() -> StringDeclaration = type_name:string_type_name() _ tok(TokenType::Colon) _ width:(tok(TokenType::String) { StringType::String } / tok(TokenType::WString) { StringType::WString }) _ tok(TokenType::LeftBracket) _ length:integer() _ tok(TokenType::RightBracket) _ init:(tok(TokenType::Assignment) _ str:character_string() {str})? {
+
+    // Reference types (IEC 61131-3 third edition)
+    rule reference_type_declaration() -> ReferenceDeclaration = type_name:type_name() _ tok(TokenType::Colon) _ spec_init:ref_spec_init() {
+      ReferenceDeclaration {
+        type_name,
+        spec_init,
+        attributes: vec![],
+      }
+    }
+    rule ref_spec_init() -> ReferenceInitialValueAssignment = tok(TokenType::RefTo) _ referenced_type_name:non_generic_type_name() _ initial_value:(tok(TokenType::Assignment) _ v:ref_value() { v })? {
+      ReferenceInitialValueAssignment {
+        referenced_type_name,
+        initial_value,
+      }
+    }
+    rule ref_value() -> ExprKind =
+      t:tok(TokenType::Null) { ExprKind::Null(NullLiteral { span: t.span.clone() }) }
+      / start:tok(TokenType::Ref) _ tok(TokenType::LeftParen) _ variable:symbolic_variable() _ end:tok(TokenType::RightParen) {
+        ExprKind::Ref(Box::new(RefExpr {
+          variable,
+          span: SourceSpan::join(&start.span, &end.span),
+        }))
+      }
+    // Lengths are usually integer literals but may be constant expressions such as MAX_NAME+1
+    rule string_length() -> StringLengthKind =
+      i:integer() &(_ (tok(TokenType::RightBracket) / tok(TokenType::RightParen))) { StringLengthKind::Integer(i) }
+      / e:expression() { StringLengthKind::Expression(Box::new(e)) }
+    rule string_type_declaration() -> StringDeclaration = type_name:string_type_name() _ tok(TokenType::Colon) _ width:(tok(TokenType::String) { StringType::String } / tok(TokenType::WString) { StringType::WString }) _ tok(TokenType::LeftBracket) _ length:string_length() _ tok(TokenType::RightBracket) _ init:(tok(TokenType::Assignment) _ str:character_string() {str})? {
       StringDeclaration {
         type_name,
         length,
         width,
         init: init.map(|v| v.into_iter().collect()),
+        attributes: vec![],
       }
     }
-    rule string_type_declaration__parenthesis() -> StringDeclaration = type_name:string_type_name() _ tok(TokenType::Colon) _ width:(tok(TokenType::String) { StringType::String } / tok(TokenType::WString) { StringType::WString }) _ tok(TokenType::LeftParen) _ length:integer() _ tok(TokenType::RightParen) _ init:(tok(TokenType::Assignment) _ str:character_string() {str})? {
+    rule string_type_declaration__parenthesis() -> StringDeclaration = type_name:string_type_name() _ tok(TokenType::Colon) _ width:(tok(TokenType::String) { StringType::String } / tok(TokenType::WString) { StringType::WString }) _ tok(TokenType::LeftParen) _ length:string_length() _ tok(TokenType::RightParen) _ init:(tok(TokenType::Assignment) _ str:character_string() {str})? {
       StringDeclaration {
         type_name,
         length,
         width,
         init: init.map(|v| v.into_iter().collect()),
+        attributes: vec![],
       }
     }
 
@@ -673,9 +779,8 @@ parser! {
     //rule symbolic_variable() -> SymbolicVariableKind =
     //  multi_element_variable()
     //  / name:variable_name() { SymbolicVariableKind::Named(NamedVariable{name}) }
-    rule symbolic_variable() -> SymbolicVariableKind = name:variable_identifier() elements:(tok(TokenType::Period) id:identifier() { Element::StructSelector(id) } / sub:subscript_list() {Element::ArraySelector(sub)})* {
-      // Start by assuming that the top is just a named variable
-      let mut head = SymbolicVariableKind::Named(NamedVariable { name });
+    rule symbolic_variable() -> SymbolicVariableKind = head:symbolic_variable_head() elements:(tok(TokenType::Period) id:field_selector() { Element::StructSelector(id) } / sub:subscript_list() {Element::ArraySelector(sub)} / c:tok(TokenType::Caret) { Element::Deref(c.span.clone()) })* {
+      let mut head = head;
 
       // Then consume additional items to
       for elem in elements {
@@ -694,11 +799,23 @@ parser! {
                 });
               head = cur;
             },
+            Element::Deref(caret) => {
+              let span = SourceSpan::join(&head.span(), &caret);
+              let cur = SymbolicVariableKind::Deref(DerefVariable{
+                  reference: Box::new(head),
+                  span,
+                });
+              head = cur;
+            },
         }
       }
 
       head
     }
+    rule symbolic_variable_head() -> SymbolicVariableKind =
+      t:tok(TokenType::This) { SymbolicVariableKind::This(ThisVariable { span: t.span.clone() }) }
+      / t:tok(TokenType::Super) { SymbolicVariableKind::Super(SuperVariable { span: t.span.clone() }) }
+      / name:variable_identifier() { SymbolicVariableKind::Named(NamedVariable { name }) }
     rule variable_name() -> Id = variable_identifier()
 
     // B.1.4.1 Directly represented variables
@@ -728,7 +845,7 @@ parser! {
     rule subscript() -> ExprKind = expression()
     rule structured_variable() -> (SymbolicVariableKind, Id) = r:record_variable() tok(TokenType::Period) f:field_selector() { (r, f) }
     rule record_variable() -> SymbolicVariableKind = symbolic_variable()
-    rule field_selector() -> Id = identifier()
+    rule field_selector() -> Id = variable_identifier()
 
     // B.1.4.3 Declarations and initialization
     rule input_declarations() -> Vec<VarDeclarations> = tok(TokenType::VarInput) _ qualifier:(tok(TokenType::Retain) {DeclarationQualifier::Retain} / tok(TokenType::NonRetain) {DeclarationQualifier::NonRetain})? _ declarations:semisep(<input_declaration()>) _ tok(TokenType::EndVar) {
@@ -748,7 +865,7 @@ parser! {
     // We have to first handle the special case of enumeration or fb_name without an initializer
     // because these share the same syntax. We only know the type after trying to resolve the
     // type name.
-    rule var_init_decl() -> Vec<UntypedVarDecl> = structured_var_init_decl__without_ambiguous() / string_var_declaration() / array_var_init_decl() /  fb_name_decl() / string_var_declaration() / var1_init_decl__with_ambiguous_struct()
+    rule var_init_decl() -> Vec<UntypedVarDecl> = ref_var_init_decl() / structured_var_init_decl__without_ambiguous() / string_var_declaration() / array_var_init_decl() /  fb_name_decl() / string_var_declaration() / var1_init_decl__with_ambiguous_struct()
     rule var1_init_decl__with_ambiguous_struct() -> Vec<UntypedVarDecl> = names:var1_list() _ tok(TokenType::Colon) _ init:(a:simple_or_enumerated_or_subrange_ambiguous_struct_spec_init()) {
       // Each of the names variables has is initialized in the same way. Here we flatten initialization
       names.into_iter().map(|name| {
@@ -759,6 +876,14 @@ parser! {
       }).collect()
     }
 
+    rule ref_var_init_decl() -> Vec<UntypedVarDecl> = names:var1_list() _ tok(TokenType::Colon) _ init:ref_spec_init() {
+      names.into_iter().map(|name| {
+        UntypedVarDecl {
+          name,
+          initializer: InitialValueAssignmentKind::Reference(init.clone()),
+        }
+      }).collect()
+    }
     rule var1_list() -> Vec<Id> = names:variable_name() ++ (_ tok(TokenType::Comma) _) { names }
     rule array_var_init_decl() -> Vec<UntypedVarDecl> = names:var1_list() _ tok(TokenType::Colon) _ init:array_spec_init() {
       names.into_iter().map(|name| {
@@ -802,7 +927,7 @@ parser! {
       VarDeclarations::flat_map(declarations, VariableType::InOut,  None)
     }
     rule var_declaration() -> Vec<UntypedVarDecl> = temp_var_decl() / fb_name_decl()
-    rule temp_var_decl() -> Vec<UntypedVarDecl> = var1_declaration() / array_var_declaration() / structured_var_declaration() / string_var_declaration()
+    rule temp_var_decl() -> Vec<UntypedVarDecl> = ref_var_init_decl() / var1_declaration() / array_var_declaration() / structured_var_declaration() / string_var_declaration()
     rule var1_declaration() -> Vec<UntypedVarDecl> = names:var1_list() _ tok(TokenType::Colon) _ init:(spec:subrange_specification__with_range() {InitialValueAssignmentKind::Subrange(spec)} / values:enumerated_specification__only_values()  {InitialValueAssignmentKind::EnumeratedValues(EnumeratedValuesInitializer{ values, initial_value: None})} / spec:simple_specification() { InitialValueAssignmentKind::LateResolvedType(spec)} ) {
       // TODO this could eventually cause duplicated definitions because
       // multiple variables have the same type declaration
@@ -824,7 +949,11 @@ parser! {
     }
     rule structured_var_declaration() -> Vec<UntypedVarDecl> = names:var1_list() _ tok(TokenType::Colon) _ name:structure_type_name() {
       names.iter().map(|identifier| {
-        let init = StructureInitializationDeclaration { type_name: name.clone(), elements_init: vec![] };
+        let init = StructureInitializationDeclaration {
+          type_name: name.clone(),
+          elements_init: vec![],
+          attributes: vec![],
+        };
         UntypedVarDecl {
           name: identifier.clone(),
           initializer: InitialValueAssignmentKind::Structure(init),
@@ -850,11 +979,14 @@ parser! {
         var_type: VariableType::Var,
         qualifier: DeclarationQualifier::Unspecified,
         initializer,
+        attributes: vec![],
+        keyword_span: SourceSpan::default(),
       }
     }
     // We use the same type as in other places for VarInit, but the external always omits the initializer
-    rule external_var_declarations() -> VarDeclarations = tok(TokenType::VarExternal) _ qualifier:(tok(TokenType::Constant) {DeclarationQualifier::Constant})? _ declarations:semisep(<external_declaration()>) _ tok(TokenType::EndVar) {
+    rule external_var_declarations() -> VarDeclarations = keyword:tok(TokenType::VarExternal) _ qualifier:(tok(TokenType::Constant) {DeclarationQualifier::Constant})? _ declarations:semisep(<external_declaration()>) _ tok(TokenType::EndVar) {
       let qualifier = qualifier.unwrap_or(DeclarationQualifier::Unspecified);
+      let declarations = VarDeclarations::with_keyword_span(declarations, &keyword.span);
       VarDeclarations::External(VarDeclarations::map(declarations, &qualifier))
     }
     // TODO external_declaration_spec needs subrange_specification, array_specification(), structure_type_name and others
@@ -870,17 +1002,20 @@ parser! {
         var_type: VariableType::External,
         qualifier: DeclarationQualifier::Unspecified,
         initializer: spec,
+        attributes: vec![],
+        keyword_span: SourceSpan::default(),
       }
     }
     rule global_var_name() -> Id = i:identifier() { i }
     rule global_var_declarations__qualifier() -> DeclarationQualifier = tok(TokenType::Constant) { DeclarationQualifier::Constant } / tok(TokenType::Retain) { DeclarationQualifier::Retain }
-    pub rule global_var_declarations() -> Vec<VarDecl> = tok(TokenType::VarGlobal) _ qualifier:global_var_declarations__qualifier()? _ declarations:semisep(<global_var_decl()>) _ tok(TokenType::EndVar) {
+    pub rule global_var_declarations() -> Vec<VarDecl> = keyword:tok(TokenType::VarGlobal) _ qualifier:global_var_declarations__qualifier()? _ declarations:semisep(<global_var_decl()>) _ tok(TokenType::EndVar) {
       // TODO set the options - this is pretty similar to VarInit - maybe it should be the same
       let declarations = declarations.into_iter().flatten();
       declarations.into_iter().map(|declaration| {
         let qualifier = qualifier.clone().unwrap_or(DeclarationQualifier::Unspecified);
         let mut declaration = declaration;
         declaration.qualifier = qualifier;
+        declaration.keyword_span = keyword.span.clone();
         declaration
       }).collect()
     }
@@ -894,6 +1029,8 @@ parser! {
           qualifier: DeclarationQualifier::Unspecified,
           // TODO this is clearly wrong
           initializer: init,
+          attributes: vec![],
+          keyword_span: SourceSpan::default(),
         }
       }).collect()
      }
@@ -917,7 +1054,7 @@ parser! {
         }
       }).collect()
     }
-    rule single_byte_string_spec() -> StringInitializer = start:tok(TokenType::String) _ length:(tok(TokenType::LeftBracket) _ i:integer() _ tok(TokenType::RightBracket) {i})? _ initial_value:(tok(TokenType::Assignment) _ v:single_byte_character_string() {v})? {
+    rule single_byte_string_spec() -> StringInitializer = start:tok(TokenType::String) _ length:(tok(TokenType::LeftBracket) _ l:string_length() _ tok(TokenType::RightBracket) {l})? _ initial_value:(tok(TokenType::Assignment) _ v:single_byte_character_string() {v})? {
       StringInitializer {
         length,
         width: StringType::String,
@@ -933,7 +1070,7 @@ parser! {
         }
       }).collect()
     }
-    rule double_byte_string_spec() -> StringInitializer = start:tok(TokenType::WString) _ length:(tok(TokenType::LeftBracket) _ i:integer() _ tok(TokenType::RightBracket) {i})? _ initial_value:(tok(TokenType::Assignment) _ v:double_byte_character_string() {v})? {
+    rule double_byte_string_spec() -> StringInitializer = start:tok(TokenType::WString) _ length:(tok(TokenType::LeftBracket) _ l:string_length() _ tok(TokenType::RightBracket) {l})? _ initial_value:(tok(TokenType::Assignment) _ v:double_byte_character_string() {v})? {
       StringInitializer {
         length,
         width: StringType::WString,
@@ -971,8 +1108,8 @@ parser! {
       sr:subrange_specification__with_range() { VariableSpecificationKind::Subrange(sr) }
       / e:enumerated_specification() { VariableSpecificationKind::Enumerated(e) }
       / a:array_specification() { VariableSpecificationKind::Array(a) }
-      / tok:tok(TokenType::String) length:(_ tok(TokenType::LeftBracket) _ l:integer() tok(TokenType::RightBracket) { l })? { VariableSpecificationKind::String(StringSpecification{ width: StringType::String, length, keyword_span: tok.span.clone(), }) }
-      / tok:tok(TokenType::WString) length:(_ tok(TokenType::LeftBracket) _ l:integer() tok(TokenType::RightBracket) { l })? { VariableSpecificationKind::String(StringSpecification{ width: StringType::WString, length, keyword_span: tok.span.clone(), }) }
+      / tok:tok(TokenType::String) length:(_ tok(TokenType::LeftBracket) _ l:string_length() _ tok(TokenType::RightBracket) { l })? { VariableSpecificationKind::String(StringSpecification{ width: StringType::String, length, keyword_span: tok.span.clone(), }) }
+      / tok:tok(TokenType::WString) length:(_ tok(TokenType::LeftBracket) _ l:string_length() _ tok(TokenType::RightBracket) { l })? { VariableSpecificationKind::String(StringSpecification{ width: StringType::WString, length, keyword_span: tok.span.clone(), }) }
       / et:elementary_type_name() { VariableSpecificationKind::Simple(et.into()) }
       / id:type_name() { VariableSpecificationKind::Ambiguous(id) }
 
@@ -980,7 +1117,7 @@ parser! {
     rule function_name() -> Id = standard_function_name() / derived_function_name()
     rule standard_function_name() -> Id = identifier()
     rule derived_function_name() -> Id = identifier()
-    rule function_declaration() -> FunctionDeclaration = tok(TokenType::Function) _  name:derived_function_name() _ tok(TokenType::Colon) _ rt:(et:elementary_type_name() { et.into() } / dt:derived_type_name() { dt }) _ var_decls:(io:io_var_declarations() / func:function_var_decls() { vec![ func ]}) ** _ _ body:function_body() _ tok(TokenType::EndFunction) {
+    rule function_declaration() -> FunctionDeclaration = tok(TokenType::Function) _  name:derived_function_name() _ tok(TokenType::Colon) _ rt:(elementary_type_specification() / derived_type_name()) _ var_decls:(io:io_var_declarations() / func:function_var_decls() { vec![ func ]}) ** _ _ body:function_body() _ tok(TokenType::EndFunction) {
       let var_decls = VarDeclarations::flatten(var_decls);
       let (variables, remainder) = VarDeclarations::drain_var_decl(var_decls);
       let (edge_variables, remainder) = VarDeclarations::drain_edge_decl(remainder);
@@ -990,14 +1127,14 @@ parser! {
         variables,
         edge_variables,
         body,
+        attributes: vec![],
       }
     }
     rule io_var_declarations() -> Vec<VarDeclarations> = input_declarations() / o:output_declarations() { vec![VarDeclarations::Outputs(o)] } / io:input_output_declarations() { vec![VarDeclarations::Inouts(io)] }
     rule function_var_decls() -> VarDeclarations = tok(TokenType::Var) _ qualifier:(tok(TokenType::Constant) {DeclarationQualifier::Constant})? _ vars:semisep_oneplus(<var2_init_decl()>) _ tok(TokenType::EndVar) {
       VarDeclarations::Var(VarDeclarations::flat_map(vars, VariableType::Var, qualifier))
     }
-    // TODO add instruction_list
-    rule function_body() -> Vec<StmtKind> = statement_list()
+    rule function_body() -> FunctionBlockBodyKind = statements:statement_list() { FunctionBlockBodyKind::stmts(statements) } / il:instruction_list() { FunctionBlockBodyKind::il(il) }
     // TODO add many types here
     rule var2_init_decl() -> Vec<UntypedVarDecl> = var1_init_decl__with_ambiguous_struct()
 
@@ -1006,16 +1143,23 @@ parser! {
     // but we don't need that distinction here.
     rule function_block_type_name() -> TypeName = type_name()
     rule derived_function_block_name() -> TypeName = type_name()
-    rule function_block_declaration() -> FunctionBlockDeclaration = start:tok(TokenType::FunctionBlock) _ name:derived_function_block_name() _ decls:(io:io_var_declarations() { io } / other:other_var_declarations() { vec![other] }) ** _ _ body:function_block_body() _ end:tok(TokenType::EndFunctionBlock) {
+    rule function_block_declaration() -> FunctionBlockDeclaration = start:tok(TokenType::FunctionBlock) _ modifier:(m:pou_modifier() _ { m })? name:derived_function_block_name() _ extends:(tok(TokenType::Extends) _ base:derived_function_block_name() _ { base })? implements:(tok(TokenType::Implements) _ names:interface_name() ++ (_ tok(TokenType::Comma) _) _ { names })? decls:(io:io_var_declarations() { io } / other:other_var_declarations() { vec![other] }) ** _ _ leading:pou_member() ** _ _ body:function_block_body() _ trailing:pou_member() ** _ _ end:tok(TokenType::EndFunctionBlock) {
       let decls = VarDeclarations::flatten(decls);
       let (variables, remainder) = VarDeclarations::drain_var_decl(decls);
       let (edge_variables, _) = VarDeclarations::drain_edge_decl(remainder);
+      let (methods, properties) = split_pou_members(leading.into_iter().chain(trailing).collect());
       FunctionBlockDeclaration {
         name,
+        modifier,
+        extends,
+        implements: implements.unwrap_or_default(),
         variables,
         edge_variables,
+        methods,
+        properties,
         body,
         span: SourceSpan::join(&start.span, &end.span),
+        attributes: vec![],
       }
     }
     // TODO temp_var_decls
@@ -1028,8 +1172,67 @@ parser! {
       let qualifier = Option::Some(DeclarationQualifier::NonRetain);
       VarDeclarations::Var(VarDeclarations::flat_map(declarations, VariableType::Var, qualifier))
     }
-    // TODO add instruction_list
-    rule function_block_body() -> FunctionBlockBodyKind = networks:sequential_function_chart() { FunctionBlockBodyKind::sfc(networks) } / statements:statement_list() { FunctionBlockBodyKind::stmts(statements) } / _ { FunctionBlockBodyKind::empty( )}
+    rule function_block_body() -> FunctionBlockBodyKind = networks:sequential_function_chart() { FunctionBlockBodyKind::sfc(networks) } / statements:statement_list() { FunctionBlockBodyKind::stmts(statements) } / il:instruction_list() { FunctionBlockBodyKind::il(il) } / _ { FunctionBlockBodyKind::empty( )}
+
+    // Object oriented extensions (IEC 61131-3 third edition). These do not
+    // have a production rule in the second edition.
+    rule interface_name() -> TypeName = type_name()
+    rule method_name() -> Id = identifier()
+    rule pou_member() -> PouMemberKind = m:method_declaration() { PouMemberKind::Method(m) } / p:property_declaration() { PouMemberKind::Property(p) }
+    rule access_specifier() -> AccessSpecifier =
+      id_eq_ignore_case("PUBLIC") { AccessSpecifier::Public }
+      / id_eq_ignore_case("PROTECTED") { AccessSpecifier::Protected }
+      / id_eq_ignore_case("PRIVATE") { AccessSpecifier::Private }
+      / id_eq_ignore_case("INTERNAL") { AccessSpecifier::Internal }
+    rule pou_modifier() -> PouModifier =
+      id_eq_ignore_case("ABSTRACT") { PouModifier::Abstract }
+      / id_eq_ignore_case("FINAL") { PouModifier::Final }
+      / id_eq_ignore_case("OVERRIDE") { PouModifier::Override }
+    rule method_declaration() -> MethodDeclaration = start:tok(TokenType::Method) _ access:(a:access_specifier() _ { a })? modifier:(m:pou_modifier() _ { m })? name:method_name() _ return_type:(tok(TokenType::Colon) _ rt:(elementary_type_specification() / derived_type_name()) { rt })? _ var_decls:(io:io_var_declarations() / func:function_var_decls() { vec![ func ]}) ** _ _ body:(function_body() / _ { FunctionBlockBodyKind::empty() }) _ end:tok(TokenType::EndMethod) {
+      let var_decls = VarDeclarations::flatten(var_decls);
+      let (variables, _) = VarDeclarations::drain_var_decl(var_decls);
+      MethodDeclaration {
+        name,
+        access,
+        modifier,
+        return_type,
+        variables,
+        body,
+        span: SourceSpan::join(&start.span, &end.span),
+        attributes: vec![],
+      }
+    }
+    // The bodies of property accessors are restricted to statements because
+    // the accessor keywords are not reserved.
+    rule property_declaration() -> PropertyDeclaration = start:tok(TokenType::Property) _ access:(a:access_specifier() _ { a })? name:identifier() _ tok(TokenType::Colon) _ property_type:(et:elementary_type_name() { et.into() } / dt:derived_type_name() { dt }) _ getter:(id_eq_ignore_case("GET") _ a:property_accessor() _ id_eq_ignore_case("END_GET") { a })? _ setter:(id_eq_ignore_case("SET") _ a:property_accessor() _ id_eq_ignore_case("END_SET") { a })? _ end:tok(TokenType::EndProperty) {
+      PropertyDeclaration {
+        name,
+        access,
+        property_type,
+        getter,
+        setter,
+        span: SourceSpan::join(&start.span, &end.span),
+        attributes: vec![],
+      }
+    }
+    rule property_accessor() -> PropertyAccessor = var_decls:function_var_decls() ** _ _ body:statement_list()? {
+      let (variables, _) = VarDeclarations::drain_var_decl(var_decls);
+      PropertyAccessor {
+        variables,
+        body: body.map(FunctionBlockBodyKind::stmts).unwrap_or(FunctionBlockBodyKind::empty()),
+      }
+    }
+    rule interface_declaration() -> InterfaceDeclaration = start:tok(TokenType::Interface) _ name:interface_name() _ extends:(tok(TokenType::Extends) _ names:interface_name() ++ (_ tok(TokenType::Comma) _) _ { names })? members:pou_member() ** _ _ end:tok(TokenType::EndInterface) {
+      let (methods, properties) = split_pou_members(members);
+      InterfaceDeclaration {
+        name,
+        extends: extends.unwrap_or_default(),
+        methods,
+        properties,
+        span: SourceSpan::join(&start.span, &end.span),
+        attributes: vec![],
+      }
+    }
 
     // B.1.5.3 Program declaration
     rule program_type_name() -> Id = identifier()
@@ -1043,6 +1246,7 @@ parser! {
         variables,
         access_variables,
         body,
+        attributes: vec![],
       }
     }
     // TODO
@@ -1302,8 +1506,160 @@ parser! {
       })
     }
 
-    // B 2.1 Instruction List
-    // TODO this entire section
+    // B.2.1 Instructions and operands
+    //
+    // Instruction lists are line oriented, so within an instruction only
+    // whitespace and comments that do not end the line separate tokens.
+    rule il_ws() = (tok(TokenType::Whitespace) {} / tok(TokenType::Pragma) {} / t:tok(TokenType::Comment) {? if t.text.ends_with('\n') { Err("comment") } else { Ok(()) } })*
+    rule eol() -> () = tok(TokenType::Newline) {} / t:tok(TokenType::Comment) {? if t.text.ends_with('\n') { Ok(()) } else { Err("end of line") } }
+    rule il_eols() = eol() (il_ws() eol())*
+    rule il_line_token() -> &'input Token = [t if t.token_type != TokenType::Newline && !(t.token_type == TokenType::Comment && t.text.ends_with('\n'))]
+    rule instruction_list() -> Vec<IlInstruction> = il_instruction()+
+    rule il_instruction() -> IlInstruction = il_ws() line:&(il_line_token()*) label:(l:label() il_ws() tok(TokenType::Colon) il_ws() { l })? kind:il_instruction_kind()? il_ws() il_eols() {?
+      if label.is_none() && kind.is_none() {
+        return Err("instruction");
+      }
+      let tokens: Vec<&&Token> = line.iter().filter(|t| !matches!(t.token_type, TokenType::Whitespace | TokenType::Comment | TokenType::Pragma)).collect();
+      let span = match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => SourceSpan::join(&first.span, &last.span),
+        _ => SourceSpan::default(),
+      };
+      Ok(IlInstruction {
+        label,
+        kind: kind.unwrap_or(IlInstructionKind::Nil),
+        span,
+      })
+    }
+    rule il_instruction_kind() -> IlInstructionKind =
+      jump:il_jump_operation() { IlInstructionKind::Jump(jump) }
+      / call:il_fb_call() { IlInstructionKind::FbCall(call) }
+      / ret:il_return_operator() { IlInstructionKind::Return(IlReturnOperation { operator: ret }) }
+      / simple:il_simple_instruction_kind() { simple.into() }
+    rule label() -> Id = identifier()
+    rule il_simple_operation() -> IlSimpleInstructionKind = operator:il_simple_operator() operand:(il_ws() o:il_operand() { o })? {
+      IlSimpleInstructionKind::SimpleOperation(IlSimpleOperation {
+        operator,
+        operand,
+      })
+    }
+    / name:function_name() operands:(il_ws() o:il_operand_list() { o })? {
+      IlSimpleInstructionKind::FunctionCall(IlFunctionCall {
+        name,
+        operands: operands.unwrap_or_default(),
+      })
+    }
+    rule il_expression() -> IlExpression = operator:il_expr_operator() il_ws() tok(TokenType::LeftParen) il_ws() operand:il_operand()? il_ws() body:(il_eols() b:simple_instr_list()? { b.unwrap_or_default() })? il_ws() tok(TokenType::RightParen) {
+      IlExpression {
+        operator,
+        operand,
+        body: body.unwrap_or_default(),
+      }
+    }
+    rule il_jump_operation() -> IlJumpOperation = operator:il_jump_operator() il_ws() target:label() {
+      IlJumpOperation {
+        operator,
+        target,
+      }
+    }
+    // Permits formal parameters on the same line as the call in addition to
+    // the strict form that requires the parameters to begin on a new line.
+    rule il_fb_call() -> IlFbCall = operator:il_call_operator() il_ws() name:fb_name() params:(il_ws() tok(TokenType::LeftParen) il_ws() il_eols()? il_ws() p:(l:il_param_list() { l } / l:il_operand_list() { l.into_iter().map(IlParamAssignmentKind::Positional).collect() })? il_ws() tok(TokenType::RightParen) { p.unwrap_or_default() })? {
+      IlFbCall {
+        operator,
+        name,
+        params: params.unwrap_or_default(),
+      }
+    }
+    rule il_formal_funct_call() -> IlFormalFunctionCall = name:function_name() il_ws() tok(TokenType::LeftParen) il_ws() il_eols()? il_ws() params:il_param_list()? il_ws() tok(TokenType::RightParen) {
+      IlFormalFunctionCall {
+        name,
+        params: params.unwrap_or_default(),
+      }
+    }
+    rule il_operand() -> IlOperand =
+      c:constant() { IlOperand::Constant(c) }
+      / ev:enumerated_value__with_type() { IlOperand::EnumeratedValue(ev) }
+      / v:variable() { IlOperand::Variable(v) }
+    rule enumerated_value__with_type() -> EnumeratedValue = type_name:enumerated_type_name() tok(TokenType::Hash) value:identifier() { EnumeratedValue { type_name: Some(type_name), value } }
+    rule il_operand_list() -> Vec<IlOperand> = il_operand() ++ (il_ws() tok(TokenType::Comma) il_ws())
+    rule simple_instr_list() -> Vec<IlSimpleInstructionKind> = il_simple_instruction()+
+    rule il_simple_instruction() -> IlSimpleInstructionKind = il_ws() i:il_simple_instruction_kind() il_ws() il_eols() { i }
+    rule il_simple_instruction_kind() -> IlSimpleInstructionKind =
+      expr:il_expression() { IlSimpleInstructionKind::Expression(expr) }
+      / call:il_formal_funct_call() { IlSimpleInstructionKind::FormalFunctionCall(call) }
+      / il_simple_operation()
+    rule il_param_list() -> Vec<IlParamAssignmentKind> = params:il_param_instruction() ++ (il_ws() tok(TokenType::Comma) il_ws() il_eols()? il_ws()) il_ws() il_eols()? { params }
+    rule il_param_instruction() -> IlParamAssignmentKind =
+      out:il_param_out_assignment() { IlParamAssignmentKind::Output(out) }
+      / input:il_param_assignment() { IlParamAssignmentKind::Input(input) }
+    rule il_param_assignment() -> IlInputParamAssignment = name:variable_name() il_ws() tok(TokenType::Assignment) il_ws() value:(o:il_operand() { IlParamValueKind::Operand(o) } / tok(TokenType::LeftParen) il_ws() il_eols() body:simple_instr_list() il_ws() tok(TokenType::RightParen) { IlParamValueKind::Instructions(IlSimpleInstructionList { body }) }) {
+      IlInputParamAssignment {
+        name,
+        value,
+      }
+    }
+    rule il_param_out_assignment() -> IlOutputParamAssignment = not:(il_not() il_ws())? src:variable_name() il_ws() tok(TokenType::RightArrow) il_ws() tgt:variable() {
+      IlOutputParamAssignment {
+        not: not.is_some(),
+        src,
+        tgt,
+      }
+    }
+
+    // B.2.2 Operators
+    //
+    // The operators (other than the keywords such as AND and NOT) are
+    // identifiers so that the names remain valid variable names in other
+    // languages. The operators match the identifier without considering case.
+    rule il_id(val: &str) -> &'input Token = [t if t.token_type == TokenType::Identifier && t.text.eq_ignore_ascii_case(val)]
+    rule il_not() -> () = tok(TokenType::Not) {} / il_id("NOT") {}
+    rule il_simple_operator() -> IlSimpleOperator =
+      il_id("LD") { IlSimpleOperator::Ld }
+      / il_id("LDN") { IlSimpleOperator::Ldn }
+      / il_id("ST") { IlSimpleOperator::St }
+      / il_id("STN") { IlSimpleOperator::Stn }
+      / il_not() { IlSimpleOperator::Not }
+      / il_id("S") { IlSimpleOperator::S }
+      / il_id("R") { IlSimpleOperator::R }
+      / il_id("S1") { IlSimpleOperator::S1 }
+      / il_id("R1") { IlSimpleOperator::R1 }
+      / il_id("CLK") { IlSimpleOperator::Clk }
+      / il_id("CU") { IlSimpleOperator::Cu }
+      / il_id("CD") { IlSimpleOperator::Cd }
+      / il_id("PV") { IlSimpleOperator::Pv }
+      / il_id("IN") { IlSimpleOperator::In }
+      / il_id("PT") { IlSimpleOperator::Pt }
+      / op:il_expr_operator() { IlSimpleOperator::Expr(op) }
+    rule il_expr_operator() -> IlExprOperator =
+      tok(TokenType::And) { IlExprOperator::And }
+      / tok(TokenType::Or) { IlExprOperator::Or }
+      / tok(TokenType::Xor) { IlExprOperator::Xor }
+      / il_id("ANDN") { IlExprOperator::Andn }
+      / il_id("ORN") { IlExprOperator::Orn }
+      / il_id("XORN") { IlExprOperator::Xorn }
+      / il_id("ADD") { IlExprOperator::Add }
+      / il_id("SUB") { IlExprOperator::Sub }
+      / il_id("MUL") { IlExprOperator::Mul }
+      / il_id("DIV") { IlExprOperator::Div }
+      / (tok(TokenType::Mod) {} / il_id("MOD") {}) { IlExprOperator::Mod }
+      / il_id("GT") { IlExprOperator::Gt }
+      / il_id("GE") { IlExprOperator::Ge }
+      / il_id("EQ") { IlExprOperator::Eq }
+      / il_id("LT") { IlExprOperator::Lt }
+      / il_id("LE") { IlExprOperator::Le }
+      / il_id("NE") { IlExprOperator::Ne }
+    rule il_call_operator() -> IlCallOperator =
+      il_id("CAL") { IlCallOperator::Cal }
+      / il_id("CALC") { IlCallOperator::Calc }
+      / il_id("CALCN") { IlCallOperator::Calcn }
+    rule il_return_operator() -> IlReturnOperator =
+      il_id("RET") { IlReturnOperator::Ret }
+      / il_id("RETC") { IlReturnOperator::Retc }
+      / il_id("RETCN") { IlReturnOperator::Retcn }
+    rule il_jump_operator() -> IlJumpOperator =
+      il_id("JMP") { IlJumpOperator::Jmp }
+      / il_id("JMPC") { IlJumpOperator::Jmpc }
+      / il_id("JMPCN") { IlJumpOperator::Jmpcn }
 
     // B.3.1 Expressions
     pub rule expression() -> ExprKind = precedence!{
@@ -1364,9 +1720,13 @@ parser! {
       / function:function_expression() {
           function
         }
-      / id:identifier() _ !(tok(TokenType::LeftParen) / tok(TokenType::LeftBracket) / tok(TokenType::Period)) {
+      / id:identifier() _ !(tok(TokenType::LeftParen) / tok(TokenType::LeftBracket) / tok(TokenType::Period) / tok(TokenType::Caret)) {
         ExprKind::LateBound(LateBound{ value: id })
       }
+      / ref_value()
+      / method:method_call() {
+        ExprKind::MethodCall(Box::new(method))
+      }
       / variable:variable() {
         ExprKind::Variable(variable)
       }
@@ -1384,14 +1744,17 @@ parser! {
     pub rule statement_list() -> Vec<StmtKind> = items:statements_or_empty()+ {
       flatten_statements(items)
     }
-    rule statements_or_empty() -> StatementsOrEmpty = _ tok(TokenType::Semicolon) _ { StatementsOrEmpty::Empty() } / s:semisep(<statement()>) { StatementsOrEmpty::Statements(s)}
-    rule statement() -> StmtKind = assignment_statement() / selection_statement() / iteration_statement() / subprogram_control_statement()
+    rule statements_or_empty() -> StatementsOrEmpty = _ e:error_statement() _ { StatementsOrEmpty::Statements(vec![e]) } / _ tok(TokenType::Semicolon) _ { StatementsOrEmpty::Empty() } / s:semisep(<statement()>) { StatementsOrEmpty::Statements(s)}
+    // Statements do not start where there is an error statement so that a list of
+    // statements ends before the error statement.
+    rule statement() -> StmtKind = !error_statement_start() s:(assignment_statement() / selection_statement() / iteration_statement() / subprogram_control_statement()) { s }
+    rule error_statement_start() = start:position!() recovered_end(recovery.statement_end(start))
 
     // B.3.2.1 Assignment statements
     pub rule assignment_statement() -> StmtKind = var:variable() _ tok(TokenType::Assignment) _ expr:expression() { StmtKind::assignment(var, expr) }
 
     // B.3.2.2 Subprogram control statements
-    rule subprogram_control_statement() -> StmtKind = fb:fb_invocation() { fb } / tok(TokenType::Return) { StmtKind::Return }
+    rule subprogram_control_statement() -> StmtKind = fb:fb_invocation() { fb } / method:method_call() { StmtKind::MethodCall(method) } / tok(TokenType::Return) { StmtKind::Return }
     rule fb_invocation() -> StmtKind = name:fb_name() _ tok(TokenType::LeftParen) _ params:param_assignment() ** (_ tok(TokenType::Comma) _) _ end:tok(TokenType::RightParen) {
       let span = SourceSpan::join(&name.span, &end.span);
       StmtKind::FbCall(FbCall {
@@ -1400,6 +1763,20 @@ parser! {
         position: span,
       })
     }
+    // A method invocation is a call on a field of a variable, for example,
+    // "instance.method()" or "THIS.method()".
+    rule method_call() -> MethodCall = receiver:symbolic_variable() _ tok(TokenType::LeftParen) _ params:param_assignment() ** (_ tok(TokenType::Comma) _) _ end:tok(TokenType::RightParen) {?
+      let position = SourceSpan::join(&receiver.span(), &end.span);
+      match receiver {
+        SymbolicVariableKind::Structured(structured) => Ok(MethodCall {
+          receiver: *structured.record,
+          name: structured.field,
+          params,
+          position,
+        }),
+        _ => Err("method call"),
+      }
+    }
     // TODO this needs much more
     rule param_assignment() -> ParamAssignmentKind = not:(tok(TokenType::Not) {})? _ src:variable_name() _ tok(TokenType::RightArrow) _ tgt:variable() {
       ParamAssignmentKind::Output (
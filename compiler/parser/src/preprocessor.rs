@@ -3,14 +3,27 @@
 //!
 //! The preprocessor:
 //! * removes OSCAT comments
+//! * applies conditional compilation directives
 //!
 //! Comments are replaced by whitespace so that language elements retain their
 //! original position (this means that source locations remain correct even
-//! after comments are removed).
+//! after comments are removed). Conditional compilation directives and the
+//! regions they exclude are replaced in the same way.
 
-pub fn preprocess(source: &str) -> String {
+use dsl::core::{FileId, SourceSpan};
+use dsl::diagnostic::{Diagnostic, Label};
+use ironplc_problems::Problem;
+
+use crate::options::ParseOptions;
+
+pub fn preprocess(
+    source: &str,
+    file_id: &FileId,
+    options: &ParseOptions,
+) -> Result<String, Diagnostic> {
     let source = source.to_string();
-    remove_oscat_comment(source)
+    let source = remove_oscat_comment(source);
+    apply_conditional_directives(source, file_id, options)
 }
 
 /// Removes the OSCAT ranged comment. This is not valid IEC 61131, but there
@@ -44,10 +57,394 @@ pub fn remove_oscat_comment(source: String) -> String {
     source
 }
 
+/// A conditional compilation directive, for example `{IF defined(X)}`.
+///
+/// Conditional compilation is not part of IEC 61131-3, but the directives
+/// have the form of a pragma and are common in other implementations.
+#[derive(Debug, PartialEq)]
+enum Directive {
+    If(Condition),
+    Elsif(Condition),
+    Else,
+    EndIf,
+}
+
+#[derive(Debug, PartialEq)]
+enum Condition {
+    Defined(String),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    fn eval(&self, options: &ParseOptions) -> bool {
+        match self {
+            Condition::Defined(name) => options
+                .defines
+                .iter()
+                .any(|define| define.eq_ignore_ascii_case(name)),
+            Condition::Not(cond) => !cond.eval(options),
+            Condition::And(lhs, rhs) => lhs.eval(options) && rhs.eval(options),
+            Condition::Or(lhs, rhs) => lhs.eval(options) || rhs.eval(options),
+        }
+    }
+}
+
+/// The state of one `{IF}` ... `{END_IF}` block.
+struct Block {
+    /// The span of the `{IF}` directive that opened the block.
+    span: SourceSpan,
+    /// Whether the region containing the block is included.
+    parent_active: bool,
+    /// Whether any branch of the block has been included.
+    taken: bool,
+    /// Whether the current branch is included.
+    active: bool,
+    /// Whether the block has seen the `{ELSE}` directive.
+    has_else: bool,
+}
+
+/// Applies conditional compilation directives by replacing the directives
+/// and the excluded regions with whitespace.
+fn apply_conditional_directives(
+    source: String,
+    file_id: &FileId,
+    options: &ParseOptions,
+) -> Result<String, Diagnostic> {
+    let directives = find_directives(&source, file_id)?;
+    if directives.is_empty() {
+        return Ok(source);
+    }
+
+    let mut blocks: Vec<Block> = vec![];
+    // The regions (start and end byte positions) to replace with whitespace.
+    let mut excluded: Vec<(usize, usize)> = vec![];
+    let mut excluded_start: Option<usize> = None;
+
+    for (span, directive) in directives {
+        let was_active = blocks.last().map(|b| b.active).unwrap_or(true);
+
+        match directive {
+            Directive::If(cond) => {
+                let active = was_active && cond.eval(options);
+                blocks.push(Block {
+                    span: span.clone(),
+                    parent_active: was_active,
+                    taken: active,
+                    active,
+                    has_else: false,
+                });
+            }
+            Directive::Elsif(cond) => {
+                let block = unmatched_if_none(blocks.last_mut(), &span, "ELSIF")?;
+                if block.has_else {
+                    return Err(unmatched(&span, "ELSIF follows ELSE"));
+                }
+                block.active = block.parent_active && !block.taken && cond.eval(options);
+                block.taken |= block.active;
+            }
+            Directive::Else => {
+                let block = unmatched_if_none(blocks.last_mut(), &span, "ELSE")?;
+                if block.has_else {
+                    return Err(unmatched(&span, "ELSE follows ELSE"));
+                }
+                block.active = block.parent_active && !block.taken;
+                block.taken = true;
+                block.has_else = true;
+            }
+            Directive::EndIf => {
+                unmatched_if_none(blocks.last_mut(), &span, "END_IF")?;
+                blocks.pop();
+            }
+        }
+
+        let is_active = blocks.last().map(|b| b.active).unwrap_or(true);
+
+        // The directive is never part of the output
+        excluded_start.get_or_insert(span.start);
+        if is_active {
+            if let Some(start) = excluded_start.take() {
+                excluded.push((start, span.end));
+            }
+        }
+    }
+
+    if let Some(block) = blocks.pop() {
+        return Err(unmatched(&block.span, "IF without END_IF"));
+    }
+
+    let mut output = String::with_capacity(source.len());
+    let mut pos = 0;
+    for (start, end) in excluded {
+        output.push_str(&source[pos..start]);
+        blank(&source[start..end], &mut output);
+        pos = end;
+    }
+    output.push_str(&source[pos..]);
+
+    Ok(output)
+}
+
+/// Writes whitespace that occupies the same positions as the text.
+fn blank(text: &str, output: &mut String) {
+    for c in text.chars() {
+        if c == '\n' || c == '\r' {
+            output.push(c);
+        } else {
+            // Retain the byte positions of multi-byte characters
+            output.push_str(&" ".repeat(c.len_utf8()));
+        }
+    }
+}
+
+/// Returns true if the source has conditional compilation directives,
+/// including directives that are not valid.
+pub fn has_conditional_directives(source: &str) -> bool {
+    find_directives(source, &FileId::default()).map_or(true, |directives| !directives.is_empty())
+}
+
+/// Finds the conditional compilation directives in the source, ignoring
+/// anything within comments and string literals.
+fn find_directives(
+    source: &str,
+    file_id: &FileId,
+) -> Result<Vec<(SourceSpan, Directive)>, Diagnostic> {
+    let mut directives = vec![];
+    let bytes = source.as_bytes();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let rest = &source[pos..];
+        let skip_to = if rest.starts_with("(*") {
+            rest.find("*)").map(|end| pos + end + 2)
+        } else if rest.starts_with("/*") {
+            rest.find("*/").map(|end| pos + end + 2)
+        } else if rest.starts_with("//") {
+            rest.find('\n').map(|end| pos + end)
+        } else if bytes[pos] == b'\'' || bytes[pos] == b'"' {
+            string_end(rest).map(|end| pos + end)
+        } else if bytes[pos] == b'{' {
+            let end = match rest.find('}') {
+                Some(end) => pos + end + 1,
+                None => return Ok(directives),
+            };
+            let span = SourceSpan {
+                start: pos,
+                end,
+                file_id: file_id.clone(),
+            };
+            if let Some(directive) = parse_directive(&source[pos + 1..end - 1], &span)? {
+                directives.push((span, directive));
+            }
+            Some(end)
+        } else {
+            rest.chars().next().map(|c| pos + c.len_utf8())
+        };
+
+        // Unterminated comments and strings are reported by the tokenizer.
+        pos = skip_to.unwrap_or(bytes.len());
+    }
+
+    Ok(directives)
+}
+
+/// Returns the position just past the closing quote of the string literal
+/// at the start of `rest`. A `$` escapes the following character so that
+/// `$'` and `$"` do not end the string.
+fn string_end(rest: &str) -> Option<usize> {
+    let bytes = rest.as_bytes();
+    let quote = bytes[0];
+    let mut pos = 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'$' => pos += 2,
+            b if b == quote => return Some(pos + 1),
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+/// Parses the content of a pragma as a directive. Returns `None` if the
+/// pragma is not a conditional compilation directive.
+fn parse_directive(content: &str, span: &SourceSpan) -> Result<Option<Directive>, Diagnostic> {
+    let mut words = Words::new(content);
+    let keyword = match words.next() {
+        Some(Word::Name(keyword)) => keyword.to_uppercase(),
+        _ => return Ok(None),
+    };
+
+    let directive = match keyword.as_str() {
+        "IF" => Directive::If(parse_condition(&mut words, span)?),
+        "ELSIF" => Directive::Elsif(parse_condition(&mut words, span)?),
+        "ELSE" => Directive::Else,
+        "END_IF" => Directive::EndIf,
+        _ => return Ok(None),
+    };
+
+    match words.next() {
+        None => Ok(Some(directive)),
+        Some(_) => Err(invalid(span)),
+    }
+}
+
+/// Parses a condition, where the condition is
+///
+/// ```text
+/// condition := term { OR term }
+/// term := factor { AND factor }
+/// factor := NOT factor | '(' condition ')' | defined '(' name ')'
+/// ```
+fn parse_condition(words: &mut Words, span: &SourceSpan) -> Result<Condition, Diagnostic> {
+    let mut cond = parse_term(words, span)?;
+    while words.next_if_keyword("OR") {
+        cond = Condition::Or(Box::new(cond), Box::new(parse_term(words, span)?));
+    }
+    Ok(cond)
+}
+
+fn parse_term(words: &mut Words, span: &SourceSpan) -> Result<Condition, Diagnostic> {
+    let mut cond = parse_factor(words, span)?;
+    while words.next_if_keyword("AND") {
+        cond = Condition::And(Box::new(cond), Box::new(parse_factor(words, span)?));
+    }
+    Ok(cond)
+}
+
+fn parse_factor(words: &mut Words, span: &SourceSpan) -> Result<Condition, Diagnostic> {
+    match words.next() {
+        Some(Word::Name(name)) if name.eq_ignore_ascii_case("NOT") => {
+            Ok(Condition::Not(Box::new(parse_factor(words, span)?)))
+        }
+        Some(Word::Name(name)) if name.eq_ignore_ascii_case("defined") => {
+            match (words.next(), words.next(), words.next()) {
+                (Some(Word::Open), Some(Word::Name(define)), Some(Word::Close)) => {
+                    Ok(Condition::Defined(define.to_string()))
+                }
+                _ => Err(invalid(span)),
+            }
+        }
+        Some(Word::Open) => {
+            let cond = parse_condition(words, span)?;
+            match words.next() {
+                Some(Word::Close) => Ok(cond),
+                _ => Err(invalid(span)),
+            }
+        }
+        _ => Err(invalid(span)),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Word<'a> {
+    Name(&'a str),
+    Open,
+    Close,
+    Other,
+}
+
+/// Splits the content of a directive into names and parentheses.
+struct Words<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Words<'a> {
+    fn new(content: &'a str) -> Self {
+        Self { rest: content }
+    }
+
+    fn next_if_keyword(&mut self, keyword: &str) -> bool {
+        let saved = self.rest;
+        match self.next() {
+            Some(Word::Name(name)) if name.eq_ignore_ascii_case(keyword) => true,
+            _ => {
+                self.rest = saved;
+                false
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = Word<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rest = self.rest.trim_start();
+        let first = self.rest.chars().next()?;
+        let len = match first {
+            '(' | ')' => 1,
+            c if c.is_ascii_alphanumeric() || c == '_' => self
+                .rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(self.rest.len()),
+            c => c.len_utf8(),
+        };
+        let (word, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(match first {
+            '(' => Word::Open,
+            ')' => Word::Close,
+            c if c.is_ascii_alphanumeric() || c == '_' => Word::Name(word),
+            _ => Word::Other,
+        })
+    }
+}
+
+fn unmatched_if_none<'a>(
+    block: Option<&'a mut Block>,
+    span: &SourceSpan,
+    directive: &str,
+) -> Result<&'a mut Block, Diagnostic> {
+    block.ok_or_else(|| unmatched(span, &format!("{directive} without IF")))
+}
+
+fn unmatched(span: &SourceSpan, message: &str) -> Diagnostic {
+    Diagnostic::problem(
+        Problem::ConditionalDirectiveUnmatched,
+        Label::span(span.clone(), message),
+    )
+}
+
+fn invalid(span: &SourceSpan) -> Diagnostic {
+    Diagnostic::problem(
+        Problem::ConditionalDirectiveInvalid,
+        Label::span(span.clone(), "Directive"),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn preprocess_with_defines(program: &str, defines: &[&str]) -> Result<String, Diagnostic> {
+        let options = ParseOptions {
+            defines: defines.iter().map(|d| d.to_string()).collect(),
+            ..ParseOptions::default()
+        };
+        preprocess(program, &FileId::default(), &options)
+    }
+
+    #[test]
+    fn has_conditional_directives_when_directive_then_true() {
+        assert!(has_conditional_directives(
+            "{IF defined(X)} x := 1; {END_IF}"
+        ));
+        assert!(!has_conditional_directives("{attribute 'hide'} x := 1;"));
+        assert!(!has_conditional_directives("(* {IF defined(X)} *) x := 1;"));
+    }
+
+    #[test]
+    fn has_conditional_directives_when_escaped_quote_in_string_then_false() {
+        assert!(!has_conditional_directives("s := 'it$'s {IF defined(X)}';"));
+        assert!(!has_conditional_directives(
+            "s := \"say $\"{IF defined(X)}$\"\";"
+        ));
+        assert!(has_conditional_directives(
+            "s := 'a$$'; {IF defined(X)} x := 1; {END_IF}"
+        ));
+    }
+
     #[test]
     fn apply_when_no_comment_then_ok() {
         let program = "
@@ -57,7 +454,7 @@ mod tests {
             END_STRUCT;
         END_TYPE";
 
-        let output = preprocess(program);
+        let output = preprocess(program, &FileId::default(), &ParseOptions::default()).unwrap();
         assert_eq!(program, output.as_str());
     }
 
@@ -79,7 +476,7 @@ TYPE
     (*@KEY@:END_DESCRIPTION*)
 END_TYPE";
 
-        let output = preprocess(program);
+        let output = preprocess(program, &FileId::default(), &ParseOptions::default()).unwrap();
         assert_eq!(expected, output.as_str());
     }
 
@@ -91,7 +488,102 @@ END_TYPE";
             (*@KEY@:DESCRIPTION*)
         END_TYPE";
 
-        let res = preprocess(program);
+        let res = preprocess(program, &FileId::default(), &ParseOptions::default()).unwrap();
         assert!(!res.is_empty());
     }
+
+    #[test]
+    fn apply_when_if_defined_then_keeps_region() {
+        let program = "{IF defined(A)}
+x := 1;
+{END_IF}";
+
+        let expected = format!("{}\nx := 1;\n{}", " ".repeat(15), " ".repeat(8));
+
+        let output = preprocess_with_defines(program, &["A"]).unwrap();
+        assert_eq!(expected, output.as_str());
+    }
+
+    #[test]
+    fn apply_when_if_not_defined_then_blanks_region() {
+        let program = "{IF defined(A)}
+x := 1;
+{END_IF}";
+
+        let expected = format!("{}\n{}\n{}", " ".repeat(15), " ".repeat(7), " ".repeat(8));
+
+        let output = preprocess_with_defines(program, &[]).unwrap();
+        assert_eq!(expected, output.as_str());
+        assert_eq!(program.len(), output.len());
+    }
+
+    #[test]
+    fn apply_when_elsif_and_else_then_keeps_first_matching_branch() {
+        let program = "{IF defined(A)}a{ELSIF defined(B)}b{ELSIF defined(C)}c{ELSE}d{END_IF}";
+
+        let output = preprocess_with_defines(program, &["b", "C"]).unwrap();
+        assert_eq!("b", output.trim());
+
+        let output = preprocess_with_defines(program, &[]).unwrap();
+        assert_eq!("d", output.trim());
+    }
+
+    #[test]
+    fn apply_when_nested_in_excluded_region_then_blanks_region() {
+        let program = "{IF defined(A)}{IF defined(B)}b{ELSE}e{END_IF}{END_IF}";
+
+        let output = preprocess_with_defines(program, &["B"]).unwrap();
+        assert_eq!("", output.trim());
+    }
+
+    #[test]
+    fn apply_when_condition_has_operators_then_evaluates() {
+        let program = "{IF (defined(A) OR defined(B)) AND NOT defined(C)}x{END_IF}";
+
+        let output = preprocess_with_defines(program, &["B"]).unwrap();
+        assert_eq!("x", output.trim());
+
+        let output = preprocess_with_defines(program, &["B", "C"]).unwrap();
+        assert_eq!("", output.trim());
+    }
+
+    #[test]
+    fn apply_when_directive_in_comment_then_ignores_directive() {
+        let program = "(* {IF defined(A)} *) x";
+
+        let output = preprocess_with_defines(program, &[]).unwrap();
+        assert_eq!(program, output.as_str());
+    }
+
+    #[test]
+    fn apply_when_attribute_pragma_then_keeps_pragma() {
+        let program = "{attribute 'hide'} x";
+
+        let output = preprocess_with_defines(program, &[]).unwrap();
+        assert_eq!(program, output.as_str());
+    }
+
+    #[test]
+    fn apply_when_if_without_end_if_then_err() {
+        let program = "{IF defined(A)} x";
+
+        let err = preprocess_with_defines(program, &[]).unwrap_err();
+        assert_eq!("P0006", err.code);
+    }
+
+    #[test]
+    fn apply_when_end_if_without_if_then_err() {
+        let program = "x {END_IF}";
+
+        let err = preprocess_with_defines(program, &[]).unwrap_err();
+        assert_eq!("P0006", err.code);
+    }
+
+    #[test]
+    fn apply_when_invalid_condition_then_err() {
+        let program = "{IF A}x{END_IF}";
+
+        let err = preprocess_with_defines(program, &[]).unwrap_err();
+        assert_eq!("P0007", err.code);
+    }
 }
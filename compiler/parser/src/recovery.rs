@@ -0,0 +1,458 @@
+//! Recovery from syntax errors.
+//!
+//! The parser does not stop at the first syntax error. Instead, when the
+//! parser fails, we find a region of tokens around the error and parse
+//! again, this time skipping the region. The regions resynchronize on:
+//! * statement terminators, so that a statement with an error becomes an
+//!   error statement and the other statements in the body are kept
+//! * `END_*` keywords, so that a library element with an error (outside of
+//!   a body) is skipped and the other library elements are kept
+//!
+//! Each attempt to parse reports one syntax error, so the regions grow until
+//! the parser succeeds. When there is no way to recover (or there are too
+//! many errors), the parser skips the remainder of the library and keeps the
+//! library elements before the error.
+use std::collections::HashMap;
+
+use crate::token::{Token, TokenType};
+
+/// The regions of tokens that the parser skips. Each region is the start
+/// token index and the end token index (exclusive).
+#[derive(Default)]
+pub struct Recovery {
+    statements: HashMap<usize, usize>,
+    elements: HashMap<usize, usize>,
+    last_error: Option<usize>,
+}
+
+impl Recovery {
+    /// Returns the end of the error statement that starts at the position.
+    pub fn statement_end(&self, start: usize) -> Option<usize> {
+        self.statements.get(&start).copied()
+    }
+
+    /// Returns the end of the skipped library element that starts at the
+    /// position.
+    pub fn element_end(&self, start: usize) -> Option<usize> {
+        self.elements.get(&start).copied()
+    }
+
+    /// Adds a region that contains the syntax error at the token index.
+    ///
+    /// Returns `false` if there is no region that would allow parsing to
+    /// make progress.
+    pub fn recover(&mut self, tokens: &[Token], index: usize) -> bool {
+        let repeated = self.last_error == Some(index);
+        self.last_error = Some(index);
+
+        if !repeated && !self.in_statement_region(index) {
+            if let Some((start, end)) = statement_region(tokens, index) {
+                self.statements.insert(start, end);
+                return true;
+            }
+        }
+
+        match element_region(tokens, index) {
+            Some((start, end)) if !self.elements.contains_key(&start) => {
+                // Statements within the element are no longer relevant.
+                self.statements.retain(|pos, _| *pos < start || *pos >= end);
+                self.elements.insert(start, end);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds a region from the library element that contains the syntax
+    /// error at the token index to the end of the tokens so that parsing
+    /// keeps only the library elements before the error.
+    pub fn skip_remainder(&mut self, tokens: &[Token], index: usize) {
+        let start = element_region(tokens, index).map_or(index, |(start, _)| start);
+        self.statements.retain(|pos, _| *pos < start);
+        self.elements.retain(|pos, _| *pos < start);
+        self.elements.insert(start, tokens.len());
+    }
+
+    fn in_statement_region(&self, index: usize) -> bool {
+        self.statements
+            .iter()
+            .any(|(start, end)| *start <= index && index < *end)
+    }
+}
+
+fn is_trivia(tok: &Token) -> bool {
+    matches!(
+        tok.token_type,
+        TokenType::Whitespace | TokenType::Newline | TokenType::Comment | TokenType::Pragma
+    )
+}
+
+/// Returns the region for an error statement that contains the token, or
+/// `None` if the token is not in the body of a program organization unit.
+fn statement_region(tokens: &[Token], index: usize) -> Option<(usize, usize)> {
+    if !in_body(tokens, index) {
+        return None;
+    }
+
+    // The statement starts after the previous terminator or after the
+    // keyword that starts a list of statements.
+    let boundary = tokens[..index].iter().rposition(|tok| {
+        matches!(
+            tok.token_type,
+            TokenType::Semicolon
+                | TokenType::Then
+                | TokenType::Else
+                | TokenType::Do
+                | TokenType::Repeat
+                | TokenType::Colon
+                | TokenType::EndVar
+        )
+    })?;
+    let start = (boundary + 1..=index).find(|pos| !is_trivia(&tokens[*pos]))?;
+    if is_statement_end(&tokens[start].token_type) {
+        return None;
+    }
+
+    // The statement ends at the terminator following the error, or before
+    // the keyword that ends the list of statements. Statements that contain
+    // other statements end at the terminator after the matching end keyword.
+    let mut depth = 0;
+    for (pos, tok) in tokens.iter().enumerate().skip(start) {
+        match tok.token_type {
+            TokenType::If
+            | TokenType::Case
+            | TokenType::For
+            | TokenType::While
+            | TokenType::Repeat => depth += 1,
+            TokenType::Semicolon if depth == 0 && pos >= index => return Some((start, pos + 1)),
+            ref token_type if is_statement_end(token_type) => {
+                if depth == 0 {
+                    return (pos > start).then_some((start, pos));
+                }
+                if is_block_end(token_type) {
+                    depth -= 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns true if the token is after the variable declarations of a
+/// program organization unit.
+fn in_body(tokens: &[Token], index: usize) -> bool {
+    tokens[..index]
+        .iter()
+        .rev()
+        .find_map(|tok| match tok.token_type {
+            TokenType::EndVar => Some(true),
+            TokenType::Var
+            | TokenType::VarInput
+            | TokenType::VarOutput
+            | TokenType::VarInOut
+            | TokenType::VarTemp
+            | TokenType::VarExternal
+            | TokenType::VarAccess
+            | TokenType::VarConfig
+            | TokenType::VarGlobal
+            | TokenType::Type
+            | TokenType::Interface
+            | TokenType::Configuration
+            | TokenType::Property => Some(false),
+            // Bodies without variable declarations follow the name.
+            TokenType::Function
+            | TokenType::FunctionBlock
+            | TokenType::Program
+            | TokenType::Method => Some(false),
+            _ => None,
+        })
+        .unwrap_or(false)
+}
+
+/// Returns true if the token type ends a list of statements.
+fn is_statement_end(token_type: &TokenType) -> bool {
+    is_block_end(token_type)
+        || matches!(
+            token_type,
+            TokenType::Elsif
+                | TokenType::Else
+                | TokenType::Until
+                | TokenType::EndFunction
+                | TokenType::EndFunctionBlock
+                | TokenType::EndProgram
+                | TokenType::EndMethod
+                | TokenType::EndProperty
+                | TokenType::EndStep
+                | TokenType::EndAction
+                | TokenType::EndTransition
+        )
+}
+
+/// Returns true if the token type ends a statement that contains other
+/// statements.
+fn is_block_end(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::EndIf
+            | TokenType::EndCase
+            | TokenType::EndFor
+            | TokenType::EndWhile
+            | TokenType::EndRepeat
+    )
+}
+
+/// Returns the end keyword for a keyword that starts a library element.
+fn element_end_keyword(token_type: &TokenType) -> Option<TokenType> {
+    match token_type {
+        TokenType::Type => Some(TokenType::EndType),
+        TokenType::Function => Some(TokenType::EndFunction),
+        TokenType::FunctionBlock => Some(TokenType::EndFunctionBlock),
+        TokenType::Program => Some(TokenType::EndProgram),
+        TokenType::Configuration => Some(TokenType::EndConfiguration),
+        TokenType::Interface => Some(TokenType::EndInterface),
+        _ => None,
+    }
+}
+
+/// Returns the region for the library element that contains the token.
+/// Text between library elements is also a region.
+fn element_region(tokens: &[Token], index: usize) -> Option<(usize, usize)> {
+    let mut pos = 0;
+    while pos < tokens.len() {
+        if is_trivia(&tokens[pos]) {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        let end = match element_end_keyword(&tokens[start].token_type) {
+            Some(end_keyword) => {
+                let is_configuration = tokens[start].token_type == TokenType::Configuration;
+                tokens
+                    .iter()
+                    .enumerate()
+                    .skip(start + 1)
+                    .find_map(|(pos, tok)| {
+                        if tok.token_type == end_keyword {
+                            return Some(pos + 1);
+                        }
+                        // A missing end keyword ends the element at the next
+                        // element (configurations contain programs).
+                        let nested = is_configuration && tok.token_type == TokenType::Program;
+                        (element_end_keyword(&tok.token_type).is_some() && !nested).then_some(pos)
+                    })
+                    .unwrap_or(tokens.len())
+            }
+            None => tokens
+                .iter()
+                .skip(start + 1)
+                .position(|tok| element_end_keyword(&tok.token_type).is_some())
+                .map(|offset| start + 1 + offset)
+                .unwrap_or(tokens.len()),
+        };
+
+        if index < end {
+            return Some((start, end));
+        }
+        pos = end;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use dsl::common::*;
+    use dsl::core::FileId;
+    use dsl::textual::StmtKind;
+
+    use crate::{options::ParseOptions, parse_program_with_recovery};
+
+    fn parse(program: &str) -> (Library, Vec<String>) {
+        let (library, diagnostics) =
+            parse_program_with_recovery(program, &FileId::default(), &ParseOptions::default());
+        let codes = diagnostics.into_iter().map(|d| d.code).collect();
+        (library, codes)
+    }
+
+    fn function_block_body(library: &Library, index: usize) -> &Vec<StmtKind> {
+        match &library.elements[index] {
+            LibraryElementKind::FunctionBlockDeclaration(fb) => match &fb.body {
+                FunctionBlockBodyKind::Statements(stmts) => &stmts.body,
+                _ => panic!("expected statements"),
+            },
+            _ => panic!("expected function block"),
+        }
+    }
+
+    #[test]
+    fn recover_when_statement_errors_then_error_statements_and_all_diagnostics() {
+        let (library, codes) = parse(
+            "
+FUNCTION_BLOCK fb
+VAR
+   x : INT;
+END_VAR
+   x := ;
+   x := 1;
+   x := 2 +;
+END_FUNCTION_BLOCK",
+        );
+
+        assert_eq!(vec!["P0002", "P0002"], codes);
+        let body = function_block_body(&library, 0);
+        assert_eq!(3, body.len());
+        assert!(matches!(body[0], StmtKind::Error(_)));
+        assert!(matches!(body[1], StmtKind::Assignment(_)));
+        assert!(matches!(body[2], StmtKind::Error(_)));
+    }
+
+    #[test]
+    fn recover_when_missing_semicolon_then_error_statement() {
+        let (library, codes) = parse(
+            "
+FUNCTION_BLOCK fb
+VAR
+   x : INT;
+END_VAR
+   x := 1
+   x := 2;
+   x := 3;
+END_FUNCTION_BLOCK",
+        );
+
+        assert_eq!(vec!["P0002"], codes);
+        let body = function_block_body(&library, 0);
+        assert_eq!(2, body.len());
+        assert!(matches!(body[0], StmtKind::Error(_)));
+    }
+
+    #[test]
+    fn recover_when_error_in_nested_statement_then_keeps_outer_statement() {
+        let (library, codes) = parse(
+            "
+FUNCTION_BLOCK fb
+VAR
+   x : INT;
+END_VAR
+   IF x > 1 THEN
+      x := ;
+   ELSE
+      x := 2;
+   END_IF;
+END_FUNCTION_BLOCK",
+        );
+
+        assert_eq!(vec!["P0002"], codes);
+        let body = function_block_body(&library, 0);
+        match &body[0] {
+            StmtKind::If(stmt) => assert!(matches!(stmt.body[0], StmtKind::Error(_))),
+            _ => panic!("expected if"),
+        }
+    }
+
+    #[test]
+    fn recover_when_error_in_condition_then_error_statement_to_end_keyword() {
+        let (library, codes) = parse(
+            "
+FUNCTION_BLOCK fb
+VAR
+   x : INT;
+END_VAR
+   IF x > THEN
+      x := 1;
+   END_IF;
+   x := 2;
+END_FUNCTION_BLOCK",
+        );
+
+        assert_eq!(vec!["P0002"], codes);
+        let body = function_block_body(&library, 0);
+        assert_eq!(2, body.len());
+        assert!(matches!(body[0], StmtKind::Error(_)));
+        assert!(matches!(body[1], StmtKind::Assignment(_)));
+    }
+
+    #[test]
+    fn recover_when_error_in_declaration_then_skips_element() {
+        let (library, codes) = parse(
+            "
+FUNCTION_BLOCK first
+VAR
+   x : ;
+END_VAR
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK second
+VAR
+   y : INT;
+END_VAR
+   y := ;
+END_FUNCTION_BLOCK
+
+TYPE
+   LEVEL : (LOW, HIGH;
+END_TYPE",
+        );
+
+        assert_eq!(vec!["P0002", "P0002", "P0002"], codes);
+        assert_eq!(1, library.elements.len());
+        let body = function_block_body(&library, 0);
+        assert!(matches!(body[0], StmtKind::Error(_)));
+    }
+
+    #[test]
+    fn recover_when_missing_end_keyword_then_keeps_next_element() {
+        let (library, codes) = parse(
+            "
+FUNCTION_BLOCK first
+VAR
+   x : INT;
+END_VAR
+   IF x > 1 THEN
+      x := 1;
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK second
+END_FUNCTION_BLOCK",
+        );
+
+        assert_eq!(vec!["P0002"], codes);
+        assert_eq!(1, library.elements.len());
+    }
+
+    #[test]
+    fn recover_when_text_between_elements_then_skips_text() {
+        let (library, codes) = parse(
+            "
+not valid text
+
+FUNCTION_BLOCK fb
+END_FUNCTION_BLOCK",
+        );
+
+        assert_eq!(vec!["P0002"], codes);
+        assert_eq!(1, library.elements.len());
+    }
+
+    #[test]
+    fn recover_when_too_many_errors_then_keeps_elements_before_error() {
+        let program = format!(
+            "
+FUNCTION_BLOCK first
+END_FUNCTION_BLOCK
+
+FUNCTION_BLOCK second
+VAR
+   x : INT;
+END_VAR
+{}
+END_FUNCTION_BLOCK",
+            "   x := ;\n".repeat(60)
+        );
+        let (library, codes) = parse(&program);
+
+        assert_eq!(50, codes.len());
+        assert_eq!(1, library.elements.len());
+    }
+}
@@ -51,6 +51,7 @@ mod test {
             &tokens,
             &ParseOptions {
                 allow_c_style_comments: false,
+                ..ParseOptions::default()
             },
         );
         assert!(result.is_err())
@@ -70,6 +71,7 @@ mod test {
             &tokens,
             &ParseOptions {
                 allow_c_style_comments: true,
+                ..ParseOptions::default()
             },
         );
         assert!(result.is_ok())
@@ -2,17 +2,18 @@
 #[cfg(test)]
 mod test {
     use dsl::common::{
-        ConstantKind, DataTypeDeclarationKind, DeclarationQualifier, EnumeratedSpecificationInit,
-        EnumerationDeclaration, FunctionBlockBodyKind, FunctionBlockDeclaration,
-        FunctionDeclaration, InitialValueAssignmentKind, Library, LibraryElementKind,
-        ProgramDeclaration, RealLiteral, SimpleInitializer, TypeName, VarDecl, VariableIdentifier,
-        VariableType,
+        Boolean, BooleanLiteral, ConstantKind, DataTypeDeclarationKind, DeclarationQualifier,
+        EnumeratedSpecificationInit, EnumerationDeclaration, FunctionBlockBodyKind,
+        FunctionBlockDeclaration, FunctionDeclaration, InitialValueAssignmentKind,
+        InterfaceDeclaration, Library, LibraryElementKind, MethodDeclaration, ProgramDeclaration,
+        RealLiteral, SimpleInitializer, TypeName, VarDecl, VariableIdentifier, VariableType,
     };
     use dsl::configuration::{
         ConfigurationDeclaration, ProgramConfiguration, ResourceDeclaration, TaskConfiguration,
     };
     use dsl::core::{FileId, Id, SourceSpan};
     use dsl::diagnostic::Diagnostic;
+    use dsl::il::*;
     use dsl::sfc::{ActionAssociation, ActionQualifier, ElementKind, Network, Step};
     use dsl::textual::*;
     use dsl::time::*;
@@ -113,6 +114,214 @@ mod test {
         assert!(res.is_ok())
     }
 
+    #[test]
+    fn parse_il_then_ok() {
+        let res: Result<Library, Diagnostic> = parse_resource("il.st");
+        assert!(res.is_ok())
+    }
+
+    #[test]
+    fn parse_program_when_il_operator_names_are_variables_then_ok() {
+        let source = "
+PROGRAM main
+VAR
+  st : INT;
+  ret : BOOL;
+  ld : INT;
+  cal : INT;
+  jmp : INT;
+  andn : BOOL;
+END_VAR
+  st := 1;
+  ret := andn;
+  ld := st + cal + jmp;
+END_PROGRAM";
+
+        let res = parse_text(source);
+        assert_eq!(1, res.elements.len());
+    }
+
+    #[test]
+    fn parse_when_il_function_block_then_builds_structure() {
+        let program = "
+FUNCTION_BLOCK fb
+  LD TRUE
+  JMPC Done
+  ST Out
+Done: RET
+END_FUNCTION_BLOCK";
+        let actual = parse_text(program);
+
+        let expected = new_library(LibraryElementKind::FunctionBlockDeclaration(
+            FunctionBlockDeclaration {
+                name: TypeName::from("fb"),
+                modifier: None,
+                extends: None,
+                implements: vec![],
+                variables: vec![],
+                edge_variables: vec![],
+                methods: vec![],
+                properties: vec![],
+                body: FunctionBlockBodyKind::il(vec![
+                    IlInstruction {
+                        label: None,
+                        kind: IlInstructionKind::SimpleOperation(IlSimpleOperation {
+                            operator: IlSimpleOperator::Ld,
+                            operand: Some(IlOperand::Constant(ConstantKind::Boolean(
+                                BooleanLiteral::new(Boolean::True),
+                            ))),
+                        }),
+                        span: SourceSpan::default(),
+                    },
+                    IlInstruction {
+                        label: None,
+                        kind: IlInstructionKind::Jump(IlJumpOperation {
+                            operator: IlJumpOperator::Jmpc,
+                            target: Id::from("Done"),
+                        }),
+                        span: SourceSpan::default(),
+                    },
+                    IlInstruction {
+                        label: None,
+                        kind: IlInstructionKind::SimpleOperation(IlSimpleOperation {
+                            operator: IlSimpleOperator::St,
+                            operand: Some(IlOperand::Variable(Variable::named("Out"))),
+                        }),
+                        span: SourceSpan::default(),
+                    },
+                    IlInstruction {
+                        label: Some(Id::from("Done")),
+                        kind: IlInstructionKind::Return(IlReturnOperation {
+                            operator: IlReturnOperator::Ret,
+                        }),
+                        span: SourceSpan::default(),
+                    },
+                ]),
+                span: SourceSpan::default(),
+                attributes: vec![],
+            },
+        ));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_object_oriented_then_ok() {
+        let res: Result<Library, Diagnostic> = parse_resource("object_oriented.st");
+        assert!(res.is_ok())
+    }
+
+    #[test]
+    fn parse_when_interface_then_builds_structure() {
+        let program = "
+INTERFACE Child EXTENDS Parent
+  METHOD Check : BOOL
+  END_METHOD
+END_INTERFACE";
+        let actual = parse_text(program);
+
+        let expected = new_library(LibraryElementKind::InterfaceDeclaration(
+            InterfaceDeclaration {
+                name: TypeName::from("Child"),
+                extends: vec![TypeName::from("Parent")],
+                methods: vec![MethodDeclaration {
+                    name: Id::from("Check"),
+                    access: None,
+                    modifier: None,
+                    return_type: Some(TypeName::from("BOOL")),
+                    variables: vec![],
+                    body: FunctionBlockBodyKind::empty(),
+                    span: SourceSpan::default(),
+                    attributes: vec![],
+                }],
+                properties: vec![],
+                span: SourceSpan::default(),
+                attributes: vec![],
+            },
+        ));
+
+        assert_eq!(actual, expected)
+    }
+
+    #[test]
+    fn parse_when_method_call_on_this_then_builds_statement() {
+        let program = "
+FUNCTION_BLOCK fb
+  THIS.Run(In := 1);
+END_FUNCTION_BLOCK";
+        let actual = parse_text(program);
+
+        let LibraryElementKind::FunctionBlockDeclaration(fb) = &actual.elements[0] else {
+            panic!("expected function block");
+        };
+        let FunctionBlockBodyKind::Statements(body) = &fb.body else {
+            panic!("expected statements");
+        };
+        let StmtKind::MethodCall(call) = &body.body[0] else {
+            panic!("expected method call");
+        };
+        assert!(matches!(call.receiver, SymbolicVariableKind::This(_)));
+        assert_eq!(call.name, Id::from("Run"));
+        assert_eq!(call.params.len(), 1);
+    }
+
+    #[test]
+    fn parse_when_ld_is_variable_name_then_ok() {
+        let program = "
+FUNCTION_BLOCK fb
+VAR_INPUT
+  LD : BOOL;
+END_VAR
+END_FUNCTION_BLOCK
+
+PROGRAM main
+VAR
+  counter : fb;
+  load : BOOL;
+END_VAR
+  counter(LD := TRUE);
+  load := counter.LD;
+END_PROGRAM";
+        let actual = parse_text(program);
+
+        let LibraryElementKind::FunctionBlockDeclaration(fb) = &actual.elements[0] else {
+            panic!("expected function block");
+        };
+        assert_eq!(
+            fb.variables[0].identifier.symbolic_id(),
+            Some(&Id::from("LD"))
+        );
+    }
+
+    #[test]
+    fn parse_reference_then_ok() {
+        let res: Result<Library, Diagnostic> = parse_resource("reference.st");
+        assert!(res.is_ok())
+    }
+
+    #[test]
+    fn parse_when_dereference_and_ref_then_builds_statement() {
+        let program = "
+FUNCTION_BLOCK fb
+  Target^ := REF(Source);
+END_FUNCTION_BLOCK";
+        let actual = parse_text(program);
+
+        let LibraryElementKind::FunctionBlockDeclaration(fb) = &actual.elements[0] else {
+            panic!("expected function block");
+        };
+        let FunctionBlockBodyKind::Statements(body) = &fb.body else {
+            panic!("expected statements");
+        };
+        let StmtKind::Assignment(assignment) = &body.body[0] else {
+            panic!("expected assignment");
+        };
+        assert!(matches!(
+            assignment.target,
+            Variable::Symbolic(SymbolicVariableKind::Deref(_))
+        ));
+        assert!(matches!(assignment.value, ExprKind::Ref(_)));
+    }
+
     #[test]
     fn parse_if_then_ok() {
         let res: Result<Library, Diagnostic> = parse_resource("if.st");
@@ -194,7 +403,7 @@ mod test {
 
         let err = res.unwrap_err();
         assert_eq!("Syntax error".to_owned(), err.description());
-        assert_eq!("Expected ' ' (space) | '\\t' (tab) | '(* ... *)' (comment) | '\\n' (new line) | (identifier). Found text '&' that matched token 'AND' | '&'".to_owned(), err.primary.message);
+        assert_eq!("Expected ' ' (space) | '\\t' (tab) | '(* ... *)' (comment) | '\\n' (new line) | '{ ... }' (pragma) | (identifier). Found text '&' that matched token 'AND' | '&'".to_owned(), err.primary.message);
     }
 
     #[test]
@@ -207,7 +416,7 @@ mod test {
 
         let err = res.unwrap_err();
         assert_eq!("Syntax error".to_owned(), err.description());
-        assert_eq!("Expected ' ' (space) | '\\t' (tab) | '(* ... *)' (comment) | 'CONFIGURATION' | 'FUNCTION' | 'FUNCTION_BLOCK' | 'PROGRAM' | 'TYPE' | '\\n' (new line). Found text 'ACTION' that matched token 'ACTION'".to_owned(), err.primary.message);
+        assert_eq!("Expected ' ' (space) | '\\t' (tab) | '(* ... *)' (comment) | 'CONFIGURATION' | 'FUNCTION' | 'FUNCTION_BLOCK' | 'INTERFACE' | 'PROGRAM' | 'TYPE' | '\\n' (new line) | '{ ... }' (pragma). Found text 'ACTION' that matched token 'ACTION'".to_owned(), err.primary.message);
     }
 
     #[test]
@@ -255,9 +464,15 @@ END_FUNCTION";
                             data_type: None,
                         })),
                     }),
+                    attributes: vec![],
+                    keyword_span: SourceSpan::default(),
                 }],
                 edge_variables: vec![],
-                body: vec![StmtKind::simple_assignment("fun", "InputsNumber")],
+                body: FunctionBlockBodyKind::stmts(vec![StmtKind::simple_assignment(
+                    "fun",
+                    "InputsNumber",
+                )]),
+                attributes: vec![],
             },
         ));
         assert_eq!(res, expected);
@@ -292,9 +507,12 @@ END_FUNCTION";
                             span: SourceSpan::default(),
                         })),
                     }),
+                    attributes: vec![],
+                    keyword_span: SourceSpan::default(),
                 }],
                 edge_variables: vec![],
-                body: vec![StmtKind::simple_assignment("fun", "tv")],
+                body: FunctionBlockBodyKind::stmts(vec![StmtKind::simple_assignment("fun", "tv")]),
+                attributes: vec![],
             },
         ));
         assert_eq!(actual, expected);
@@ -306,6 +524,9 @@ END_FUNCTION";
         let expected = new_library(LibraryElementKind::FunctionBlockDeclaration(
             FunctionBlockDeclaration {
                 name: TypeName::from("CounterFBD"),
+                modifier: None,
+                extends: None,
+                implements: vec![],
                 variables: vec![
                     VarDecl::simple("Reset", "BOOL").with_type(VariableType::Input),
                     VarDecl::simple("OUT", "INT").with_type(VariableType::Output),
@@ -318,16 +539,21 @@ END_FUNCTION";
                             type_name: TypeName::from("INT"),
                             initial_value: None,
                         }),
+                        attributes: vec![],
+                        keyword_span: SourceSpan::default(),
                     },
                     VarDecl::simple("_TMP_ADD4_OUT", "INT"),
                     VarDecl::simple("_TMP_SEL7_OUT", "INT"),
                 ],
                 edge_variables: vec![],
+                methods: vec![],
+                properties: vec![],
                 body: FunctionBlockBodyKind::stmts(vec![
                     StmtKind::simple_assignment("Cnt", "_TMP_SEL7_OUT"),
                     StmtKind::simple_assignment("OUT", "Cnt"),
                 ]),
                 span: SourceSpan::default(),
+                attributes: vec![],
             },
         ));
         assert_eq!(actual, expected);
@@ -357,10 +583,12 @@ END_FUNCTION";
                                 data_type: None,
                             })),
                         }),
+                        attributes: vec![],
+                        keyword_span: SourceSpan::default(),
                     },
                 ],
                 edge_variables: vec![],
-                body: vec![StmtKind::assignment(
+                body: FunctionBlockBodyKind::stmts(vec![StmtKind::assignment(
                     Variable::named("AverageVal"),
                     ExprKind::binary(
                         Operator::Div,
@@ -388,7 +616,8 @@ END_FUNCTION";
                         }),
                         ExprKind::late_bound("InputsNumber"),
                     ),
-                )],
+                )]),
+                attributes: vec![],
             },
         ));
         assert_eq!(actual, expected)
@@ -433,6 +662,7 @@ END_FUNCTION";
                 StmtKind::fb_call_mapped("CounterLD0", vec![("Reset", "Reset")]),
                 StmtKind::structured_assignment("Cnt5", "CounterLD0", "Out"),
             ]),
+            attributes: vec![],
         }));
         assert_eq!(actual, expected)
     }
@@ -451,6 +681,8 @@ END_FUNCTION";
                         type_name: TypeName::from("INT"),
                         initial_value: Some(ConstantKind::integer_literal("17").unwrap()),
                     }),
+                    attributes: vec![],
+                    keyword_span: SourceSpan::default(),
                 }],
                 resource_decl: vec![ResourceDeclaration {
                     name: Id::from("resource1"),
@@ -488,6 +720,9 @@ END_FUNCTION";
         let expected = new_library(LibraryElementKind::FunctionBlockDeclaration(
             FunctionBlockDeclaration {
                 name: TypeName::from("LOGGER"),
+                modifier: None,
+                extends: None,
+                implements: vec![],
                 variables: vec![
                     VarDecl::simple("TRIG", "BOOL").with_type(VariableType::Input),
                     VarDecl::string(
@@ -499,6 +734,8 @@ END_FUNCTION";
                     VarDecl::simple("TRIG0", "BOOL"),
                 ],
                 edge_variables: vec![],
+                methods: vec![],
+                properties: vec![],
                 body: FunctionBlockBodyKind::stmts(vec![
                     StmtKind::if_then(
                         ExprKind::compare(
@@ -511,6 +748,7 @@ END_FUNCTION";
                     StmtKind::assignment(Variable::named("TRIG0"), ExprKind::late_bound("TRIG")),
                 ]),
                 span: SourceSpan::default(),
+                attributes: vec![],
             },
         ));
 
@@ -523,6 +761,9 @@ END_FUNCTION";
         let expected = new_library(LibraryElementKind::FunctionBlockDeclaration(
             FunctionBlockDeclaration {
                 name: TypeName::from("CounterSFC"),
+                modifier: None,
+                extends: None,
+                implements: vec![],
                 variables: vec![
                     VarDecl::simple("Reset", "BOOL").with_type(VariableType::Input),
                     VarDecl::simple("OUT", "INT").with_type(VariableType::Output),
@@ -535,9 +776,13 @@ END_FUNCTION";
                             type_name: TypeName::from("INT"),
                             initial_value: None,
                         }),
+                        attributes: vec![],
+                        keyword_span: SourceSpan::default(),
                     },
                 ],
                 edge_variables: vec![],
+                methods: vec![],
+                properties: vec![],
                 body: FunctionBlockBodyKind::sfc(vec![Network {
                     initial_step: Step {
                         name: Id::from("Start"),
@@ -606,6 +851,7 @@ END_FUNCTION";
                     ],
                 }]),
                 span: SourceSpan::default(),
+                attributes: vec![],
             },
         ));
         assert_eq!(actual, expected);
@@ -630,6 +876,7 @@ END_FUNCTION";
                     vec!["CRITICAL", "WARNING", "INFO", "DEBUG"],
                     "INFO",
                 ),
+                attributes: vec![],
             }),
         ));
         assert_eq!(actual, expected)
@@ -76,6 +76,11 @@ pub enum TokenType {
     #[regex(r"//[^\r\n]*(\r\n|\n)?", priority = 0)]
     Comment,
 
+    // Pragmas are implementation specific (see section 2.1.5) and
+    // are not part of the language elements.
+    #[regex(r"\{[^}]*\}")]
+    Pragma,
+
     // Grouping and other markers
     #[token("(", priority = 1)]
     LeftParen,
@@ -103,6 +108,9 @@ pub enum TokenType {
     #[token("#")]
     Hash,
 
+    #[token("^")]
+    Caret,
+
     // Separate the single byte and double byte representations
     // because those have different valid prefixes.
     #[regex(r"'[^']*'")]
@@ -171,6 +179,9 @@ pub enum TokenType {
     #[token("EXIT", ignore(case))]
     Exit,
 
+    #[token("EXTENDS", ignore(case))]
+    Extends,
+
     #[token("FALSE", ignore(case))]
     False,
 
@@ -207,11 +218,24 @@ pub enum TokenType {
     #[token("END_IF", ignore(case))]
     EndIf,
 
+    #[token("IMPLEMENTS", ignore(case))]
+    Implements,
+
     #[token("INITIAL_STEP", ignore(case))]
     InitialStep,
     #[token("END_STEP", ignore(case))]
     EndStep,
 
+    #[token("INTERFACE", ignore(case))]
+    Interface,
+    #[token("END_INTERFACE", ignore(case))]
+    EndInterface,
+
+    #[token("METHOD", ignore(case))]
+    Method,
+    #[token("END_METHOD", ignore(case))]
+    EndMethod,
+
     #[token("PROGRAM", ignore(case))]
     Program,
     #[token("WITH", ignore(case))]
@@ -219,6 +243,11 @@ pub enum TokenType {
     #[token("END_PROGRAM", ignore(case))]
     EndProgram,
 
+    #[token("PROPERTY", ignore(case))]
+    Property,
+    #[token("END_PROPERTY", ignore(case))]
+    EndProperty,
+
     #[token("R_EDGE", ignore(case))]
     REdge,
 
@@ -227,6 +256,13 @@ pub enum TokenType {
     #[token("READ_WRITE", ignore(case))]
     ReadWrite,
 
+    #[token("REF", ignore(case))]
+    Ref,
+    #[token("REF_TO", ignore(case))]
+    RefTo,
+    #[token("NULL", ignore(case))]
+    Null,
+
     #[token("REPEAT", ignore(case))]
     Repeat,
     #[token("UNTIL", ignore(case))]
@@ -257,11 +293,17 @@ pub enum TokenType {
     #[token("END_STRUCT", ignore(case))]
     EndStruct,
 
+    #[token("SUPER", ignore(case))]
+    Super,
+
     #[token("TASK", ignore(case))]
     Task,
     #[token("END_TASK", ignore(case))]
     EndTask,
 
+    #[token("THIS", ignore(case))]
+    This,
+
     #[token("TRANSITION", ignore(case))]
     Transition,
     #[token("FROM", ignore(case))]
@@ -401,6 +443,7 @@ impl TokenType {
             TokenType::Newline => "'\\n' (new line)",
             TokenType::Whitespace => "' ' (space) | '\\t' (tab)",
             TokenType::Comment => "'(* ... *)' (comment)",
+            TokenType::Pragma => "'{ ... }' (pragma)",
             TokenType::LeftParen => "'('",
             TokenType::RightParen => "')'",
             TokenType::LeftBrace => "'{'",
@@ -413,6 +456,7 @@ impl TokenType {
             TokenType::Period => "'.'",
             TokenType::Range => "'..' (range)",
             TokenType::Hash => "'#'",
+            TokenType::Caret => "'^' (dereference)",
             TokenType::SingleByteString => "\\'[^\\']*\\' (single byte string)",
             TokenType::DoubleByteString => "\"[^\"]*\" (double byte string)",
             TokenType::Identifier => "(identifier)",
@@ -436,6 +480,7 @@ impl TokenType {
             TokenType::En => "'EN'",
             TokenType::Eno => "'ENO'",
             TokenType::Exit => "'EXIT'",
+            TokenType::Extends => "'EXTENDS'",
             TokenType::False => "'FALSE'",
             TokenType::FEdge => "'F_EDGE'",
             TokenType::For => "'FOR'",
@@ -451,14 +496,24 @@ impl TokenType {
             TokenType::Then => "'THEN'",
             TokenType::Elsif => "'ELSIF'",
             TokenType::EndIf => "'END_IF'",
+            TokenType::Implements => "'IMPLEMENTS'",
             TokenType::InitialStep => "'INITIAL_STEP'",
             TokenType::EndStep => "'END_STEP'",
+            TokenType::Interface => "'INTERFACE'",
+            TokenType::EndInterface => "'END_INTERFACE'",
+            TokenType::Method => "'METHOD'",
+            TokenType::EndMethod => "'END_METHOD'",
             TokenType::Program => "'PROGRAM'",
             TokenType::With => "'WITH'",
             TokenType::EndProgram => "'END_PROGRAM'",
+            TokenType::Property => "'PROPERTY'",
+            TokenType::EndProperty => "'END_PROPERTY'",
             TokenType::REdge => "'R_EDGE'",
             TokenType::ReadOnly => "'READ_ONLY'",
             TokenType::ReadWrite => "'READ_WRITE'",
+            TokenType::Ref => "'REF'",
+            TokenType::RefTo => "'REF_TO'",
+            TokenType::Null => "'NULL'",
             TokenType::Repeat => "'REPEAT'",
             TokenType::Until => "'UNTIL'",
             TokenType::EndRepeat => "'END_REPEAT'",
@@ -471,8 +526,10 @@ impl TokenType {
             TokenType::Step => "'STEP'",
             TokenType::Struct => "'STRUCT'",
             TokenType::EndStruct => "'END_STRUCT'",
+            TokenType::Super => "'SUPER'",
             TokenType::Task => "'TASK'",
             TokenType::EndTask => "'END_TASK'",
+            TokenType::This => "'THIS'",
             TokenType::Transition => "'TRANSITION'",
             TokenType::From => "'FROM'",
             TokenType::EndTransition => "'END_TRANSITION'",
@@ -591,6 +648,7 @@ mod tests {
             (Newline, "\\n"),
             (Whitespace, " "),
             (Comment, "(* comment *)"),
+            (Pragma, "{attribute 'hide'}"),
             (LeftParen, "("),
             (RightParen, ")"),
             (LeftBrace, "{"),
@@ -603,6 +661,7 @@ mod tests {
             (Period, "."),
             (Range, ".."),
             (Hash, "#"),
+            (Caret, "^"),
             (SingleByteString, "'abc'"),
             (DoubleByteString, "\"abc\""),
             (Identifier, "ident"),
@@ -626,6 +685,7 @@ mod tests {
             (En, "EN"),
             (Eno, "ENO"),
             (Exit, "EXIT"),
+            (Extends, "EXTENDS"),
             (False, "FALSE"),
             (FEdge, "F_EDGE"),
             (For, "FOR"),
@@ -641,14 +701,24 @@ mod tests {
             (Then, "THEN"),
             (Elsif, "ELSIF"),
             (EndIf, "END_IF"),
+            (Implements, "IMPLEMENTS"),
             (InitialStep, "INITIAL_STEP"),
             (EndStep, "END_STEP"),
+            (Interface, "INTERFACE"),
+            (EndInterface, "END_INTERFACE"),
+            (Method, "METHOD"),
+            (EndMethod, "END_METHOD"),
             (Program, "PROGRAM"),
             (With, "WITH"),
             (EndProgram, "END_PROGRAM"),
+            (Property, "PROPERTY"),
+            (EndProperty, "END_PROPERTY"),
             (REdge, "R_EDGE"),
             (ReadOnly, "READ_ONLY"),
             (ReadWrite, "READ_WRITE"),
+            (Ref, "REF"),
+            (RefTo, "REF_TO"),
+            (Null, "NULL"),
             (Repeat, "REPEAT"),
             (Until, "UNTIL"),
             (EndRepeat, "END_REPEAT"),
@@ -661,8 +731,10 @@ mod tests {
             (Step, "STEP"),
             (Struct, "STRUCT"),
             (EndStruct, "END_STRUCT"),
+            (Super, "SUPER"),
             (Task, "TASK"),
             (EndTask, "END_TASK"),
+            (This, "THIS"),
             (Transition, "TRANSITION"),
             (From, "FROM"),
             (EndTransition, "END_TRANSITION"),
@@ -61,6 +61,7 @@ impl From<IncomplVarDecl> for VarDecl {
                 InitialValueAssignmentKind::Structure(StructureInitializationDeclaration {
                     type_name: node.type_name,
                     elements_init: vec![],
+                    attributes: vec![],
                 })
             }
             VariableSpecificationKind::String(node) => {
@@ -93,6 +94,8 @@ impl From<IncomplVarDecl> for VarDecl {
             var_type: VariableType::Var,
             qualifier: val.qualifier,
             initializer: init,
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 }
@@ -104,6 +107,8 @@ impl UntypedVarDecl {
             var_type,
             qualifier: DeclarationQualifier::Unspecified,
             initializer: self.initializer,
+            attributes: vec![],
+            keyword_span: SourceSpan::default(),
         }
     }
 }
@@ -340,6 +345,16 @@ impl VarDeclarations {
             .collect()
     }
 
+    pub fn with_keyword_span(declarations: Vec<VarDecl>, span: &SourceSpan) -> Vec<VarDecl> {
+        declarations
+            .into_iter()
+            .map(|mut declaration| {
+                declaration.keyword_span = span.clone();
+                declaration
+            })
+            .collect()
+    }
+
     pub fn map_incomplete(
         declarations: Vec<IncomplVarDecl>,
         qualifier: &DeclarationQualifier,
@@ -387,6 +402,8 @@ impl VarDeclarations {
                     var_type: var_type.clone(),
                     qualifier,
                     initializer: declaration.initializer,
+                    attributes: vec![],
+                    keyword_span: SourceSpan::default(),
                 }
             })
             .collect()
@@ -0,0 +1,272 @@
+//! Transform that attaches pragmas to the declarations, variables and
+//! program organization units that the pragmas precede.
+//!
+//! Pragmas can appear anywhere that whitespace can appear, so the parser
+//! treats pragmas as whitespace. Before parsing, we find the pragmas in the
+//! token stream and identify the position of the name that follows each
+//! pragma. After parsing, this transform attaches the pragmas to the item
+//! having a name at that position. Pragmas that do not precede a named item
+//! (for example, pragmas in a statement list) are not attached.
+use std::collections::HashMap;
+
+use dsl::common::*;
+use dsl::core::{Id, Located, SourceSpan};
+use dsl::diagnostic::Diagnostic;
+use dsl::fold::Fold;
+
+use crate::token::{Token, TokenType};
+
+/// Attributes keyed by the start position of the name that follows the
+/// attributes.
+pub type PendingAttributes = HashMap<usize, Vec<Attribute>>;
+
+/// Finds the pragmas in the tokens and the position of the item that each
+/// pragma precedes.
+pub fn collect(tokens: &[Token]) -> PendingAttributes {
+    let mut attributes = PendingAttributes::new();
+    let mut pending: Vec<Attribute> = vec![];
+    let mut after_introducer = false;
+
+    for tok in tokens {
+        match tok.token_type {
+            TokenType::Pragma => pending.push(parse_attribute(tok)),
+            TokenType::Whitespace | TokenType::Newline | TokenType::Comment => {}
+            _ if pending.is_empty() => {}
+            // The pragma precedes the keyword that introduces the item, for
+            // example {attribute 'x'} FUNCTION_BLOCK name
+            TokenType::Type
+            | TokenType::Function
+            | TokenType::FunctionBlock
+            | TokenType::Program
+            | TokenType::Interface
+            | TokenType::Method
+            | TokenType::Property => after_introducer = true,
+            TokenType::Identifier if after_introducer && is_modifier(&tok.text) => {}
+            _ => {
+                attributes
+                    .entry(tok.span.start)
+                    .or_default()
+                    .append(&mut pending);
+                after_introducer = false;
+            }
+        }
+    }
+
+    attributes
+}
+
+/// Attaches the attributes to the items in the library.
+pub fn apply(lib: Library, attributes: PendingAttributes) -> Result<Library, Diagnostic> {
+    if attributes.is_empty() {
+        return Ok(lib);
+    }
+    let mut transform = TransformAttachAttributes { attributes };
+    transform.fold_library(lib)
+}
+
+fn is_modifier(text: &str) -> bool {
+    [
+        "ABSTRACT",
+        "FINAL",
+        "OVERRIDE",
+        "PUBLIC",
+        "PROTECTED",
+        "PRIVATE",
+        "INTERNAL",
+    ]
+    .iter()
+    .any(|modifier| modifier.eq_ignore_ascii_case(text))
+}
+
+/// Parses the text of a pragma into an attribute. The parts that are not
+/// understood are only retained as text.
+fn parse_attribute(tok: &Token) -> Attribute {
+    let content = tok
+        .text
+        .trim_start_matches('{')
+        .trim_end_matches('}')
+        .trim();
+
+    let kind_len = content
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(content.len());
+    let (kind, rest) = content.split_at(kind_len);
+
+    let (name, rest) = match take_quoted(rest.trim_start()) {
+        Some((name, rest)) => (Some(name), rest),
+        None => (None, rest),
+    };
+    let value = rest
+        .trim_start()
+        .strip_prefix(":=")
+        .and_then(|rest| take_quoted(rest.trim_start()))
+        .map(|(value, _)| value);
+
+    Attribute {
+        kind: Id::from(kind).with_position(SourceSpan {
+            start: tok.span.start + 1,
+            end: tok.span.start + 1 + kind_len,
+            file_id: tok.span.file_id.clone(),
+        }),
+        name,
+        value,
+        text: tok.text.clone(),
+        span: tok.span.clone(),
+    }
+}
+
+/// Returns the text within single quotes at the start of the input and
+/// the remaining input.
+fn take_quoted(input: &str) -> Option<(String, &str)> {
+    let rest = input.strip_prefix('\'')?;
+    let end = rest.find('\'')?;
+    Some((rest[..end].to_string(), &rest[end + 1..]))
+}
+
+struct TransformAttachAttributes {
+    attributes: PendingAttributes,
+}
+
+impl TransformAttachAttributes {
+    fn take(&mut self, span: SourceSpan) -> Vec<Attribute> {
+        self.attributes.remove(&span.start).unwrap_or_default()
+    }
+}
+
+macro_rules! attach {
+    ($fn_name:ident, $ty:ty, $name:ident) => {
+        fn $fn_name(&mut self, node: $ty) -> Result<$ty, Diagnostic> {
+            let mut attributes = self.take(node.$name.span());
+            let mut node = node.recurse_fold(self)?;
+            node.attributes.append(&mut attributes);
+            Ok(node)
+        }
+    };
+}
+
+impl Fold<Diagnostic> for TransformAttachAttributes {
+    attach!(fold_function_declaration, FunctionDeclaration, name);
+    attach!(
+        fold_function_block_declaration,
+        FunctionBlockDeclaration,
+        name
+    );
+    attach!(fold_program_declaration, ProgramDeclaration, name);
+    attach!(fold_interface_declaration, InterfaceDeclaration, name);
+    attach!(fold_method_declaration, MethodDeclaration, name);
+    attach!(fold_property_declaration, PropertyDeclaration, name);
+    attach!(fold_var_decl, VarDecl, identifier);
+    attach!(
+        fold_structure_element_declaration,
+        StructureElementDeclaration,
+        name
+    );
+    attach!(
+        fold_enumeration_declaration,
+        EnumerationDeclaration,
+        type_name
+    );
+    attach!(fold_subrange_declaration, SubrangeDeclaration, type_name);
+    attach!(fold_simple_declaration, SimpleDeclaration, type_name);
+    attach!(fold_array_declaration, ArrayDeclaration, type_name);
+    attach!(fold_structure_declaration, StructureDeclaration, type_name);
+    attach!(
+        fold_structure_initialization_declaration,
+        StructureInitializationDeclaration,
+        type_name
+    );
+    attach!(fold_string_declaration, StringDeclaration, type_name);
+    attach!(fold_reference_declaration, ReferenceDeclaration, type_name);
+    attach!(
+        fold_late_bound_declaration,
+        LateBoundDeclaration,
+        data_type_name
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use dsl::common::*;
+    use dsl::core::{FileId, Id};
+
+    use crate::{options::ParseOptions, parse_program};
+
+    fn parse(program: &str) -> Library {
+        parse_program(program, &FileId::default(), &ParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn apply_when_attribute_before_function_block_then_attaches_to_function_block() {
+        let library = parse(
+            "
+{attribute 'hide'}
+FUNCTION_BLOCK ABSTRACT fb
+END_FUNCTION_BLOCK",
+        );
+
+        let LibraryElementKind::FunctionBlockDeclaration(fb) = &library.elements[0] else {
+            panic!("expected function block");
+        };
+        assert_eq!(1, fb.attributes.len());
+        assert!(fb.has_attribute("hide"));
+    }
+
+    #[test]
+    fn apply_when_attribute_with_value_then_has_name_and_value() {
+        let library = parse(
+            "
+PROGRAM main
+VAR
+   {attribute 'hmi' := 'visible'}
+   speed : INT;
+   other : INT;
+END_VAR
+END_PROGRAM",
+        );
+
+        let LibraryElementKind::ProgramDeclaration(program) = &library.elements[0] else {
+            panic!("expected program");
+        };
+        let attribute = program.variables[0].find_attribute("hmi").unwrap();
+        assert_eq!(Id::from("attribute"), attribute.kind);
+        assert_eq!(Some("visible".to_string()), attribute.value);
+        assert_eq!("{attribute 'hmi' := 'visible'}", attribute.text);
+        assert!(program.variables[1].attributes.is_empty());
+        assert!(program.attributes.is_empty());
+    }
+
+    #[test]
+    fn apply_when_attribute_before_type_block_then_attaches_to_type() {
+        let library = parse(
+            "
+{attribute 'qualified_only'}
+TYPE
+   LEVEL : (LOW, HIGH);
+END_TYPE",
+        );
+
+        let LibraryElementKind::DataTypeDeclaration(decl) = &library.elements[0] else {
+            panic!("expected type declaration");
+        };
+        assert!(decl.has_attribute("qualified_only"));
+    }
+
+    #[test]
+    fn apply_when_pragma_in_statements_then_ignores_pragma() {
+        let library = parse(
+            "
+FUNCTION_BLOCK fb
+VAR
+   x : INT;
+END_VAR
+   {warning 'check this'}
+   x := 1;
+END_FUNCTION_BLOCK",
+        );
+
+        let LibraryElementKind::FunctionBlockDeclaration(fb) = &library.elements[0] else {
+            panic!("expected function block");
+        };
+        assert!(fb.variables[0].attributes.is_empty());
+    }
+}
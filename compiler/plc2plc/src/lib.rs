@@ -1,11 +1,41 @@
+// Allow large errors because this is a compiler - we expect large errors.
+#![allow(clippy::result_large_err)]
 extern crate ironplc_dsl as dsl;
 
-use ironplc_dsl::{common::Library, diagnostic::Diagnostic};
-use renderer::apply;
+use ironplc_dsl::{
+    common::{DataTypeDeclarationKind, Library, StructureElementDeclaration, VarDecl},
+    diagnostic::Diagnostic,
+};
+use renderer::{
+    apply, apply_data_type_declaration, apply_structure_element_declaration, apply_var_decl,
+};
 
 mod renderer;
 mod tests;
 
+/// Writes the library as structured text to a string.
+///
+/// The library does not retain comments or pragmas that precede statements,
+/// so these are not written.
 pub fn write_to_string(lib: &Library) -> Result<String, Vec<Diagnostic>> {
     apply(lib)
 }
+
+/// Writes a single variable declaration (as a declaration block) to a string.
+pub fn write_var_decl_to_string(node: &VarDecl) -> Result<String, Vec<Diagnostic>> {
+    apply_var_decl(node)
+}
+
+/// Writes a single data type declaration (as a `TYPE` block) to a string.
+pub fn write_data_type_declaration_to_string(
+    node: &DataTypeDeclarationKind,
+) -> Result<String, Vec<Diagnostic>> {
+    apply_data_type_declaration(node)
+}
+
+/// Writes a single element of a structure declaration to a string.
+pub fn write_structure_element_declaration_to_string(
+    node: &StructureElementDeclaration,
+) -> Result<String, Vec<Diagnostic>> {
+    apply_structure_element_declaration(node)
+}
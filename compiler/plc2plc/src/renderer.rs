@@ -7,6 +7,7 @@
 use dsl::configuration::LocatedVarInit;
 use ironplc_dsl::common::*;
 use ironplc_dsl::core::Id;
+use ironplc_dsl::il::*;
 use ironplc_dsl::time::*;
 use ironplc_dsl::{diagnostic::Diagnostic, visitor::Visitor};
 use paste::paste;
@@ -53,6 +54,37 @@ pub fn apply(lib: &Library) -> Result<String, Vec<Diagnostic>> {
         .map_err(|e| vec![e])
 }
 
+/// Renders a variable declaration as a declaration block that contains
+/// only the variable.
+pub fn apply_var_decl(node: &VarDecl) -> Result<String, Vec<Diagnostic>> {
+    render(|visitor| visitor.visit_var_decl(node))
+}
+
+/// Renders a data type declaration as a `TYPE` block that contains only
+/// the data type declaration.
+pub fn apply_data_type_declaration(
+    node: &DataTypeDeclarationKind,
+) -> Result<String, Vec<Diagnostic>> {
+    render(|visitor| visitor.visit_data_type_declaration_kind(node))
+}
+
+/// Renders an element of a structure declaration.
+pub fn apply_structure_element_declaration(
+    node: &StructureElementDeclaration,
+) -> Result<String, Vec<Diagnostic>> {
+    render(|visitor| visitor.visit_structure_element_declaration(node))
+}
+
+fn render<F>(f: F) -> Result<String, Vec<Diagnostic>>
+where
+    F: FnOnce(&mut LibraryRenderer) -> Result<(), Diagnostic>,
+{
+    let mut visitor = LibraryRenderer::new();
+    f(&mut visitor)
+        .map(|_| visitor.buffer.trim().to_string())
+        .map_err(|e| vec![e])
+}
+
 struct LibraryRenderer {
     buffer: String,
     indents: usize,
@@ -94,6 +126,33 @@ impl LibraryRenderer {
     fn outdent(&mut self) {
         self.indents -= 1;
     }
+
+    /// Writes the attributes (pragmas) that precede an item, one per line.
+    fn write_attributes(&mut self, attributes: &[Attribute]) -> Result<(), Diagnostic> {
+        for attribute in attributes.iter() {
+            self.visit_attribute(attribute)?;
+        }
+        Ok(())
+    }
+
+    /// Writes instruction list formal parameters with one parameter per line.
+    fn write_il_formal_params(
+        &mut self,
+        params: &[IlParamAssignmentKind],
+    ) -> Result<(), Diagnostic> {
+        self.newline();
+        self.indent();
+        let mut it = params.iter().peekable();
+        while let Some(param) = it.next() {
+            self.visit_il_param_assignment_kind(param)?;
+            if it.peek().is_some() {
+                self.write(",");
+            }
+            self.newline();
+        }
+        self.outdent();
+        Ok(())
+    }
 }
 
 impl Visitor<Diagnostic> for LibraryRenderer {
@@ -210,6 +269,13 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         Ok(())
     }
 
+    // 2.1.5
+    fn visit_attribute(&mut self, node: &Attribute) -> Result<Self::Value, Diagnostic> {
+        self.write_ws(&node.text);
+        self.newline();
+        Ok(())
+    }
+
     // 2.3.3.1
     fn visit_data_type_declaration_kind(
         &mut self,
@@ -229,10 +295,23 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         Ok(())
     }
 
+    fn visit_simple_declaration(
+        &mut self,
+        node: &SimpleDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
+        self.visit_type_name(&node.type_name)?;
+
+        self.write_ws(":");
+
+        self.visit_initial_value_assignment_kind(&node.spec_and_init)
+    }
+
     fn visit_late_bound_declaration(
         &mut self,
         node: &LateBoundDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.visit_type_name(&node.data_type_name)?;
 
         self.write_ws(":");
@@ -244,6 +323,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &EnumerationDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.visit_type_name(&node.type_name)?;
 
         self.write_ws(":");
@@ -281,6 +361,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &SubrangeDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.visit_type_name(&node.type_name)?;
 
         self.write_ws(":");
@@ -311,6 +392,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &StructureDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.visit_type_name(&node.type_name)?;
 
         self.write_ws(":");
@@ -335,6 +417,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &StructureElementDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.visit_id(&node.name)?;
         self.write_ws(":");
         self.visit_initial_value_assignment_kind(&node.init)
@@ -345,6 +428,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &StructureInitializationDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.visit_type_name(&node.type_name)?;
 
         if !node.elements_init.is_empty() {
@@ -375,6 +459,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &ArrayDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.visit_type_name(&node.type_name)?;
 
         self.write_ws(":");
@@ -389,6 +474,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &StringDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.visit_type_name(&node.type_name)?;
 
         self.write_ws(":");
@@ -400,7 +486,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         self.write_ws(typ);
 
         self.write_ws("[");
-        self.visit_integer(&node.length)?;
+        self.visit_string_length_kind(&node.length)?;
         self.write_ws("]");
 
         if let Some(init) = &node.init {
@@ -419,6 +505,18 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         Ok(())
     }
 
+    fn visit_reference_declaration(
+        &mut self,
+        node: &ReferenceDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
+        self.visit_type_name(&node.type_name)?;
+
+        self.write_ws(":");
+
+        self.visit_reference_initial_value_assignment(&node.spec_init)
+    }
+
     fn visit_array_specification_kind(
         &mut self,
         node: &ArraySpecificationKind,
@@ -449,9 +547,9 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &ironplc_dsl::common::Subrange,
     ) -> Result<Self::Value, Diagnostic> {
-        self.visit_signed_integer(&node.start)?;
+        self.visit_subrange_bound_kind(&node.start)?;
         self.write("..");
-        self.visit_signed_integer(&node.end)
+        self.visit_subrange_bound_kind(&node.end)
     }
 
     fn visit_program_access_decl(
@@ -514,6 +612,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         self.newline();
 
         self.indent();
+        self.write_attributes(&node.attributes)?;
         match &node.identifier {
             VariableIdentifier::Symbol(id) => {
                 self.visit_id(id)?;
@@ -636,7 +735,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
 
         if let Some(len) = &node.length {
             self.write_ws("[");
-            self.visit_integer(len)?;
+            self.visit_string_length_kind(len)?;
             self.write_ws("]");
         }
 
@@ -700,6 +799,21 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         Ok(())
     }
 
+    fn visit_reference_initial_value_assignment(
+        &mut self,
+        node: &ReferenceInitialValueAssignment,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_ws("REF_TO");
+        self.visit_type_name(&node.referenced_type_name)?;
+
+        if let Some(init) = &node.initial_value {
+            self.write_ws(":=");
+            self.visit_expr_kind(init)?;
+        }
+
+        Ok(())
+    }
+
     fn visit_enumerated_initial_value_assignment(
         &mut self,
         node: &EnumeratedInitialValueAssignment,
@@ -734,6 +848,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &FunctionDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.write_ws("FUNCTION");
         self.visit_id(&node.name)?;
         self.write_ws(":");
@@ -758,9 +873,7 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         }
 
         self.indent();
-        for stmt in node.body.iter() {
-            self.visit_stmt_kind(stmt)?;
-        }
+        node.body.recurse_visit(self)?;
         self.outdent();
 
         self.write_ws("END_FUNCTION");
@@ -773,14 +886,32 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         &mut self,
         node: &FunctionBlockDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.write_ws("FUNCTION_BLOCK");
+        if let Some(modifier) = &node.modifier {
+            self.write_ws(modifier.to_string().as_str());
+        }
         self.visit_id(&node.name.name)?;
+        if let Some(base) = &node.extends {
+            self.write_ws("EXTENDS");
+            self.visit_type_name(base)?;
+        }
+        if !node.implements.is_empty() {
+            self.write_ws("IMPLEMENTS");
+            visit_comma_separated!(self, node.implements.iter(), TypeName);
+        }
         self.newline();
 
         self.indent();
         for var in node.variables.iter() {
             self.visit_var_decl(var)?;
         }
+        for method in node.methods.iter() {
+            self.visit_method_declaration(method)?;
+        }
+        for property in node.properties.iter() {
+            self.visit_property_declaration(property)?;
+        }
         self.outdent();
 
         self.indent();
@@ -793,11 +924,121 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         Ok(())
     }
 
+    fn visit_method_declaration(
+        &mut self,
+        node: &MethodDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.newline();
+        self.write_attributes(&node.attributes)?;
+        self.write_ws("METHOD");
+        if let Some(access) = &node.access {
+            self.write_ws(access.to_string().as_str());
+        }
+        if let Some(modifier) = &node.modifier {
+            self.write_ws(modifier.to_string().as_str());
+        }
+        self.visit_id(&node.name)?;
+        if let Some(return_type) = &node.return_type {
+            self.write_ws(":");
+            self.visit_type_name(return_type)?;
+        }
+        self.newline();
+
+        self.indent();
+        for var in node.variables.iter() {
+            self.visit_var_decl(var)?;
+        }
+        node.body.recurse_visit(self)?;
+        self.outdent();
+
+        self.write_ws("END_METHOD");
+        self.newline();
+        Ok(())
+    }
+
+    fn visit_property_declaration(
+        &mut self,
+        node: &PropertyDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.newline();
+        self.write_attributes(&node.attributes)?;
+        self.write_ws("PROPERTY");
+        if let Some(access) = &node.access {
+            self.write_ws(access.to_string().as_str());
+        }
+        self.visit_id(&node.name)?;
+        self.write_ws(":");
+        self.visit_type_name(&node.property_type)?;
+        self.newline();
+
+        self.indent();
+        if let Some(getter) = &node.getter {
+            self.write_ws("GET");
+            self.newline();
+            self.visit_property_accessor(getter)?;
+            self.write_ws("END_GET");
+            self.newline();
+        }
+        if let Some(setter) = &node.setter {
+            self.write_ws("SET");
+            self.newline();
+            self.visit_property_accessor(setter)?;
+            self.write_ws("END_SET");
+            self.newline();
+        }
+        self.outdent();
+
+        self.write_ws("END_PROPERTY");
+        self.newline();
+        Ok(())
+    }
+
+    fn visit_property_accessor(
+        &mut self,
+        node: &PropertyAccessor,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.indent();
+        for var in node.variables.iter() {
+            self.visit_var_decl(var)?;
+        }
+        node.body.recurse_visit(self)?;
+        self.outdent();
+        Ok(())
+    }
+
+    fn visit_interface_declaration(
+        &mut self,
+        node: &InterfaceDeclaration,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
+        self.write_ws("INTERFACE");
+        self.visit_id(&node.name.name)?;
+        if !node.extends.is_empty() {
+            self.write_ws("EXTENDS");
+            visit_comma_separated!(self, node.extends.iter(), TypeName);
+        }
+        self.newline();
+
+        self.indent();
+        for method in node.methods.iter() {
+            self.visit_method_declaration(method)?;
+        }
+        for property in node.properties.iter() {
+            self.visit_property_declaration(property)?;
+        }
+        self.outdent();
+
+        self.write_ws("END_INTERFACE");
+        self.newline();
+        Ok(())
+    }
+
     // 2.5.3
     fn visit_program_declaration(
         &mut self,
         node: &ProgramDeclaration,
     ) -> Result<Self::Value, Diagnostic> {
+        self.write_attributes(&node.attributes)?;
         self.write_ws("PROGRAM");
 
         self.visit_id(&node.name)?;
@@ -1110,6 +1351,149 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         Ok(())
     }
 
+    // 3.2.1
+    fn visit_il_instruction(&mut self, node: &IlInstruction) -> Result<Self::Value, Diagnostic> {
+        if let Some(label) = &node.label {
+            self.visit_id(label)?;
+            self.write(":");
+        }
+        self.visit_il_instruction_kind(&node.kind)?;
+        self.newline();
+        Ok(())
+    }
+
+    // 3.2.2
+    fn visit_il_simple_operation(
+        &mut self,
+        node: &IlSimpleOperation,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_ws(node.operator.to_string().as_str());
+        if let Some(operand) = &node.operand {
+            self.visit_il_operand(operand)?;
+        }
+        Ok(())
+    }
+
+    // 3.2.2
+    fn visit_il_expression(&mut self, node: &IlExpression) -> Result<Self::Value, Diagnostic> {
+        self.write_ws(node.operator.to_string().as_str());
+        self.write("(");
+        if let Some(operand) = &node.operand {
+            self.visit_il_operand(operand)?;
+        }
+        if !node.body.is_empty() {
+            self.newline();
+            self.indent();
+            for item in node.body.iter() {
+                self.visit_il_simple_instruction_kind(item)?;
+                self.newline();
+            }
+            self.outdent();
+        }
+        self.write_ws(")");
+        Ok(())
+    }
+
+    // 3.2.2
+    fn visit_il_jump_operation(
+        &mut self,
+        node: &IlJumpOperation,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_ws(node.operator.to_string().as_str());
+        self.visit_id(&node.target)
+    }
+
+    // 3.2.2
+    fn visit_il_return_operation(
+        &mut self,
+        node: &IlReturnOperation,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_ws(node.operator.to_string().as_str());
+        Ok(())
+    }
+
+    // 3.2.3
+    fn visit_il_function_call(&mut self, node: &IlFunctionCall) -> Result<Self::Value, Diagnostic> {
+        self.visit_id(&node.name)?;
+        visit_comma_separated!(self, node.operands.iter(), IlOperand);
+        Ok(())
+    }
+
+    // 3.2.3
+    fn visit_il_formal_function_call(
+        &mut self,
+        node: &IlFormalFunctionCall,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.visit_id(&node.name)?;
+        self.write("(");
+        self.write_il_formal_params(&node.params)?;
+        self.write_ws(")");
+        Ok(())
+    }
+
+    // 3.2.3
+    fn visit_il_fb_call(&mut self, node: &IlFbCall) -> Result<Self::Value, Diagnostic> {
+        self.write_ws(node.operator.to_string().as_str());
+        self.visit_id(&node.name)?;
+
+        if node.params.is_empty() {
+            return Ok(());
+        }
+
+        self.write("(");
+        if node
+            .params
+            .iter()
+            .all(|p| matches!(p, IlParamAssignmentKind::Positional(_)))
+        {
+            visit_comma_separated!(self, node.params.iter(), IlParamAssignmentKind);
+        } else {
+            self.write_il_formal_params(&node.params)?;
+        }
+        self.write_ws(")");
+        Ok(())
+    }
+
+    // 3.2.3
+    fn visit_il_input_param_assignment(
+        &mut self,
+        node: &IlInputParamAssignment,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.visit_id(&node.name)?;
+        self.write_ws(":=");
+        self.visit_il_param_value_kind(&node.value)
+    }
+
+    // 3.2.3
+    fn visit_il_simple_instruction_list(
+        &mut self,
+        node: &IlSimpleInstructionList,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_ws("(");
+        self.newline();
+        self.indent();
+        for item in node.body.iter() {
+            self.visit_il_simple_instruction_kind(item)?;
+            self.newline();
+        }
+        self.outdent();
+        self.write_ws(")");
+        Ok(())
+    }
+
+    // 3.2.3
+    fn visit_il_output_param_assignment(
+        &mut self,
+        node: &IlOutputParamAssignment,
+    ) -> Result<Self::Value, Diagnostic> {
+        if node.not {
+            self.write_ws("NOT");
+        }
+        self.visit_id(&node.src)?;
+        self.write_ws("=>");
+        self.visit_variable(&node.tgt)
+    }
+
     // 3.2.3
     fn visit_fb_call(&mut self, node: &dsl::textual::FbCall) -> Result<Self::Value, Diagnostic> {
         self.visit_id(&node.var_name)?;
@@ -1121,6 +1505,73 @@ impl Visitor<Diagnostic> for LibraryRenderer {
         Ok(())
     }
 
+    fn visit_named_input(
+        &mut self,
+        node: &dsl::textual::NamedInput,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.visit_id(&node.name)?;
+        self.write_ws(":=");
+        self.visit_expr_kind(&node.expr)
+    }
+
+    fn visit_output(&mut self, node: &dsl::textual::Output) -> Result<Self::Value, Diagnostic> {
+        if node.not {
+            self.write_ws("NOT");
+        }
+        self.visit_id(&node.src)?;
+        self.write_ws("=>");
+        self.visit_variable(&node.tgt)
+    }
+
+    fn visit_method_call(
+        &mut self,
+        node: &dsl::textual::MethodCall,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.visit_symbolic_variable_kind(&node.receiver)?;
+        self.write(".");
+        self.write(node.name.original().as_str());
+
+        self.write_ws("(");
+        visit_comma_separated!(self, node.params.iter(), ParamAssignmentKind);
+        self.write_ws(")");
+
+        Ok(())
+    }
+
+    fn visit_ref_expr(&mut self, node: &dsl::textual::RefExpr) -> Result<Self::Value, Diagnostic> {
+        self.write_ws("REF");
+        self.write_ws("(");
+        self.visit_symbolic_variable_kind(&node.variable)?;
+        self.write_ws(")");
+        Ok(())
+    }
+
+    fn visit_null_literal(
+        &mut self,
+        _node: &dsl::textual::NullLiteral,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_ws("NULL");
+        Ok(())
+    }
+
+    fn visit_stmt_kind(
+        &mut self,
+        node: &dsl::textual::StmtKind,
+    ) -> Result<Self::Value, Diagnostic> {
+        node.recurse_visit(self)?;
+
+        // Invocations are shared with expressions so the statement
+        // terminator is written here.
+        match node {
+            dsl::textual::StmtKind::FbCall(_) | dsl::textual::StmtKind::MethodCall(_) => {
+                self.write_ws(";");
+                self.newline();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     // 3.3.2.1
     fn visit_assignment(
         &mut self,
@@ -1406,4 +1857,29 @@ impl Visitor<Diagnostic> for LibraryRenderer {
 
         Ok(())
     }
+
+    fn visit_this_variable(
+        &mut self,
+        _node: &dsl::textual::ThisVariable,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_ws("THIS");
+        Ok(())
+    }
+
+    fn visit_super_variable(
+        &mut self,
+        _node: &dsl::textual::SuperVariable,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.write_ws("SUPER");
+        Ok(())
+    }
+
+    fn visit_deref_variable(
+        &mut self,
+        node: &dsl::textual::DerefVariable,
+    ) -> Result<Self::Value, Diagnostic> {
+        self.visit_symbolic_variable_kind(&node.reference)?;
+        self.write("^");
+        Ok(())
+    }
 }
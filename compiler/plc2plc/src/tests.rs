@@ -75,6 +75,13 @@ mod test {
         assert_eq!(rendered, expected);
     }
 
+    #[test]
+    fn write_to_string_il() {
+        let rendered = parse_and_render_resource("il.st");
+        let expected = read_resource("il_rendered.st");
+        assert_eq!(rendered, expected);
+    }
+
     #[test]
     fn write_to_string_nested() {
         let rendered = parse_and_render_resource("nested.st");
@@ -82,6 +89,13 @@ mod test {
         assert_eq!(rendered, expected);
     }
 
+    #[test]
+    fn write_to_string_object_oriented() {
+        let rendered = parse_and_render_resource("object_oriented.st");
+        let expected = read_resource("object_oriented_rendered.st");
+        assert_eq!(rendered, expected);
+    }
+
     #[test]
     fn write_to_string_program() {
         let rendered = parse_and_render_resource("program.st");
@@ -89,6 +103,20 @@ mod test {
         assert_eq!(rendered, expected);
     }
 
+    #[test]
+    fn write_to_string_pragma() {
+        let rendered = parse_and_render_resource("pragma.st");
+        let expected = read_resource("pragma_rendered.st");
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn write_to_string_reference() {
+        let rendered = parse_and_render_resource("reference.st");
+        let expected = read_resource("reference_rendered.st");
+        assert_eq!(rendered, expected);
+    }
+
     #[test]
     fn write_to_string_sfc() {
         let rendered = parse_and_render_resource("sfc.st");
@@ -134,6 +162,7 @@ mod test {
         let late_bound_decl = LateBoundDeclaration {
             data_type_name: TypeName::from("MY_ALIAS"),
             base_type_name: TypeName::from("INT"),
+            attributes: vec![],
         };
 
         let library = Library {
@@ -149,4 +178,45 @@ mod test {
         let expected = "TYPE\n   MY_ALIAS : INT ;\nEND_TYPE\n";
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn write_var_decl_to_string_when_initial_value_then_declaration_block() {
+        use dsl::common::*;
+
+        let decl = VarDecl {
+            identifier: VariableIdentifier::new_symbol("counter"),
+            var_type: VariableType::Input,
+            qualifier: DeclarationQualifier::Unspecified,
+            initializer: InitialValueAssignmentKind::simple(
+                "INT",
+                ConstantKind::IntegerLiteral(IntegerLiteral {
+                    value: SignedInteger::new("5", dsl::core::SourceSpan::default()).unwrap(),
+                    data_type: None,
+                }),
+            ),
+            attributes: vec![],
+            keyword_span: dsl::core::SourceSpan::default(),
+        };
+
+        let result = crate::write_var_decl_to_string(&decl).unwrap();
+
+        assert_eq!("VAR_INPUT\n   counter : INT := 5;\nEND_VAR", result);
+    }
+
+    #[test]
+    fn write_data_type_declaration_to_string_when_enumeration_then_type_block() {
+        use dsl::common::*;
+
+        let decl = DataTypeDeclarationKind::Enumeration(EnumerationDeclaration {
+            type_name: TypeName::from("LEVEL"),
+            spec_init: EnumeratedSpecificationInit::values_and_default(vec!["LOW", "HIGH"], "LOW"),
+            attributes: vec![],
+        });
+
+        let result = crate::write_data_type_declaration_to_string(&decl).unwrap();
+
+        assert!(result.starts_with("TYPE\n   LEVEL :"));
+        assert!(result.contains("HIGH"));
+        assert!(result.ends_with("END_TYPE"));
+    }
 }
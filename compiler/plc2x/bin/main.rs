@@ -2,11 +2,15 @@ use std::path::PathBuf;
 
 use clap::Parser;
 
+use ironplc_parser::options::ParseOptions;
 use ironplcc::cli;
+use ironplcc::config::ProjectConfiguration;
 use ironplcc::logger;
 use ironplcc::lsp;
 use ironplcc::lsp_project::LspProject;
 use ironplcc::project::FileBackedProject;
+use ironplcc::report::OutputFormat;
+use ironplcc::rules::RuleSet;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -21,11 +25,85 @@ struct Args {
     #[arg(short, long)]
     log_file: Option<PathBuf>,
 
+    /// Sets the project configuration file. If not set, then uses the
+    /// `ironplc.toml` file in the current directory or the nearest parent
+    /// directory that has the file.
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+
     /// Selects the subcommand.
     #[command(subcommand)]
     action: Action,
 }
 
+/// Options that affect how files are parsed.
+#[derive(clap::Args, Debug)]
+struct ParseArgs {
+    /// Defines a name for conditional compilation, for example
+    /// `{IF defined(NAME)}`. Repeat to define more than one name.
+    #[arg(short = 'D', long = "define", value_name = "NAME")]
+    defines: Vec<String>,
+}
+
+impl ParseArgs {
+    /// Returns the options from the project configuration combined with
+    /// the options from the command line.
+    fn options(&self, config: Option<&ProjectConfiguration>) -> ParseOptions {
+        let mut options = config
+            .map(|config| config.parse_options().clone())
+            .unwrap_or_default();
+        options.defines.extend(self.defines.iter().cloned());
+        options
+    }
+}
+
+/// Options that affect which problems are reported.
+#[derive(clap::Args, Debug)]
+struct RuleArgs {
+    /// Sets the severity of a problem code, for example `P4007=warning`.
+    /// The severity is one of error, warning or off. Repeat to set more than
+    /// one problem code.
+    #[arg(long = "severity", value_name = "CODE=LEVEL")]
+    severities: Vec<String>,
+}
+
+impl RuleArgs {
+    /// Returns the rules from the project configuration combined with
+    /// the rules from the command line. The command line rules take
+    /// precedence.
+    fn rules(&self, config: Option<&ProjectConfiguration>) -> Result<RuleSet, String> {
+        let mut rules = config
+            .map(|config| config.rules().clone())
+            .unwrap_or_default();
+        for severity in &self.severities {
+            rules.set_from_str(severity)?;
+        }
+        Ok(rules)
+    }
+}
+
+/// The format for reporting diagnostics.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+enum FormatArg {
+    /// Human readable text.
+    #[default]
+    Text,
+    /// A JSON document with the diagnostics.
+    Json,
+    /// A Static Analysis Results Interchange Format (SARIF) document.
+    Sarif,
+}
+
+impl From<FormatArg> for OutputFormat {
+    fn from(value: FormatArg) -> Self {
+        match value {
+            FormatArg::Text => OutputFormat::Text,
+            FormatArg::Json => OutputFormat::Json,
+            FormatArg::Sarif => OutputFormat::Sarif,
+        }
+    }
+}
+
 #[derive(clap::Subcommand, Debug)]
 enum Action {
     /// The check action checks a file (or set of files) for syntax and semantic correctness.
@@ -36,16 +114,31 @@ enum Action {
         /// Files to include in the check. Directory names can be given to
         /// add all files in the given directory.
         files: Vec<PathBuf>,
+
+        #[command(flatten)]
+        parse: ParseArgs,
+
+        #[command(flatten)]
+        rules: RuleArgs,
+
+        /// Sets the format for reporting diagnostics. The JSON and SARIF
+        /// formats write to the standard output.
+        #[arg(long, value_enum, default_value_t = FormatArg::Text)]
+        format: FormatArg,
     },
     /// The echo action reads (parses) the libraries and writes the context to the
     /// standard output.
     ///
     /// The echo acton is primarily for diagnostics to understand the internal
-    /// structure of the parsed files.
+    /// structure of the parsed files. Comments are not written and pragmas
+    /// are only written when they precede a declaration, variable or POU.
     Echo {
         /// Files to include in the check. Directory names can be given to
         /// add all files in the given directory.
         files: Vec<PathBuf>,
+
+        #[command(flatten)]
+        parse: ParseArgs,
     },
     /// The tokenize action checks a file if it can be tokenized with all content
     /// matching a token.
@@ -55,6 +148,9 @@ enum Action {
     Tokenize {
         /// Files to tokenize.
         files: Vec<PathBuf>,
+
+        #[command(flatten)]
+        parse: ParseArgs,
     },
     /// Run in Language Server Protocol mode to integrate with development tools.
     Lsp {
@@ -76,12 +172,55 @@ pub fn main() -> Result<(), String> {
             let proj = LspProject::new(Box::<FileBackedProject>::default());
             lsp::start(proj)
         }
-        Action::Check { files } => cli::check(&files, false),
-        Action::Echo { files } => cli::echo(&files, false),
-        Action::Tokenize { files } => cli::tokenize(&files, false),
+        Action::Check {
+            files,
+            parse,
+            rules,
+            format,
+        } => {
+            let format = format.into();
+            let config = cli::load_configuration(args.config.as_deref(), format, false)?;
+            let config = config.as_ref();
+            cli::check(
+                &files_or_configured(files, config),
+                &parse.options(config),
+                &rules.rules(config)?,
+                format,
+                false,
+            )
+        }
+        Action::Echo { files, parse } => {
+            let config =
+                cli::load_configuration(args.config.as_deref(), OutputFormat::Text, false)?;
+            let config = config.as_ref();
+            cli::echo(
+                &files_or_configured(files, config),
+                &parse.options(config),
+                false,
+            )
+        }
+        Action::Tokenize { files, parse } => {
+            let config =
+                cli::load_configuration(args.config.as_deref(), OutputFormat::Text, false)?;
+            let config = config.as_ref();
+            cli::tokenize(
+                &files_or_configured(files, config),
+                &parse.options(config),
+                false,
+            )
+        }
         Action::Version => {
             println!("ironplcc version {VERSION}");
             Ok(())
         }
     }
 }
+
+/// Returns the files from the command line or, if there are no files on the
+/// command line, the files from the project configuration.
+fn files_or_configured(files: Vec<PathBuf>, config: Option<&ProjectConfiguration>) -> Vec<PathBuf> {
+    match (files.is_empty(), config) {
+        (true, Some(config)) => config.files(),
+        _ => files,
+    }
+}
@@ -14,33 +14,82 @@ use ironplc_dsl::{
 };
 use ironplc_parser::{options::ParseOptions, tokenize_program};
 use ironplc_plc2plc::write_to_string;
-use ironplc_problems::Problem;
+use ironplc_problems::{Problem, Severity as ProblemSeverity};
 use log::{debug, error, trace};
 use std::{
     collections::{HashMap, HashSet},
+    env::current_dir,
     fs::{canonicalize, metadata, read_dir},
     ops::Range,
     path::{Path, PathBuf},
 };
 
-use crate::project::{FileBackedProject, Project};
+use crate::{
+    config::ProjectConfiguration,
+    project::{FileBackedProject, Project},
+    report::{self, OutputFormat},
+    rules::RuleSet,
+};
 
-// Checks specified files.
-pub fn check(paths: &[PathBuf], suppress_output: bool) -> Result<(), String> {
-    let mut project = create_project(paths, suppress_output)?;
+/// Loads the project configuration.
+///
+/// The project configuration is the file at the path if the path is given.
+/// Otherwise, the project configuration is the configuration file found
+/// by searching from the current directory. Returns `None` if the path is
+/// not given and there is no configuration file. Problems with the
+/// configuration are reported in the format.
+pub fn load_configuration(
+    path: Option<&Path>,
+    format: OutputFormat,
+    suppress_output: bool,
+) -> Result<Option<ProjectConfiguration>, String> {
+    let path = match path {
+        Some(path) => Some(path.to_path_buf()),
+        None => current_dir()
+            .ok()
+            .and_then(|dir| ProjectConfiguration::discover(&dir)),
+    };
 
-    // Analyze the set
-    if let Err(err) = project.semantic() {
-        trace!("Errors {err:?}");
-        handle_diagnostics(&err, Some(&project), suppress_output);
+    match path {
+        Some(path) => {
+            debug!("Using project configuration {}", path.display());
+            ProjectConfiguration::load(&path).map(Some).map_err(|err| {
+                report(&err, None, format, suppress_output);
+                String::from("Error reading project configuration")
+            })
+        }
+        None => Ok(None),
+    }
+}
+
+// Checks specified files.
+pub fn check(
+    paths: &[PathBuf],
+    options: &ParseOptions,
+    rules: &RuleSet,
+    format: OutputFormat,
+    suppress_output: bool,
+) -> Result<(), String> {
+    let mut project = create_project(paths, options, format, suppress_output)?;
+    project.set_rules(rules.clone());
+
+    // Analyze the set. Warnings are reported but do not fail the check.
+    let diagnostics = project.semantic().err().unwrap_or_default();
+    trace!("Diagnostics {diagnostics:?}");
+    report(&diagnostics, Some(&project), format, suppress_output);
+    if diagnostics.iter().any(|d| d.is_error()) {
         return Err(String::from("Error during analysis"));
     }
 
     Ok(())
 }
 
-pub fn echo(paths: &[PathBuf], suppress_output: bool) -> Result<(), String> {
-    let mut project = create_project(paths, suppress_output)?;
+pub fn echo(
+    paths: &[PathBuf],
+    options: &ParseOptions,
+    suppress_output: bool,
+) -> Result<(), String> {
+    let mut project = create_project(paths, options, OutputFormat::Text, suppress_output)?;
 
     // Collect the results and output after because getting the results may change
     // the project itself
@@ -79,18 +128,16 @@ pub fn echo(paths: &[PathBuf], suppress_output: bool) -> Result<(), String> {
     }
 }
 
-pub fn tokenize(paths: &[PathBuf], suppress_output: bool) -> Result<(), String> {
-    let project = create_project(paths, suppress_output)?;
+pub fn tokenize(
+    paths: &[PathBuf],
+    options: &ParseOptions,
+    suppress_output: bool,
+) -> Result<(), String> {
+    let project = create_project(paths, options, OutputFormat::Text, suppress_output)?;
 
     // Write the set
     for src in project.sources() {
-        let (tokens, diagnostics) = tokenize_program(
-            src.as_string(),
-            src.file_id(),
-            &ParseOptions::default(),
-            0,
-            0,
-        );
+        let (tokens, diagnostics) = tokenize_program(src.as_string(), src.file_id(), options, 0, 0);
 
         let tokens = tokens
             .iter()
@@ -111,7 +158,12 @@ pub fn tokenize(paths: &[PathBuf], suppress_output: bool) -> Result<(), String>
     Ok(())
 }
 
-fn create_project(paths: &[PathBuf], suppress_output: bool) -> Result<FileBackedProject, String> {
+fn create_project(
+    paths: &[PathBuf],
+    options: &ParseOptions,
+    format: OutputFormat,
+    suppress_output: bool,
+) -> Result<FileBackedProject, String> {
     trace!("Reading paths {paths:?}");
     let mut files: Vec<PathBuf> = vec![];
     let mut had_error = false;
@@ -120,7 +172,7 @@ fn create_project(paths: &[PathBuf], suppress_output: bool) -> Result<FileBacked
         match enumerate_files(path) {
             Ok(mut paths) => files.append(&mut paths),
             Err(err) => {
-                handle_diagnostics(&err, None, suppress_output);
+                report(&err, None, format, suppress_output);
                 had_error = true;
             }
         }
@@ -132,6 +184,7 @@ fn create_project(paths: &[PathBuf], suppress_output: bool) -> Result<FileBacked
 
     // Create the project
     let mut project = FileBackedProject::new();
+    project.set_parse_options(options.clone());
     let mut errors: Vec<Diagnostic> = vec![];
 
     for file_path in files {
@@ -145,7 +198,7 @@ fn create_project(paths: &[PathBuf], suppress_output: bool) -> Result<FileBacked
     }
 
     if !errors.is_empty() {
-        handle_diagnostics(&errors, Some(&project), suppress_output);
+        report(&errors, Some(&project), format, suppress_output);
         return Err(String::from("Error reading source files"));
     }
 
@@ -194,6 +247,27 @@ fn enumerate_files(path: &PathBuf) -> Result<Vec<PathBuf>, Vec<Diagnostic>> {
     Ok(vec![])
 }
 
+/// Writes the diagnostics in the format. Text is written to the standard
+/// error and the machine-readable formats are written to the standard output.
+fn report(
+    diagnostics: &[Diagnostic],
+    project: Option<&FileBackedProject>,
+    format: OutputFormat,
+    suppress_output: bool,
+) {
+    let document = match format {
+        OutputFormat::Text => return handle_diagnostics(diagnostics, project, suppress_output),
+        OutputFormat::Json => report::to_json(diagnostics, project),
+        OutputFormat::Sarif => report::to_sarif(diagnostics, project),
+    };
+    if !suppress_output {
+        match serde_json::to_string_pretty(&document) {
+            Ok(document) => println!("{document}"),
+            Err(err) => error!("Failed writing report: {err}"),
+        }
+    }
+}
+
 /// Converts an IronPLC diagnostic into the
 fn handle_diagnostics(
     diagnostics: &[Diagnostic],
@@ -256,15 +330,24 @@ fn map_diagnostic(
         file_to_id,
     )];
 
-    // Add any secondary labels
+    // Add any secondary labels. Secondary labels may refer to declarations
+    // that are not in a source file (such as standard function blocks) and
+    // so there is nothing to show for those labels.
     labels.extend(
         diagnostic
             .secondary
             .iter()
+            .filter(|lbl| file_to_id.contains_key(&lbl.file_id))
             .map(|lbl| map_label(lbl, LabelStyle::Secondary, file_to_id)),
     );
 
-    CodeSpanDiagnostic::new(Severity::Error)
+    let severity = match diagnostic.severity {
+        ProblemSeverity::Error => Severity::Error,
+        ProblemSeverity::Warning => Severity::Warning,
+        ProblemSeverity::Info => Severity::Note,
+    };
+
+    CodeSpanDiagnostic::new(severity)
         .with_code(diagnostic.code.clone())
         .with_message(description)
         .with_labels(labels)
@@ -292,49 +375,134 @@ fn diagnostic(problem: Problem, path: &Path, message: String) -> Vec<Diagnostic>
 
 #[cfg(test)]
 mod tests {
+    use ironplc_parser::options::ParseOptions;
     use ironplc_test::shared_resource_path;
 
-    use crate::{cli::check, cli::echo, test_helpers::resource_path};
+    use crate::{
+        cli::{check, echo, load_configuration},
+        report::OutputFormat,
+        rules::{RuleLevel, RuleSet},
+        test_helpers::resource_path,
+    };
 
     #[test]
     fn check_first_steps_when_invalid_syntax_then_error() {
         let paths = vec![shared_resource_path("first_steps_semantic_error.st")];
-        let result = check(&paths, true);
+        let result = check(
+            &paths,
+            &ParseOptions::default(),
+            &RuleSet::new(),
+            OutputFormat::Text,
+            true,
+        );
         assert!(result.is_err())
     }
 
     #[test]
     fn check_first_steps_when_valid_syntax_then_ok() {
         let paths = vec![shared_resource_path("first_steps.st")];
-        let result = check(&paths, true);
+        let result = check(
+            &paths,
+            &ParseOptions::default(),
+            &RuleSet::new(),
+            OutputFormat::Text,
+            true,
+        );
         assert!(result.is_ok())
     }
 
     #[test]
     fn check_first_steps_dir_when_valid_syntax_then_ok() {
         let paths = vec![resource_path("set")];
-        let result = check(&paths, true);
+        let result = check(
+            &paths,
+            &ParseOptions::default(),
+            &RuleSet::new(),
+            OutputFormat::Text,
+            true,
+        );
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn check_conditional_when_defined_then_ok() {
+        let paths = vec![shared_resource_path("conditional_compilation.st")];
+        let options = ParseOptions {
+            defines: vec![String::from("VARIANT_A")],
+            ..ParseOptions::default()
+        };
+        let result = check(&paths, &options, &RuleSet::new(), OutputFormat::Text, true);
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn check_first_steps_when_semantic_error_is_warning_then_ok() {
+        let paths = vec![shared_resource_path("first_steps_semantic_error.st")];
+        let mut rules = RuleSet::new();
+        rules.set("P2006", RuleLevel::Warning).unwrap();
+        let result = check(
+            &paths,
+            &ParseOptions::default(),
+            &rules,
+            OutputFormat::Text,
+            true,
+        );
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn check_project_when_configured_then_ok() {
+        let config = load_configuration(
+            Some(&resource_path("project").join("ironplc.toml")),
+            OutputFormat::Text,
+            true,
+        )
+        .unwrap()
+        .unwrap();
+        let result = check(
+            &config.files(),
+            config.parse_options(),
+            config.rules(),
+            OutputFormat::Text,
+            true,
+        );
         assert!(result.is_ok())
     }
 
+    #[test]
+    fn load_configuration_when_not_exists_then_err() {
+        let result = load_configuration(
+            Some(&resource_path("ironplc.toml")),
+            OutputFormat::Text,
+            true,
+        );
+        assert!(result.is_err())
+    }
+
     #[test]
     fn echo_first_steps_when_invalid_syntax_then_error() {
         let paths = vec![shared_resource_path("first_steps_syntax_error.st")];
-        let result = check(&paths, true);
+        let result = check(
+            &paths,
+            &ParseOptions::default(),
+            &RuleSet::new(),
+            OutputFormat::Text,
+            true,
+        );
         assert!(result.is_err())
     }
 
     #[test]
     fn echo_first_steps_when_valid_syntax_then_ok() {
         let paths = vec![shared_resource_path("first_steps.st")];
-        let result = echo(&paths, true);
+        let result = echo(&paths, &ParseOptions::default(), true);
         assert!(result.is_ok())
     }
 
     #[test]
     fn tokenize_first_steps_when_valid_syntax_then_ok() {
         let paths = vec![shared_resource_path("first_steps.st")];
-        let result = echo(&paths, true);
+        let result = echo(&paths, &ParseOptions::default(), true);
         assert!(result.is_ok())
     }
 }
@@ -0,0 +1,326 @@
+//! Implements the project configuration file (`ironplc.toml`).
+//!
+//! The project configuration file declares the files in a project and the
+//! options for the project so that the command line and the language server
+//! use the same configuration. A project configuration file is:
+//!
+//! ```toml
+//! [sources]
+//! include = ["src/**/*.st"]
+//! exclude = ["src/generated/**"]
+//!
+//! [dialect]
+//! allow_c_style_comments = true
+//! defines = ["VARIANT_A"]
+//!
+//! [libraries]
+//! paths = ["../common"]
+//!
+//! [rules]
+//! P4007 = "warning"
+//! ```
+//!
+//! Paths and patterns are relative to the directory that contains the
+//! project configuration file.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use glob::Pattern;
+use ironplc_dsl::{
+    core::FileId,
+    diagnostic::{Diagnostic, Label},
+};
+use ironplc_parser::options::ParseOptions;
+use ironplc_problems::Problem;
+use ironplc_sources::FileType;
+use log::warn;
+use serde::Deserialize;
+
+use crate::rules::RuleSet;
+
+/// The name of the project configuration file.
+pub const CONFIGURATION_FILE_NAME: &str = "ironplc.toml";
+
+/// The pattern for the sources when the configuration does not declare
+/// the sources.
+const DEFAULT_INCLUDE: &str = "**/*";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigurationFile {
+    sources: SourcesSection,
+    dialect: DialectSection,
+    libraries: LibrariesSection,
+    rules: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SourcesSection {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DialectSection {
+    allow_c_style_comments: bool,
+    defines: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LibrariesSection {
+    paths: Vec<PathBuf>,
+}
+
+/// A validated project configuration.
+#[derive(Debug)]
+pub struct ProjectConfiguration {
+    /// The directory that contains the configuration file.
+    dir: PathBuf,
+    include: Vec<String>,
+    exclude: Vec<Pattern>,
+    library_paths: Vec<PathBuf>,
+    parse_options: ParseOptions,
+    rules: RuleSet,
+}
+
+impl ProjectConfiguration {
+    /// Finds the project configuration file by searching the directory and
+    /// then the ancestors of the directory.
+    pub fn discover(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIGURATION_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Reads and validates the project configuration file.
+    pub fn load(path: &Path) -> Result<Self, Vec<Diagnostic>> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            vec![Diagnostic::problem(
+                Problem::CannotReadFile,
+                Label::file(FileId::from_path(path), err.to_string()),
+            )]
+        })?;
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self::parse(&content, &dir, &FileId::from_path(path))
+    }
+
+    /// Parses and validates the content of a project configuration file.
+    fn parse(content: &str, dir: &Path, file_id: &FileId) -> Result<Self, Vec<Diagnostic>> {
+        let file: ConfigurationFile = toml::from_str(content)
+            .map_err(|err| vec![invalid(file_id, err.message().to_string())])?;
+
+        let mut errors = vec![];
+
+        let include = match file.sources.include.is_empty() {
+            true => vec![DEFAULT_INCLUDE.to_string()],
+            false => file.sources.include,
+        };
+        for pattern in &include {
+            if let Err(err) = Pattern::new(pattern) {
+                errors.push(invalid(file_id, format!("Include '{pattern}' {err}")));
+            }
+        }
+
+        let exclude = file
+            .sources
+            .exclude
+            .iter()
+            .filter_map(|pattern| match Pattern::new(pattern) {
+                Ok(pattern) => Some(pattern),
+                Err(err) => {
+                    errors.push(invalid(file_id, format!("Exclude '{pattern}' {err}")));
+                    None
+                }
+            })
+            .collect();
+
+        let mut rules = RuleSet::new();
+        // Sort so that errors are in a consistent order.
+        let mut levels: Vec<_> = file.rules.iter().collect();
+        levels.sort();
+        for (code, level) in levels {
+            if let Err(err) = level.parse().and_then(|level| rules.set(code, level)) {
+                errors.push(invalid(file_id, err));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            include,
+            exclude,
+            library_paths: file.libraries.paths,
+            parse_options: ParseOptions {
+                allow_c_style_comments: file.dialect.allow_c_style_comments,
+                defines: file.dialect.defines,
+            },
+            rules,
+        })
+    }
+
+    /// Returns the options for parsing sources in the project.
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.parse_options
+    }
+
+    /// Returns the overrides for the severity of problems.
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// Returns the supported source files that match the include patterns
+    /// and do not match an exclude pattern, followed by the supported files
+    /// in the library paths.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = vec![];
+
+        for include in &self.include {
+            let pattern = self.dir.join(include);
+            files.extend(
+                glob_files(&pattern)
+                    .into_iter()
+                    .filter(|path| !self.is_excluded(path)),
+            );
+        }
+
+        for library in &self.library_paths {
+            files.extend(glob_files(&self.dir.join(library).join(DEFAULT_INCLUDE)));
+        }
+
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.dir).unwrap_or(path);
+        self.exclude
+            .iter()
+            .any(|pattern| pattern.matches_path(relative))
+    }
+}
+
+/// Returns the supported files that match the pattern.
+fn glob_files(pattern: &Path) -> Vec<PathBuf> {
+    let pattern = pattern.to_string_lossy();
+    match glob::glob(&pattern) {
+        Ok(paths) => paths
+            .filter_map(|entry| match entry {
+                Ok(path) => Some(path),
+                Err(err) => {
+                    warn!("Unable to read path {err}");
+                    None
+                }
+            })
+            .filter(|path| path.is_file() && FileType::from_path(path).is_supported())
+            .collect(),
+        Err(err) => {
+            warn!("Pattern '{pattern}' is not valid: {err}");
+            vec![]
+        }
+    }
+}
+
+fn invalid(file_id: &FileId, message: String) -> Diagnostic {
+    Diagnostic::problem(
+        Problem::ProjectConfigurationInvalid,
+        Label::file(file_id.clone(), message),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use ironplc_dsl::core::FileId;
+    use ironplc_problems::Problem;
+
+    use crate::test_helpers::resource_path;
+
+    use super::{ProjectConfiguration, CONFIGURATION_FILE_NAME};
+
+    fn parse(content: &str) -> ProjectConfiguration {
+        ProjectConfiguration::parse(content, Path::new("."), &FileId::default()).unwrap()
+    }
+
+    #[test]
+    fn parse_when_empty_then_default_options() {
+        let config = parse("");
+        assert!(!config.parse_options().allow_c_style_comments);
+        assert!(config.parse_options().defines.is_empty());
+    }
+
+    #[test]
+    fn parse_when_dialect_then_parse_options() {
+        let config = parse(
+            "
+[dialect]
+allow_c_style_comments = true
+defines = [\"VARIANT_A\"]",
+        );
+        assert!(config.parse_options().allow_c_style_comments);
+        assert_eq!(vec!["VARIANT_A"], config.parse_options().defines);
+    }
+
+    #[test]
+    fn parse_when_unknown_key_then_err() {
+        let result = ProjectConfiguration::parse(
+            "[dialect]\nunknown = 1",
+            Path::new("."),
+            &FileId::default(),
+        );
+        let errors = result.unwrap_err();
+        assert_eq!(Problem::ProjectConfigurationInvalid.code(), errors[0].code);
+    }
+
+    #[test]
+    fn parse_when_invalid_rule_then_err() {
+        let result = ProjectConfiguration::parse(
+            "[rules]\nP4007 = \"loud\"\nP0000 = \"off\"",
+            Path::new("."),
+            &FileId::default(),
+        );
+        assert_eq!(2, result.unwrap_err().len());
+    }
+
+    #[test]
+    fn load_when_project_then_files_match_include_and_exclude() {
+        let path = resource_path("project").join(CONFIGURATION_FILE_NAME);
+        let config = ProjectConfiguration::load(&path).unwrap();
+
+        let files: Vec<PathBuf> = config
+            .files()
+            .iter()
+            .map(|f| {
+                f.strip_prefix(resource_path("project"))
+                    .unwrap()
+                    .to_path_buf()
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                PathBuf::from("lib/library.st"),
+                PathBuf::from("src/main.st")
+            ],
+            files
+        );
+        assert_eq!(vec!["VARIANT_A"], config.parse_options().defines);
+    }
+
+    #[test]
+    fn discover_when_in_subdirectory_then_finds_file() {
+        let path = ProjectConfiguration::discover(&resource_path("project").join("src")).unwrap();
+        assert_eq!(resource_path("project").join(CONFIGURATION_FILE_NAME), path);
+    }
+}
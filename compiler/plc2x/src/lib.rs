@@ -5,10 +5,21 @@ extern crate ironplc_dsl;
 extern crate ironplc_parser;
 
 pub mod cli;
+pub mod config;
 pub mod logger;
 pub mod lsp;
+mod lsp_blocks;
+mod lsp_call_hierarchy;
+mod lsp_completion;
+mod lsp_hover;
+mod lsp_inlay_hints;
+mod lsp_instances;
 pub mod lsp_project;
+mod lsp_signature;
+mod lsp_symbols;
 pub mod project;
+pub mod report;
+pub use ironplc_analyzer::rules;
 
 #[cfg(test)]
 mod test_helpers;
@@ -1,22 +1,32 @@
 //! Implements the language server protocol for integration with an IDE such
 //! as Visual Studio Code.
 
-use crossbeam_channel::{Receiver, Sender};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use log::{debug, trace};
 use lsp_server::{Connection, ExtractError, Message, RequestId};
 use lsp_types::{
     notification::{self, Notification, PublishDiagnostics},
     request::{self, Request},
-    InitializeParams, PublishDiagnosticsParams, SemanticTokens, SemanticTokensFullOptions,
-    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensResult,
-    SemanticTokensServerCapabilities, ServerCapabilities, TextDocumentSyncCapability,
-    TextDocumentSyncKind, WorkDoneProgressOptions, WorkspaceFoldersServerCapabilities,
-    WorkspaceServerCapabilities,
+    CallHierarchyServerCapability, CodeActionProviderCapability, CompletionOptions,
+    CompletionResponse, DocumentSymbolResponse, FoldingRangeProviderCapability,
+    GotoDefinitionResponse, HoverProviderCapability, InitializeParams, OneOf,
+    PublishDiagnosticsParams, RenameOptions, SelectionRangeProviderCapability, SemanticTokens,
+    SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions, SemanticTokensResult,
+    SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelpOptions,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Uri, WorkDoneProgressOptions,
+    WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities, WorkspaceSymbolResponse,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::lsp_instances::InstanceTreeRequest;
 use crate::lsp_project::{LspProject, TOKEN_TYPE_LEGEND};
 
+/// The time without changes before analyzing the changed documents. This
+/// avoids analyzing the project for every keystroke.
+const ANALYSIS_DELAY: Duration = Duration::from_millis(250);
+
 /// Start the LSP server with the specified project as the context.
 pub fn start(project: LspProject) -> Result<(), String> {
     let (connection, io_threads) = Connection::stdio();
@@ -68,6 +78,9 @@ fn start_with_connection(connection: Connection, project: LspProject) -> Result<
 struct LspServer<'a> {
     sender: &'a Sender<Message>,
     project: LspProject,
+    /// The documents (and the version of each document) that changed since
+    /// the last analysis.
+    pending: Vec<(Uri, i32)>,
 }
 
 impl<'a> LspServer<'a> {
@@ -77,7 +90,9 @@ impl<'a> LspServer<'a> {
     /// do.
     fn server_capabilities() -> ServerCapabilities {
         ServerCapabilities {
-            text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                TextDocumentSyncKind::INCREMENTAL,
+            )),
             semantic_tokens_provider: Some(
                 SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
                     // We don't report progress in generating tokens so
@@ -93,6 +108,31 @@ impl<'a> LspServer<'a> {
                     full: Some(SemanticTokensFullOptions::Bool(true)),
                 }),
             ),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            references_provider: Some(OneOf::Left(true)),
+            rename_provider: Some(OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            })),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec!["(".to_owned(), ",".to_owned()]),
+                retrigger_characters: None,
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            }),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+            selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+            document_formatting_provider: Some(OneOf::Left(true)),
+            document_range_formatting_provider: Some(OneOf::Left(true)),
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+            completion_provider: Some(CompletionOptions {
+                trigger_characters: Some(vec![".".to_owned(), "#".to_owned()]),
+                ..CompletionOptions::default()
+            }),
             workspace: Some(WorkspaceServerCapabilities {
                 workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                     supported: Some(true),
@@ -105,13 +145,32 @@ impl<'a> LspServer<'a> {
     }
 
     fn new(sender: &'a Sender<Message>, project: LspProject) -> Self {
-        Self { sender, project }
+        Self {
+            sender,
+            project,
+            pending: vec![],
+        }
     }
 
     /// The main event loop. The event loop receives messages from the other
     /// end of the channel.
     fn run(&mut self, receiver: &Receiver<Message>) -> Result<lsp_server::Request, String> {
-        for msg in receiver {
+        loop {
+            let msg = match self.pending.is_empty() {
+                true => match receiver.recv() {
+                    Ok(msg) => msg,
+                    Err(_) => break,
+                },
+                false => match receiver.recv_timeout(ANALYSIS_DELAY) {
+                    Ok(msg) => msg,
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.publish_diagnostics();
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                },
+            };
+
             match msg {
                 lsp_server::Message::Request(req) => {
                     if req.method == request::Shutdown::METHOD {
@@ -132,7 +191,7 @@ impl<'a> LspServer<'a> {
         Err("terminated but no shutdown".to_owned())
     }
 
-    fn handle_request(&self, req: lsp_server::Request) -> &'static str {
+    fn handle_request(&mut self, req: lsp_server::Request) -> &'static str {
         let req_id = req.id.clone();
         let req = match Self::cast_request::<request::Shutdown>(req) {
             Ok(_params) => {
@@ -140,7 +199,7 @@ impl<'a> LspServer<'a> {
             }
             Err(req) => req,
         };
-        let _request = match Self::cast_request::<request::SemanticTokensFullRequest>(req) {
+        let req = match Self::cast_request::<request::SemanticTokensFullRequest>(req) {
             Ok(params) => {
                 let uri = params.text_document.uri;
                 let token_result = self.project.tokenize(&uri);
@@ -166,6 +225,265 @@ impl<'a> LspServer<'a> {
             }
             Err(req) => req,
         };
+        let req = match Self::cast_request::<request::GotoDefinition>(req) {
+            Ok(params) => {
+                let params = params.text_document_position_params;
+                let location = self
+                    .project
+                    .definition(&params.text_document.uri, params.position);
+
+                trace!("GotoDefinition Response {location:?}");
+                self.send_response::<request::GotoDefinition>(
+                    req_id,
+                    location.map(GotoDefinitionResponse::Scalar),
+                );
+
+                return request::GotoDefinition::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::HoverRequest>(req) {
+            Ok(params) => {
+                let params = params.text_document_position_params;
+                let hover = self
+                    .project
+                    .hover(&params.text_document.uri, params.position);
+
+                trace!("HoverRequest Response {hover:?}");
+                self.send_response::<request::HoverRequest>(req_id, hover);
+
+                return request::HoverRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::References>(req) {
+            Ok(params) => {
+                let position = params.text_document_position;
+                let locations = self.project.references(
+                    &position.text_document.uri,
+                    position.position,
+                    params.context.include_declaration,
+                );
+
+                trace!("References Response {locations:?}");
+                self.send_response::<request::References>(req_id, Some(locations));
+
+                return request::References::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::Completion>(req) {
+            Ok(params) => {
+                let position = params.text_document_position;
+                let items = self
+                    .project
+                    .completion(&position.text_document.uri, position.position);
+
+                trace!("Completion Response {items:?}");
+                self.send_response::<request::Completion>(
+                    req_id,
+                    Some(CompletionResponse::Array(items)),
+                );
+
+                return request::Completion::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::SignatureHelpRequest>(req) {
+            Ok(params) => {
+                let position = params.text_document_position_params;
+                let help = self
+                    .project
+                    .signature_help(&position.text_document.uri, position.position);
+
+                trace!("SignatureHelpRequest Response {help:?}");
+                self.send_response::<request::SignatureHelpRequest>(req_id, help);
+
+                return request::SignatureHelpRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::InlayHintRequest>(req) {
+            Ok(params) => {
+                let hints = self
+                    .project
+                    .inlay_hints(&params.text_document.uri, params.range);
+
+                trace!("InlayHintRequest Response {hints:?}");
+                self.send_response::<request::InlayHintRequest>(req_id, Some(hints));
+
+                return request::InlayHintRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::CodeActionRequest>(req) {
+            Ok(params) => {
+                let actions = self
+                    .project
+                    .code_actions(&params.text_document.uri, params.range);
+
+                trace!("CodeActionRequest Response {actions:?}");
+                self.send_response::<request::CodeActionRequest>(req_id, Some(actions));
+
+                return request::CodeActionRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::FoldingRangeRequest>(req) {
+            Ok(params) => {
+                let ranges = self.project.folding_ranges(&params.text_document.uri);
+
+                trace!("FoldingRangeRequest Response {ranges:?}");
+                self.send_response::<request::FoldingRangeRequest>(req_id, Some(ranges));
+
+                return request::FoldingRangeRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::SelectionRangeRequest>(req) {
+            Ok(params) => {
+                let ranges = self
+                    .project
+                    .selection_ranges(&params.text_document.uri, &params.positions);
+
+                trace!("SelectionRangeRequest Response {ranges:?}");
+                self.send_response::<request::SelectionRangeRequest>(req_id, Some(ranges));
+
+                return request::SelectionRangeRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::Formatting>(req) {
+            Ok(params) => {
+                let edits = self.project.formatting(&params.text_document.uri);
+
+                trace!("Formatting Response {edits:?}");
+                self.send_response::<request::Formatting>(req_id, edits);
+
+                return request::Formatting::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::RangeFormatting>(req) {
+            Ok(params) => {
+                let edits = self
+                    .project
+                    .range_formatting(&params.text_document.uri, params.range);
+
+                trace!("RangeFormatting Response {edits:?}");
+                self.send_response::<request::RangeFormatting>(req_id, edits);
+
+                return request::RangeFormatting::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::CallHierarchyPrepare>(req) {
+            Ok(params) => {
+                let items = self.project.prepare_call_hierarchy(
+                    &params.text_document_position_params.text_document.uri,
+                    params.text_document_position_params.position,
+                );
+
+                trace!("CallHierarchyPrepare Response {items:?}");
+                self.send_response::<request::CallHierarchyPrepare>(req_id, items);
+
+                return request::CallHierarchyPrepare::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::CallHierarchyIncomingCalls>(req) {
+            Ok(params) => {
+                let calls = self.project.incoming_calls(&params.item);
+
+                trace!("CallHierarchyIncomingCalls Response {calls:?}");
+                self.send_response::<request::CallHierarchyIncomingCalls>(req_id, Some(calls));
+
+                return request::CallHierarchyIncomingCalls::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::CallHierarchyOutgoingCalls>(req) {
+            Ok(params) => {
+                let calls = self.project.outgoing_calls(&params.item);
+
+                trace!("CallHierarchyOutgoingCalls Response {calls:?}");
+                self.send_response::<request::CallHierarchyOutgoingCalls>(req_id, Some(calls));
+
+                return request::CallHierarchyOutgoingCalls::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<InstanceTreeRequest>(req) {
+            Ok(params) => {
+                let trees = self.project.instance_tree(&params);
+
+                trace!("InstanceTreeRequest Response {trees:?}");
+                self.send_response::<InstanceTreeRequest>(req_id, trees);
+
+                return InstanceTreeRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::DocumentSymbolRequest>(req) {
+            Ok(params) => {
+                let symbols = self.project.document_symbols(&params.text_document.uri);
+
+                trace!("DocumentSymbolRequest Response {symbols:?}");
+                self.send_response::<request::DocumentSymbolRequest>(
+                    req_id,
+                    Some(DocumentSymbolResponse::Nested(symbols)),
+                );
+
+                return request::DocumentSymbolRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::WorkspaceSymbolRequest>(req) {
+            Ok(params) => {
+                let symbols = self.project.workspace_symbols(&params.query);
+
+                trace!("WorkspaceSymbolRequest Response {symbols:?}");
+                self.send_response::<request::WorkspaceSymbolRequest>(
+                    req_id,
+                    Some(WorkspaceSymbolResponse::Nested(symbols)),
+                );
+
+                return request::WorkspaceSymbolRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let req = match Self::cast_request::<request::PrepareRenameRequest>(req) {
+            Ok(params) => {
+                let response = self
+                    .project
+                    .prepare_rename(&params.text_document.uri, params.position);
+
+                trace!("PrepareRenameRequest Response {response:?}");
+                self.send_response::<request::PrepareRenameRequest>(req_id, response);
+
+                return request::PrepareRenameRequest::METHOD;
+            }
+            Err(req) => req,
+        };
+        let _request = match Self::cast_request::<request::Rename>(req) {
+            Ok(params) => {
+                let position = params.text_document_position;
+                let result = self.project.rename(
+                    &position.text_document.uri,
+                    position.position,
+                    &params.new_name,
+                );
+
+                trace!("Rename Response {result:?}");
+                match result {
+                    Ok(edit) => self.send_response::<request::Rename>(req_id, edit),
+                    Err(message) => self.send_error(req_id, message),
+                }
+
+                return request::Rename::METHOD;
+            }
+            Err(req) => req,
+        };
         ""
     }
 
@@ -193,6 +511,43 @@ impl<'a> LspServer<'a> {
         self.sender.send(Message::Response(response)).unwrap()
     }
 
+    /// Records that the document changed so that the next analysis
+    /// publishes diagnostics for the document.
+    fn schedule_analysis(&mut self, uri: Uri, version: i32) {
+        self.pending.retain(|(pending, _)| *pending != uri);
+        self.pending.push((uri, version));
+    }
+
+    /// Analyzes the project and publishes the diagnostics for the documents
+    /// that changed since the last analysis and for the documents that had
+    /// or now have diagnostics.
+    fn publish_diagnostics(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        let uris: Vec<Uri> = pending.iter().map(|(uri, _)| uri.clone()).collect();
+
+        for (uri, diagnostics) in self.project.semantic(&uris) {
+            let version = pending
+                .iter()
+                .find(|(pending, _)| *pending == uri)
+                .map(|(_, version)| *version);
+            self.send_notification::<PublishDiagnostics>(PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version,
+            });
+        }
+    }
+
+    fn send_error(&self, request_id: RequestId, message: String) {
+        trace!("Error response {message}");
+        let response = lsp_server::Response::new_err(
+            request_id,
+            lsp_server::ErrorCode::RequestFailed as i32,
+            message,
+        );
+        self.sender.send(Message::Response(response)).unwrap()
+    }
+
     fn handle_notification(&mut self, notification: &lsp_server::Notification) -> &'static str {
         let _notification = match Self::cast_notification::<notification::Exit>(notification) {
             Ok(_params) => {
@@ -212,17 +567,10 @@ impl<'a> LspServer<'a> {
                     let uri = params.text_document.uri;
                     let version = params.text_document.version;
 
-                    self.project
-                        .change_text_document(&uri, contents.as_str().to_string());
-                    let diagnostics = self.project.semantic(&uri);
+                    self.project.change_text_document(&uri, contents);
+                    self.schedule_analysis(uri, version);
 
-                    self.send_notification::<PublishDiagnostics>(PublishDiagnosticsParams {
-                        uri,
-                        diagnostics,
-                        version: Some(version),
-                    });
-
-                    return notification::DidChangeTextDocument::METHOD;
+                    return notification::DidOpenTextDocument::METHOD;
                 }
                 Err(notification) => notification,
             };
@@ -234,19 +582,11 @@ impl<'a> LspServer<'a> {
                         "DidChangeTextDocument {}",
                         params.text_document.uri.as_str()
                     );
-                    let contents = params.content_changes.into_iter().next().unwrap().text;
                     let uri = params.text_document.uri;
                     let version = params.text_document.version;
 
-                    self.project
-                        .change_text_document(&uri, contents.as_str().to_string());
-                    let diagnostics = self.project.semantic(&uri);
-
-                    self.send_notification::<PublishDiagnostics>(PublishDiagnosticsParams {
-                        uri,
-                        diagnostics,
-                        version: Some(version),
-                    });
+                    self.project.apply_changes(&uri, params.content_changes);
+                    self.schedule_analysis(uri, version);
 
                     return notification::DidChangeTextDocument::METHOD;
                 }
@@ -295,8 +635,10 @@ mod test {
     use lsp_types::VersionedTextDocumentIdentifier;
     use lsp_types::{notification, WorkDoneProgressParams};
     use lsp_types::{
-        request, ClientCapabilities, InitializeParams, InitializeResult, InitializedParams,
-        PublishDiagnosticsParams, TextDocumentContentChangeEvent,
+        request, ClientCapabilities, GotoDefinitionParams, GotoDefinitionResponse,
+        InitializeParams, InitializeResult, InitializedParams, PartialResultParams, Position,
+        PublishDiagnosticsParams, Range, TextDocumentContentChangeEvent, TextDocumentIdentifier,
+        TextDocumentPositionParams,
     };
     use serde::de::DeserializeOwned;
     use serde::Serialize;
@@ -461,4 +803,87 @@ mod test {
 
         server.receive_notification::<PublishDiagnosticsParams>();
     }
+
+    #[test]
+    fn text_document_changed_when_incremental_changes_then_one_analysis_for_latest_version() {
+        let proj = Box::new(FileBackedProject::default());
+        let mut server = TestServer::new(proj);
+        let uri = Uri::from_str("file:///a/main.st").unwrap();
+        server.send_notification::<notification::DidChangeTextDocument>(
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 1,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: String::from("PROGRAM main\n   x := 1;\nEND_PROGRAM"),
+                }],
+            },
+        );
+        server.send_notification::<notification::DidChangeTextDocument>(
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 2,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(1, 0), Position::new(1, 11))),
+                    range_length: None,
+                    text: String::new(),
+                }],
+            },
+        );
+
+        let diagnostics = server.receive_notification::<PublishDiagnosticsParams>();
+
+        assert_eq!(Some(2), diagnostics.version);
+        assert!(diagnostics.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn goto_definition_when_variable_reference_then_returns_declaration() {
+        let proj = Box::new(FileBackedProject::default());
+        let mut server = TestServer::new(proj);
+        let uri = Uri::from_str("file:///a/main.st").unwrap();
+        server.send_notification::<notification::DidChangeTextDocument>(
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 1,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: String::from(
+                        "PROGRAM main\nVAR\n   x : INT;\nEND_VAR\n   x := 1;\nEND_PROGRAM",
+                    ),
+                }],
+            },
+        );
+        server.receive_notification::<PublishDiagnosticsParams>();
+
+        let id = server.send_request::<request::GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(4, 3),
+            },
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+            partial_result_params: PartialResultParams {
+                partial_result_token: None,
+            },
+        });
+        let response = server.receive_response::<Option<GotoDefinitionResponse>>(id);
+
+        match response {
+            Some(GotoDefinitionResponse::Scalar(location)) => {
+                assert_eq!(uri, location.uri);
+                assert_eq!(Position::new(2, 3), location.range.start);
+            }
+            _ => panic!("Expected location"),
+        }
+    }
 }
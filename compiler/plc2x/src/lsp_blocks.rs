@@ -0,0 +1,376 @@
+//! Implements the language server features that depend on the block
+//! structure of the text: folding ranges, selection ranges and formatting.
+//!
+//! A block is the text between a keyword that starts a declaration or
+//! statement (such as `FUNCTION_BLOCK` or `IF`) and the matching keyword that
+//! ends the block (such as `END_FUNCTION_BLOCK` or `END_IF`). The blocks come
+//! from the tokens rather than the library so that the blocks are available
+//! while the text has syntax errors.
+
+use ironplc_dsl::{
+    common::{Library, LibraryElementKind},
+    core::Id,
+    visitor::Visitor,
+};
+use ironplc_parser::{
+    has_conditional_directives,
+    token::{Token, TokenType},
+};
+use ironplc_plc2plc::write_to_string;
+use lsp_types::{FoldingRange, FoldingRangeKind, Range, SelectionRange, TextEdit};
+
+use crate::lsp_project::to_position;
+
+/// A block of text between matching keywords.
+#[derive(Debug, Clone)]
+pub(crate) struct Block {
+    /// The line of the keyword that starts the block.
+    start_line: usize,
+    /// The line of the keyword that ends the block.
+    end_line: usize,
+    /// The offset of the start of the keyword that starts the block.
+    start: usize,
+    /// The offset of the end of the keyword that ends the block.
+    end: usize,
+    /// The number of blocks that contain this block.
+    depth: usize,
+}
+
+/// Returns the keyword that ends the block that the token type starts.
+fn end_of(token_type: &TokenType) -> Option<TokenType> {
+    match token_type {
+        TokenType::Program => Some(TokenType::EndProgram),
+        TokenType::Function => Some(TokenType::EndFunction),
+        TokenType::FunctionBlock => Some(TokenType::EndFunctionBlock),
+        TokenType::Method => Some(TokenType::EndMethod),
+        TokenType::Property => Some(TokenType::EndProperty),
+        TokenType::Interface => Some(TokenType::EndInterface),
+        TokenType::Configuration => Some(TokenType::EndConfiguration),
+        TokenType::Resource => Some(TokenType::EndResource),
+        TokenType::Type => Some(TokenType::EndType),
+        TokenType::Struct => Some(TokenType::EndStruct),
+        TokenType::Var
+        | TokenType::VarInput
+        | TokenType::VarOutput
+        | TokenType::VarInOut
+        | TokenType::VarTemp
+        | TokenType::VarExternal
+        | TokenType::VarAccess
+        | TokenType::VarConfig
+        | TokenType::VarGlobal => Some(TokenType::EndVar),
+        TokenType::If => Some(TokenType::EndIf),
+        TokenType::Case => Some(TokenType::EndCase),
+        TokenType::For => Some(TokenType::EndFor),
+        TokenType::While => Some(TokenType::EndWhile),
+        TokenType::Repeat => Some(TokenType::EndRepeat),
+        TokenType::Step | TokenType::InitialStep => Some(TokenType::EndStep),
+        TokenType::Transition => Some(TokenType::EndTransition),
+        TokenType::Action => Some(TokenType::EndAction),
+        _ => None,
+    }
+}
+
+/// Returns the blocks in the tokens ordered by the start of the block.
+///
+/// Keywords that do not have a match (because the text is incomplete or
+/// because the keyword is used as a name, such as `STEP`) do not form blocks.
+pub(crate) fn blocks(tokens: &[Token]) -> Vec<Block> {
+    let mut open: Vec<(&Token, TokenType)> = vec![];
+    let mut blocks = vec![];
+
+    for token in tokens {
+        if let Some(end) = end_of(&token.token_type) {
+            open.push((token, end));
+            continue;
+        }
+        let Some(position) = open.iter().rposition(|(_, end)| *end == token.token_type) else {
+            continue;
+        };
+        let (start, _) = open[position];
+        open.truncate(position);
+        blocks.push(Block {
+            start_line: start.line,
+            end_line: token.line,
+            start: start.span.start,
+            end: token.span.end,
+            depth: position,
+        });
+    }
+
+    blocks.sort_by_key(|block| block.start);
+    blocks
+}
+
+/// Returns the ranges that can be folded: the blocks and the comments that
+/// span multiple lines. The range of a block excludes the line of the
+/// keyword that ends the block so that the end keyword remains visible.
+pub(crate) fn folding_ranges(tokens: &[Token]) -> Vec<FoldingRange> {
+    let mut ranges: Vec<FoldingRange> = blocks(tokens)
+        .into_iter()
+        .filter(|block| block.end_line > block.start_line + 1)
+        .map(|block| FoldingRange {
+            start_line: block.start_line as u32,
+            start_character: None,
+            end_line: (block.end_line - 1) as u32,
+            end_character: None,
+            kind: None,
+            collapsed_text: None,
+        })
+        .collect();
+
+    for token in tokens
+        .iter()
+        .filter(|token| token.token_type == TokenType::Comment)
+    {
+        let lines = token.text.matches('\n').count();
+        if lines > 0 {
+            ranges.push(FoldingRange {
+                start_line: token.line as u32,
+                start_character: None,
+                end_line: (token.line + lines) as u32,
+                end_character: None,
+                kind: Some(FoldingRangeKind::Comment),
+                collapsed_text: None,
+            });
+        }
+    }
+
+    ranges.sort_by_key(|range| range.start_line);
+    ranges
+}
+
+/// Returns the selection range for each offset. The innermost range is the
+/// token at the offset followed by the blocks that contain the offset and
+/// then the entire text.
+pub(crate) fn selection_ranges(
+    tokens: &[Token],
+    contents: &str,
+    offsets: &[usize],
+) -> Vec<SelectionRange> {
+    let blocks = blocks(tokens);
+    let range = |start: usize, end: usize| {
+        Range::new(to_position(contents, start), to_position(contents, end))
+    };
+
+    offsets
+        .iter()
+        .map(|offset| {
+            let mut spans = vec![(0, contents.len())];
+
+            let mut containing: Vec<&Block> = blocks
+                .iter()
+                .filter(|block| block.start <= *offset && *offset <= block.end)
+                .collect();
+            containing.sort_by_key(|block| block.depth);
+            spans.extend(containing.iter().map(|block| (block.start, block.end)));
+
+            let token = tokens.iter().find(|token| {
+                token.span.start <= *offset
+                    && *offset <= token.span.end
+                    && !matches!(
+                        token.token_type,
+                        TokenType::Whitespace | TokenType::Newline | TokenType::Comment
+                    )
+            });
+            if let Some(token) = token {
+                spans.push((token.span.start, token.span.end));
+            }
+            spans.dedup();
+
+            let mut selection: Option<SelectionRange> = None;
+            for (start, end) in spans {
+                selection = Some(SelectionRange {
+                    range: range(start, end),
+                    parent: selection.map(Box::new),
+                });
+            }
+            // There is always the entire text.
+            selection.unwrap_or_else(|| SelectionRange {
+                range: range(0, contents.len()),
+                parent: None,
+            })
+        })
+        .collect()
+}
+
+/// Returns the edits that format the entire text or `None` if the text
+/// cannot be formatted.
+pub(crate) fn format_document(
+    tokens: &[Token],
+    library: &Library,
+    contents: &str,
+) -> Option<Vec<TextEdit>> {
+    if has_unrendered_text(tokens, contents) {
+        return None;
+    }
+    let text = write_to_string(library).ok()?;
+    Some(replace(contents, 0, contents.len(), text))
+}
+
+/// Returns the edits that format the library elements that intersect the
+/// range or `None` if the text cannot be formatted.
+pub(crate) fn format_range(
+    tokens: &[Token],
+    library: &Library,
+    contents: &str,
+    start: usize,
+    end: usize,
+) -> Option<Vec<TextEdit>> {
+    if has_unrendered_text(tokens, contents) {
+        return None;
+    }
+
+    // Library elements are top-level blocks, except that a TYPE block
+    // may declare more than one element.
+    let top_level: Vec<Block> = blocks(tokens)
+        .into_iter()
+        .filter(|block| block.depth == 0 && block.start <= end && start <= block.end)
+        .collect();
+
+    let mut edits = vec![];
+    for block in top_level {
+        let elements: Vec<LibraryElementKind> = library
+            .elements
+            .iter()
+            .filter(|element| {
+                first_start(element).is_some_and(|at| block.start <= at && at <= block.end)
+            })
+            .cloned()
+            .collect();
+        if elements.is_empty() {
+            continue;
+        }
+        let text = write_to_string(&Library { elements }).ok()?;
+        edits.extend(replace(
+            contents,
+            block.start,
+            block.end,
+            text.trim_end().to_owned(),
+        ));
+    }
+    Some(edits)
+}
+
+/// The renderer does not keep comments, pragmas or conditional compilation
+/// directives (nor the text that the directives exclude) and so formatting
+/// would remove them from the text.
+fn has_unrendered_text(tokens: &[Token], contents: &str) -> bool {
+    // The tokens do not include the directives because the tokenizer applies
+    // the directives, so check the directives in the text.
+    has_conditional_directives(contents)
+        || tokens
+            .iter()
+            .any(|token| matches!(token.token_type, TokenType::Comment | TokenType::Pragma))
+}
+
+/// Returns the edit that replaces the text between the offsets unless the
+/// text is the same.
+fn replace(contents: &str, start: usize, end: usize, text: String) -> Vec<TextEdit> {
+    if contents[start..end] == text {
+        return vec![];
+    }
+    vec![TextEdit::new(
+        Range::new(to_position(contents, start), to_position(contents, end)),
+        text,
+    )]
+}
+
+/// Returns the start of the first name in the element.
+fn first_start(element: &LibraryElementKind) -> Option<usize> {
+    let mut visitor = FirstStart::default();
+    // The visitor does not fail.
+    let _ = visitor.visit_library_element_kind(element);
+    visitor.start
+}
+
+#[derive(Default)]
+struct FirstStart {
+    start: Option<usize>,
+}
+
+impl Visitor<()> for FirstStart {
+    type Value = ();
+
+    fn visit_id(&mut self, node: &Id) -> Result<(), ()> {
+        // Names that are not from the text (such as elementary types) do not
+        // have a position.
+        if node.span.end > 0 {
+            self.start = Some(
+                self.start
+                    .map_or(node.span.start, |s| s.min(node.span.start)),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_dsl::core::FileId;
+    use ironplc_parser::{options::ParseOptions, parse_program, tokenize_program};
+    use lsp_types::Position;
+
+    use super::{folding_ranges, format_range, selection_ranges};
+
+    const PROGRAM: &str = "PROGRAM main
+VAR
+   x : INT;
+END_VAR
+   IF x > 0 THEN
+      x := 0;
+   END_IF;
+END_PROGRAM";
+
+    fn tokens(text: &str) -> Vec<ironplc_parser::token::Token> {
+        tokenize_program(text, &FileId::default(), &ParseOptions::default(), 0, 0).0
+    }
+
+    #[test]
+    fn folding_ranges_when_program_then_program_var_and_if() {
+        let ranges = folding_ranges(&tokens(PROGRAM));
+
+        let lines: Vec<(u32, u32)> = ranges
+            .iter()
+            .map(|range| (range.start_line, range.end_line))
+            .collect();
+        assert_eq!(vec![(0, 6), (1, 2), (4, 5)], lines);
+    }
+
+    #[test]
+    fn folding_ranges_when_comment_then_comment_range() {
+        let ranges = folding_ranges(&tokens("(* first\nsecond *)\nPROGRAM main\nEND_PROGRAM"));
+
+        assert_eq!(1, ranges.len());
+        assert_eq!(Some(lsp_types::FoldingRangeKind::Comment), ranges[0].kind);
+    }
+
+    #[test]
+    fn selection_ranges_when_in_if_then_token_if_and_program() {
+        let offset = PROGRAM.find("x := 0").unwrap();
+
+        let selection = &selection_ranges(&tokens(PROGRAM), PROGRAM, &[offset])[0];
+
+        assert_eq!(Position::new(5, 6), selection.range.start);
+        assert_eq!(Position::new(5, 7), selection.range.end);
+        let block = selection.parent.as_ref().unwrap();
+        assert_eq!(Position::new(4, 3), block.range.start);
+        assert_eq!(Position::new(6, 9), block.range.end);
+        let program = block.parent.as_ref().unwrap();
+        assert_eq!(Position::new(0, 0), program.range.start);
+        // The program is the entire text so there is no separate range for the text.
+        assert!(program.parent.is_none());
+    }
+
+    #[test]
+    fn format_range_when_in_function_block_then_formats_only_function_block() {
+        let text = "FUNCTION_BLOCK fb\nVAR\nx:INT;\nEND_VAR\nEND_FUNCTION_BLOCK\nPROGRAM main\nVAR\ny:INT;\nEND_VAR\nEND_PROGRAM";
+        let library = parse_program(text, &FileId::default(), &ParseOptions::default()).unwrap();
+
+        let edits = format_range(&tokens(text), &library, text, 0, 5).unwrap();
+
+        assert_eq!(1, edits.len());
+        assert_eq!(Position::new(4, 18), edits[0].range.end);
+        assert!(edits[0].new_text.contains("x : INT"));
+        assert!(!edits[0].new_text.contains("main"));
+    }
+}
@@ -0,0 +1,275 @@
+//! Implements the call hierarchy for the language server.
+//!
+//! The calls are between POUs: a function calls a function, a function
+//! block calls (an instance of) a function block and a configuration calls
+//! the programs that the configuration instantiates. Calls in methods are
+//! calls from the function block that declares the method.
+
+use ironplc_analyzer::symbol_index::{DefinitionId, DefinitionKind, SymbolIndex};
+use ironplc_dsl::{
+    common::{FunctionBlockDeclaration, FunctionDeclaration, Library, ProgramDeclaration},
+    configuration::{ConfigurationDeclaration, ProgramConfiguration},
+    core::{Id, SourceSpan},
+    textual::{FbCall, Function},
+    visitor::Visitor,
+};
+
+/// A call from one POU (the caller) to another POU (the callee).
+#[derive(Debug)]
+pub(crate) struct Call {
+    pub caller: DefinitionId,
+    pub callee: DefinitionId,
+    /// The location of the name of the callee in the caller.
+    pub span: SourceSpan,
+}
+
+/// Returns the calls in the libraries.
+pub(crate) fn calls(index: &SymbolIndex, libraries: &[&Library]) -> Vec<Call> {
+    let mut collector = CallCollector {
+        index,
+        caller: None,
+        calls: vec![],
+    };
+    for library in libraries {
+        // The collector does not fail.
+        let _ = collector.walk(library);
+    }
+    collector.calls
+}
+
+/// Returns the POU that the definition refers to for the call hierarchy,
+/// that is, the definition if it is a POU or the type of an instance.
+pub(crate) fn callable(index: &SymbolIndex, id: DefinitionId) -> Option<DefinitionId> {
+    let definition = index.definition(id);
+    match definition.kind {
+        DefinitionKind::Function
+        | DefinitionKind::FunctionBlock
+        | DefinitionKind::Program
+        | DefinitionKind::Configuration => Some(id),
+        DefinitionKind::Variable(_) | DefinitionKind::ProgramInstance => {
+            let type_id = index.type_definition(definition.type_name.as_ref()?)?;
+            matches!(
+                index.definition(type_id).kind,
+                DefinitionKind::FunctionBlock | DefinitionKind::Program
+            )
+            .then_some(type_id)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the calls to the callee grouped by the caller.
+pub(crate) fn incoming(
+    calls: &[Call],
+    callee: DefinitionId,
+) -> Vec<(DefinitionId, Vec<&SourceSpan>)> {
+    group(
+        calls
+            .iter()
+            .filter(|call| call.callee == callee)
+            .map(|call| (call.caller, &call.span)),
+    )
+}
+
+/// Returns the calls from the caller grouped by the callee.
+pub(crate) fn outgoing(
+    calls: &[Call],
+    caller: DefinitionId,
+) -> Vec<(DefinitionId, Vec<&SourceSpan>)> {
+    group(
+        calls
+            .iter()
+            .filter(|call| call.caller == caller)
+            .map(|call| (call.callee, &call.span)),
+    )
+}
+
+/// Groups the locations by the definition in the order of the first
+/// location for each definition.
+fn group<'a>(
+    items: impl Iterator<Item = (DefinitionId, &'a SourceSpan)>,
+) -> Vec<(DefinitionId, Vec<&'a SourceSpan>)> {
+    let mut groups: Vec<(DefinitionId, Vec<&SourceSpan>)> = vec![];
+    for (id, span) in items {
+        match groups.iter_mut().find(|(other, _)| *other == id) {
+            Some((_, spans)) => spans.push(span),
+            None => groups.push((id, vec![span])),
+        }
+    }
+    groups
+}
+
+struct CallCollector<'a> {
+    index: &'a SymbolIndex,
+    caller: Option<DefinitionId>,
+    calls: Vec<Call>,
+}
+
+impl CallCollector<'_> {
+    /// Returns the definition that the name declares or refers to.
+    fn find(&self, name: &Id) -> Option<DefinitionId> {
+        self.index.find(&name.span.file_id, name.span.start)
+    }
+
+    fn with_caller<F>(&mut self, name: &Id, f: F) -> Result<(), ()>
+    where
+        F: FnOnce(&mut Self) -> Result<(), ()>,
+    {
+        self.caller = self.find(name);
+        let result = f(self);
+        self.caller = None;
+        result
+    }
+
+    fn push(&mut self, callee: DefinitionId, name: &Id) {
+        if let Some(caller) = self.caller {
+            self.calls.push(Call {
+                caller,
+                callee,
+                span: name.span.clone(),
+            });
+        }
+    }
+}
+
+impl Visitor<()> for CallCollector<'_> {
+    type Value = ();
+
+    fn visit_function_declaration(&mut self, node: &FunctionDeclaration) -> Result<(), ()> {
+        self.with_caller(&node.name, |collector| node.recurse_visit(collector))
+    }
+
+    fn visit_function_block_declaration(
+        &mut self,
+        node: &FunctionBlockDeclaration,
+    ) -> Result<(), ()> {
+        self.with_caller(&node.name.name, |collector| node.recurse_visit(collector))
+    }
+
+    fn visit_program_declaration(&mut self, node: &ProgramDeclaration) -> Result<(), ()> {
+        self.with_caller(&node.name, |collector| node.recurse_visit(collector))
+    }
+
+    fn visit_configuration_declaration(
+        &mut self,
+        node: &ConfigurationDeclaration,
+    ) -> Result<(), ()> {
+        self.with_caller(&node.name, |collector| node.recurse_visit(collector))
+    }
+
+    fn visit_function(&mut self, node: &Function) -> Result<(), ()> {
+        if let Some(function) = self.find(&node.name) {
+            if self.index.definition(function).kind == DefinitionKind::Function {
+                self.push(function, &node.name);
+            }
+        }
+        node.recurse_visit(self)
+    }
+
+    fn visit_fb_call(&mut self, node: &FbCall) -> Result<(), ()> {
+        let function_block = self
+            .find(&node.var_name)
+            .and_then(|instance| callable(self.index, instance))
+            .filter(|id| self.index.definition(*id).kind == DefinitionKind::FunctionBlock);
+        if let Some(function_block) = function_block {
+            self.push(function_block, &node.var_name);
+        }
+        node.recurse_visit(self)
+    }
+
+    fn visit_program_configuration(&mut self, node: &ProgramConfiguration) -> Result<(), ()> {
+        if let Some(program) = self.find(&node.type_name) {
+            if self.index.definition(program).kind == DefinitionKind::Program {
+                self.push(program, &node.type_name);
+            }
+        }
+        node.recurse_visit(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_analyzer::symbol_index::{DefinitionId, SymbolIndex};
+    use ironplc_dsl::core::{FileId, Id, SourceSpan};
+    use ironplc_parser::{options::ParseOptions, parse_program};
+
+    use super::{calls, incoming, outgoing};
+
+    const PROGRAM: &str = "
+FUNCTION double : INT
+VAR_INPUT
+   value : INT;
+END_VAR
+   double := value * 2;
+END_FUNCTION
+FUNCTION_BLOCK counter
+VAR
+   count : INT;
+END_VAR
+   count := double(count);
+END_FUNCTION_BLOCK
+PROGRAM main
+VAR
+   first : counter;
+   second : counter;
+   value : INT;
+END_VAR
+   first();
+   second();
+   value := double(value);
+END_PROGRAM
+CONFIGURATION config
+   RESOURCE res ON PLC
+      TASK plc_task(INTERVAL := T#100ms, PRIORITY := 1);
+      PROGRAM plc_task_instance WITH plc_task : main;
+   END_RESOURCE
+END_CONFIGURATION";
+
+    fn names(
+        index: &SymbolIndex,
+        groups: &[(DefinitionId, Vec<&SourceSpan>)],
+    ) -> Vec<(String, usize)> {
+        groups
+            .iter()
+            .map(|(id, spans)| {
+                (
+                    index.definition(*id).name.original().to_owned(),
+                    spans.len(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn outgoing_when_program_then_function_blocks_and_functions() {
+        let library = parse_program(PROGRAM, &FileId::default(), &ParseOptions::default()).unwrap();
+        let index = SymbolIndex::new(&[&library]);
+        let calls = calls(&index, &[&library]);
+        let main = index.lookup(None, &Id::from("main")).unwrap();
+
+        let groups = outgoing(&calls, main);
+
+        assert_eq!(
+            vec![("counter".to_owned(), 2), ("double".to_owned(), 1)],
+            names(&index, &groups)
+        );
+    }
+
+    #[test]
+    fn incoming_when_function_then_callers() {
+        let library = parse_program(PROGRAM, &FileId::default(), &ParseOptions::default()).unwrap();
+        let index = SymbolIndex::new(&[&library]);
+        let calls = calls(&index, &[&library]);
+        let double = index.lookup(None, &Id::from("double")).unwrap();
+        let main = index.lookup(None, &Id::from("main")).unwrap();
+
+        assert_eq!(
+            vec![("counter".to_owned(), 1), ("main".to_owned(), 1)],
+            names(&index, &incoming(&calls, double))
+        );
+        assert_eq!(
+            vec![("config".to_owned(), 1)],
+            names(&index, &incoming(&calls, main))
+        );
+    }
+}
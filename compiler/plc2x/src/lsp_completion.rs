@@ -0,0 +1,517 @@
+//! Implements the items that the language server offers to complete the
+//! name at a position.
+//!
+//! The items depend on the text just before the position:
+//!
+//! * after `name.`, the members of `name` (structure elements or the
+//!   inputs, outputs, methods and properties of a function block)
+//! * after `TYPE#`, the values of the enumeration type
+//! * after `name(` or `,` in a call, the parameters of the call followed
+//!   by the general items
+//! * otherwise the general items: the variables in scope, enumeration
+//!   values, type and POU names and statement templates
+//!
+//! The text is usually incomplete while editing, so the context comes from
+//! the text and the scope comes from the symbol index (that only depends on
+//! the parts of the source that parse).
+
+use ironplc_analyzer::symbol_index::{Declaration, DefinitionId, DefinitionKind, SymbolIndex};
+use ironplc_dsl::{
+    common::{DataTypeDeclarationKind, VariableType},
+    core::{FileId, Id},
+};
+use lsp_types::{CompletionItem, CompletionItemKind, InsertTextFormat};
+
+/// Templates for statements as (label, description, snippet).
+const STATEMENT_SNIPPETS: [(&str, &str, &str); 7] = [
+    (
+        "IF",
+        "IF statement",
+        "IF ${1:condition} THEN\n\t$0\nEND_IF;",
+    ),
+    (
+        "IF ELSE",
+        "IF statement with ELSE",
+        "IF ${1:condition} THEN\n\t$2\nELSE\n\t$0\nEND_IF;",
+    ),
+    (
+        "CASE",
+        "CASE statement",
+        "CASE ${1:selector} OF\n\t${2:value}:\n\t\t$0\nEND_CASE;",
+    ),
+    (
+        "FOR",
+        "FOR loop",
+        "FOR ${1:index} := ${2:1} TO ${3:10} DO\n\t$0\nEND_FOR;",
+    ),
+    (
+        "WHILE",
+        "WHILE loop",
+        "WHILE ${1:condition} DO\n\t$0\nEND_WHILE;",
+    ),
+    (
+        "REPEAT",
+        "REPEAT loop",
+        "REPEAT\n\t$0\nUNTIL ${1:condition}\nEND_REPEAT;",
+    ),
+    ("VAR", "Variable declarations", "VAR\n\t$0\nEND_VAR"),
+];
+
+/// Returns the completion items for the offset in the contents of the file.
+pub(crate) fn completions(
+    index: &SymbolIndex,
+    contents: &str,
+    file_id: &FileId,
+    offset: usize,
+) -> Vec<CompletionItem> {
+    let before = &contents[..offset.min(contents.len())];
+    let word_start = identifier_start(before);
+    let before_word = &before[..word_start];
+    let scope = scope_at(index, contents, file_id, offset);
+
+    if let Some(receiver) = before_word.strip_suffix('.') {
+        return match resolve_chain(index, scope, receiver) {
+            Some(id) => member_items(index, id, is_self_reference(receiver)),
+            None => vec![],
+        };
+    }
+
+    if let Some(type_name) = before_word.strip_suffix('#') {
+        let type_name = &type_name[identifier_start(type_name)..];
+        return match index.lookup(None, &Id::from(type_name)) {
+            Some(id) => index
+                .visible_members(id)
+                .into_iter()
+                .filter(|member| index.definition(*member).kind == DefinitionKind::EnumerationValue)
+                .map(|member| item(index, member))
+                .collect(),
+            None => vec![],
+        };
+    }
+
+    let mut items = vec![];
+    if let Some(callee) = call_receiver(before_word) {
+        if let Some(id) = resolve_chain(index, scope, callee) {
+            items.extend(parameter_items(index, id));
+        }
+    }
+    items.extend(general_items(index, scope));
+    items
+}
+
+pub(crate) fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns the offset of the identifier that the text ends with.
+pub(crate) fn identifier_start(text: &str) -> usize {
+    text.char_indices()
+        .rev()
+        .find(|(_, c)| !is_identifier_char(*c))
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0)
+}
+
+/// Returns the innermost POU, configuration or resource that contains the
+/// offset. The index only has the location of the name of the declaration,
+/// so the scope is the last declaration before the offset that has not
+/// ended before the offset.
+pub(crate) fn scope_at(
+    index: &SymbolIndex,
+    contents: &str,
+    file_id: &FileId,
+    offset: usize,
+) -> Option<DefinitionId> {
+    let mut scope = index
+        .definitions()
+        .filter(|id| {
+            let definition = index.definition(*id);
+            end_keyword(&definition.kind).is_some()
+                && definition.name.span.file_id == *file_id
+                && definition.name.span.end <= offset
+        })
+        .max_by_key(|id| index.definition(*id).name.span.start);
+
+    while let Some(id) = scope {
+        let definition = index.definition(id);
+        let end = end_keyword(&definition.kind)?;
+        let start = definition.name.span.end.min(offset);
+        let has_ended = contents
+            .get(start..offset)
+            .unwrap_or_default()
+            .split(|c: char| !is_identifier_char(c))
+            .any(|word| word.eq_ignore_ascii_case(end));
+        if !has_ended {
+            break;
+        }
+        scope = definition.container;
+    }
+    scope
+}
+
+/// Returns the keyword that ends the declaration for kinds that are scopes.
+fn end_keyword(kind: &DefinitionKind) -> Option<&'static str> {
+    match kind {
+        DefinitionKind::Function => Some("END_FUNCTION"),
+        DefinitionKind::FunctionBlock => Some("END_FUNCTION_BLOCK"),
+        DefinitionKind::Program => Some("END_PROGRAM"),
+        DefinitionKind::Method => Some("END_METHOD"),
+        DefinitionKind::Interface => Some("END_INTERFACE"),
+        DefinitionKind::Configuration => Some("END_CONFIGURATION"),
+        DefinitionKind::Resource => Some("END_RESOURCE"),
+        _ => None,
+    }
+}
+
+/// Splits the text that ends with a variable such as `a.b[1].c` into the
+/// names of the variable (`a`, `b`, `c`). Subscripts and dereferences are
+/// skipped because they do not change the members.
+fn variable_names(text: &str) -> Vec<&str> {
+    let mut names = vec![];
+    let mut end = text.len();
+    loop {
+        let mut rest = &text[..end];
+        rest = rest.trim_end_matches('^');
+        if rest.ends_with(']') {
+            let mut depth = 0;
+            let mut open = None;
+            for (i, c) in rest.char_indices().rev() {
+                match c {
+                    ']' => depth += 1,
+                    '[' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            open = Some(i);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            match open {
+                Some(open) => rest = &rest[..open],
+                None => return vec![],
+            }
+        }
+        let start = identifier_start(rest);
+        if start == rest.len() {
+            return vec![];
+        }
+        names.push(&rest[start..]);
+        match rest[..start].strip_suffix('.') {
+            Some(prefix) => end = prefix.len(),
+            None => break,
+        }
+    }
+    names.reverse();
+    names
+}
+
+/// Returns true if the variable is `THIS` or `SUPER` so that all members
+/// (rather than only the public members) are accessible.
+fn is_self_reference(text: &str) -> bool {
+    variable_names(text)
+        .first()
+        .is_some_and(|name| name.eq_ignore_ascii_case("THIS") || name.eq_ignore_ascii_case("SUPER"))
+}
+
+/// Returns the definition for the variable that the text ends with.
+pub(crate) fn resolve_chain(
+    index: &SymbolIndex,
+    scope: Option<DefinitionId>,
+    text: &str,
+) -> Option<DefinitionId> {
+    let names = variable_names(text);
+    let (first, rest) = names.split_first()?;
+
+    let mut current = if first.eq_ignore_ascii_case("THIS") || first.eq_ignore_ascii_case("SUPER") {
+        let mut function_block = scope;
+        while let Some(id) = function_block {
+            if index.definition(id).kind == DefinitionKind::FunctionBlock {
+                break;
+            }
+            function_block = index.definition(id).container;
+        }
+        let function_block = function_block?;
+        if first.eq_ignore_ascii_case("SUPER") {
+            let base = index.definition(function_block).type_name.as_ref()?;
+            index.type_definition(base)?
+        } else {
+            function_block
+        }
+    } else {
+        index.lookup(scope, &Id::from(first))?
+    };
+
+    for name in rest {
+        current = index.member(current, &Id::from(name))?;
+    }
+    Some(current)
+}
+
+/// Returns the text of the callee if the text ends inside the argument list
+/// of a call at the start of an argument.
+fn call_receiver(text: &str) -> Option<&str> {
+    let trimmed = text.trim_end();
+    if !trimmed.ends_with('(') && !trimmed.ends_with(',') {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (i, c) in trimmed.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => return Some(trimmed[..i].trim_end()),
+            '(' => depth -= 1,
+            ';' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the items for the members that are accessible through the
+/// definition.
+fn member_items(index: &SymbolIndex, id: DefinitionId, is_self: bool) -> Vec<CompletionItem> {
+    index
+        .visible_members(id)
+        .into_iter()
+        .filter(|member| {
+            is_self
+                || !matches!(
+                    index.definition(*member).kind,
+                    DefinitionKind::Variable(
+                        VariableType::Var | VariableType::VarTemp | VariableType::External
+                    )
+                )
+        })
+        .map(|member| item(index, member))
+        .collect()
+}
+
+/// Returns the items for the named parameters of the function, method or
+/// function block instance.
+fn parameter_items(index: &SymbolIndex, id: DefinitionId) -> Vec<CompletionItem> {
+    index
+        .visible_members(id)
+        .into_iter()
+        .filter_map(|member| {
+            let definition = index.definition(member);
+            let operator = match definition.kind {
+                DefinitionKind::Variable(VariableType::Input | VariableType::InOut) => ":=",
+                DefinitionKind::Variable(VariableType::Output) => "=>",
+                _ => return None,
+            };
+            let name = definition.name.original();
+            Some(CompletionItem {
+                label: format!("{name} {operator}"),
+                insert_text: Some(format!("{name} {operator} ")),
+                ..item(index, member)
+            })
+        })
+        .collect()
+}
+
+/// Returns the items that do not depend on a receiver: the variables in
+/// scope, type and POU names, enumeration values and statement templates.
+fn general_items(index: &SymbolIndex, scope: Option<DefinitionId>) -> Vec<CompletionItem> {
+    let mut ids: Vec<DefinitionId> = vec![];
+
+    let mut current = scope;
+    while let Some(id) = current {
+        ids.extend(index.visible_members(id));
+        current = index.definition(id).container;
+    }
+
+    ids.extend(index.definitions().filter(|id| {
+        let definition = index.definition(*id);
+        match definition.kind {
+            DefinitionKind::Type
+            | DefinitionKind::Function
+            | DefinitionKind::FunctionBlock
+            | DefinitionKind::Program
+            | DefinitionKind::Interface => definition.container.is_none(),
+            DefinitionKind::EnumerationValue => true,
+            _ => false,
+        }
+    }));
+
+    // Inner declarations hide outer declarations with the same name.
+    let mut items: Vec<CompletionItem> = vec![];
+    for id in ids {
+        let name = &index.definition(id).name;
+        let is_hidden = index.definition(id).kind != DefinitionKind::EnumerationValue
+            && items.iter().any(|item| Id::from(&item.label) == *name);
+        if !is_hidden {
+            items.push(item(index, id));
+        }
+    }
+
+    let is_body = scope.is_some_and(|id| {
+        matches!(
+            index.definition(id).kind,
+            DefinitionKind::Function
+                | DefinitionKind::FunctionBlock
+                | DefinitionKind::Program
+                | DefinitionKind::Method
+        )
+    });
+    if is_body {
+        items.extend(
+            STATEMENT_SNIPPETS
+                .iter()
+                .map(|(label, detail, snippet)| CompletionItem {
+                    label: label.to_string(),
+                    kind: Some(CompletionItemKind::SNIPPET),
+                    detail: Some(detail.to_string()),
+                    insert_text: Some(snippet.to_string()),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    ..Default::default()
+                }),
+        );
+    }
+
+    items
+}
+
+/// Returns the item for the definition.
+fn item(index: &SymbolIndex, id: DefinitionId) -> CompletionItem {
+    let definition = index.definition(id);
+    let detail = match definition.kind {
+        DefinitionKind::EnumerationValue => definition
+            .container
+            .map(|container| index.definition(container))
+            .filter(|container| container.kind == DefinitionKind::Type)
+            .map(|container| container.name.original().to_string()),
+        DefinitionKind::Type => None,
+        _ => definition
+            .type_name
+            .as_ref()
+            .map(|type_name| type_name.name.original().to_string()),
+    };
+
+    CompletionItem {
+        label: definition.name.original().to_string(),
+        kind: Some(item_kind(index, id)),
+        detail,
+        ..Default::default()
+    }
+}
+
+fn item_kind(index: &SymbolIndex, id: DefinitionId) -> CompletionItemKind {
+    let definition = index.definition(id);
+    match &definition.kind {
+        DefinitionKind::Variable(_) | DefinitionKind::ProgramInstance => {
+            CompletionItemKind::VARIABLE
+        }
+        DefinitionKind::StructureElement => CompletionItemKind::FIELD,
+        DefinitionKind::EnumerationValue => CompletionItemKind::ENUM_MEMBER,
+        DefinitionKind::Type => match definition.declaration {
+            Some(Declaration::DataType(DataTypeDeclarationKind::Structure(_))) => {
+                CompletionItemKind::STRUCT
+            }
+            Some(Declaration::DataType(DataTypeDeclarationKind::Enumeration(_))) => {
+                CompletionItemKind::ENUM
+            }
+            _ => CompletionItemKind::TYPE_PARAMETER,
+        },
+        DefinitionKind::Function => CompletionItemKind::FUNCTION,
+        DefinitionKind::FunctionBlock => CompletionItemKind::CLASS,
+        DefinitionKind::Program | DefinitionKind::Configuration | DefinitionKind::Resource => {
+            CompletionItemKind::MODULE
+        }
+        DefinitionKind::Interface => CompletionItemKind::INTERFACE,
+        DefinitionKind::Method => CompletionItemKind::METHOD,
+        DefinitionKind::Property => CompletionItemKind::PROPERTY,
+        DefinitionKind::Task => CompletionItemKind::EVENT,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_analyzer::symbol_index::SymbolIndex;
+    use ironplc_dsl::{common::Library, core::FileId};
+    use ironplc_parser::{options::ParseOptions, parse_program};
+    use lsp_types::CompletionItem;
+
+    use super::{completions, variable_names};
+
+    const PROGRAM: &str = "
+TYPE
+   LEVEL : (LOW, HIGH) := LOW;
+   POINT : STRUCT
+      x : INT;
+      y : INT;
+   END_STRUCT;
+END_TYPE
+FUNCTION_BLOCK counter
+VAR_INPUT
+   reset : BOOL;
+END_VAR
+VAR_OUTPUT
+   value : DINT;
+END_VAR
+VAR
+   count : DINT;
+END_VAR
+END_FUNCTION_BLOCK
+PROGRAM main
+VAR
+   p : POINT;
+   instance : counter;
+   level_value : LEVEL;
+END_VAR
+   p.x := 1;
+   instance(reset := TRUE);
+   level_value := HIGH;
+END_PROGRAM";
+
+    /// Returns the completion items at the position that is after the
+    /// text in the program.
+    fn complete(after: &str) -> Vec<CompletionItem> {
+        let library: Library =
+            parse_program(PROGRAM, &FileId::default(), &ParseOptions::default()).unwrap();
+        let index = SymbolIndex::new(&[&library]);
+        let offset = PROGRAM.find(after).unwrap() + after.len();
+        completions(&index, PROGRAM, &FileId::default(), offset)
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|item| item.label.as_str()).collect()
+    }
+
+    #[test]
+    fn variable_names_when_members_and_subscripts_then_names() {
+        assert_eq!(vec!["a", "b", "c"], variable_names("x := a.b[i + 1].c"));
+        assert_eq!(vec!["ref"], variable_names("ref^"));
+    }
+
+    #[test]
+    fn completions_when_structure_member_then_fields() {
+        let items = complete("   p.");
+        assert_eq!(vec!["x", "y"], labels(&items));
+    }
+
+    #[test]
+    fn completions_when_function_block_call_then_inputs_and_outputs() {
+        let items = complete("instance(");
+        let labels = labels(&items);
+        assert!(labels.contains(&"reset :="));
+        assert!(labels.contains(&"value =>"));
+        assert!(!labels.contains(&"count :="));
+        assert!(!labels.contains(&"count"));
+    }
+
+    #[test]
+    fn completions_when_in_program_body_then_variables_types_values_and_snippets() {
+        let items = complete("   level_value := ");
+        let labels = labels(&items);
+        assert!(labels.contains(&"p"));
+        assert!(labels.contains(&"instance"));
+        assert!(labels.contains(&"POINT"));
+        assert!(labels.contains(&"counter"));
+        assert!(labels.contains(&"HIGH"));
+        assert!(labels.contains(&"FOR"));
+        assert!(!labels.contains(&"reset"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Implements the content that the language server shows when hovering
+//! over a name.
+//!
+//! The content is Markdown and shows the declaration of the name so that
+//! the declaration is visible without navigating to the declaration.
+
+use ironplc_analyzer::{
+    symbol_index::{Declaration, DefinitionId, DefinitionKind, SymbolIndex},
+    TypeEnvironment,
+};
+use ironplc_dsl::common::{
+    InitialValueAssignmentKind, SubrangeSpecificationKind, TypeName, VariableType,
+};
+use ironplc_plc2plc::{
+    write_data_type_declaration_to_string, write_structure_element_declaration_to_string,
+    write_var_decl_to_string,
+};
+
+/// The language identifier for code blocks in the Markdown content. This
+/// matches the language identifier of the Visual Studio Code extension.
+const LANGUAGE_ID: &str = "61131-3-st";
+
+/// Returns the Markdown content that describes the definition.
+pub(crate) fn hover_text(index: &SymbolIndex, types: &TypeEnvironment, id: DefinitionId) -> String {
+    let definition = index.definition(id);
+    let name = definition.name.original();
+
+    let code = match &definition.kind {
+        DefinitionKind::Variable(_) | DefinitionKind::StructureElement | DefinitionKind::Type => {
+            declaration_text(index, id).unwrap_or_else(|| match &definition.type_name {
+                Some(type_name) => format!("{name} : {}", type_name.name.original()),
+                None => name.to_string(),
+            })
+        }
+        DefinitionKind::EnumerationValue => {
+            let container = definition
+                .container
+                .map(|container| index.definition(container));
+            match container {
+                Some(container) if container.kind == DefinitionKind::Type => {
+                    let value = format!("{}#{name}", container.name.original());
+                    match definition
+                        .container
+                        .and_then(|c| declaration_text(index, c))
+                    {
+                        Some(declaration) => format!("{value}\n\n{declaration}"),
+                        None => value,
+                    }
+                }
+                _ => name.to_string(),
+            }
+        }
+        DefinitionKind::Function => pou_text(index, id, "FUNCTION", "END_FUNCTION"),
+        DefinitionKind::FunctionBlock => {
+            pou_text(index, id, "FUNCTION_BLOCK", "END_FUNCTION_BLOCK")
+        }
+        DefinitionKind::Method => pou_text(index, id, "METHOD", "END_METHOD"),
+        DefinitionKind::Program => pou_text(index, id, "PROGRAM", "END_PROGRAM"),
+        DefinitionKind::Interface => format!("INTERFACE {name}"),
+        DefinitionKind::Property => with_type("PROPERTY", name, &definition.type_name),
+        DefinitionKind::Configuration => format!("CONFIGURATION {name}"),
+        DefinitionKind::Resource => format!("RESOURCE {name}"),
+        DefinitionKind::Task => format!("TASK {name}"),
+        DefinitionKind::ProgramInstance => with_type("PROGRAM", name, &definition.type_name),
+    };
+
+    let mut text = format!("```{LANGUAGE_ID}\n{code}\n```");
+    if let Some(size) = memory_size(index, types, id) {
+        let unit = if size == 1 { "byte" } else { "bytes" };
+        text.push_str(&format!("\n\nSize: {size} {unit}"));
+    }
+    text
+}
+
+/// Returns the declaration as source text.
+fn declaration_text(index: &SymbolIndex, id: DefinitionId) -> Option<String> {
+    match index.definition(id).declaration.as_ref()? {
+        Declaration::Variable(decl) => write_var_decl_to_string(decl).ok(),
+        Declaration::StructureElement(decl) => {
+            write_structure_element_declaration_to_string(decl).ok()
+        }
+        Declaration::DataType(decl) => write_data_type_declaration_to_string(decl).ok(),
+    }
+}
+
+/// Returns the header of the POU and the declarations of the variables
+/// that are the interface to the POU.
+fn pou_text(index: &SymbolIndex, id: DefinitionId, keyword: &str, end_keyword: &str) -> String {
+    let definition = index.definition(id);
+    let mut text = match (&definition.kind, &definition.type_name) {
+        (DefinitionKind::Function | DefinitionKind::Method, Some(_)) => {
+            with_type(keyword, definition.name.original(), &definition.type_name)
+        }
+        (DefinitionKind::FunctionBlock, Some(base)) => format!(
+            "{keyword} {} EXTENDS {}",
+            definition.name.original(),
+            base.name.original()
+        ),
+        _ => format!("{keyword} {}", definition.name.original()),
+    };
+
+    for member in index.members(id) {
+        let is_interface = matches!(
+            index.definition(*member).kind,
+            DefinitionKind::Variable(VariableType::Input)
+                | DefinitionKind::Variable(VariableType::Output)
+                | DefinitionKind::Variable(VariableType::InOut)
+        );
+        if is_interface {
+            if let Some(declaration) = declaration_text(index, *member) {
+                text.push('\n');
+                text.push_str(&declaration);
+            }
+        }
+    }
+
+    text.push('\n');
+    text.push_str(end_keyword);
+    text
+}
+
+fn with_type(keyword: &str, name: &str, type_name: &Option<TypeName>) -> String {
+    match type_name {
+        Some(type_name) => format!("{keyword} {name} : {}", type_name.name.original()),
+        None => format!("{keyword} {name}"),
+    }
+}
+
+/// Returns the size in bytes of the definition if the definition is a
+/// variable, structure element or type that has a known size.
+fn memory_size(index: &SymbolIndex, types: &TypeEnvironment, id: DefinitionId) -> Option<u32> {
+    let definition = index.definition(id);
+    let type_name = match definition.declaration.as_ref()? {
+        Declaration::Variable(decl) => declared_type_name(&decl.initializer)?,
+        Declaration::StructureElement(decl) => declared_type_name(&decl.init)?,
+        Declaration::DataType(_) => TypeName::from_id(&definition.name),
+    };
+    types.get_memory_size(&type_name).ok().flatten()
+}
+
+/// Returns the name of the type for initializers that declare a variable
+/// with a named type.
+pub(crate) fn declared_type_name(init: &InitialValueAssignmentKind) -> Option<TypeName> {
+    match init {
+        InitialValueAssignmentKind::Simple(init) => Some(init.type_name.clone()),
+        InitialValueAssignmentKind::EnumeratedType(init) => Some(init.type_name.clone()),
+        InitialValueAssignmentKind::FunctionBlock(init) => Some(init.type_name.clone()),
+        InitialValueAssignmentKind::Structure(init) => Some(init.type_name.clone()),
+        InitialValueAssignmentKind::LateResolvedType(type_name) => Some(type_name.clone()),
+        InitialValueAssignmentKind::Subrange(SubrangeSpecificationKind::Type(type_name)) => {
+            Some(type_name.clone())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_analyzer::{
+        stages::resolve_type_environment, symbol_index::SymbolIndex, TypeEnvironment,
+    };
+    use ironplc_dsl::{common::Library, core::FileId};
+    use ironplc_parser::{options::ParseOptions, parse_program};
+
+    use super::hover_text;
+
+    const PROGRAM: &str = "
+TYPE
+   LEVEL : (LOW, HIGH) := LOW;
+   POINT : STRUCT
+      x : INT;
+      y : INT;
+   END_STRUCT;
+END_TYPE
+FUNCTION_BLOCK counter
+VAR_INPUT
+   reset : BOOL;
+END_VAR
+VAR
+   count : DINT := 10;
+END_VAR
+END_FUNCTION_BLOCK
+PROGRAM main
+VAR
+   p : POINT;
+   instance : counter;
+   level_value : LEVEL := HIGH;
+END_VAR
+   p.x := 1;
+   instance(reset := TRUE);
+END_PROGRAM";
+
+    fn hover(text: &str, nth: usize) -> String {
+        let library: Library =
+            parse_program(PROGRAM, &FileId::default(), &ParseOptions::default()).unwrap();
+        let index = SymbolIndex::new(&[&library]);
+        let types: TypeEnvironment = resolve_type_environment(&[&library]).unwrap();
+        let offset = PROGRAM.match_indices(text).nth(nth).unwrap().0;
+        let id = index.find(&FileId::default(), offset).unwrap();
+        hover_text(&index, &types, id)
+    }
+
+    #[test]
+    fn hover_text_when_variable_then_class_type_initial_value_and_size() {
+        let text = hover("count :", 0);
+        assert!(text.contains("VAR\n   count : DINT := 10;\nEND_VAR"));
+        assert!(text.ends_with("Size: 4 bytes"));
+    }
+
+    #[test]
+    fn hover_text_when_structure_type_then_fields_and_size() {
+        let text = hover("POINT", 1);
+        assert!(text.contains("POINT : STRUCT"));
+        assert!(text.contains("y : INT"));
+        assert!(text.ends_with("Size: 4 bytes"));
+    }
+
+    #[test]
+    fn hover_text_when_enumeration_value_then_values() {
+        let text = hover("HIGH", 1);
+        assert!(text.contains("LEVEL#HIGH"));
+        assert!(text.contains("LOW"));
+    }
+
+    #[test]
+    fn hover_text_when_function_block_then_inputs() {
+        let text = hover("counter", 1);
+        assert!(text.contains("FUNCTION_BLOCK counter\nVAR_INPUT\n   reset : BOOL;\nEND_VAR"));
+        assert!(!text.contains("count :"));
+    }
+}
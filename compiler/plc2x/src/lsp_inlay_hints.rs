@@ -0,0 +1,354 @@
+//! Implements the inlay hints that the language server shows in the text.
+//!
+//! The hints are:
+//!
+//! * the names of the inputs for positional arguments to functions, such
+//!   as `LIMIT(MN: 0, IN: x, MX: 10)`
+//! * the base type of variables declared with an alias or subrange type,
+//!   such as `level : LEVEL : INT(0..10)`
+//! * the value of references to enumeration values, such as `RED = 0`
+
+use ironplc_analyzer::{
+    stdlib::functions::find_standard_function,
+    symbol_index::{Declaration, DefinitionId, DefinitionKind, SymbolIndex},
+    IntermediateType, TypeEnvironment,
+};
+use ironplc_dsl::{
+    common::{
+        DataTypeDeclarationKind, Library, SubrangeSpecificationKind, TypeName, VarDecl,
+        VariableType,
+    },
+    core::{FileId, Id},
+    textual::{Function, ParamAssignmentKind},
+    visitor::Visitor,
+};
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel};
+
+use crate::lsp_completion::is_identifier_char;
+use crate::lsp_hover::declared_type_name;
+use crate::lsp_project::to_position;
+
+/// Returns the inlay hints for the library that is the contents of the file.
+pub(crate) fn inlay_hints(
+    index: &SymbolIndex,
+    types: &TypeEnvironment,
+    library: &Library,
+    contents: &str,
+    file_id: &FileId,
+) -> Vec<InlayHint> {
+    let mut builder = HintBuilder {
+        index,
+        types,
+        contents,
+        hints: vec![],
+    };
+    // The builder does not fail.
+    let _ = builder.walk(library);
+    builder.enumeration_values(file_id);
+
+    builder.hints.sort_by_key(|hint| hint.position);
+    builder.hints
+}
+
+struct HintBuilder<'a> {
+    index: &'a SymbolIndex,
+    types: &'a TypeEnvironment,
+    contents: &'a str,
+    hints: Vec<InlayHint>,
+}
+
+impl HintBuilder<'_> {
+    fn push(&mut self, offset: usize, label: String, kind: InlayHintKind) {
+        let is_parameter = kind == InlayHintKind::PARAMETER;
+        self.hints.push(InlayHint {
+            position: to_position(self.contents, offset),
+            label: InlayHintLabel::String(label),
+            kind: Some(kind),
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(!is_parameter),
+            padding_right: Some(is_parameter),
+            data: None,
+        });
+    }
+
+    /// Adds the value of each reference in the file to an enumeration value.
+    fn enumeration_values(&mut self, file_id: &FileId) {
+        let values: Vec<DefinitionId> = self
+            .index
+            .definitions()
+            .filter(|id| self.index.definition(*id).kind == DefinitionKind::EnumerationValue)
+            .collect();
+
+        for id in values {
+            let Some(value) = enumeration_value(self.index, id) else {
+                continue;
+            };
+            let ends: Vec<usize> = self
+                .index
+                .references(id)
+                .iter()
+                .filter(|span| span.file_id == *file_id)
+                .map(|span| span.end)
+                .collect();
+            for end in ends {
+                self.push(end, format!("= {value}"), InlayHintKind::TYPE);
+            }
+        }
+    }
+
+    /// Returns the input names of the function in the order of positional
+    /// arguments.
+    fn input_names(&self, name: &Id) -> Option<Vec<String>> {
+        if let Some(id) = self.index.lookup(None, name) {
+            if self.index.definition(id).kind != DefinitionKind::Function {
+                return None;
+            }
+            return Some(
+                self.index
+                    .members(id)
+                    .iter()
+                    .map(|member| self.index.definition(*member))
+                    .filter(|member| {
+                        matches!(
+                            member.kind,
+                            DefinitionKind::Variable(VariableType::Input | VariableType::InOut)
+                        )
+                    })
+                    .map(|member| member.name.original().to_owned())
+                    .collect(),
+            );
+        }
+
+        // Additional arguments to extensible functions do not have hints.
+        let function = find_standard_function(name)?;
+        Some(
+            function
+                .inputs
+                .iter()
+                .map(|input| input.name.original().to_owned())
+                .collect(),
+        )
+    }
+
+    /// Returns the text of the base type if the type is an alias or a
+    /// subrange, for example `INT(0..10)`.
+    fn base_type_text(&self, type_name: &TypeName) -> Option<String> {
+        let mut base = type_name.clone();
+        let mut visited = vec![];
+        while let Some(id) = self.index.type_definition(&base) {
+            let next = match self.index.definition(id).declaration.as_ref()? {
+                Declaration::DataType(DataTypeDeclarationKind::Simple(decl)) => {
+                    declared_type_name(&decl.spec_and_init)?
+                }
+                Declaration::DataType(DataTypeDeclarationKind::LateBound(decl)) => {
+                    decl.base_type_name.clone()
+                }
+                Declaration::DataType(DataTypeDeclarationKind::Subrange(decl)) => {
+                    match &decl.spec {
+                        SubrangeSpecificationKind::Specification(spec) => {
+                            spec.type_name.clone().into()
+                        }
+                        SubrangeSpecificationKind::Type(type_name) => type_name.clone(),
+                    }
+                }
+                _ => return None,
+            };
+            // Guard against declarations that refer to themselves.
+            if visited.contains(&next) {
+                return None;
+            }
+            visited.push(base);
+            base = next;
+        }
+        if visited.is_empty() {
+            return None;
+        }
+
+        let representation = &self.types.get(type_name)?.representation;
+        Some(match representation {
+            IntermediateType::Subrange {
+                min_value,
+                max_value,
+                ..
+            } => format!("{}({min_value}..{max_value})", base.name.original()),
+            _ => base.name.original().to_owned(),
+        })
+    }
+}
+
+impl Visitor<()> for HintBuilder<'_> {
+    type Value = ();
+
+    fn visit_var_decl(&mut self, node: &VarDecl) -> Result<(), ()> {
+        if let Some(type_name) = declared_type_name(&node.initializer) {
+            if let Some(text) = self.base_type_text(&type_name) {
+                self.push(
+                    type_name.name.span.end,
+                    format!(": {text}"),
+                    InlayHintKind::TYPE,
+                );
+            }
+        }
+        node.recurse_visit(self)
+    }
+
+    fn visit_function(&mut self, node: &Function) -> Result<(), ()> {
+        let is_positional = node
+            .param_assignment
+            .iter()
+            .all(|param| matches!(param, ParamAssignmentKind::PositionalInput(_)));
+        if is_positional && !node.param_assignment.is_empty() {
+            if let Some(names) = self.input_names(&node.name) {
+                let arguments = argument_starts(self.contents, node.name.span.end);
+                if arguments.len() == node.param_assignment.len() {
+                    for ((start, text), name) in arguments.into_iter().zip(names) {
+                        // The name is noise when the argument is the same name.
+                        if !text.eq_ignore_ascii_case(&name) {
+                            self.push(start, format!("{name}:"), InlayHintKind::PARAMETER);
+                        }
+                    }
+                }
+            }
+        }
+        node.recurse_visit(self)
+    }
+}
+
+/// Returns the start and the identifier (if any) of each argument in the
+/// argument list that follows the offset.
+fn argument_starts(contents: &str, offset: usize) -> Vec<(usize, String)> {
+    let Some(rest) = contents.get(offset..) else {
+        return vec![];
+    };
+    let Some(open) = rest.find('(') else {
+        return vec![];
+    };
+    if !rest[..open].trim().is_empty() {
+        return vec![];
+    }
+
+    let mut starts = vec![];
+    let mut start = Some(offset + open + 1);
+    let mut depth = 0;
+    let mut in_string = false;
+    for (i, c) in rest[open + 1..].char_indices() {
+        let position = offset + open + 1 + i;
+        if let Some(argument) = start {
+            if c.is_whitespace() {
+                start = Some(position + c.len_utf8());
+                continue;
+            }
+            let text: String = contents[argument..]
+                .chars()
+                .take_while(|c| is_identifier_char(*c))
+                .collect();
+            starts.push((argument, text));
+            start = None;
+        }
+        match c {
+            '\'' | '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' | '[' => depth += 1,
+            ')' if depth == 0 => break,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => start = Some(position + 1),
+            _ => {}
+        }
+    }
+    starts
+}
+
+/// Returns the value of the enumeration value, that is, the position of
+/// the value in the declaration.
+fn enumeration_value(index: &SymbolIndex, id: DefinitionId) -> Option<usize> {
+    let container = index.definition(id).container?;
+    index
+        .members(container)
+        .iter()
+        .filter(|member| index.definition(**member).kind == DefinitionKind::EnumerationValue)
+        .position(|member| *member == id)
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_analyzer::{stages::resolve_type_environment, symbol_index::SymbolIndex};
+    use ironplc_dsl::core::FileId;
+    use ironplc_parser::{options::ParseOptions, parse_program};
+    use lsp_types::{InlayHint, InlayHintLabel};
+
+    use super::inlay_hints;
+
+    fn labels(program: &str) -> Vec<String> {
+        let library = parse_program(program, &FileId::default(), &ParseOptions::default()).unwrap();
+        let index = SymbolIndex::new(&[&library]);
+        let types = resolve_type_environment(&[&library]).unwrap();
+        inlay_hints(&index, &types, &library, program, &FileId::default())
+            .into_iter()
+            .map(|hint: InlayHint| match hint.label {
+                InlayHintLabel::String(label) => label,
+                InlayHintLabel::LabelParts(_) => panic!("Expected string label"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inlay_hints_when_positional_call_then_input_names() {
+        let program = "
+FUNCTION scale : INT
+VAR_INPUT
+   value : INT;
+   factor : INT;
+END_VAR
+   scale := value * factor;
+END_FUNCTION
+PROGRAM main
+VAR
+   value : INT;
+END_VAR
+   value := scale(value, 2);
+   value := LIMIT(0, value, (10 + 1));
+END_PROGRAM";
+
+        assert_eq!(vec!["factor:", "MN:", "IN:", "MX:"], labels(program));
+    }
+
+    #[test]
+    fn inlay_hints_when_alias_and_subrange_then_base_types() {
+        let program = "
+TYPE
+   PERCENT : INT(0..100);
+   AMOUNT : PERCENT;
+   COUNT : DINT := 0;
+END_TYPE
+PROGRAM main
+VAR
+   done : PERCENT;
+   total : AMOUNT;
+   items : COUNT;
+   other : INT;
+END_VAR
+END_PROGRAM";
+
+        assert_eq!(
+            vec![": INT(0..100)", ": INT(0..100)", ": DINT"],
+            labels(program)
+        );
+    }
+
+    #[test]
+    fn inlay_hints_when_enumeration_value_then_value() {
+        let program = "
+TYPE
+   LEVEL : (LOW, MEDIUM, HIGH) := LOW;
+END_TYPE
+PROGRAM main
+VAR
+   level_value : LEVEL;
+END_VAR
+   level_value := HIGH;
+END_PROGRAM";
+
+        assert_eq!(vec!["= 0", "= 2"], labels(program));
+    }
+}
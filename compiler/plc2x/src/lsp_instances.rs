@@ -0,0 +1,324 @@
+//! Implements the custom request for the instance tree of a configuration.
+//!
+//! The instance tree is the configuration, then the resources of the
+//! configuration, then the tasks of each resource, then the program
+//! instances that each task runs and then the function block instances
+//! that the programs (and function blocks) declare. Program instances that
+//! are not associated with a task are children of the resource.
+
+use ironplc_analyzer::symbol_index::{Declaration, DefinitionId, DefinitionKind, SymbolIndex};
+use ironplc_dsl::{
+    common::{InitialValueAssignmentKind, Library, LibraryElementKind, TypeName, VariableType},
+    configuration::{ConfigurationDeclaration, ProgramConfiguration, ResourceDeclaration},
+    core::{Id, SourceSpan},
+};
+use lsp_types::{request::Request, Location};
+use serde::{Deserialize, Serialize};
+
+use crate::lsp_signature::STANDARD_FUNCTION_BLOCKS;
+
+/// Request for the instance trees of the configurations in the project.
+pub(crate) enum InstanceTreeRequest {}
+
+impl Request for InstanceTreeRequest {
+    type Params = InstanceTreeParams;
+    type Result = Vec<InstanceNode>;
+    const METHOD: &'static str = "ironplc/instanceTree";
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InstanceTreeParams {
+    /// The name of the configuration. The result is the trees for all
+    /// configurations when the name is not specified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub configuration: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum InstanceKind {
+    Configuration,
+    Resource,
+    Task,
+    Program,
+    FunctionBlock,
+}
+
+/// A node in the instance tree.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InstanceNode {
+    pub name: String,
+    pub kind: InstanceKind,
+    /// The name of the type of the instance: the program or function block
+    /// for instances and the processor for resources.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    /// The location of the declaration of the instance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    pub children: Vec<InstanceNode>,
+}
+
+/// Returns the instance tree of the configuration. The location function
+/// converts the location of a name into the location for the response.
+pub(crate) fn instance_tree<F>(
+    index: &SymbolIndex,
+    configuration: &ConfigurationDeclaration,
+    location: F,
+) -> InstanceNode
+where
+    F: Fn(&SourceSpan) -> Option<Location>,
+{
+    let builder = TreeBuilder {
+        index,
+        standard: &STANDARD_FUNCTION_BLOCKS,
+        location,
+    };
+    builder.node(
+        &configuration.name,
+        InstanceKind::Configuration,
+        None,
+        configuration
+            .resource_decl
+            .iter()
+            .map(|resource| builder.resource(resource))
+            .collect(),
+    )
+}
+
+/// Returns the configurations in the libraries that have the name (or all
+/// configurations if there is no name).
+pub(crate) fn configurations(
+    libraries: &[&Library],
+    name: Option<&str>,
+) -> Vec<ConfigurationDeclaration> {
+    libraries
+        .iter()
+        .flat_map(|library| library.elements.iter())
+        .filter_map(|element| match element {
+            LibraryElementKind::ConfigurationDeclaration(decl) => Some(decl),
+            _ => None,
+        })
+        .filter(|decl| name.is_none_or(|name| decl.name == Id::from(name)))
+        .cloned()
+        .collect()
+}
+
+struct TreeBuilder<'a, F> {
+    index: &'a SymbolIndex,
+    /// The index of the standard function blocks (that are not declared in
+    /// the project).
+    standard: &'a SymbolIndex,
+    location: F,
+}
+
+impl<F> TreeBuilder<'_, F>
+where
+    F: Fn(&SourceSpan) -> Option<Location>,
+{
+    fn node(
+        &self,
+        name: &Id,
+        kind: InstanceKind,
+        type_name: Option<&Id>,
+        children: Vec<InstanceNode>,
+    ) -> InstanceNode {
+        InstanceNode {
+            name: name.original().to_owned(),
+            kind,
+            type_name: type_name.map(|type_name| type_name.original().to_owned()),
+            location: (self.location)(&name.span),
+            children,
+        }
+    }
+
+    fn resource(&self, resource: &ResourceDeclaration) -> InstanceNode {
+        let mut children: Vec<InstanceNode> = resource
+            .tasks
+            .iter()
+            .map(|task| {
+                let programs = resource
+                    .programs
+                    .iter()
+                    .filter(|program| program.task_name.as_ref() == Some(&task.name))
+                    .map(|program| self.program(program))
+                    .collect();
+                self.node(&task.name, InstanceKind::Task, None, programs)
+            })
+            .collect();
+        children.extend(
+            resource
+                .programs
+                .iter()
+                .filter(|program| program.task_name.is_none())
+                .map(|program| self.program(program)),
+        );
+        self.node(
+            &resource.name,
+            InstanceKind::Resource,
+            Some(&resource.resource),
+            children,
+        )
+    }
+
+    fn program(&self, program: &ProgramConfiguration) -> InstanceNode {
+        let children = self
+            .index
+            .type_definition(&TypeName::from_id(&program.type_name))
+            .filter(|id| self.index.definition(*id).kind == DefinitionKind::Program)
+            .map(|id| self.instances(self.index, id, &mut vec![]))
+            .unwrap_or_default();
+        self.node(
+            &program.name,
+            InstanceKind::Program,
+            Some(&program.type_name),
+            children,
+        )
+    }
+
+    /// Returns the function block instances that the program or function
+    /// block declares. The path is the function blocks that contain the
+    /// instances so that recursive declarations terminate.
+    fn instances(
+        &self,
+        index: &SymbolIndex,
+        container: DefinitionId,
+        path: &mut Vec<DefinitionId>,
+    ) -> Vec<InstanceNode> {
+        path.push(container);
+        let mut nodes = vec![];
+        for member in index.visible_members(container) {
+            let definition = index.definition(member);
+            let Some(type_name) = instance_type_name(index, member) else {
+                continue;
+            };
+            let Some((type_index, type_id)) = self.function_block(type_name) else {
+                continue;
+            };
+            let children = if std::ptr::eq(type_index, index) && path.contains(&type_id) {
+                vec![]
+            } else {
+                self.instances(type_index, type_id, path)
+            };
+            nodes.push(self.node(
+                &definition.name,
+                InstanceKind::FunctionBlock,
+                Some(&type_name.name),
+                children,
+            ));
+        }
+        path.pop();
+        nodes
+    }
+
+    /// Returns the definition of the function block with the name from
+    /// the project or the standard library.
+    fn function_block(&self, type_name: &TypeName) -> Option<(&SymbolIndex, DefinitionId)> {
+        [self.index, self.standard].into_iter().find_map(|index| {
+            index
+                .type_definition(type_name)
+                .filter(|id| index.definition(*id).kind == DefinitionKind::FunctionBlock)
+                .map(|id| (index, id))
+        })
+    }
+}
+
+/// Returns the type name of the variable if the variable is an instance,
+/// that is, a variable that the container owns (not external, in-out or
+/// a reference).
+fn instance_type_name(index: &SymbolIndex, id: DefinitionId) -> Option<&TypeName> {
+    let definition = index.definition(id);
+    match &definition.kind {
+        DefinitionKind::Variable(VariableType::External | VariableType::InOut) => return None,
+        DefinitionKind::Variable(_) => {}
+        _ => return None,
+    }
+    if let Some(Declaration::Variable(decl)) = &definition.declaration {
+        if matches!(decl.initializer, InitialValueAssignmentKind::Reference(_)) {
+            return None;
+        }
+    }
+    definition.type_name.as_ref()
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_analyzer::symbol_index::SymbolIndex;
+    use ironplc_dsl::core::FileId;
+    use ironplc_parser::{options::ParseOptions, parse_program};
+
+    use super::{configurations, instance_tree, InstanceKind, InstanceNode};
+
+    fn describe(node: &InstanceNode, depth: usize, lines: &mut Vec<String>) {
+        lines.push(format!(
+            "{}{} {:?} {}",
+            "  ".repeat(depth),
+            node.name,
+            node.kind,
+            node.type_name.as_deref().unwrap_or_default()
+        ));
+        for child in &node.children {
+            describe(child, depth + 1, lines);
+        }
+    }
+
+    #[test]
+    fn instance_tree_when_configuration_then_resources_tasks_programs_and_instances() {
+        let program = "
+FUNCTION_BLOCK motor
+VAR
+   timer : TON;
+   speed : INT;
+END_VAR
+END_FUNCTION_BLOCK
+FUNCTION_BLOCK line
+VAR
+   first : motor;
+   second : motor;
+END_VAR
+END_FUNCTION_BLOCK
+PROGRAM main
+VAR
+   conveyor : line;
+END_VAR
+END_PROGRAM
+CONFIGURATION plant
+   RESOURCE cpu ON PLC
+      TASK fast(INTERVAL := T#10ms, PRIORITY := 1);
+      PROGRAM main_instance WITH fast : main;
+      PROGRAM background : main;
+   END_RESOURCE
+END_CONFIGURATION";
+        let library = parse_program(program, &FileId::default(), &ParseOptions::default()).unwrap();
+        let index = SymbolIndex::new(&[&library]);
+        let configuration = &configurations(&[&library], Some("PLANT"))[0];
+
+        let tree = instance_tree(&index, configuration, |_| None);
+
+        let mut lines = vec![];
+        describe(&tree, 0, &mut lines);
+        assert_eq!(InstanceKind::Configuration, tree.kind);
+        assert_eq!(
+            vec![
+                "plant Configuration ",
+                "  cpu Resource PLC",
+                "    fast Task ",
+                "      main_instance Program main",
+                "        conveyor FunctionBlock line",
+                "          first FunctionBlock motor",
+                "            timer FunctionBlock TON",
+                "          second FunctionBlock motor",
+                "            timer FunctionBlock TON",
+                "    background Program main",
+                "      conveyor FunctionBlock line",
+                "        first FunctionBlock motor",
+                "          timer FunctionBlock TON",
+                "        second FunctionBlock motor",
+                "          timer FunctionBlock TON",
+            ],
+            lines
+        );
+    }
+}
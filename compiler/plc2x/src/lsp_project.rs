@@ -1,38 +1,79 @@
 //! Adapts data types between what is required by the compiler
 //! and the language server protocol.
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use ironplc_dsl::core::FileId;
+use ironplc_analyzer::symbol_index::{DefinitionId, DefinitionKind, SymbolIndex};
+use ironplc_dsl::common::Library;
+use ironplc_dsl::core::{FileId, Id, SourceSpan};
+use ironplc_parser::options::ParseOptions;
 use ironplc_parser::token::{Token, TokenType};
+use ironplc_parser::tokenize_program;
+use ironplc_problems::{Problem, Severity};
+use ironplc_sources::{FileType, Source};
 use log::error;
 use lsp_types::{
-    CodeDescription, Diagnostic, DiagnosticSeverity, NumberOrString, SemanticTokenType,
-    WorkspaceFolder,
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, CodeAction,
+    CodeActionKind, CodeActionOrCommand, CodeDescription, CompletionItem, Diagnostic,
+    DiagnosticSeverity, DocumentSymbol, FoldingRange, Hover, HoverContents, InlayHint, Location,
+    MarkupContent, MarkupKind, NumberOrString, Position, PrepareRenameResponse, SelectionRange,
+    SemanticTokenType, SignatureHelp, SymbolKind, TextDocumentContentChangeEvent, TextEdit,
+    WorkspaceEdit, WorkspaceFolder,
 };
-use lsp_types::{SemanticToken, Uri};
+use lsp_types::{SemanticToken, Uri, WorkspaceSymbol};
 
+use crate::lsp_blocks::{folding_ranges, format_document, format_range, selection_ranges};
+use crate::lsp_call_hierarchy::{callable, calls, incoming, outgoing, Call};
+use crate::lsp_completion::completions;
+use crate::lsp_hover::hover_text;
+use crate::lsp_inlay_hints::inlay_hints;
+use crate::lsp_instances::{configurations, instance_tree, InstanceNode, InstanceTreeParams};
+use crate::lsp_signature::signature_help;
+use crate::lsp_symbols::{document_symbols, workspace_symbols};
 use crate::project::Project;
 
 fn to_path_buf(uri: &Uri) -> Result<PathBuf, ()> {
     Ok(PathBuf::from(uri.path().as_str()))
 }
 
+/// Converts the file identifier (a path) into a URI. This is the inverse
+/// of [`to_path_buf`].
+fn to_uri(file_id: &FileId) -> Option<Uri> {
+    let path = file_id.to_string().replace('\\', "/");
+    let uri = match path.starts_with('/') {
+        true => format!("file://{path}"),
+        false => format!("file:///{path}"),
+    };
+    Uri::from_str(&uri).ok()
+}
+
 /// The LSP project provides a view onto a project that accepts
 /// and returns LSP types.
 pub struct LspProject {
     wrapped: Box<dyn Project + Send>,
+    /// The diagnostics from the last analysis of the project.
+    diagnostics: Vec<ironplc_dsl::diagnostic::Diagnostic>,
 }
 
 impl LspProject {
     pub fn new(project: Box<dyn Project + Send>) -> Self {
-        Self { wrapped: project }
+        Self {
+            wrapped: project,
+            diagnostics: vec![],
+        }
     }
 
     pub(crate) fn initialize(&mut self, folder: &WorkspaceFolder) {
         let path = to_path_buf(&folder.uri);
         if let Ok(path) = path {
-            self.wrapped.initialize(&path);
+            for diagnostic in self.wrapped.initialize(&path) {
+                error!(
+                    "Unable to initialize project {}: {}",
+                    diagnostic.code, diagnostic.primary.message
+                );
+            }
         } else {
             error!(
                 "URL must be convertible to a file path {}",
@@ -78,26 +119,648 @@ impl LspProject {
         Err(vec![])
     }
 
-    pub(crate) fn semantic(&mut self, uri: &Uri) -> Vec<lsp_types::Diagnostic> {
-        let path = to_path_buf(uri);
-        if let Ok(path) = path {
-            let file_id = FileId::from_path(&path);
-            let semantic_result = self.wrapped.semantic();
+    /// Applies the changes to the text of a document. A change without
+    /// a range replaces the entire text.
+    pub(crate) fn apply_changes(
+        &mut self,
+        uri: &Uri,
+        changes: Vec<TextDocumentContentChangeEvent>,
+    ) {
+        let Ok(path) = to_path_buf(uri) else {
+            error!("URL must be convertible to a file path {}", uri.as_str());
+            return;
+        };
+        let file_id = FileId::from_path(&path);
+        let mut contents = self
+            .wrapped
+            .find(&file_id)
+            .map(|source| source.as_string().to_owned())
+            .unwrap_or_default();
 
-            return match semantic_result {
-                Ok(_) => vec![],
-                Err(diagnostics) => diagnostics
-                    .into_iter()
+        for change in changes {
+            match change.range {
+                Some(range) => {
+                    let start = to_offset(&contents, range.start);
+                    let end = to_offset(&contents, range.end).max(start);
+                    contents.replace_range(start..end, &change.text);
+                }
+                None => contents = change.text,
+            }
+        }
+
+        self.wrapped.change_text_document(&file_id, contents);
+    }
+
+    /// Analyzes the project and returns the diagnostics for each of the
+    /// documents and for every other document that had diagnostics from the
+    /// last analysis or has diagnostics now. A change to one document can
+    /// add or resolve problems in other documents. This analyzes the project
+    /// once for all of the documents and keeps the diagnostics for the code
+    /// actions.
+    pub(crate) fn semantic(&mut self, uris: &[Uri]) -> Vec<(Uri, Vec<lsp_types::Diagnostic>)> {
+        let previous = std::mem::take(&mut self.diagnostics);
+        self.diagnostics = self.wrapped.semantic().err().unwrap_or_default();
+
+        let mut documents: Vec<(Uri, FileId)> = uris
+            .iter()
+            .filter_map(|uri| match to_path_buf(uri) {
+                Ok(path) => Some((uri.clone(), FileId::from_path(&path))),
+                Err(_) => {
+                    error!("URL must be convertible to a file path {uri:?}");
+                    None
+                }
+            })
+            .collect();
+        for file_id in previous
+            .iter()
+            .chain(self.diagnostics.iter())
+            .flat_map(|d| d.file_ids())
+        {
+            if *file_id == FileId::default() || documents.iter().any(|(_, id)| id == file_id) {
+                continue;
+            }
+            if let Some(uri) = to_uri(file_id) {
+                documents.push((uri, file_id.clone()));
+            }
+        }
+
+        documents
+            .into_iter()
+            .map(|(uri, file_id)| {
+                let diagnostics = self
+                    .diagnostics
+                    .iter()
                     .filter(|d| d.file_ids().contains(&file_id))
-                    .map(|d| map_diagnostic(d, self.wrapped.as_ref()))
-                    .collect(),
+                    .map(|d| map_diagnostic(d.clone(), self.wrapped.as_ref()))
+                    .collect();
+                (uri, diagnostics)
+            })
+            .collect()
+    }
+
+    /// Returns the quick fixes for the problems in the range of the document.
+    ///
+    /// The problems are from the last analysis of the project (so that a
+    /// request does not analyze the project again).
+    // The workspace edit is keyed by URI (that clippy considers mutable).
+    #[allow(clippy::mutable_key_type)]
+    pub(crate) fn code_actions(
+        &mut self,
+        uri: &Uri,
+        range: lsp_types::Range,
+    ) -> Vec<CodeActionOrCommand> {
+        let Ok(path) = to_path_buf(uri) else {
+            error!("URL must be convertible to a file path {}", uri.as_str());
+            return vec![];
+        };
+        let file_id = FileId::from_path(&path);
+        let Some(source) = self.wrapped.find(&file_id) else {
+            return vec![];
+        };
+        let start = to_offset(source.as_string(), range.start);
+        let end = to_offset(source.as_string(), range.end);
+
+        let mut actions = vec![];
+        for diagnostic in self.diagnostics.iter().filter(|d| {
+            d.primary.file_id == file_id
+                && d.primary.location.start <= end
+                && start <= d.primary.location.end
+        }) {
+            let lsp_diagnostic = map_diagnostic(diagnostic.clone(), self.wrapped.as_ref());
+            for fix in &diagnostic.fixes {
+                let mut changes: HashMap<Uri, Vec<TextEdit>> = HashMap::new();
+                for edit in &fix.edits {
+                    let span = SourceSpan {
+                        start: edit.location.start,
+                        end: edit.location.end,
+                        file_id: edit.file_id.clone(),
+                    };
+                    if let Some(location) = map_location(&span, self.wrapped.as_ref()) {
+                        changes
+                            .entry(location.uri)
+                            .or_default()
+                            .push(TextEdit::new(location.range, edit.new_text.clone()));
+                    }
+                }
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title.clone(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![lsp_diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit::new(changes)),
+                    is_preferred: Some(diagnostic.fixes.len() == 1),
+                    ..CodeAction::default()
+                }));
+            }
+        }
+        actions
+    }
+
+    /// Returns the location of the declaration for the name at the position.
+    pub(crate) fn definition(&mut self, uri: &Uri, position: Position) -> Option<Location> {
+        let file_id = FileId::from_path(&to_path_buf(uri).ok()?);
+        let offset = to_offset(self.wrapped.find(&file_id)?.as_string(), position);
+
+        let index = self.wrapped.symbol_index();
+        let id = index.find(&file_id, offset)?;
+        map_location(&index.definition(id).name.span, self.wrapped.as_ref())
+    }
+
+    /// Returns the description of the name at the position.
+    pub(crate) fn hover(&mut self, uri: &Uri, position: Position) -> Option<Hover> {
+        let file_id = FileId::from_path(&to_path_buf(uri).ok()?);
+        let offset = to_offset(self.wrapped.find(&file_id)?.as_string(), position);
+
+        let index = self.wrapped.symbol_index();
+        let id = index.find(&file_id, offset)?;
+        let types = self.wrapped.type_environment();
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: hover_text(&index, &types, id),
+            }),
+            range: None,
+        })
+    }
+
+    /// Returns the items that complete the name at the position.
+    pub(crate) fn completion(&mut self, uri: &Uri, position: Position) -> Vec<CompletionItem> {
+        let Ok(path) = to_path_buf(uri) else {
+            error!("URL must be convertible to a file path {}", uri.as_str());
+            return vec![];
+        };
+        let file_id = FileId::from_path(&path);
+        let Some(source) = self.wrapped.find(&file_id) else {
+            return vec![];
+        };
+        let contents = source.as_string().to_owned();
+        let offset = to_offset(&contents, position);
+
+        let index = self.wrapped.symbol_index();
+        completions(&index, &contents, &file_id, offset)
+    }
+
+    /// Returns the signature of the call that has the position in the
+    /// arguments of the call.
+    pub(crate) fn signature_help(
+        &mut self,
+        uri: &Uri,
+        position: Position,
+    ) -> Option<SignatureHelp> {
+        let file_id = FileId::from_path(&to_path_buf(uri).ok()?);
+        let contents = self.wrapped.find(&file_id)?.as_string().to_owned();
+        let offset = to_offset(&contents, position);
+
+        let index = self.wrapped.symbol_index();
+        signature_help(&index, &contents, &file_id, offset)
+    }
+
+    /// Returns the range of the name at the position if the name is a
+    /// declaration that can be renamed.
+    pub(crate) fn prepare_rename(
+        &mut self,
+        uri: &Uri,
+        position: Position,
+    ) -> Option<PrepareRenameResponse> {
+        let file_id = FileId::from_path(&to_path_buf(uri).ok()?);
+        let contents = self.wrapped.find(&file_id)?.as_string().to_owned();
+        let offset = to_offset(&contents, position);
+
+        let index = self.wrapped.symbol_index();
+        let (id, span) = index.find_name(&file_id, offset)?;
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: lsp_types::Range::new(
+                to_position(&contents, span.start),
+                to_position(&contents, span.end),
+            ),
+            placeholder: index.definition(id).name.original().to_owned(),
+        })
+    }
+
+    /// Returns the edits that rename the name at the position, the
+    /// declaration and all references in the project.
+    ///
+    /// Returns `Err` with a message if the new name is not an identifier or
+    /// if another declaration in the same scope has the new name.
+    // The workspace edit is keyed by URI (that clippy considers mutable).
+    #[allow(clippy::mutable_key_type)]
+    pub(crate) fn rename(
+        &mut self,
+        uri: &Uri,
+        position: Position,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>, String> {
+        let Ok(path) = to_path_buf(uri) else {
+            return Err(format!(
+                "URL must be convertible to a file path {}",
+                uri.as_str()
+            ));
+        };
+        let file_id = FileId::from_path(&path);
+        let Some(source) = self.wrapped.find(&file_id) else {
+            return Ok(None);
+        };
+        let offset = to_offset(source.as_string(), position);
+
+        let index = self.wrapped.symbol_index();
+        let Some(id) = index.find(&file_id, offset) else {
+            return Ok(None);
+        };
+
+        if !is_identifier(new_name) {
+            return Err(format!("{new_name} is not a valid identifier"));
+        }
+        let definition = index.definition(id);
+        if let Some(conflict) = index.conflict(id, &Id::from(new_name)) {
+            let problem = match definition.container {
+                Some(_) => Problem::SymbolDeclDuplicated,
+                None => Problem::DefinitionNameDuplicated,
             };
+            return Err(format!(
+                "{} - {}: {} is already declared",
+                problem.code(),
+                problem.message(),
+                index.definition(conflict).name.original()
+            ));
+        }
+
+        let mut changes: HashMap<Uri, Vec<TextEdit>> = HashMap::new();
+        let spans = std::iter::once(&definition.name.span).chain(index.references(id));
+        for location in spans.filter_map(|span| map_location(span, self.wrapped.as_ref())) {
+            changes
+                .entry(location.uri)
+                .or_default()
+                .push(TextEdit::new(location.range, new_name.to_owned()));
+        }
+        Ok(Some(WorkspaceEdit::new(changes)))
+    }
+
+    /// Returns the symbols declared in the document.
+    pub(crate) fn document_symbols(&mut self, uri: &Uri) -> Vec<DocumentSymbol> {
+        let Ok(path) = to_path_buf(uri) else {
+            error!("URL must be convertible to a file path {}", uri.as_str());
+            return vec![];
+        };
+        let file_id = FileId::from_path(&path);
+        let Some(source) = self
+            .wrapped
+            .sources_mut()
+            .into_iter()
+            .find(|source| *source.file_id() == file_id)
+        else {
+            return vec![];
+        };
+        let contents = source.as_string().to_owned();
+        let (library, _) = Source::partial_library(source);
+        document_symbols(library, &contents)
+    }
+
+    /// Returns the inlay hints in the range of the document.
+    pub(crate) fn inlay_hints(&mut self, uri: &Uri, range: lsp_types::Range) -> Vec<InlayHint> {
+        let Ok(path) = to_path_buf(uri) else {
+            error!("URL must be convertible to a file path {}", uri.as_str());
+            return vec![];
+        };
+        let file_id = FileId::from_path(&path);
+        let index = self.wrapped.symbol_index();
+        let types = self.wrapped.type_environment();
+        let Some(source) = self
+            .wrapped
+            .sources_mut()
+            .into_iter()
+            .find(|source| *source.file_id() == file_id)
+        else {
+            return vec![];
+        };
+        let contents = source.as_string().to_owned();
+        let (library, _) = Source::partial_library(source);
+
+        inlay_hints(&index, &types, library, &contents, &file_id)
+            .into_iter()
+            .filter(|hint| range.start <= hint.position && hint.position <= range.end)
+            .collect()
+    }
+
+    /// Returns the ranges that can be folded in the document.
+    pub(crate) fn folding_ranges(&self, uri: &Uri) -> Vec<FoldingRange> {
+        self.structured_text(uri)
+            .map(|(_, tokens, _)| folding_ranges(&tokens))
+            .unwrap_or_default()
+    }
+
+    /// Returns the ranges to expand the selection to for each position.
+    pub(crate) fn selection_ranges(
+        &self,
+        uri: &Uri,
+        positions: &[Position],
+    ) -> Vec<SelectionRange> {
+        let Some((_, tokens, contents)) = self.structured_text(uri) else {
+            return vec![];
+        };
+        let offsets: Vec<usize> = positions
+            .iter()
+            .map(|position| to_offset(&contents, *position))
+            .collect();
+        selection_ranges(&tokens, &contents, &offsets)
+    }
+
+    /// Returns the edits that format the document or `None` if the document
+    /// cannot be formatted (because the document has errors or comments).
+    pub(crate) fn formatting(&mut self, uri: &Uri) -> Option<Vec<TextEdit>> {
+        let (file_id, tokens, contents) = self.structured_text(uri)?;
+        let library = self.library(&file_id)?;
+        format_document(&tokens, &library, &contents)
+    }
+
+    /// Returns the edits that format the declarations that intersect the
+    /// range or `None` if the document cannot be formatted.
+    pub(crate) fn range_formatting(
+        &mut self,
+        uri: &Uri,
+        range: lsp_types::Range,
+    ) -> Option<Vec<TextEdit>> {
+        let (file_id, tokens, contents) = self.structured_text(uri)?;
+        let library = self.library(&file_id)?;
+        let start = to_offset(&contents, range.start);
+        let end = to_offset(&contents, range.end);
+        format_range(&tokens, &library, &contents, start, end)
+    }
+
+    /// Returns the tokens and contents of the document if the document is
+    /// structured text.
+    fn structured_text(&self, uri: &Uri) -> Option<(FileId, Vec<Token>, String)> {
+        let Ok(path) = to_path_buf(uri) else {
+            error!("URL must be convertible to a file path {}", uri.as_str());
+            return None;
+        };
+        let file_id = FileId::from_path(&path);
+        let source = self.wrapped.find(&file_id)?;
+        if source.file_type() != FileType::StructuredText {
+            return None;
+        }
+        let contents = source.as_string().to_owned();
+        let (tokens, _) = self.wrapped.tokenize(&file_id);
+        Some((file_id, tokens, contents))
+    }
+
+    /// Returns the library for the document if the document has no errors.
+    fn library(&mut self, file_id: &FileId) -> Option<Library> {
+        self.wrapped
+            .sources_mut()
+            .into_iter()
+            .find(|source| *source.file_id() == *file_id)?
+            .library()
+            .ok()
+            .cloned()
+    }
+
+    /// Returns the symbols declared in the project that match the query.
+    pub(crate) fn workspace_symbols(&mut self, query: &str) -> Vec<WorkspaceSymbol> {
+        let mut symbols = vec![];
+        for source in self.wrapped.sources_mut() {
+            let Some(uri) = to_uri(source.file_id()) else {
+                continue;
+            };
+            let contents = source.as_string().to_owned();
+            let (library, _) = Source::partial_library(source);
+            symbols.extend(workspace_symbols(library, &contents, &uri, query));
+        }
+        symbols
+    }
+
+    /// Returns the call hierarchy item for the function, function block,
+    /// program or configuration at the position.
+    pub(crate) fn prepare_call_hierarchy(
+        &mut self,
+        uri: &Uri,
+        position: Position,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        let file_id = FileId::from_path(&to_path_buf(uri).ok()?);
+        let offset = to_offset(self.wrapped.find(&file_id)?.as_string(), position);
+
+        let index = self.wrapped.symbol_index();
+        let id = callable(&index, index.find(&file_id, offset)?)?;
+        Some(vec![call_hierarchy_item(
+            &index,
+            id,
+            self.wrapped.as_ref(),
+        )?])
+    }
+
+    /// Returns the callers of the call hierarchy item.
+    pub(crate) fn incoming_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Vec<CallHierarchyIncomingCall> {
+        let Some((index, calls, id)) = self.calls(item) else {
+            return vec![];
+        };
+        incoming(&calls, id)
+            .into_iter()
+            .filter_map(|(caller, spans)| {
+                Some(CallHierarchyIncomingCall {
+                    from: call_hierarchy_item(&index, caller, self.wrapped.as_ref())?,
+                    from_ranges: self.map_ranges(&spans),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the callees of the call hierarchy item.
+    pub(crate) fn outgoing_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Vec<CallHierarchyOutgoingCall> {
+        let Some((index, calls, id)) = self.calls(item) else {
+            return vec![];
+        };
+        outgoing(&calls, id)
+            .into_iter()
+            .filter_map(|(callee, spans)| {
+                Some(CallHierarchyOutgoingCall {
+                    to: call_hierarchy_item(&index, callee, self.wrapped.as_ref())?,
+                    from_ranges: self.map_ranges(&spans),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the index, the calls in the project and the definition of
+    /// the call hierarchy item.
+    fn calls(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Option<(Arc<SymbolIndex>, Vec<Call>, DefinitionId)> {
+        let file_id = FileId::from_path(&to_path_buf(&item.uri).ok()?);
+        let offset = to_offset(
+            self.wrapped.find(&file_id)?.as_string(),
+            item.selection_range.start,
+        );
+
+        let index = self.wrapped.symbol_index();
+        let id = callable(&index, index.find(&file_id, offset)?)?;
+        let libraries: Vec<&Library> = self
+            .wrapped
+            .sources_mut()
+            .into_iter()
+            .map(|source| Source::partial_library(source).0)
+            .collect();
+        let calls = calls(&index, &libraries);
+        Some((index, calls, id))
+    }
+
+    fn map_ranges(&self, spans: &[&SourceSpan]) -> Vec<lsp_types::Range> {
+        spans
+            .iter()
+            .filter_map(|span| map_location(span, self.wrapped.as_ref()))
+            .map(|location| location.range)
+            .collect()
+    }
+
+    /// Returns the instance trees of the configurations in the project.
+    pub(crate) fn instance_tree(&mut self, params: &InstanceTreeParams) -> Vec<InstanceNode> {
+        let index = self.wrapped.symbol_index();
+        let libraries: Vec<&Library> = self
+            .wrapped
+            .sources_mut()
+            .into_iter()
+            .map(|source| Source::partial_library(source).0)
+            .collect();
+        let configurations = configurations(&libraries, params.configuration.as_deref());
+
+        configurations
+            .iter()
+            .map(|configuration| {
+                instance_tree(&index, configuration, |span| {
+                    map_location(span, self.wrapped.as_ref())
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the locations that refer to the name at the position.
+    pub(crate) fn references(
+        &mut self,
+        uri: &Uri,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let Ok(path) = to_path_buf(uri) else {
+            error!("URL must be convertible to a file path {}", uri.as_str());
+            return vec![];
+        };
+        let file_id = FileId::from_path(&path);
+        let Some(source) = self.wrapped.find(&file_id) else {
+            return vec![];
+        };
+        let offset = to_offset(source.as_string(), position);
+
+        let index = self.wrapped.symbol_index();
+        let Some(id) = index.find(&file_id, offset) else {
+            return vec![];
+        };
+
+        let mut spans = vec![];
+        if include_declaration {
+            spans.push(&index.definition(id).name.span);
+        }
+        spans.extend(index.references(id));
+
+        spans
+            .into_iter()
+            .filter_map(|span| map_location(span, self.wrapped.as_ref()))
+            .collect()
+    }
+}
+
+/// Returns true if the name is an identifier (and not a keyword).
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let (tokens, diagnostics) =
+        tokenize_program(name, &FileId::default(), &ParseOptions::default(), 0, 0);
+    diagnostics.is_empty() && tokens.len() == 1 && tokens[0].token_type == TokenType::Identifier
+}
+
+/// Converts the LSP position in the contents into the offset. The position
+/// character is the number of UTF-16 code units before the position in the
+/// line.
+fn to_offset(contents: &str, position: Position) -> usize {
+    let mut line = 0;
+    let mut character = 0;
+    for (offset, char) in contents.char_indices() {
+        // Positions count UTF-16 code units, so a position may be within a
+        // character that needs two code units.
+        if line == position.line && character >= position.character {
+            return offset;
+        }
+        if char == '\n' {
+            if line == position.line {
+                // The position is after the end of the line.
+                return offset;
+            }
+            line += 1;
+            character = 0;
         } else {
-            error!("URL must be convertible to a file path {uri:?}");
+            character += char.len_utf16() as u32;
         }
+    }
+    contents.len()
+}
 
-        vec![]
+/// Converts the offset in the contents into the LSP position.
+pub(crate) fn to_position(contents: &str, offset: usize) -> Position {
+    let mut line = 0;
+    let mut character = 0;
+    for (index, char) in contents.char_indices() {
+        if index >= offset {
+            break;
+        }
+        if char == '\n' {
+            line += 1;
+            character = 0;
+        } else {
+            character += char.len_utf16() as u32;
+        }
     }
+    Position::new(line, character)
+}
+
+/// Returns the call hierarchy item for the definition.
+fn call_hierarchy_item(
+    index: &SymbolIndex,
+    id: DefinitionId,
+    project: &dyn Project,
+) -> Option<CallHierarchyItem> {
+    let definition = index.definition(id);
+    let kind = match definition.kind {
+        DefinitionKind::Function => SymbolKind::FUNCTION,
+        DefinitionKind::FunctionBlock => SymbolKind::CLASS,
+        DefinitionKind::Program => SymbolKind::MODULE,
+        DefinitionKind::Configuration => SymbolKind::PACKAGE,
+        _ => return None,
+    };
+    let location = map_location(&definition.name.span, project)?;
+    Some(CallHierarchyItem {
+        name: definition.name.original().to_owned(),
+        kind,
+        tags: None,
+        detail: None,
+        uri: location.uri,
+        range: location.range,
+        selection_range: location.range,
+        data: None,
+    })
+}
+
+/// Converts the span into the LSP location type.
+fn map_location(span: &SourceSpan, project: &dyn Project) -> Option<Location> {
+    let contents = project.find(&span.file_id)?.as_string();
+    Some(Location::new(
+        to_uri(&span.file_id)?,
+        lsp_types::Range::new(
+            to_position(contents, span.start),
+            to_position(contents, span.end),
+        ),
+    ))
 }
 
 // Token types that this produces.
@@ -125,6 +788,7 @@ impl From<LspTokenType> for Option<SemanticToken> {
             TokenType::Newline => None,
             TokenType::Whitespace => None,
             TokenType::Comment => Some(COMMENT_INDEX),
+            TokenType::Pragma => Some(MODIFIER_INDEX),
             TokenType::LeftParen => None,
             TokenType::RightParen => None,
             TokenType::LeftBrace => None,
@@ -136,6 +800,7 @@ impl From<LspTokenType> for Option<SemanticToken> {
             TokenType::Colon => None,
             TokenType::Period => None,
             TokenType::Hash => None,
+            TokenType::Caret => Some(OPERATOR_INDEX),
             TokenType::String => Some(STRING_INDEX),
             TokenType::Identifier => Some(VARIABLE_INDEX),
             TokenType::HexDigits => None,
@@ -166,6 +831,16 @@ impl From<LspTokenType> for Option<SemanticToken> {
             TokenType::EndFunction => Some(KEYWORD_INDEX),
             TokenType::FunctionBlock => Some(KEYWORD_INDEX),
             TokenType::EndFunctionBlock => Some(KEYWORD_INDEX),
+            TokenType::Extends => Some(KEYWORD_INDEX),
+            TokenType::Implements => Some(KEYWORD_INDEX),
+            TokenType::Interface => Some(KEYWORD_INDEX),
+            TokenType::EndInterface => Some(KEYWORD_INDEX),
+            TokenType::Method => Some(KEYWORD_INDEX),
+            TokenType::EndMethod => Some(KEYWORD_INDEX),
+            TokenType::Property => Some(KEYWORD_INDEX),
+            TokenType::EndProperty => Some(KEYWORD_INDEX),
+            TokenType::This => Some(KEYWORD_INDEX),
+            TokenType::Super => Some(KEYWORD_INDEX),
             TokenType::Configuration => Some(KEYWORD_INDEX),
             TokenType::EndConfiguration => Some(KEYWORD_INDEX),
             TokenType::Resource => Some(KEYWORD_INDEX),
@@ -223,6 +898,9 @@ impl From<LspTokenType> for Option<SemanticToken> {
             TokenType::REdge => Some(KEYWORD_INDEX),
             TokenType::ReadOnly => Some(KEYWORD_INDEX),
             TokenType::ReadWrite => Some(KEYWORD_INDEX),
+            TokenType::Ref => Some(KEYWORD_INDEX),
+            TokenType::RefTo => Some(KEYWORD_INDEX),
+            TokenType::Null => Some(KEYWORD_INDEX),
             TokenType::NonRetain => Some(KEYWORD_INDEX),
             TokenType::Return => Some(KEYWORD_INDEX),
             TokenType::Step => Some(KEYWORD_INDEX),
@@ -287,9 +965,15 @@ fn map_diagnostic(
         Err(_) => None,
     };
 
+    let severity = match diagnostic.severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Info => DiagnosticSeverity::INFORMATION,
+    };
+
     lsp_types::Diagnostic {
         range,
-        severity: Some(DiagnosticSeverity::ERROR),
+        severity: Some(severity),
         code: Some(NumberOrString::String(diagnostic.code)),
         code_description,
         source: Some("ironplc".into()),
@@ -307,33 +991,9 @@ fn map_label(label: &ironplc_dsl::diagnostic::Label, project: &dyn Project) -> l
 
     if let Some(contents) = contents {
         let contents = contents.as_string();
-
-        let mut start_line = 0;
-        let mut start_offset = 0;
-
-        for char in contents[0..label.location.start].chars() {
-            if char == '\n' {
-                start_line += 1;
-                start_offset = 0;
-            } else {
-                start_offset += 1;
-            }
-        }
-
-        let mut end_line = start_line;
-        let mut end_offset = start_offset;
-        for char in contents[label.location.start..label.location.start].chars() {
-            if char == '\n' {
-                end_line += 1;
-                end_offset = 0;
-            } else {
-                end_offset += 1;
-            }
-        }
-
         return lsp_types::Range::new(
-            lsp_types::Position::new(start_line, start_offset),
-            lsp_types::Position::new(end_line, end_offset),
+            to_position(contents, label.location.start),
+            to_position(contents, label.location.end),
         );
     }
     lsp_types::Range::new(
@@ -346,14 +1006,22 @@ fn map_label(label: &ironplc_dsl::diagnostic::Label, project: &dyn Project) -> l
 mod test {
     use std::str::FromStr;
 
-    use ironplc_dsl::core::SourceSpan;
+    use ironplc_dsl::{
+        core::SourceSpan,
+        diagnostic::{Diagnostic, Label},
+    };
     use ironplc_parser::token::{Token, TokenType};
+    use ironplc_problems::{Problem, Severity};
     use ironplc_test::read_shared_resource;
-    use lsp_types::{SemanticToken, Uri};
+    use lsp_types::{
+        CodeActionOrCommand, DiagnosticSeverity, HoverContents, Position, PrepareRenameResponse,
+        SemanticToken, TextDocumentContentChangeEvent, Uri,
+    };
 
+    use crate::lsp_instances::InstanceTreeParams;
     use crate::project::FileBackedProject;
 
-    use super::{LspProject, LspTokenType};
+    use super::{map_diagnostic, to_offset, to_position, LspProject, LspTokenType};
 
     #[cfg(target_os = "macos")]
     static FAKE_PATH: &str = "file:///localhost/first_steps.st";
@@ -402,6 +1070,7 @@ mod test {
             TokenType::Newline,
             TokenType::Whitespace,
             TokenType::Comment,
+            TokenType::Pragma,
             TokenType::LeftParen,
             TokenType::RightParen,
             TokenType::LeftBrace,
@@ -412,6 +1081,7 @@ mod test {
             TokenType::Period,
             TokenType::Range,
             TokenType::Hash,
+            TokenType::Caret,
             TokenType::SingleByteString,
             TokenType::DoubleByteString,
             TokenType::Identifier,
@@ -441,6 +1111,16 @@ mod test {
             TokenType::EndFunction,
             TokenType::FunctionBlock,
             TokenType::EndFunctionBlock,
+            TokenType::Extends,
+            TokenType::Implements,
+            TokenType::Interface,
+            TokenType::EndInterface,
+            TokenType::Method,
+            TokenType::EndMethod,
+            TokenType::Property,
+            TokenType::EndProperty,
+            TokenType::This,
+            TokenType::Super,
             TokenType::If,
             TokenType::Then,
             TokenType::Elsif,
@@ -453,6 +1133,9 @@ mod test {
             TokenType::REdge,
             TokenType::ReadOnly,
             TokenType::ReadWrite,
+            TokenType::Ref,
+            TokenType::RefTo,
+            TokenType::Null,
             TokenType::Repeat,
             TokenType::Until,
             TokenType::EndRepeat,
@@ -556,6 +1239,449 @@ INVALID_SYNTAX"
         proj.change_text_document(&url, invalid_content);
 
         // Call semantic analysis which will internally call map_label when creating diagnostics
-        let _diagnostics = proj.semantic(&url);
+        let _diagnostics = proj.semantic(&[url]);
+    }
+
+    #[test]
+    fn semantic_when_other_document_had_diagnostics_then_includes_document() {
+        let mut proj = new_empty_project();
+        let main = Uri::from_str(FAKE_PATH).unwrap();
+        let counter = Uri::from_str(&FAKE_PATH.replace("first_steps", "counter")).unwrap();
+        proj.change_text_document(
+            &counter,
+            "PROGRAM main\nVAR\n   c : Counter;\nEND_VAR\nEND_PROGRAM".to_owned(),
+        );
+        proj.change_text_document(&main, "FUNCTION_BLOCK Other\nEND_FUNCTION_BLOCK".to_owned());
+
+        let result = proj.semantic(std::slice::from_ref(&main));
+        assert_eq!(2, result.len());
+        assert_eq!(main, result[0].0);
+        assert!(result[0].1.is_empty());
+        assert_eq!(counter, result[1].0);
+        assert!(!result[1].1.is_empty());
+
+        proj.change_text_document(
+            &main,
+            "FUNCTION_BLOCK Counter\nEND_FUNCTION_BLOCK".to_owned(),
+        );
+        let result = proj.semantic(std::slice::from_ref(&main));
+        assert_eq!(2, result.len());
+        assert_eq!(counter, result[1].0);
+        assert!(result[1].1.is_empty());
+    }
+
+    #[test]
+    fn map_diagnostic_when_warning_then_warning_severity() {
+        let project = FileBackedProject::default();
+        let diagnostic = Diagnostic::problem(
+            Problem::VariableUndefined,
+            Label::span(SourceSpan::default(), "Variable"),
+        )
+        .with_severity(Severity::Warning);
+
+        let result = map_diagnostic(diagnostic, &project);
+
+        assert_eq!(Some(DiagnosticSeverity::WARNING), result.severity);
+    }
+
+    const COUNTER_PROGRAM: &str = "PROGRAM main
+VAR
+   counter : INT;
+END_VAR
+   counter := counter + 1;
+END_PROGRAM";
+
+    #[test]
+    fn to_offset_when_position_then_offset_of_position() {
+        let contents = "abc\ndäf\ng";
+        assert_eq!(5, to_offset(contents, Position::new(1, 1)));
+        assert_eq!(Position::new(1, 1), to_position(contents, 5));
+        assert_eq!(8, to_offset(contents, Position::new(1, 10)));
+        assert_eq!(contents.len(), to_offset(contents, Position::new(5, 0)));
+    }
+
+    #[test]
+    fn to_offset_when_character_outside_basic_plane_then_counts_utf16_code_units() {
+        let contents = "a😀b
+c";
+        assert_eq!(5, to_offset(contents, Position::new(0, 3)));
+        assert_eq!(Position::new(0, 3), to_position(contents, 5));
+        assert_eq!(Position::new(1, 0), to_position(contents, 7));
+    }
+
+    #[test]
+    fn apply_changes_when_emoji_before_range_then_replaces_range() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\n   counter : INT;\nEND_VAR\n   (* 😀 *) counter := counter + 1;\nEND_PROGRAM"
+                .to_owned(),
+        );
+
+        // The emoji is two UTF-16 code units, so the last `1` starts at 33
+        proj.apply_changes(
+            &url,
+            vec![TextDocumentContentChangeEvent {
+                range: Some(lsp_types::Range::new(
+                    Position::new(4, 33),
+                    Position::new(4, 34),
+                )),
+                range_length: None,
+                text: "counter".to_owned(),
+            }],
+        );
+
+        let references = proj.references(&url, Position::new(2, 4), false);
+        assert_eq!(3, references.len());
+        assert_eq!(Position::new(4, 33), references[2].range.start);
+    }
+
+    #[test]
+    fn definition_when_variable_reference_then_declaration_location() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        let location = proj.definition(&url, Position::new(4, 5)).unwrap();
+
+        assert_eq!(url, location.uri);
+        assert_eq!(Position::new(2, 3), location.range.start);
+        assert_eq!(Position::new(2, 10), location.range.end);
+    }
+
+    #[test]
+    fn definition_when_not_name_then_none() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        assert!(proj.definition(&url, Position::new(1, 1)).is_none());
+    }
+
+    #[test]
+    fn hover_when_variable_then_markdown_declaration() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        let hover = proj.hover(&url, Position::new(4, 5)).unwrap();
+
+        match hover.contents {
+            HoverContents::Markup(content) => {
+                assert!(content.value.contains("counter : INT;"));
+                assert!(content.value.contains("Size: 2 bytes"));
+            }
+            _ => panic!("Expected markup"),
+        }
+    }
+
+    #[test]
+    fn references_when_include_declaration_then_all_locations() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        let with_declaration = proj.references(&url, Position::new(2, 4), true);
+        let without_declaration = proj.references(&url, Position::new(2, 4), false);
+
+        assert_eq!(3, with_declaration.len());
+        assert_eq!(2, without_declaration.len());
+        assert_eq!(Position::new(4, 14), without_declaration[1].range.start);
+    }
+
+    #[test]
+    fn apply_changes_when_range_then_replaces_range() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        proj.apply_changes(
+            &url,
+            vec![TextDocumentContentChangeEvent {
+                range: Some(lsp_types::Range::new(
+                    Position::new(4, 24),
+                    Position::new(4, 25),
+                )),
+                range_length: None,
+                text: "counter".to_owned(),
+            }],
+        );
+
+        let references = proj.references(&url, Position::new(2, 4), false);
+        assert_eq!(3, references.len());
+        assert_eq!(Position::new(4, 24), references[2].range.start);
+    }
+
+    #[test]
+    fn rename_when_variable_then_edits_declaration_and_references() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        let edit = proj
+            .rename(&url, Position::new(4, 5), "total")
+            .unwrap()
+            .unwrap();
+
+        let edits = &edit.changes.unwrap()[&url];
+        assert_eq!(3, edits.len());
+        assert!(edits.iter().all(|edit| edit.new_text == "total"));
+    }
+
+    #[test]
+    fn code_actions_when_undefined_variable_then_declare_variable() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\n   x : INT;\nEND_VAR\n   x := y;\nEND_PROGRAM".to_owned(),
+        );
+        proj.semantic(std::slice::from_ref(&url));
+
+        let actions = proj.code_actions(
+            &url,
+            lsp_types::Range::new(Position::new(4, 8), Position::new(4, 8)),
+        );
+
+        assert_eq!(1, actions.len());
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("Expected code action");
+        };
+        assert_eq!("Declare variable y", action.title);
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&url];
+        assert_eq!(Position::new(0, 12), edits[0].range.start);
+    }
+
+    #[test]
+    fn code_actions_when_not_analyzed_then_none() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\n   x : INT;\nEND_VAR\n   x := y;\nEND_PROGRAM".to_owned(),
+        );
+
+        let actions = proj.code_actions(
+            &url,
+            lsp_types::Range::new(Position::new(4, 8), Position::new(4, 8)),
+        );
+
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn signature_help_when_in_call_then_signature() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\n   x : INT;\nEND_VAR\n   x := MAX(x, \nEND_PROGRAM".to_owned(),
+        );
+
+        let help = proj.signature_help(&url, Position::new(4, 15)).unwrap();
+
+        assert_eq!(Some(1), help.active_parameter);
+        assert!(help.signatures[0].label.starts_with("MAX("));
+    }
+
+    #[test]
+    fn inlay_hints_when_in_range_then_hints() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\n   x : INT;\nEND_VAR\n   x := MAX(1, 2);\n   x := MIN(1, 2);\nEND_PROGRAM".to_owned(),
+        );
+
+        let hints = proj.inlay_hints(
+            &url,
+            lsp_types::Range::new(Position::new(4, 0), Position::new(4, 20)),
+        );
+
+        assert_eq!(2, hints.len());
+        assert_eq!(Position::new(4, 12), hints[0].position);
+    }
+
+    #[test]
+    fn folding_ranges_when_program_then_ranges() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        let ranges = proj.folding_ranges(&url);
+
+        assert!(!ranges.is_empty());
+    }
+
+    #[test]
+    fn call_hierarchy_when_function_then_incoming_and_outgoing_calls() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "FUNCTION one : INT\n   one := 1;\nEND_FUNCTION\nPROGRAM main\nVAR\n   x : INT;\nEND_VAR\n   x := one();\nEND_PROGRAM".to_owned(),
+        );
+
+        let items = proj
+            .prepare_call_hierarchy(&url, Position::new(7, 9))
+            .unwrap();
+        assert_eq!("one", items[0].name);
+        assert_eq!(Position::new(0, 9), items[0].selection_range.start);
+
+        let incoming = proj.incoming_calls(&items[0]);
+        assert_eq!(1, incoming.len());
+        assert_eq!("main", incoming[0].from.name);
+        assert_eq!(Position::new(7, 8), incoming[0].from_ranges[0].start);
+
+        let outgoing = proj.outgoing_calls(&incoming[0].from);
+        assert_eq!(1, outgoing.len());
+        assert_eq!("one", outgoing[0].to.name);
+    }
+
+    #[test]
+    fn instance_tree_when_configuration_then_tree_with_locations() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\n   timer : TON;\nEND_VAR\nEND_PROGRAM\nCONFIGURATION config\n   RESOURCE res ON PLC\n      PROGRAM instance : main;\n   END_RESOURCE\nEND_CONFIGURATION".to_owned(),
+        );
+
+        let trees = proj.instance_tree(&InstanceTreeParams::default());
+
+        assert_eq!(1, trees.len());
+        let instance = &trees[0].children[0].children[0];
+        assert_eq!("instance", instance.name);
+        assert_eq!(
+            Position::new(7, 14),
+            instance.location.as_ref().unwrap().range.start
+        );
+        assert_eq!("timer", instance.children[0].name);
+    }
+
+    #[test]
+    fn formatting_when_valid_then_replaces_document() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\nx:INT;\nEND_VAR\nEND_PROGRAM".to_owned(),
+        );
+
+        let edits = proj.formatting(&url).unwrap();
+
+        assert_eq!(1, edits.len());
+        assert_eq!(Position::new(0, 0), edits[0].range.start);
+        assert!(edits[0].new_text.contains("x : INT"));
+    }
+
+    #[test]
+    fn formatting_when_comment_then_none() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "(* keep *)\nPROGRAM main\nVAR\nx:INT;\nEND_VAR\nEND_PROGRAM".to_owned(),
+        );
+
+        assert!(proj.formatting(&url).is_none());
+    }
+
+    #[test]
+    fn formatting_when_pragma_then_none() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\nx:INT;\nEND_VAR\n{attribute 'hide'}\nx := 3;\nEND_PROGRAM"
+                .to_owned(),
+        );
+
+        assert!(proj.formatting(&url).is_none());
+        assert!(proj
+            .range_formatting(
+                &url,
+                lsp_types::Range::new(Position::new(0, 0), Position::new(1, 0))
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn formatting_when_conditional_directive_then_none() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\nx:INT;\nEND_VAR\n{IF DEFINED(VARIANT_A)} x := 1; {ELSE} x := 2; {END_IF}\nEND_PROGRAM"
+                .to_owned(),
+        );
+
+        assert!(proj.formatting(&url).is_none());
+        assert!(proj
+            .range_formatting(
+                &url,
+                lsp_types::Range::new(Position::new(0, 0), Position::new(1, 0))
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn formatting_when_syntax_error_then_none() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, "PROGRAM main\nVAR\nx:INT\nEND_PROGRAM".to_owned());
+
+        assert!(proj.formatting(&url).is_none());
+    }
+
+    #[test]
+    fn rename_when_keyword_then_error() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        assert!(proj.rename(&url, Position::new(4, 5), "END_IF").is_err());
+    }
+
+    #[test]
+    fn rename_when_name_conflicts_then_error() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(
+            &url,
+            "PROGRAM main\nVAR\n   x : INT;\n   y : INT;\nEND_VAR\nEND_PROGRAM".to_owned(),
+        );
+
+        let err = proj.rename(&url, Position::new(2, 3), "Y").unwrap_err();
+
+        assert!(err.starts_with("P4014"));
+    }
+
+    #[test]
+    fn prepare_rename_when_reference_then_range_of_reference() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        match proj.prepare_rename(&url, Position::new(4, 15)).unwrap() {
+            PrepareRenameResponse::RangeWithPlaceholder { range, placeholder } => {
+                assert_eq!(Position::new(4, 14), range.start);
+                assert_eq!(Position::new(4, 21), range.end);
+                assert_eq!("counter", placeholder);
+            }
+            _ => panic!("Expected range with placeholder"),
+        }
+    }
+
+    #[test]
+    fn completion_when_in_program_body_then_has_variable() {
+        let mut proj = new_empty_project();
+        let url = Uri::from_str(FAKE_PATH).unwrap();
+        proj.change_text_document(&url, COUNTER_PROGRAM.to_owned());
+
+        let items = proj.completion(&url, Position::new(4, 3));
+
+        assert!(items.iter().any(|item| item.label == "counter"));
     }
 }
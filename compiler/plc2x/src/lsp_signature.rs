@@ -0,0 +1,492 @@
+//! Implements the signature help that the language server shows while
+//! writing the arguments of a call.
+//!
+//! The signature is the formal parameters (`VAR_INPUT`, `VAR_IN_OUT` and
+//! `VAR_OUTPUT`) of the function, method or function block instance that
+//! is called. The active parameter depends on the style of the call:
+//!
+//! * in a formal call (`fb(IN := x, PT := t)`), the parameter that is named
+//!   by the argument
+//! * in a positional call (`LIMIT(0, x, 10)`), the input at the position
+//!   of the argument
+//!
+//! Like completion, the call comes from the text before the position
+//! because the text is usually incomplete while editing.
+
+use std::sync::LazyLock;
+
+use ironplc_analyzer::{
+    stdlib::{
+        functions::{find_standard_function, FunctionSignature, InputType, ReturnType},
+        standard_function_blocks,
+    },
+    symbol_index::{DefinitionId, DefinitionKind, SymbolIndex},
+};
+use ironplc_dsl::{
+    common::VariableType,
+    core::{FileId, Id},
+};
+use lsp_types::{
+    Documentation, ParameterInformation, ParameterLabel, SignatureHelp, SignatureInformation,
+};
+
+use crate::lsp_completion::{identifier_start, is_identifier_char, resolve_chain, scope_at};
+use crate::lsp_project::is_identifier;
+
+/// The index of the standard function blocks (that are not declared in the
+/// project). The standard function blocks do not change, so parse and index
+/// the declarations once.
+pub(crate) static STANDARD_FUNCTION_BLOCKS: LazyLock<SymbolIndex> = LazyLock::new(|| {
+    let library = standard_function_blocks().unwrap_or_default();
+    SymbolIndex::new(&[&library])
+});
+
+/// The operators that are keywords and also the names of standard
+/// functions.
+const OPERATOR_KEYWORDS: [&str; 5] = ["AND", "OR", "XOR", "MOD", "NOT"];
+
+/// A formal parameter of a call.
+struct Parameter {
+    name: String,
+    var_type: VariableType,
+    type_name: Option<String>,
+}
+
+impl Parameter {
+    /// Returns true if the parameter receives a positional argument.
+    fn is_positional(&self) -> bool {
+        matches!(self.var_type, VariableType::Input | VariableType::InOut)
+    }
+}
+
+/// The signature of the callee.
+struct Signature {
+    name: String,
+    parameters: Vec<Parameter>,
+    return_type: Option<String>,
+    /// True if the callee accepts any number of additional inputs having
+    /// the type of the last input.
+    extensible: bool,
+}
+
+/// Returns the signature help for the offset in the contents of the file
+/// or `None` if the offset is not in the arguments of a call.
+pub(crate) fn signature_help(
+    index: &SymbolIndex,
+    contents: &str,
+    file_id: &FileId,
+    offset: usize,
+) -> Option<SignatureHelp> {
+    let before = &contents[..offset.min(contents.len())];
+    let (callee, arguments) = call_arguments(before)?;
+    let scope = scope_at(index, contents, file_id, offset);
+
+    let signature = match resolve_chain(index, scope, callee) {
+        Some(id) => index_signature(index, id)?,
+        None => {
+            let name = &callee[identifier_start(callee)..];
+            standard_signature(&find_standard_function(&Id::from(name))?)
+        }
+    };
+
+    let active_parameter = active_parameter(&signature, &arguments);
+    Some(SignatureHelp {
+        signatures: vec![signature_information(&signature)],
+        active_signature: Some(0),
+        active_parameter,
+    })
+}
+
+/// Returns the text of the callee and the arguments if the text ends inside
+/// the argument list of a call.
+fn call_arguments(text: &str) -> Option<(&str, Vec<&str>)> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut open = None;
+    for (i, c) in text.char_indices().rev() {
+        match c {
+            '\'' | '"' => in_string = !in_string,
+            _ if in_string => {}
+            ')' | ']' => depth += 1,
+            '(' | '[' if depth > 0 => depth -= 1,
+            '(' => {
+                open = Some(i);
+                break;
+            }
+            '[' | ';' => return None,
+            _ => {}
+        }
+    }
+    let open = open?;
+
+    let callee = text[..open].trim_end();
+    let spaced = callee.len() < open;
+    if identifier_start(callee) == callee.len() || is_operator(callee, spaced) {
+        return None;
+    }
+
+    let mut arguments = vec![];
+    let mut start = open + 1;
+    let mut depth = 0;
+    let mut in_string = false;
+    for (i, c) in text[open + 1..].char_indices() {
+        match c {
+            '\'' | '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(&text[start..open + 1 + i]);
+                start = open + 1 + i + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(&text[start..]);
+    Some((callee, arguments))
+}
+
+/// Returns true if the callee is an operator keyword that is an operator
+/// rather than a call of the standard function. The keyword is an operator
+/// if the keyword follows an operand (for example `a AND (b`) or if there is
+/// whitespace before the parenthesis (for example `NOT (b`). The parenthesis
+/// then starts a subexpression rather than the arguments.
+fn is_operator(callee: &str, spaced: bool) -> bool {
+    let start = identifier_start(callee);
+    let name = &callee[start..];
+    if !OPERATOR_KEYWORDS
+        .iter()
+        .any(|operator| operator.eq_ignore_ascii_case(name))
+    {
+        return false;
+    }
+    if spaced {
+        return true;
+    }
+
+    let before = callee[..start].trim_end();
+    match before.chars().last() {
+        Some(')' | ']' | '\'' | '"') => true,
+        Some(c) if is_identifier_char(c) => {
+            let operand = &before[identifier_start(before)..];
+            operand.starts_with(|c: char| c.is_ascii_digit()) || is_identifier(operand)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the name of the parameter if the argument is formal, that is,
+/// the argument has the form `name :=` or `name =>`.
+fn formal_name(argument: &str) -> Option<&str> {
+    let mut argument = argument.trim_start();
+    // Outputs can be negated, for example `NOT Q => x`.
+    if argument
+        .get(..4)
+        .is_some_and(|not| not.eq_ignore_ascii_case("NOT "))
+    {
+        argument = argument[4..].trim_start();
+    }
+    let end = argument
+        .char_indices()
+        .find(|(_, c)| !is_identifier_char(*c))
+        .map(|(i, _)| i)
+        .unwrap_or(argument.len());
+    if end == 0 {
+        return None;
+    }
+    let rest = argument[end..].trim_start();
+    (rest.starts_with(":=") || rest.starts_with("=>")).then_some(&argument[..end])
+}
+
+/// Returns the index of the parameter that receives the last argument.
+fn active_parameter(signature: &Signature, arguments: &[&str]) -> Option<u32> {
+    let (current, previous) = arguments.split_last()?;
+
+    if let Some(name) = formal_name(current) {
+        return signature
+            .parameters
+            .iter()
+            .position(|parameter| parameter.name.eq_ignore_ascii_case(name))
+            .map(|position| position as u32);
+    }
+
+    let is_formal = previous
+        .iter()
+        .any(|argument| formal_name(argument).is_some());
+    if is_formal {
+        // The next parameter in a formal call is the first parameter that
+        // does not yet have an argument.
+        return signature
+            .parameters
+            .iter()
+            .position(|parameter| {
+                !previous.iter().any(|argument| {
+                    formal_name(argument)
+                        .is_some_and(|name| parameter.name.eq_ignore_ascii_case(name))
+                })
+            })
+            .map(|position| position as u32);
+    }
+
+    let positional: Vec<usize> = signature
+        .parameters
+        .iter()
+        .enumerate()
+        .filter(|(_, parameter)| parameter.is_positional())
+        .map(|(position, _)| position)
+        .collect();
+    let position = match positional.get(previous.len()) {
+        Some(position) => Some(position),
+        None if signature.extensible => positional.last(),
+        None => None,
+    };
+    position.map(|position| *position as u32)
+}
+
+/// Returns the signature of the function, method or function block
+/// instance in the index.
+fn index_signature(index: &SymbolIndex, id: DefinitionId) -> Option<Signature> {
+    let definition = index.definition(id);
+    match definition.kind {
+        DefinitionKind::Function | DefinitionKind::Method => Some(Signature {
+            name: definition.name.original().to_owned(),
+            parameters: index_parameters(index, id),
+            return_type: definition
+                .type_name
+                .as_ref()
+                .map(|type_name| type_name.name.original().to_owned()),
+            extensible: false,
+        }),
+        DefinitionKind::Variable(_) => {
+            let type_name = definition.type_name.as_ref()?;
+            let parameters = match index.type_definition(type_name) {
+                Some(type_id)
+                    if index.definition(type_id).kind == DefinitionKind::FunctionBlock =>
+                {
+                    index_parameters(index, id)
+                }
+                Some(_) => return None,
+                None => {
+                    // Standard function blocks are not part of the project.
+                    let type_id = STANDARD_FUNCTION_BLOCKS.type_definition(type_name)?;
+                    index_parameters(&STANDARD_FUNCTION_BLOCKS, type_id)
+                }
+            };
+            Some(Signature {
+                name: type_name.name.original().to_owned(),
+                parameters,
+                return_type: None,
+                extensible: false,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns the formal parameters that are the members of the definition.
+fn index_parameters(index: &SymbolIndex, id: DefinitionId) -> Vec<Parameter> {
+    index
+        .visible_members(id)
+        .into_iter()
+        .filter_map(|member| {
+            let definition = index.definition(member);
+            match &definition.kind {
+                DefinitionKind::Variable(
+                    var_type @ (VariableType::Input | VariableType::InOut | VariableType::Output),
+                ) => Some(Parameter {
+                    name: definition.name.original().to_owned(),
+                    var_type: var_type.clone(),
+                    type_name: definition
+                        .type_name
+                        .as_ref()
+                        .map(|type_name| type_name.name.original().to_owned()),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Returns the signature of the standard function.
+fn standard_signature(function: &FunctionSignature) -> Signature {
+    let inputs = function.inputs.iter().map(|input| Parameter {
+        name: input.name.original().to_owned(),
+        var_type: VariableType::Input,
+        type_name: match &input.input_type {
+            InputType::Generic(generic) | InputType::Independent(generic) => {
+                Some(generic.name().to_owned())
+            }
+            InputType::Elementary(elementary) => Some(elementary.as_id().original().to_owned()),
+            InputType::Named(type_name) => Some(type_name.name.original().to_owned()),
+            InputType::Unknown => None,
+        },
+    });
+    let outputs = function.outputs.iter().map(|output| Parameter {
+        name: output.original().to_owned(),
+        var_type: VariableType::Output,
+        type_name: None,
+    });
+
+    let return_type = match &function.return_type {
+        // The generic type is the common type of the generic inputs.
+        ReturnType::Generic => function
+            .inputs
+            .iter()
+            .find_map(|input| match input.input_type {
+                InputType::Generic(generic) => Some(generic.name().to_owned()),
+                _ => None,
+            }),
+        ReturnType::Family(generic) => Some(generic.name().to_owned()),
+        ReturnType::Elementary(elementary) => Some(elementary.as_id().original().to_owned()),
+        ReturnType::Named(type_name) => Some(type_name.name.original().to_owned()),
+    };
+
+    Signature {
+        name: function.name.original().to_owned(),
+        parameters: inputs.chain(outputs).collect(),
+        return_type,
+        extensible: function.extensible,
+    }
+}
+
+/// Returns the signature as the LSP type. The label is the call with the
+/// formal parameters, such as `LIMIT(MN : ANY_ELEMENTARY, ...)`.
+fn signature_information(signature: &Signature) -> SignatureInformation {
+    let mut label = format!("{}(", signature.name);
+    let mut parameters = vec![];
+
+    for (position, parameter) in signature.parameters.iter().enumerate() {
+        if position > 0 {
+            label.push_str(", ");
+        }
+        let start = label.encode_utf16().count() as u32;
+        let operator = match parameter.var_type {
+            VariableType::Output => "=>",
+            _ => ":",
+        };
+        label.push_str(&parameter.name);
+        if let Some(type_name) = &parameter.type_name {
+            label.push_str(&format!(" {operator} {type_name}"));
+        }
+        let end = label.encode_utf16().count() as u32;
+
+        let class = match parameter.var_type {
+            VariableType::InOut => "VAR_IN_OUT",
+            VariableType::Output => "VAR_OUTPUT",
+            _ => "VAR_INPUT",
+        };
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, end]),
+            documentation: Some(Documentation::String(class.to_owned())),
+        });
+    }
+    if signature.extensible {
+        label.push_str(", ...");
+    }
+    label.push(')');
+    if let Some(return_type) = &signature.return_type {
+        label.push_str(&format!(" : {return_type}"));
+    }
+
+    SignatureInformation {
+        label,
+        documentation: None,
+        parameters: Some(parameters),
+        active_parameter: None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_analyzer::symbol_index::SymbolIndex;
+    use ironplc_dsl::core::FileId;
+    use ironplc_parser::{options::ParseOptions, parse_program};
+    use lsp_types::SignatureHelp;
+
+    use super::signature_help;
+
+    const PROGRAM: &str = "
+FUNCTION_BLOCK counter
+VAR_INPUT
+   reset : BOOL;
+   step : INT;
+END_VAR
+VAR_OUTPUT
+   count : DINT;
+END_VAR
+END_FUNCTION_BLOCK
+PROGRAM main
+VAR
+   instance : counter;
+   timer : TON;
+   value : INT;
+END_VAR
+   value := 1;
+END_PROGRAM";
+
+    fn help(call: &str) -> SignatureHelp {
+        try_help(call).unwrap()
+    }
+
+    fn try_help(call: &str) -> Option<SignatureHelp> {
+        let library = parse_program(PROGRAM, &FileId::default(), &ParseOptions::default()).unwrap();
+        let index = SymbolIndex::new(&[&library]);
+        let offset = PROGRAM.find("   value := 1;").unwrap();
+        let contents = format!("{}   {call}{}", &PROGRAM[..offset], &PROGRAM[offset..]);
+        signature_help(
+            &index,
+            &contents,
+            &FileId::default(),
+            offset + 3 + call.len(),
+        )
+    }
+
+    #[test]
+    fn signature_help_when_formal_call_then_named_parameter_is_active() {
+        let help = help("instance(step := 1, reset := ");
+
+        assert_eq!(
+            "counter(reset : BOOL, step : INT, count => DINT)",
+            help.signatures[0].label
+        );
+        assert_eq!(Some(0), help.active_parameter);
+    }
+
+    #[test]
+    fn signature_help_when_positional_standard_function_then_position_is_active() {
+        let help = help("LIMIT(0, value, ");
+
+        assert_eq!(
+            "LIMIT(MN : ANY_ELEMENTARY, IN : ANY_ELEMENTARY, MX : ANY_ELEMENTARY) : ANY_ELEMENTARY",
+            help.signatures[0].label
+        );
+        assert_eq!(Some(2), help.active_parameter);
+    }
+
+    #[test]
+    fn signature_help_when_standard_function_block_then_inputs_and_outputs() {
+        let help = help("timer(IN := TRUE, ");
+
+        assert!(help.signatures[0]
+            .label
+            .starts_with("TON(IN : BOOL, PT : TIME"));
+        assert_eq!(Some(1), help.active_parameter);
+    }
+
+    #[test]
+    fn signature_help_when_operator_function_then_signature() {
+        let help = help("value := AND(value, ");
+
+        assert!(help.signatures[0].label.starts_with("AND("));
+        assert_eq!(Some(1), help.active_parameter);
+    }
+
+    #[test]
+    fn signature_help_when_operator_after_operand_then_none() {
+        assert!(try_help("value := value AND (").is_none());
+        assert!(try_help("value := (value) or (").is_none());
+        assert!(try_help("value := 5 MOD (").is_none());
+        assert!(try_help("value := value XOR NOT (").is_none());
+        assert!(try_help("value := value AND(").is_none());
+    }
+}
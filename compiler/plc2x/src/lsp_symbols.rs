@@ -0,0 +1,703 @@
+//! Implements the symbols that the language server provides for outline
+//! views (the symbols in a document) and for searching symbols in the
+//! workspace.
+//!
+//! The symbols come from the (possibly partial) library for a source. The
+//! library only has the location of names, so the range of a symbol is
+//! from the keyword that starts the declaration to the keyword that ends
+//! the declaration.
+
+use ironplc_dsl::{
+    common::*,
+    configuration::*,
+    core::{Id, Located},
+    sfc::{ElementKind, Sfc, Step},
+    visitor::Visitor,
+};
+use lsp_types::{DocumentSymbol, Location, OneOf, SymbolKind, Uri, WorkspaceSymbol};
+
+use crate::lsp_completion::is_identifier_char;
+use crate::lsp_hover::declared_type_name;
+use crate::lsp_project::to_position;
+
+/// Returns the symbols declared in the library as a hierarchy. The
+/// contents is the text that the library is from.
+pub(crate) fn document_symbols(library: &Library, contents: &str) -> Vec<DocumentSymbol> {
+    let builder = SymbolBuilder { contents };
+    library
+        .elements
+        .iter()
+        .map(|element| builder.element(element))
+        .collect()
+}
+
+/// Returns the symbols declared in the library that match the query. The
+/// symbols do not include variables and values because there are too many
+/// of those to be useful in a search.
+pub(crate) fn workspace_symbols(
+    library: &Library,
+    contents: &str,
+    uri: &Uri,
+    query: &str,
+) -> Vec<WorkspaceSymbol> {
+    let query = query.to_lowercase();
+    let mut symbols = vec![];
+    flatten(
+        &document_symbols(library, contents),
+        None,
+        &mut |symbol, container| {
+            let is_searchable = !matches!(
+                symbol.kind,
+                SymbolKind::VARIABLE
+                    | SymbolKind::FIELD
+                    | SymbolKind::ENUM_MEMBER
+                    | SymbolKind::NAMESPACE
+            );
+            if is_searchable && symbol.name.to_lowercase().contains(&query) {
+                symbols.push(WorkspaceSymbol {
+                    name: symbol.name.clone(),
+                    kind: symbol.kind,
+                    tags: None,
+                    container_name: container.map(str::to_owned),
+                    location: OneOf::Left(Location::new(uri.clone(), symbol.selection_range)),
+                    data: None,
+                });
+            }
+        },
+    );
+    symbols
+}
+
+fn flatten<F>(symbols: &[DocumentSymbol], container: Option<&str>, f: &mut F)
+where
+    F: FnMut(&DocumentSymbol, Option<&str>),
+{
+    for symbol in symbols {
+        f(symbol, container);
+        if let Some(children) = &symbol.children {
+            flatten(children, Some(&symbol.name), f);
+        }
+    }
+}
+
+/// Creates symbols with ranges in the contents.
+struct SymbolBuilder<'a> {
+    contents: &'a str,
+}
+
+impl SymbolBuilder<'_> {
+    fn element(&self, element: &LibraryElementKind) -> DocumentSymbol {
+        match element {
+            LibraryElementKind::DataTypeDeclaration(decl) => self.data_type(decl),
+            LibraryElementKind::FunctionDeclaration(decl) => {
+                let mut children = self.variables(&decl.variables);
+                children.extend(self.body(&decl.body));
+                self.symbol(
+                    &decl.name,
+                    SymbolKind::FUNCTION,
+                    Some(decl.return_type.to_string()),
+                    Extent::new(last_end(|v| v.visit_function_declaration(decl)))
+                        .keywords("FUNCTION", "END_FUNCTION"),
+                    children,
+                )
+            }
+            LibraryElementKind::FunctionBlockDeclaration(decl) => {
+                let mut children = self.variables(&decl.variables);
+                children.extend(decl.methods.iter().map(|method| self.method(method)));
+                children.extend(
+                    decl.properties
+                        .iter()
+                        .map(|property| self.property(property)),
+                );
+                children.extend(self.body(&decl.body));
+                self.symbol(
+                    &decl.name.name,
+                    SymbolKind::CLASS,
+                    decl.extends.as_ref().map(|base| format!("EXTENDS {base}")),
+                    Extent::new(
+                        decl.span
+                            .end
+                            .max(last_end(|v| v.visit_function_block_declaration(decl))),
+                    )
+                    .keywords("FUNCTION_BLOCK", "END_FUNCTION_BLOCK"),
+                    children,
+                )
+            }
+            LibraryElementKind::ProgramDeclaration(decl) => {
+                let mut children = self.variables(&decl.variables);
+                children.extend(self.body(&decl.body));
+                self.symbol(
+                    &decl.name,
+                    SymbolKind::MODULE,
+                    None,
+                    Extent::new(last_end(|v| v.visit_program_declaration(decl)))
+                        .keywords("PROGRAM", "END_PROGRAM"),
+                    children,
+                )
+            }
+            LibraryElementKind::ConfigurationDeclaration(decl) => {
+                let mut children = self.variables(&decl.global_var);
+                children.extend(
+                    decl.resource_decl
+                        .iter()
+                        .map(|resource| self.resource(resource)),
+                );
+                self.symbol(
+                    &decl.name,
+                    SymbolKind::PACKAGE,
+                    None,
+                    Extent::new(last_end(|v| v.visit_configuration_declaration(decl)))
+                        .keywords("CONFIGURATION", "END_CONFIGURATION"),
+                    children,
+                )
+            }
+            LibraryElementKind::InterfaceDeclaration(decl) => {
+                let mut children: Vec<DocumentSymbol> = decl
+                    .methods
+                    .iter()
+                    .map(|method| self.method(method))
+                    .collect();
+                children.extend(
+                    decl.properties
+                        .iter()
+                        .map(|property| self.property(property)),
+                );
+                self.symbol(
+                    &decl.name.name,
+                    SymbolKind::INTERFACE,
+                    None,
+                    Extent::new(
+                        decl.span
+                            .end
+                            .max(last_end(|v| v.visit_interface_declaration(decl))),
+                    )
+                    .keywords("INTERFACE", "END_INTERFACE"),
+                    children,
+                )
+            }
+        }
+    }
+
+    fn data_type(&self, decl: &DataTypeDeclarationKind) -> DocumentSymbol {
+        let end = last_end(|v| v.visit_data_type_declaration_kind(decl));
+        let (type_name, kind, children, extent) = match decl {
+            DataTypeDeclarationKind::Enumeration(decl) => {
+                let children = match &decl.spec_init.spec {
+                    EnumeratedSpecificationKind::Values(values) => values
+                        .values
+                        .iter()
+                        .map(|value| {
+                            self.symbol(
+                                &value.value,
+                                SymbolKind::ENUM_MEMBER,
+                                None,
+                                Extent::new(value.value.span.end),
+                                vec![],
+                            )
+                        })
+                        .collect(),
+                    EnumeratedSpecificationKind::TypeName(_) => vec![],
+                };
+                (
+                    &decl.type_name,
+                    SymbolKind::ENUM,
+                    children,
+                    Extent::new(end),
+                )
+            }
+            DataTypeDeclarationKind::Structure(decl) => {
+                let children = decl
+                    .elements
+                    .iter()
+                    .map(|element| {
+                        self.symbol(
+                            &element.name,
+                            SymbolKind::FIELD,
+                            declared_type_name(&element.init).map(|name| name.to_string()),
+                            Extent::new(element.name.span.end),
+                            vec![],
+                        )
+                    })
+                    .collect();
+                (
+                    &decl.type_name,
+                    SymbolKind::STRUCT,
+                    children,
+                    Extent::new(end).end_keyword("END_STRUCT"),
+                )
+            }
+            DataTypeDeclarationKind::Subrange(decl) => (
+                &decl.type_name,
+                SymbolKind::TYPE_PARAMETER,
+                vec![],
+                Extent::new(end),
+            ),
+            DataTypeDeclarationKind::Simple(decl) => (
+                &decl.type_name,
+                SymbolKind::TYPE_PARAMETER,
+                vec![],
+                Extent::new(end),
+            ),
+            DataTypeDeclarationKind::Array(decl) => {
+                (&decl.type_name, SymbolKind::ARRAY, vec![], Extent::new(end))
+            }
+            DataTypeDeclarationKind::StructureInitialization(decl) => (
+                &decl.type_name,
+                SymbolKind::STRUCT,
+                vec![],
+                Extent::new(end),
+            ),
+            DataTypeDeclarationKind::String(decl) => (
+                &decl.type_name,
+                SymbolKind::STRING,
+                vec![],
+                Extent::new(end),
+            ),
+            DataTypeDeclarationKind::Reference(decl) => (
+                &decl.type_name,
+                SymbolKind::TYPE_PARAMETER,
+                vec![],
+                Extent::new(end),
+            ),
+            DataTypeDeclarationKind::LateBound(decl) => (
+                &decl.data_type_name,
+                SymbolKind::TYPE_PARAMETER,
+                vec![],
+                Extent::new(end),
+            ),
+        };
+        self.symbol(&type_name.name, kind, None, extent, children)
+    }
+
+    fn method(&self, decl: &MethodDeclaration) -> DocumentSymbol {
+        let mut children = self.variables(&decl.variables);
+        children.extend(self.body(&decl.body));
+        self.symbol(
+            &decl.name,
+            SymbolKind::METHOD,
+            decl.return_type.as_ref().map(|t| t.to_string()),
+            Extent::new(
+                decl.span
+                    .end
+                    .max(last_end(|v| v.visit_method_declaration(decl))),
+            )
+            .keywords("METHOD", "END_METHOD"),
+            children,
+        )
+    }
+
+    fn property(&self, decl: &PropertyDeclaration) -> DocumentSymbol {
+        self.symbol(
+            &decl.name,
+            SymbolKind::PROPERTY,
+            Some(decl.property_type.to_string()),
+            Extent::new(
+                decl.span
+                    .end
+                    .max(last_end(|v| v.visit_property_declaration(decl))),
+            )
+            .keywords("PROPERTY", "END_PROPERTY"),
+            vec![],
+        )
+    }
+
+    fn resource(&self, decl: &ResourceDeclaration) -> DocumentSymbol {
+        let mut children = self.variables(&decl.global_vars);
+        children.extend(decl.tasks.iter().map(|task| {
+            let interval = task
+                .interval
+                .as_ref()
+                .and_then(|interval| self.contents.get(interval.span.start..interval.span.end));
+            let detail = match interval {
+                Some(interval) => format!("INTERVAL := {interval}, PRIORITY := {}", task.priority),
+                None => format!("PRIORITY := {}", task.priority),
+            };
+            self.symbol(
+                &task.name,
+                SymbolKind::EVENT,
+                Some(detail),
+                Extent::new(last_end(|v| v.visit_task_configuration(task))),
+                vec![],
+            )
+        }));
+        children.extend(decl.programs.iter().map(|program| {
+            let detail = match &program.task_name {
+                Some(task) => format!("WITH {task} : {}", program.type_name),
+                None => program.type_name.to_string(),
+            };
+            self.symbol(
+                &program.name,
+                SymbolKind::OBJECT,
+                Some(detail),
+                Extent::new(last_end(|v| v.visit_program_configuration(program))),
+                vec![],
+            )
+        }));
+        self.symbol(
+            &decl.name,
+            SymbolKind::PACKAGE,
+            Some(format!("ON {}", decl.resource)),
+            Extent::new(last_end(|v| v.visit_resource_declaration(decl)))
+                .keywords("RESOURCE", "END_RESOURCE"),
+            children,
+        )
+    }
+
+    /// Returns the symbols for the variables grouped into the blocks (such
+    /// as `VAR_INPUT`) that declare the variables.
+    fn variables(&self, variables: &[VarDecl]) -> Vec<DocumentSymbol> {
+        let mut blocks: Vec<Vec<&VarDecl>> = vec![];
+        for variable in variables {
+            match blocks.last_mut() {
+                Some(block)
+                    if block[0].var_type == variable.var_type
+                        && block[0].qualifier == variable.qualifier =>
+                {
+                    block.push(variable)
+                }
+                _ => blocks.push(vec![variable]),
+            }
+        }
+
+        blocks
+            .into_iter()
+            .filter_map(|block| {
+                let keyword = var_keyword(&block[0].var_type);
+                let children: Vec<DocumentSymbol> = block
+                    .iter()
+                    .filter_map(|variable| {
+                        let name = variable.identifier.symbolic_id()?;
+                        Some(self.symbol(
+                            name,
+                            SymbolKind::VARIABLE,
+                            declared_type_name(&variable.initializer).map(|name| name.to_string()),
+                            Extent::new(name.span.end),
+                            vec![],
+                        ))
+                    })
+                    .collect();
+                let first = children.first()?;
+                let start = block[0].identifier.span().start;
+                let end = block
+                    .iter()
+                    .map(|variable| last_end(|v| v.visit_var_decl(variable)))
+                    .max()
+                    .unwrap_or(start);
+                let start = keyword_before(self.contents, start, keyword).unwrap_or(start);
+                let end = keyword_after(self.contents, end, "END_VAR").unwrap_or(end);
+                Some(new_symbol(
+                    keyword.to_owned(),
+                    SymbolKind::NAMESPACE,
+                    None,
+                    self.range(start, end),
+                    first.selection_range,
+                    children,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the symbols for the steps and actions in a sequential
+    /// function chart body.
+    fn body(&self, body: &FunctionBlockBodyKind) -> Vec<DocumentSymbol> {
+        match body {
+            FunctionBlockBodyKind::Sfc(sfc) => self.sfc(sfc),
+            _ => vec![],
+        }
+    }
+
+    fn sfc(&self, sfc: &Sfc) -> Vec<DocumentSymbol> {
+        let mut symbols = vec![];
+        for network in &sfc.networks {
+            symbols.push(self.step(&network.initial_step, "INITIAL_STEP"));
+            for element in &network.elements {
+                match element {
+                    ElementKind::Step(step) => symbols.push(self.step(step, "STEP")),
+                    ElementKind::Action(action) => symbols.push(
+                        self.symbol(
+                            &action.name,
+                            SymbolKind::FUNCTION,
+                            None,
+                            Extent::new(last_end(|v| v.visit_action(action)))
+                                .keywords("ACTION", "END_ACTION"),
+                            vec![],
+                        ),
+                    ),
+                    ElementKind::Transition(_) => {}
+                }
+            }
+        }
+        symbols
+    }
+
+    fn step(&self, step: &Step, keyword: &'static str) -> DocumentSymbol {
+        self.symbol(
+            &step.name,
+            SymbolKind::OBJECT,
+            None,
+            Extent::new(last_end(|v| v.visit_step(step))).keywords(keyword, "END_STEP"),
+            vec![],
+        )
+    }
+
+    fn symbol(
+        &self,
+        name: &Id,
+        kind: SymbolKind,
+        detail: Option<String>,
+        extent: Extent,
+        children: Vec<DocumentSymbol>,
+    ) -> DocumentSymbol {
+        let start = extent
+            .keyword
+            .and_then(|keyword| keyword_before(self.contents, name.span.start, keyword))
+            .unwrap_or(name.span.start);
+        let end = extent.end.max(name.span.end);
+        let end = extent
+            .end_keyword
+            .and_then(|keyword| keyword_after(self.contents, end, keyword))
+            .unwrap_or(end);
+        new_symbol(
+            name.original().to_owned(),
+            kind,
+            detail,
+            self.range(start, end),
+            self.range(name.span.start, name.span.end),
+            children,
+        )
+    }
+
+    fn range(&self, start: usize, end: usize) -> lsp_types::Range {
+        lsp_types::Range::new(
+            to_position(self.contents, start),
+            to_position(self.contents, end),
+        )
+    }
+}
+
+#[allow(deprecated)]
+fn new_symbol(
+    name: String,
+    kind: SymbolKind,
+    detail: Option<String>,
+    range: lsp_types::Range,
+    selection_range: lsp_types::Range,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: match children.is_empty() {
+            true => None,
+            false => Some(children),
+        },
+    }
+}
+
+/// The extent of a declaration: the keywords that start and end the
+/// declaration and the end of the last name in the declaration.
+struct Extent {
+    keyword: Option<&'static str>,
+    end_keyword: Option<&'static str>,
+    end: usize,
+}
+
+impl Extent {
+    fn new(end: usize) -> Self {
+        Self {
+            keyword: None,
+            end_keyword: None,
+            end,
+        }
+    }
+
+    fn keywords(self, keyword: &'static str, end_keyword: &'static str) -> Self {
+        Self {
+            keyword: Some(keyword),
+            end_keyword: Some(end_keyword),
+            ..self
+        }
+    }
+
+    fn end_keyword(self, end_keyword: &'static str) -> Self {
+        Self {
+            end_keyword: Some(end_keyword),
+            ..self
+        }
+    }
+}
+
+/// Finds the end of the last name in a declaration.
+#[derive(Default)]
+struct LastEnd {
+    end: usize,
+}
+
+impl Visitor<()> for LastEnd {
+    type Value = ();
+
+    fn visit_id(&mut self, node: &Id) -> Result<(), ()> {
+        self.end = self.end.max(node.span.end);
+        Ok(())
+    }
+}
+
+fn last_end<F>(f: F) -> usize
+where
+    F: FnOnce(&mut LastEnd) -> Result<(), ()>,
+{
+    let mut visitor = LastEnd::default();
+    // The visitor does not fail.
+    let _ = f(&mut visitor);
+    visitor.end
+}
+
+fn var_keyword(var_type: &VariableType) -> &'static str {
+    match var_type {
+        VariableType::Var => "VAR",
+        VariableType::VarTemp => "VAR_TEMP",
+        VariableType::Input => "VAR_INPUT",
+        VariableType::Output => "VAR_OUTPUT",
+        VariableType::InOut => "VAR_IN_OUT",
+        VariableType::External => "VAR_EXTERNAL",
+        VariableType::Global => "VAR_GLOBAL",
+        VariableType::Access => "VAR_ACCESS",
+    }
+}
+
+/// Returns the start of the last occurrence of the keyword before the offset.
+fn keyword_before(contents: &str, offset: usize, keyword: &str) -> Option<usize> {
+    words(contents.get(..offset)?)
+        .filter(|(_, word)| word.eq_ignore_ascii_case(keyword))
+        .last()
+        .map(|(start, _)| start)
+}
+
+/// Returns the end of the first occurrence of the keyword after the offset.
+fn keyword_after(contents: &str, offset: usize, keyword: &str) -> Option<usize> {
+    words(contents.get(offset..)?)
+        .find(|(_, word)| word.eq_ignore_ascii_case(keyword))
+        .map(|(start, word)| offset + start + word.len())
+}
+
+/// Returns the words in the text with the offset of each word.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(|c: char| !is_identifier_char(c))
+        .filter(|word| !word.is_empty())
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use ironplc_dsl::{common::Library, core::FileId};
+    use ironplc_parser::{options::ParseOptions, parse_program};
+    use ironplc_test::read_shared_resource;
+    use lsp_types::{DocumentSymbol, Position, SymbolKind, Uri};
+
+    use super::{document_symbols, workspace_symbols};
+
+    const PROGRAM: &str = "TYPE
+   POINT : STRUCT
+      x : INT;
+   END_STRUCT;
+END_TYPE
+FUNCTION_BLOCK counter
+VAR_INPUT
+   reset : BOOL;
+END_VAR
+VAR
+   count : DINT;
+END_VAR
+END_FUNCTION_BLOCK
+PROGRAM main
+VAR
+   instance : counter;
+END_VAR
+   instance(reset := TRUE);
+END_PROGRAM
+CONFIGURATION config
+   RESOURCE res ON PLC
+      TASK plc_task(INTERVAL := T#100ms, PRIORITY := 1);
+      PROGRAM plc_task_instance WITH plc_task : main;
+   END_RESOURCE
+END_CONFIGURATION";
+
+    fn library() -> Library {
+        parse_program(PROGRAM, &FileId::default(), &ParseOptions::default()).unwrap()
+    }
+
+    fn names(symbols: &[DocumentSymbol]) -> Vec<&str> {
+        symbols.iter().map(|symbol| symbol.name.as_str()).collect()
+    }
+
+    #[test]
+    fn document_symbols_when_library_then_hierarchy() {
+        let symbols = document_symbols(&library(), PROGRAM);
+
+        assert_eq!(vec!["POINT", "counter", "main", "config"], names(&symbols));
+
+        let counter = &symbols[1];
+        assert_eq!(SymbolKind::CLASS, counter.kind);
+        assert_eq!(Position::new(5, 0), counter.range.start);
+        assert_eq!(Position::new(12, 18), counter.range.end);
+        assert_eq!(Position::new(5, 15), counter.selection_range.start);
+
+        let blocks = counter.children.as_ref().unwrap();
+        assert_eq!(vec!["VAR_INPUT", "VAR"], names(blocks));
+        assert_eq!(Position::new(6, 0), blocks[0].range.start);
+        assert_eq!(Position::new(8, 7), blocks[0].range.end);
+        assert_eq!(vec!["reset"], names(blocks[0].children.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn document_symbols_when_configuration_then_resource_tasks_and_programs() {
+        let symbols = document_symbols(&library(), PROGRAM);
+
+        let resources = symbols[3].children.as_ref().unwrap();
+        assert_eq!(vec!["res"], names(resources));
+        let resource = resources[0].children.as_ref().unwrap();
+        assert_eq!(vec!["plc_task", "plc_task_instance"], names(resource));
+        assert_eq!(Some("WITH plc_task : main".to_owned()), resource[1].detail);
+    }
+
+    #[test]
+    fn document_symbols_when_sfc_then_steps_and_actions() {
+        let program = read_shared_resource("sfc.st");
+        let library =
+            parse_program(&program, &FileId::default(), &ParseOptions::default()).unwrap();
+
+        let symbols = document_symbols(&library, &program);
+
+        let children = symbols[0].children.as_ref().unwrap();
+        assert_eq!(
+            vec![
+                "VAR_INPUT",
+                "VAR_OUTPUT",
+                "VAR",
+                "Start",
+                "MidStep",
+                "RESET"
+            ],
+            names(children)
+        );
+        assert_eq!(SymbolKind::FUNCTION, children[5].kind);
+    }
+
+    #[test]
+    fn workspace_symbols_when_query_then_matching_declarations() {
+        let uri = Uri::from_str("file:///main.st").unwrap();
+
+        let symbols = workspace_symbols(&library(), PROGRAM, &uri, "TASK");
+
+        let names: Vec<&str> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(vec!["plc_task", "plc_task_instance"], names);
+        assert_eq!(Some("res".to_owned()), symbols[0].container_name);
+    }
+}
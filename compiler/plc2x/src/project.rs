@@ -3,10 +3,15 @@
 //!
 //! The trait enables easy testing of the language server protocol integration.
 
-use std::path::Path;
+use std::{path::Path, sync::Arc};
 
-use ironplc_analyzer::stages::analyze;
+use ironplc_analyzer::{
+    stages::{analyze_with_rules, resolve_type_environment},
+    symbol_index::SymbolIndex,
+    TypeEnvironment, TypeEnvironmentBuilder,
+};
 use ironplc_dsl::{
+    common::Library,
     core::{FileId, SourceSpan},
     diagnostic::{Diagnostic, Label},
 };
@@ -15,6 +20,8 @@ use ironplc_problems::Problem;
 use ironplc_sources::{Source, SourceProject};
 use log::trace;
 
+use crate::{config::ProjectConfiguration, rules::RuleSet};
+
 /// A project consisting of one or more files.
 ///
 /// The project acts is akin to an interface for interacting with the compiler
@@ -30,8 +37,23 @@ pub trait Project {
     fn tokenize(&self, file_id: &FileId) -> (Vec<Token>, Vec<Diagnostic>);
 
     /// Requests semantic analysis for the project.
+    ///
+    /// Returns `Err` with the diagnostics if there are any diagnostics to
+    /// report. The diagnostics may include warnings so use
+    /// [`Diagnostic::is_error`] to decide if the analysis failed.
     fn semantic(&mut self) -> Result<(), Vec<Diagnostic>>;
 
+    /// Requests the index of declarations and references for the project.
+    ///
+    /// The index includes the valid parts of sources that have syntax errors.
+    fn symbol_index(&mut self) -> Arc<SymbolIndex>;
+
+    /// Requests the types declared in the project.
+    ///
+    /// The types are the elementary types if the project has a type
+    /// declaration that is not valid.
+    fn type_environment(&mut self) -> Arc<TypeEnvironment>;
+
     /// Gets the sources that are the project.
     fn sources(&self) -> Vec<&Source>;
 
@@ -44,6 +66,14 @@ pub trait Project {
 pub struct FileBackedProject {
     /// The underlying source project
     source_project: SourceProject,
+    /// The overrides for the severity of problems
+    rules: RuleSet,
+    /// The index for the sources. The index is built when requested and
+    /// discarded when the sources change.
+    symbol_index: Option<Arc<SymbolIndex>>,
+    /// The types for the sources. The types are resolved when requested
+    /// and discarded when the sources change.
+    type_environment: Option<Arc<TypeEnvironment>>,
 }
 
 impl Default for FileBackedProject {
@@ -56,22 +86,79 @@ impl FileBackedProject {
     pub fn new() -> Self {
         FileBackedProject {
             source_project: SourceProject::new(),
+            rules: RuleSet::new(),
+            symbol_index: None,
+            type_environment: None,
         }
     }
 
     pub fn push(&mut self, file_id: FileId) -> Result<(), Diagnostic> {
+        self.discard_analysis();
         self.source_project.add_file(file_id)
     }
 
     pub fn get(&self, file_id: &FileId) -> Option<&Source> {
         self.source_project.get_source(file_id)
     }
+
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        self.discard_analysis();
+        self.source_project.set_parse_options(options)
+    }
+
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        self.rules = rules;
+    }
+
+    /// Discards the index and types so that these are built again from the
+    /// sources when next requested.
+    fn discard_analysis(&mut self) {
+        self.symbol_index = None;
+        self.type_environment = None;
+    }
+
+    /// Returns the libraries for the sources. The libraries include the
+    /// valid parts of sources that have syntax errors.
+    fn partial_libraries(&mut self) -> Vec<&Library> {
+        self.source_project
+            .sources_mut()
+            .into_iter()
+            .map(|source| Source::partial_library(source).0)
+            .collect()
+    }
+
+    /// Replaces the sources and options of the project with the sources and
+    /// options from the project configuration.
+    pub fn configure(&mut self, config: &ProjectConfiguration) -> Vec<Diagnostic> {
+        self.discard_analysis();
+        self.source_project.clear();
+        self.set_parse_options(config.parse_options().clone());
+        self.set_rules(config.rules().clone());
+
+        config
+            .files()
+            .into_iter()
+            .filter_map(|path| self.push(FileId::from_path(&path)).err())
+            .collect()
+    }
 }
 
 impl Project for FileBackedProject {
-    /// Create a new project from the files in the specified directory.
+    /// Create a new project from the project configuration file for the
+    /// directory or, if there is no project configuration file, from the
+    /// files in the specified directory.
     fn initialize(&mut self, dir: &Path) -> Vec<Diagnostic> {
-        self.source_project.initialize_from_directory(dir)
+        self.discard_analysis();
+        match ProjectConfiguration::discover(dir) {
+            Some(path) => match ProjectConfiguration::load(&path) {
+                Ok(config) => self.configure(&config),
+                Err(mut diagnostics) => {
+                    diagnostics.extend(self.source_project.initialize_from_directory(dir));
+                    diagnostics
+                }
+            },
+            None => self.source_project.initialize_from_directory(dir),
+        }
     }
 
     fn change_text_document(&mut self, file_id: &FileId, content: String) {
@@ -80,6 +167,16 @@ impl Project for FileBackedProject {
             self.source_project.len()
         );
 
+        // Keep the index and types when the text is unchanged, for example,
+        // when the document is saved.
+        let unchanged = self
+            .source_project
+            .get_source(file_id)
+            .is_some_and(|source| source.as_string() == content);
+        if !unchanged {
+            self.discard_analysis();
+        }
+
         self.source_project.add_source(file_id.clone(), content);
 
         trace!(
@@ -92,7 +189,13 @@ impl Project for FileBackedProject {
         let source = self.source_project.get_source(file_id);
 
         match source {
-            Some(src) => tokenize_program(src.as_string(), file_id, &ParseOptions::default(), 0, 0),
+            Some(src) => tokenize_program(
+                src.as_string(),
+                file_id,
+                self.source_project.parse_options(),
+                0,
+                0,
+            ),
             None => (
                 vec![],
                 vec![Diagnostic::problem(
@@ -110,31 +213,53 @@ impl Project for FileBackedProject {
         let mut all_libraries = vec![];
         let mut all_diagnostics: Vec<Diagnostic> = vec![];
 
-        // Process each source individually to avoid borrowing issues
+        // Process each source individually to avoid borrowing issues. Sources
+        // with syntax errors still contribute the parts that are valid.
         for source in self.source_project.sources_mut() {
-            match source.library() {
-                Ok(library) => {
-                    all_libraries.push(library);
-                }
-                Err(diagnostics) => {
-                    for diagnostic in diagnostics {
-                        all_diagnostics.push(diagnostic.clone());
-                    }
-                }
-            }
+            let (library, diagnostics) = source.partial_library();
+            all_libraries.push(library);
+            all_diagnostics.extend(diagnostics.iter().cloned());
         }
 
+        // Syntax problems are not found by analysis, so apply the rules to
+        // these here. Analysis applies the rules to the problems it finds.
+        let mut all_diagnostics = self.rules.apply(all_diagnostics);
+
         // Do the analysis
-        match analyze(&all_libraries) {
-            Ok(_) => Ok(()),
-            Err(diagnostics) => {
-                // If we had an error, then add more diagnostics to any that we already had
-                all_diagnostics.extend(diagnostics);
-                Err(all_diagnostics)
-            }
+        if let Err(diagnostics) = analyze_with_rules(&all_libraries, &self.rules) {
+            all_diagnostics.extend(diagnostics);
+        }
+
+        match all_diagnostics.is_empty() {
+            true => Ok(()),
+            false => Err(all_diagnostics),
         }
     }
 
+    fn symbol_index(&mut self) -> Arc<SymbolIndex> {
+        if let Some(index) = &self.symbol_index {
+            return index.clone();
+        }
+        let index = Arc::new(SymbolIndex::new(&self.partial_libraries()));
+        self.symbol_index = Some(index.clone());
+        index
+    }
+
+    fn type_environment(&mut self) -> Arc<TypeEnvironment> {
+        if let Some(types) = &self.type_environment {
+            return types.clone();
+        }
+        let types = resolve_type_environment(&self.partial_libraries()).unwrap_or_else(|_| {
+            TypeEnvironmentBuilder::new()
+                .with_elementary_types()
+                .build()
+                .unwrap_or_default()
+        });
+        let types = Arc::new(types);
+        self.type_environment = Some(types.clone());
+        types
+    }
+
     fn sources(&self) -> Vec<&Source> {
         self.source_project.sources()
     }
@@ -150,8 +275,17 @@ impl Project for FileBackedProject {
 
 #[cfg(test)]
 mod test {
+    use std::sync::Arc;
+
     use ironplc_dsl::core::FileId;
 
+    use ironplc_problems::Severity;
+
+    use crate::{
+        rules::{RuleLevel, RuleSet},
+        test_helpers::resource_path,
+    };
+
     use super::{FileBackedProject, Project};
 
     #[test]
@@ -182,6 +316,211 @@ mod test {
         project.change_text_document(&FileId::default(), "AAA".to_owned());
     }
 
+    #[test]
+    fn semantic_when_multiple_syntax_errors_then_all_diagnostics() {
+        let mut project = FileBackedProject::default();
+        project.change_text_document(
+            &FileId::from_string("main.st"),
+            "
+FUNCTION_BLOCK fb
+VAR
+   x : INT;
+END_VAR
+   x := ;
+   x := 1;
+   x := 2 +;
+END_FUNCTION_BLOCK"
+                .to_owned(),
+        );
+
+        let diagnostics = project.semantic().unwrap_err();
+        let syntax_errors = diagnostics.iter().filter(|d| d.code == "P0002").count();
+        assert_eq!(2, syntax_errors);
+    }
+
+    #[test]
+    fn semantic_when_one_syntax_error_then_only_syntax_diagnostic() {
+        let mut project = FileBackedProject::default();
+        project.change_text_document(
+            &FileId::from_string("main.st"),
+            "
+TYPE
+   LEVEL : (LOW, HIGH);
+END_TYPE
+
+FUNCTION_BLOCK fb
+VAR
+   x : INT;
+   level : LEVEL := LOW;
+END_VAR
+   x := ;
+   level := HIGH;
+END_FUNCTION_BLOCK"
+                .to_owned(),
+        );
+
+        let diagnostics = project.semantic().unwrap_err();
+        let codes: Vec<_> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(vec!["P0002"], codes);
+    }
+
+    #[test]
+    fn semantic_when_type_declaration_has_syntax_error_then_not_implemented_not_reported() {
+        let mut project = FileBackedProject::default();
+        project.change_text_document(
+            &FileId::from_string("main.st"),
+            "
+TYPE
+   IntRef : REF_TO INT;
+   MyInt : INT;
+END_TYPE
+
+FUNCTION_BLOCK fb
+VAR
+   x : MyInt;
+   y : INT;
+END_VAR
+   x := y;
+END_FUNCTION_BLOCK"
+                .to_owned(),
+        );
+
+        let diagnostics = project.semantic().unwrap_err();
+        assert_eq!("P0002", diagnostics[0].code);
+        assert!(diagnostics.iter().all(|d| d.code != "P9999"));
+    }
+
+    const UNDEFINED_VARIABLE: &str = "
+PROGRAM main
+VAR
+   x : INT;
+END_VAR
+   x := y;
+END_PROGRAM";
+
+    #[test]
+    fn semantic_when_rule_is_warning_then_warning_diagnostic() {
+        let mut rules = RuleSet::new();
+        rules.set("P4007", RuleLevel::Warning).unwrap();
+        let mut project = FileBackedProject::default();
+        project.set_rules(rules);
+        project.change_text_document(
+            &FileId::from_string("main.st"),
+            UNDEFINED_VARIABLE.to_owned(),
+        );
+
+        let diagnostics = project.semantic().unwrap_err();
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(Severity::Warning, diagnostics[0].severity);
+    }
+
+    #[test]
+    fn semantic_when_rule_is_off_then_ok() {
+        let mut rules = RuleSet::new();
+        rules.set("P4007", RuleLevel::Off).unwrap();
+        let mut project = FileBackedProject::default();
+        project.set_rules(rules);
+        project.change_text_document(
+            &FileId::from_string("main.st"),
+            UNDEFINED_VARIABLE.to_owned(),
+        );
+
+        assert!(project.semantic().is_ok());
+    }
+
+    const SUBRANGE_AND_EXPRESSION_ERRORS: &str = "
+TYPE
+   SMALL : INT (10..1);
+END_TYPE
+
+PROGRAM main
+VAR
+   r : REAL;
+   b : BOOL;
+END_VAR
+   b := r + 'text';
+END_PROGRAM";
+
+    #[test]
+    fn semantic_when_early_problem_is_warning_then_has_later_problems() {
+        let mut rules = RuleSet::new();
+        rules.set("P2002", RuleLevel::Warning).unwrap();
+        let mut project = FileBackedProject::default();
+        project.set_rules(rules);
+        project.change_text_document(
+            &FileId::from_string("main.st"),
+            SUBRANGE_AND_EXPRESSION_ERRORS.to_owned(),
+        );
+
+        let diagnostics = project.semantic().unwrap_err();
+        assert_eq!(1, diagnostics.iter().filter(|d| d.code == "P2002").count());
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.code == "P2002" && d.severity == Severity::Warning));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.code == "P4022" && d.is_error()));
+    }
+
+    #[test]
+    fn semantic_when_early_problem_is_off_then_has_later_problems() {
+        let mut rules = RuleSet::new();
+        rules.set("P2002", RuleLevel::Off).unwrap();
+        let mut project = FileBackedProject::default();
+        project.set_rules(rules);
+        project.change_text_document(
+            &FileId::from_string("main.st"),
+            SUBRANGE_AND_EXPRESSION_ERRORS.to_owned(),
+        );
+
+        let diagnostics = project.semantic().unwrap_err();
+        assert!(diagnostics.iter().all(|d| d.code != "P2002"));
+        assert!(diagnostics.iter().any(|d| d.code == "P4022"));
+    }
+
+    #[test]
+    fn symbol_index_when_text_unchanged_then_reuses_index() {
+        let mut project = FileBackedProject::default();
+        let file_id = FileId::from_string("main.st");
+        project.change_text_document(&file_id, UNDEFINED_VARIABLE.to_owned());
+
+        let index = project.symbol_index();
+        let types = project.type_environment();
+        project.change_text_document(&file_id, UNDEFINED_VARIABLE.to_owned());
+
+        assert!(Arc::ptr_eq(&index, &project.symbol_index()));
+        assert!(Arc::ptr_eq(&types, &project.type_environment()));
+    }
+
+    #[test]
+    fn symbol_index_when_text_changed_then_new_index() {
+        let mut project = FileBackedProject::default();
+        let file_id = FileId::from_string("main.st");
+        project.change_text_document(&file_id, UNDEFINED_VARIABLE.to_owned());
+
+        let index = project.symbol_index();
+        let types = project.type_environment();
+        project.change_text_document(&file_id, UNDEFINED_VARIABLE.replace('y', "x"));
+
+        assert!(!Arc::ptr_eq(&index, &project.symbol_index()));
+        assert!(!Arc::ptr_eq(&types, &project.type_environment()));
+    }
+
+    #[test]
+    fn initialize_when_has_configuration_then_uses_configuration() {
+        let mut project = FileBackedProject::default();
+        let diagnostics = project.initialize(&resource_path("project"));
+        assert!(diagnostics.is_empty());
+        assert_eq!(2, project.sources().len());
+
+        // The configuration defines the variant and makes the undefined
+        // variable a warning.
+        let diagnostics = project.semantic().unwrap_err();
+        assert_eq!(1, diagnostics.len());
+        assert_eq!("P4007", diagnostics[0].code);
+        assert_eq!(Severity::Warning, diagnostics[0].severity);
+    }
+
     #[test]
     fn xml_file_returns_empty_library() {
         let mut project = FileBackedProject::default();
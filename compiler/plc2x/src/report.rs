@@ -0,0 +1,302 @@
+//! Implements machine-readable reports of diagnostics.
+//!
+//! The reports are for tools such as continuous integration pipelines
+//! rather than for people. The JSON report is a simple list of diagnostics
+//! and the SARIF report follows the Static Analysis Results Interchange
+//! Format version 2.1.0.
+
+use std::{env::current_dir, path::Path};
+
+use ironplc_dsl::{
+    core::FileId,
+    diagnostic::{Diagnostic, Label},
+};
+use ironplc_problems::{Problem, Severity};
+use serde_json::{json, Value};
+
+use crate::project::FileBackedProject;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The format for reporting diagnostics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable text written to the terminal.
+    #[default]
+    Text,
+    /// A JSON document.
+    Json,
+    /// A SARIF document.
+    Sarif,
+}
+
+/// A position in a source file. Line and column start at 1 and the column
+/// is the number of characters (rather than bytes) in the line.
+#[derive(Debug, PartialEq)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+/// Returns the diagnostics as a JSON document.
+pub fn to_json(diagnostics: &[Diagnostic], project: Option<&FileBackedProject>) -> Value {
+    let diagnostics: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            json!({
+                "code": diagnostic.code,
+                "severity": diagnostic.severity.to_string(),
+                "message": diagnostic.description(),
+                "file": diagnostic.primary.file_id.to_string(),
+                "primary": json_label(&diagnostic.primary, project),
+                "secondary": diagnostic
+                    .secondary
+                    .iter()
+                    .filter(|label| has_file(label, project))
+                    .map(|label| json_label(label, project))
+                    .collect::<Vec<Value>>(),
+            })
+        })
+        .collect();
+
+    json!({ "diagnostics": diagnostics })
+}
+
+fn json_label(label: &Label, project: Option<&FileBackedProject>) -> Value {
+    let (start, end) = positions(label, project);
+    json!({
+        "file": label.file_id.to_string(),
+        "message": label.message,
+        "start": { "line": start.line, "column": start.column },
+        "end": { "line": end.line, "column": end.column },
+    })
+}
+
+/// Returns the diagnostics as a SARIF document. The rules in the document
+/// are all problems that the compiler can report.
+pub fn to_sarif(diagnostics: &[Diagnostic], project: Option<&FileBackedProject>) -> Value {
+    let rules: Vec<Value> = Problem::ALL
+        .iter()
+        .map(|problem| {
+            json!({
+                "id": problem.code(),
+                "name": problem.name(),
+                "shortDescription": { "text": problem.message() },
+                "helpUri": help_uri(problem.code()),
+                "defaultConfiguration": { "level": sarif_level(problem.severity()) },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let message = format!(
+                "{}: {}",
+                diagnostic.description(),
+                diagnostic.primary.message
+            );
+            let mut result = json!({
+                "ruleId": diagnostic.code,
+                "level": sarif_level(diagnostic.severity),
+                "message": { "text": message },
+                "locations": [sarif_location(&diagnostic.primary, project)],
+            });
+            if let Some(index) = Problem::ALL
+                .iter()
+                .position(|problem| problem.code() == diagnostic.code)
+            {
+                result["ruleIndex"] = json!(index);
+            }
+            let related: Vec<Value> = diagnostic
+                .secondary
+                .iter()
+                .filter(|label| has_file(label, project))
+                .enumerate()
+                .map(|(id, label)| {
+                    let mut location = sarif_location(label, project);
+                    location["id"] = json!(id);
+                    location["message"] = json!({ "text": label.message });
+                    location
+                })
+                .collect();
+            if !related.is_empty() {
+                result["relatedLocations"] = json!(related);
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "ironplcc",
+                    "version": VERSION,
+                    "informationUri": "https://www.ironplc.com",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn sarif_location(label: &Label, project: Option<&FileBackedProject>) -> Value {
+    let (start, end) = positions(label, project);
+    json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": artifact_uri(&label.file_id) },
+            "region": {
+                "startLine": start.line,
+                "startColumn": start.column,
+                "endLine": end.line,
+                "endColumn": end.column,
+            }
+        }
+    })
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+fn help_uri(code: &str) -> String {
+    format!("https://www.ironplc.com/compiler/problems/{code}.html")
+}
+
+/// Returns the URI for the file. The URI is relative to the current
+/// directory when the file is in the current directory so that tools can
+/// match the file to the repository.
+fn artifact_uri(file_id: &FileId) -> String {
+    let file = file_id.to_string();
+    let path = Path::new(&file);
+    if let Some(relative) = current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf))
+    {
+        return relative.to_string_lossy().replace('\\', "/");
+    }
+    if path.is_absolute() {
+        let path = file.replace('\\', "/");
+        return match path.starts_with('/') {
+            true => format!("file://{path}"),
+            false => format!("file:///{path}"),
+        };
+    }
+    file.replace('\\', "/")
+}
+
+/// Secondary labels may refer to declarations that are not in a source
+/// file (such as standard function blocks) and so there is nothing to
+/// report for those labels.
+fn has_file(label: &Label, project: Option<&FileBackedProject>) -> bool {
+    match project {
+        Some(project) => project.get(&label.file_id).is_some(),
+        None => true,
+    }
+}
+
+fn positions(label: &Label, project: Option<&FileBackedProject>) -> (Position, Position) {
+    let text = project
+        .and_then(|project| project.get(&label.file_id))
+        .map(|source| source.as_string())
+        .unwrap_or_default();
+    (
+        position(text, label.location.start),
+        position(text, label.location.end),
+    )
+}
+
+/// Returns the line and column of the byte offset in the text.
+fn position(text: &str, offset: usize) -> Position {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Position {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ironplc_dsl::{
+        core::{FileId, SourceSpan},
+        diagnostic::{Diagnostic, Label},
+    };
+    use ironplc_problems::Problem;
+
+    use crate::project::{FileBackedProject, Project};
+
+    use super::{position, to_json, to_sarif, Position};
+
+    fn project_and_diagnostic() -> (FileBackedProject, Diagnostic) {
+        let file_id = FileId::from_string("main.st");
+        let mut project = FileBackedProject::default();
+        project.change_text_document(&file_id, "PROGRAM main\n  x := y;\nEND_PROGRAM".to_owned());
+
+        let diagnostic = Diagnostic::problem(
+            Problem::VariableUndefined,
+            Label::span(
+                SourceSpan::range(20, 21).with_file_id(&file_id),
+                "Variable reference",
+            ),
+        );
+        (project, diagnostic)
+    }
+
+    #[test]
+    fn position_when_second_line_then_line_and_column() {
+        assert_eq!(Position { line: 2, column: 3 }, position("abc\ndefg", 6));
+    }
+
+    #[test]
+    fn position_when_multibyte_then_counts_characters() {
+        assert_eq!(Position { line: 1, column: 3 }, position("äb c", 3));
+    }
+
+    #[test]
+    fn to_json_when_diagnostic_then_has_code_and_span() {
+        let (project, diagnostic) = project_and_diagnostic();
+
+        let json = to_json(&[diagnostic], Some(&project));
+
+        let item = &json["diagnostics"][0];
+        assert_eq!("P4007", item["code"]);
+        assert_eq!("error", item["severity"]);
+        assert_eq!("main.st", item["file"]);
+        assert_eq!(2, item["primary"]["start"]["line"]);
+        assert_eq!(8, item["primary"]["start"]["column"]);
+        assert_eq!(9, item["primary"]["end"]["column"]);
+    }
+
+    #[test]
+    fn to_sarif_when_diagnostic_then_has_rule_and_result() {
+        let (project, diagnostic) = project_and_diagnostic();
+
+        let sarif = to_sarif(&[diagnostic], Some(&project));
+
+        let run = &sarif["runs"][0];
+        let result = &run["results"][0];
+        assert_eq!("2.1.0", sarif["version"]);
+        assert_eq!("P4007", result["ruleId"]);
+        let rule = &run["tool"]["driver"]["rules"][result["ruleIndex"].as_u64().unwrap() as usize];
+        assert_eq!("P4007", rule["id"]);
+        assert_eq!("VariableUndefined", rule["name"]);
+        let region = &result["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(2, region["startLine"]);
+        assert_eq!(8, region["startColumn"]);
+    }
+}
@@ -51,6 +51,71 @@ fn check_when_valid_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn check_when_instruction_list_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check").arg(shared_resource_path("il.st"));
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn check_when_object_oriented_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg(shared_resource_path("object_oriented.st"));
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn check_when_conditional_file_with_define_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg("--define")
+        .arg("VARIANT_B")
+        .arg(shared_resource_path("conditional_compilation.st"));
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn check_when_pragma_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check").arg(shared_resource_path("pragma.st"));
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn check_when_nested_structure_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg(shared_resource_path("nested_structure.st"));
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn check_when_reference_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check").arg(shared_resource_path("reference.st"));
+    cmd.assert().success().stdout(predicate::str::is_empty());
+
+    Ok(())
+}
+
 #[test]
 fn check_when_valid_file_8859_encoded_then_ok() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::new(cargo::cargo_bin!());
@@ -101,6 +166,19 @@ fn check_when_semantic_error_file_then_err() -> Result<(), Box<dyn std::error::E
     Ok(())
 }
 
+#[test]
+fn check_when_expression_type_error_file_then_err() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg(shared_resource_path("expression_type_error.st"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("P4022"));
+
+    Ok(())
+}
+
 #[test]
 fn echo_when_valid_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::new(cargo::cargo_bin!());
@@ -167,3 +245,144 @@ fn version_then_ok() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn check_when_standard_function_blocks_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg(shared_resource_path("standard_function_blocks.st"));
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn check_when_constant_expressions_file_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg(shared_resource_path("constant_expressions.st"));
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn check_when_severity_is_warning_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg("--severity")
+        .arg("P2006=warning")
+        .arg(shared_resource_path("first_steps_semantic_error.st"));
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("warning[P2006]"));
+
+    Ok(())
+}
+
+#[test]
+fn check_when_default_severity_is_warning_then_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg(path_to_test_resource("c_style_comment.st"));
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("warning[P0004]"));
+
+    Ok(())
+}
+
+#[test]
+fn check_when_severity_is_not_valid_then_err() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg("--severity")
+        .arg("P2006=loud")
+        .arg(shared_resource_path("first_steps_semantic_error.st"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("not valid"));
+
+    Ok(())
+}
+
+#[test]
+fn check_when_project_configuration_then_uses_configured_files(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.current_dir(path_to_test_resource("project").join("src"))
+        .arg("check");
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("warning[P4007]"));
+
+    Ok(())
+}
+
+#[test]
+fn check_when_config_not_valid_then_err() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("--config")
+        .arg(path_to_test_resource("invalid_ironplc.toml"))
+        .arg("check");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("P6008"));
+
+    Ok(())
+}
+
+#[test]
+fn check_when_config_not_valid_and_format_json_then_writes_json(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("--config")
+        .arg(path_to_test_resource("invalid_ironplc.toml"))
+        .arg("check")
+        .arg("--format")
+        .arg("json");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("\"code\": \"P6008\""));
+
+    Ok(())
+}
+
+#[test]
+fn check_when_format_json_then_writes_json() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg("--format")
+        .arg("json")
+        .arg(shared_resource_path("first_steps_semantic_error.st"));
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("\"code\": \"P2006\""));
+
+    Ok(())
+}
+
+#[test]
+fn check_when_format_sarif_then_writes_sarif() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::new(cargo::cargo_bin!());
+
+    cmd.arg("check")
+        .arg("--format")
+        .arg("sarif")
+        .arg(shared_resource_path("first_steps_semantic_error.st"));
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("\"ruleId\": \"P2006\""))
+        .stdout(predicate::str::contains("\"version\": \"2.1.0\""));
+
+    Ok(())
+}
@@ -17,6 +17,8 @@ struct ProblemDef {
     name: String,
     /// A message describing the type of error.
     message: String,
+    /// The default severity of the problem.
+    severity: String,
 }
 
 fn create_problems() -> Result<(), Box<dyn Error>> {
@@ -45,10 +47,20 @@ fn create_problems() -> Result<(), Box<dyn Error>> {
         let message = record
             .get(2)
             .ok_or_else(|| format!("Record {record:?} is not valid at column 2"))?;
+        let severity = record
+            .get(3)
+            .ok_or_else(|| format!("Record {record:?} is not valid at column 3"))?;
+        let severity = match severity {
+            "error" => "Error",
+            "warning" => "Warning",
+            "info" => "Info",
+            other => return Err(format!("Record {record:?} has unknown severity {other}").into()),
+        };
         defs.push(ProblemDef {
             code: code.to_string(),
             name: name.to_string(),
             message: message.to_string(),
+            severity: severity.to_string(),
         });
     }
 
@@ -98,6 +110,61 @@ fn create_problems() -> Result<(), Box<dyn Error>> {
         )?;
     }
     out.write_all(b"        }\n")?;
+    out.write_all(b"    }\n\n")?;
+
+    // Define severity()
+    out.write_all(b"    /// Returns the default severity for the particular problem.\n")?;
+    out.write_all(b"    pub fn severity(&self) -> Severity {\n")?;
+    out.write_all(b"        match self {\n")?;
+    for def in &defs {
+        out.write_all(
+            format!(
+                "            Problem::{} => Severity::{},\n",
+                def.name, def.severity
+            )
+            .as_bytes(),
+        )?;
+    }
+    out.write_all(b"        }\n")?;
+    out.write_all(b"    }\n\n")?;
+
+    // Define name()
+    out.write_all(b"    /// Returns the name for the particular problem as a string.\n")?;
+    out.write_all(b"    pub fn name(&self) -> &str {\n")?;
+    out.write_all(b"        match self {\n")?;
+    for def in &defs {
+        out.write_all(
+            format!("            Problem::{} => \"{}\",\n", def.name, def.name).as_bytes(),
+        )?;
+    }
+    out.write_all(b"        }\n")?;
+    out.write_all(b"    }\n\n")?;
+
+    // Define ALL
+    out.write_all(b"    /// All problems in the order of the problem codes.\n")?;
+    out.write_all(b"    pub const ALL: &'static [Problem] = &[\n")?;
+    for def in &defs {
+        out.write_all(format!("        Problem::{},\n", def.name).as_bytes())?;
+    }
+    out.write_all(b"    ];\n\n")?;
+
+    // Define from_code()
+    out.write_all(
+        b"    /// Returns the problem that has the code or `None` if no problem has the code.\n",
+    )?;
+    out.write_all(b"    pub fn from_code(code: &str) -> Option<Problem> {\n")?;
+    out.write_all(b"        match code {\n")?;
+    for def in &defs {
+        out.write_all(
+            format!(
+                "            \"{}\" => Some(Problem::{}),\n",
+                def.code, def.name
+            )
+            .as_bytes(),
+        )?;
+    }
+    out.write_all(b"            _ => None,\n")?;
+    out.write_all(b"        }\n")?;
     out.write_all(b"    }\n")?;
 
     out.write_all(b"}\n")?;
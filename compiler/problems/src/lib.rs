@@ -1,3 +1,57 @@
 //! Provides compiler error definitions.
 
+use std::fmt;
+
+/// The severity of a problem.
+///
+/// Errors prevent a successful build. Warnings and information describe
+/// possible issues but do not prevent a successful build.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+            Severity::Info => f.write_str("info"),
+        }
+    }
+}
+
 include!(concat!(env!("OUT_DIR"), "/problems.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_code_when_known_code_then_problem() {
+        let problem = Problem::from_code("P0002").unwrap();
+        assert_eq!("P0002", problem.code());
+        assert_eq!(Severity::Error, problem.severity());
+    }
+
+    #[test]
+    fn severity_when_lint_problem_then_warning() {
+        assert_eq!(Severity::Warning, Problem::CStyleComment.severity());
+    }
+
+    #[test]
+    fn all_when_iterated_then_has_every_code_once() {
+        let mut codes: Vec<&str> = Problem::ALL.iter().map(|p| p.code()).collect();
+        let len = codes.len();
+        codes.dedup();
+        assert_eq!(len, codes.len());
+        assert_eq!("OpenComment", Problem::ALL[0].name());
+    }
+
+    #[test]
+    fn from_code_when_unknown_code_then_none() {
+        assert!(Problem::from_code("P0000").is_none());
+    }
+}
@@ -4,6 +4,7 @@ pub mod st_parser;
 pub mod xml_parser;
 
 use ironplc_dsl::{common::Library, core::FileId, diagnostic::Diagnostic};
+use ironplc_parser::options::ParseOptions;
 use ironplc_problems::Problem;
 
 use crate::file_type::FileType;
@@ -13,9 +14,10 @@ pub fn parse_source(
     file_type: FileType,
     content: &str,
     file_id: &FileId,
+    options: &ParseOptions,
 ) -> Result<Library, Diagnostic> {
     match file_type {
-        FileType::StructuredText => st_parser::parse(content, file_id),
+        FileType::StructuredText => st_parser::parse(content, file_id, options),
         FileType::Xml => xml_parser::parse(content, file_id),
         FileType::Unknown => Err(Diagnostic::problem(
             Problem::UnsupportedFileType,
@@ -27,6 +29,25 @@ pub fn parse_source(
     }
 }
 
+/// Parse source content based on file type, recovering from errors when the
+/// parser for the file type supports recovery.
+///
+/// Returns the (possibly partial) library and the diagnostics.
+pub fn parse_source_with_recovery(
+    file_type: FileType,
+    content: &str,
+    file_id: &FileId,
+    options: &ParseOptions,
+) -> (Library, Vec<Diagnostic>) {
+    match file_type {
+        FileType::StructuredText => st_parser::parse_with_recovery(content, file_id, options),
+        _ => match parse_source(file_type, content, file_id, options) {
+            Ok(library) => (library, vec![]),
+            Err(diagnostic) => (Library::new(), vec![diagnostic]),
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -36,7 +57,12 @@ mod tests {
     fn parse_source_structured_text() {
         let content = "PROGRAM Main\nEND_PROGRAM";
         let file_id = FileId::from_string("test.st");
-        let result = parse_source(FileType::StructuredText, content, &file_id);
+        let result = parse_source(
+            FileType::StructuredText,
+            content,
+            &file_id,
+            &ParseOptions::default(),
+        );
         assert!(result.is_ok());
     }
 
@@ -58,7 +84,7 @@ mod tests {
   </types>
 </project>"#;
         let file_id = FileId::from_string("test.xml");
-        let result = parse_source(FileType::Xml, content, &file_id);
+        let result = parse_source(FileType::Xml, content, &file_id, &ParseOptions::default());
         assert!(result.is_ok());
         let library = result.unwrap();
         assert_eq!(library.elements.len(), 0);
@@ -68,7 +94,12 @@ mod tests {
     fn parse_source_unknown_file_type() {
         let content = "some content";
         let file_id = FileId::from_string("test.unknown");
-        let result = parse_source(FileType::Unknown, content, &file_id);
+        let result = parse_source(
+            FileType::Unknown,
+            content,
+            &file_id,
+            &ParseOptions::default(),
+        );
         assert!(result.is_err());
     }
 }
@@ -1,11 +1,25 @@
 //! Structured Text parser implementation
 
 use ironplc_dsl::{common::Library, core::FileId, diagnostic::Diagnostic};
-use ironplc_parser::{options::ParseOptions, parse_program};
+use ironplc_parser::{options::ParseOptions, parse_program, parse_program_with_recovery};
 
 /// Parse Structured Text (.st, .iec) files
-pub fn parse(content: &str, file_id: &FileId) -> Result<Library, Diagnostic> {
-    parse_program(content, file_id, &ParseOptions::default())
+pub fn parse(
+    content: &str,
+    file_id: &FileId,
+    options: &ParseOptions,
+) -> Result<Library, Diagnostic> {
+    parse_program(content, file_id, options)
+}
+
+/// Parse Structured Text (.st, .iec) files, recovering from syntax errors
+/// to return the library elements that are valid along with all diagnostics
+pub fn parse_with_recovery(
+    content: &str,
+    file_id: &FileId,
+    options: &ParseOptions,
+) -> (Library, Vec<Diagnostic>) {
+    parse_program_with_recovery(content, file_id, options)
 }
 
 #[cfg(test)]
@@ -24,18 +38,36 @@ END_VAR
 END_PROGRAM
 "#;
         let file_id = FileId::from_string("test.st");
-        let result = parse(content, &file_id);
+        let result = parse(content, &file_id, &ParseOptions::default());
 
         assert!(result.is_ok());
         let library = result.unwrap();
         assert_eq!(library.elements.len(), 1);
     }
 
+    #[test]
+    fn parse_with_recovery_when_invalid_statement_then_library_and_diagnostic() {
+        let content = r#"
+PROGRAM Main
+VAR
+    x : INT := 42;
+END_VAR
+    x := ;
+END_PROGRAM
+"#;
+        let file_id = FileId::from_string("test.st");
+        let (library, diagnostics) =
+            parse_with_recovery(content, &file_id, &ParseOptions::default());
+
+        assert_eq!(library.elements.len(), 1);
+        assert_eq!(diagnostics.len(), 1);
+    }
+
     #[test]
     fn parse_invalid_syntax() {
         let content = "INVALID SYNTAX";
         let file_id = FileId::from_string("test.st");
-        let result = parse(content, &file_id);
+        let result = parse(content, &file_id, &ParseOptions::default());
 
         assert!(result.is_err());
     }
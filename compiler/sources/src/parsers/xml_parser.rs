@@ -16,8 +16,8 @@ use crate::xml::{position::parse_plcopen_xml, transform::transform_project};
 /// # Errors
 ///
 /// Returns a `Diagnostic` if:
-/// - The XML is malformed (P0006)
-/// - The XML doesn't conform to PLCopen schema (P0007)
+/// - The XML is malformed (P0002)
+/// - The XML doesn't conform to PLCopen schema (P0002)
 /// - An unsupported body language is used (P9003)
 pub fn parse(content: &str, file_id: &FileId) -> Result<Library, Diagnostic> {
     debug!("Parsing PLCopen XML file: {}", file_id);
@@ -6,6 +6,7 @@ use ironplc_dsl::{
     core::FileId,
     diagnostic::{Diagnostic, Label},
 };
+use ironplc_parser::options::ParseOptions;
 use ironplc_problems::Problem;
 use log::{info, trace, warn};
 
@@ -15,6 +16,8 @@ use crate::{file_type::FileType, source::Source};
 pub struct SourceProject {
     /// The source files in the project
     sources: HashMap<FileId, Source>,
+    /// The options for parsing the source files
+    parse_options: ParseOptions,
 }
 
 impl Default for SourceProject {
@@ -28,9 +31,24 @@ impl SourceProject {
     pub fn new() -> Self {
         SourceProject {
             sources: HashMap::new(),
+            parse_options: ParseOptions::default(),
         }
     }
 
+    /// Get the options for parsing the source files
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.parse_options
+    }
+
+    /// Set the options for parsing the source files, including the
+    /// source files that are already in the project
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        for source in self.sources.values_mut() {
+            source.set_parse_options(options.clone());
+        }
+        self.parse_options = options;
+    }
+
     /// Add a source file to the project by file ID
     pub fn add_file(&mut self, file_id: FileId) -> Result<(), Diagnostic> {
         match Source::try_from_file_id(&file_id) {
@@ -42,10 +60,18 @@ impl SourceProject {
         }
     }
 
-    /// Add source content directly to the project
+    /// Add source content directly to the project. If the project already
+    /// has the source, then this replaces the content of the source.
     pub fn add_source(&mut self, file_id: FileId, content: String) {
+        if let Some(source) = self.sources.get_mut(&file_id) {
+            trace!("Updating source file: {}", file_id);
+            source.set_text(content);
+            return;
+        }
+
         trace!("Adding source file: {}", file_id);
-        let source = Source::new(content, &file_id);
+        let mut source = Source::new(content, &file_id);
+        source.set_parse_options(self.parse_options.clone());
         self.sources.insert(file_id, source);
     }
 
@@ -197,6 +223,26 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn set_parse_options_when_define_then_parses_with_define() {
+        let mut project = SourceProject::new();
+        let file_id = FileId::from_string("test.st");
+        project.add_source(
+            file_id.clone(),
+            "{IF defined(A)} PROGRAM Main END_PROGRAM {ELSE} invalid {END_IF}".to_string(),
+        );
+
+        let source = project.get_source_mut(&file_id).unwrap();
+        assert!(source.library().is_err());
+
+        project.set_parse_options(ParseOptions {
+            defines: vec!["A".to_string()],
+            ..ParseOptions::default()
+        });
+        let source = project.get_source_mut(&file_id).unwrap();
+        assert!(source.library().is_ok());
+    }
+
     #[test]
     fn get_source_mut_returns_mutable_reference() {
         let mut project = SourceProject::new();
@@ -10,6 +10,7 @@ use ironplc_dsl::{
     core::FileId,
     diagnostic::{Diagnostic, Label},
 };
+use ironplc_parser::options::ParseOptions;
 use ironplc_problems::Problem;
 use log::{debug, trace};
 
@@ -21,7 +22,8 @@ pub struct Source {
     file_id: FileId,
     data: String,
     file_type: FileType,
-    library: Option<Result<Library, Diagnostic>>,
+    parse_options: ParseOptions,
+    library: Option<(Library, Vec<Diagnostic>)>,
 }
 
 impl Source {
@@ -34,10 +36,28 @@ impl Source {
             file_id: file_id.clone(),
             data: source,
             file_type,
+            parse_options: ParseOptions::default(),
             library: None,
         }
     }
 
+    /// Sets the options for parsing the source. Changing the options
+    /// discards any previously parsed library.
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        self.parse_options = options;
+        self.library = None;
+    }
+
+    /// Replaces the contents of the source. The parsed library is kept
+    /// when the contents are unchanged so that only sources that change
+    /// are parsed again.
+    pub fn set_text(&mut self, text: String) {
+        if self.data != text {
+            self.data = text;
+            self.library = None;
+        }
+    }
+
     /// Create a Source by reading from a file
     pub fn try_from_file_id(item: &FileId) -> Result<Source, Diagnostic> {
         let path: PathBuf = item.to_string().into();
@@ -62,25 +82,24 @@ impl Source {
 
     /// Parse the source into a Library, caching the result
     pub fn library(&mut self) -> Result<&Library, Vec<Diagnostic>> {
-        if self.library.is_none() {
-            self.library = Some(self.parse_content());
-        }
-
-        match &self.library {
-            Some(result) => match result {
-                Ok(library) => Ok(library),
-                Err(diagnostic) => Err(vec![diagnostic.clone()]),
-            },
-            None => {
-                // This should not be possible to reach since we set self.library above
-                Err(vec![Diagnostic::internal_error(file!(), line!())])
-            }
+        match self.partial_library() {
+            (library, []) => Ok(library),
+            (_, diagnostics) => Err(diagnostics.to_vec()),
         }
     }
 
-    /// Parse the content using the appropriate parser
-    fn parse_content(&self) -> Result<Library, Diagnostic> {
-        parsers::parse_source(self.file_type.clone(), &self.data, &self.file_id)
+    /// Parse the source into a Library, caching the result. When the source
+    /// has errors, the library contains the elements that are valid.
+    pub fn partial_library(&mut self) -> (&Library, &[Diagnostic]) {
+        let (library, diagnostics) = self.library.get_or_insert_with(|| {
+            parsers::parse_source_with_recovery(
+                self.file_type.clone(),
+                &self.data,
+                &self.file_id,
+                &self.parse_options,
+            )
+        });
+        (library, diagnostics)
     }
 }
 
@@ -239,4 +258,18 @@ mod tests {
         // Verify the library is cached by checking it's not None
         assert!(source.library.is_some());
     }
+
+    #[test]
+    fn set_text_when_same_text_then_keeps_library() {
+        let file_id = FileId::from_string("test.st");
+        let mut source = Source::new("PROGRAM main END_PROGRAM".to_string(), &file_id);
+        assert!(source.library().is_ok());
+
+        source.set_text("PROGRAM main END_PROGRAM".to_string());
+        assert!(source.library.is_some());
+
+        source.set_text("PROGRAM other END_PROGRAM".to_string());
+        assert!(source.library.is_none());
+        assert_eq!("PROGRAM other END_PROGRAM", source.as_string());
+    }
 }
@@ -10,10 +10,10 @@ use ironplc_dsl::{
         EnumeratedSpecificationKind, EnumeratedSpecificationValues, EnumeratedValue,
         EnumerationDeclaration, FunctionBlockBodyKind, FunctionBlockDeclaration,
         FunctionDeclaration, InitialValueAssignmentKind, Integer, Library, LibraryElementKind,
-        ProgramDeclaration, SignedInteger, SimpleDeclaration, SimpleInitializer,
-        StructureDeclaration, StructureElementDeclaration, Subrange, SubrangeDeclaration,
-        SubrangeSpecification, SubrangeSpecificationKind, TypeName, VarDecl, VariableIdentifier,
-        VariableType,
+        ProgramDeclaration, ReferenceDeclaration, ReferenceInitialValueAssignment, SignedInteger,
+        SimpleDeclaration, SimpleInitializer, StructureDeclaration, StructureElementDeclaration,
+        Subrange, SubrangeDeclaration, SubrangeSpecification, SubrangeSpecificationKind, TypeName,
+        VarDecl, VariableIdentifier, VariableType,
     },
     core::{FileId, Id, SourceSpan},
     diagnostic::{Diagnostic, Label},
@@ -23,8 +23,8 @@ use ironplc_parser::options::ParseOptions;
 use ironplc_problems::Problem;
 
 use super::schema::{
-    ArrayType, DataType, DataTypeDecl, Dimension, EnumType, Interface, Pou, PouType, Project,
-    StructType, SubrangeSigned, SubrangeUnsigned, VarList, Variable,
+    ArrayType, DataType, DataTypeDecl, Dimension, EnumType, Interface, PointerType, Pou, PouType,
+    Project, StructType, SubrangeSigned, SubrangeUnsigned, VarList, Variable,
 };
 
 /// Create a SourceSpan for the given file with no position info
@@ -72,6 +72,13 @@ fn transform_data_type_decl(
         DataType::SubrangeUnsigned(subrange) => {
             transform_subrange_unsigned_decl(&type_name, subrange, file_id)
         }
+        DataType::Pointer(pointer) => {
+            Ok(DataTypeDeclarationKind::Reference(ReferenceDeclaration {
+                type_name,
+                spec_init: transform_pointer(pointer, file_id)?,
+                attributes: vec![],
+            }))
+        }
         // Elementary types as type aliases
         _ => {
             let base_type_name = transform_data_type(&decl.base_type, file_id)?;
@@ -81,6 +88,7 @@ fn transform_data_type_decl(
                     type_name: base_type_name,
                     initial_value: None,
                 }),
+                attributes: vec![],
             }))
         }
     }
@@ -110,6 +118,7 @@ fn transform_enum_decl(
                 spec,
                 default: None,
             },
+            attributes: vec![],
         },
     ))
 }
@@ -130,6 +139,7 @@ fn transform_array_decl(
             type_name: base_type_name,
         }),
         init: vec![],
+        attributes: vec![],
     }))
 }
 
@@ -158,14 +168,16 @@ fn transform_dimensions(
                         value: lower.unsigned_abs(),
                     },
                     is_neg: lower < 0,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span,
                         value: upper.unsigned_abs(),
                     },
                     is_neg: upper < 0,
-                },
+                }
+                .into(),
             })
         })
         .collect()
@@ -181,13 +193,10 @@ fn transform_struct_decl(
         .variable
         .iter()
         .map(|member| {
-            let member_type = transform_data_type(&member.member_type, file_id)?;
             Ok(StructureElementDeclaration {
                 name: Id::from(member.name.as_str()),
-                init: InitialValueAssignmentKind::Simple(SimpleInitializer {
-                    type_name: member_type,
-                    initial_value: None,
-                }),
+                init: transform_type_initializer(&member.member_type, file_id)?,
+                attributes: vec![],
             })
         })
         .collect();
@@ -195,6 +204,7 @@ fn transform_struct_decl(
     Ok(DataTypeDeclarationKind::Structure(StructureDeclaration {
         type_name: type_name.clone(),
         elements: elements?,
+        attributes: vec![],
     }))
 }
 
@@ -227,17 +237,20 @@ fn transform_subrange_signed_decl(
                         value: lower.unsigned_abs(),
                     },
                     is_neg: lower < 0,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer {
                         span,
                         value: upper.unsigned_abs(),
                     },
                     is_neg: upper < 0,
-                },
+                }
+                .into(),
             },
         }),
         default: None,
+        attributes: vec![],
     }))
 }
 
@@ -270,14 +283,17 @@ fn transform_subrange_unsigned_decl(
                         value: lower,
                     },
                     is_neg: false,
-                },
+                }
+                .into(),
                 end: SignedInteger {
                     value: Integer { span, value: upper },
                     is_neg: false,
-                },
+                }
+                .into(),
             },
         }),
         default: None,
+        attributes: vec![],
     }))
 }
 
@@ -359,10 +375,38 @@ fn transform_data_type(data_type: &DataType, _file_id: &FileId) -> Result<TypeNa
         DataType::SubrangeSigned(_) | DataType::SubrangeUnsigned(_) => {
             Err(Diagnostic::todo(file!(), line!()))
         }
+        // Pointers are not named types; see transform_type_initializer
         DataType::Pointer(_) => Err(Diagnostic::todo(file!(), line!())),
     }
 }
 
+/// Transform a pointer type into a reference to the base type
+fn transform_pointer(
+    pointer: &PointerType,
+    file_id: &FileId,
+) -> Result<ReferenceInitialValueAssignment, Diagnostic> {
+    Ok(ReferenceInitialValueAssignment {
+        referenced_type_name: transform_data_type(&pointer.base_type, file_id)?,
+        initial_value: None,
+    })
+}
+
+/// Transform the type of a variable or structure member to an initializer
+fn transform_type_initializer(
+    data_type: &DataType,
+    file_id: &FileId,
+) -> Result<InitialValueAssignmentKind, Diagnostic> {
+    match data_type {
+        DataType::Pointer(pointer) => Ok(InitialValueAssignmentKind::Reference(transform_pointer(
+            pointer, file_id,
+        )?)),
+        _ => Ok(InitialValueAssignmentKind::Simple(SimpleInitializer {
+            type_name: transform_data_type(data_type, file_id)?,
+            initial_value: None, // TODO: Handle initial values
+        })),
+    }
+}
+
 /// Transform a POU (Program Organization Unit)
 fn transform_pou(pou: &Pou, file_id: &FileId) -> Result<LibraryElementKind, Diagnostic> {
     match pou.pou_type {
@@ -410,7 +454,8 @@ fn transform_function(pou: &Pou, file_id: &FileId) -> Result<LibraryElementKind,
             return_type,
             variables,
             edge_variables: vec![],
-            body,
+            body: FunctionBlockBodyKind::stmts(body),
+            attributes: vec![],
         },
     ))
 }
@@ -426,10 +471,16 @@ fn transform_function_block(pou: &Pou, file_id: &FileId) -> Result<LibraryElemen
     Ok(LibraryElementKind::FunctionBlockDeclaration(
         FunctionBlockDeclaration {
             name,
+            modifier: None,
+            extends: None,
+            implements: vec![],
             variables,
             edge_variables: vec![],
+            methods: vec![],
+            properties: vec![],
             body,
             span,
+            attributes: vec![],
         },
     ))
 }
@@ -446,6 +497,7 @@ fn transform_program(pou: &Pou, file_id: &FileId) -> Result<LibraryElementKind,
         variables,
         access_variables: vec![],
         body,
+        attributes: vec![],
     }))
 }
 
@@ -537,18 +589,15 @@ fn transform_variable(
     file_id: &FileId,
 ) -> Result<VarDecl, Diagnostic> {
     let identifier = VariableIdentifier::Symbol(Id::from(var.name.as_str()));
-    let type_name = transform_data_type(&var.var_type, file_id)?;
-
-    let initializer = InitialValueAssignmentKind::Simple(SimpleInitializer {
-        type_name,
-        initial_value: None, // TODO: Handle initial values
-    });
+    let initializer = transform_type_initializer(&var.var_type, file_id)?;
 
     Ok(VarDecl {
         identifier,
         var_type,
         qualifier,
         initializer,
+        attributes: vec![],
+        keyword_span: SourceSpan::default(),
     })
 }
 
@@ -844,6 +893,62 @@ IF Reset THEN Count := 0; END_IF;
         assert_eq!(prog_decl.variables.len(), 1);
     }
 
+    #[test]
+    fn transform_when_pointer_type_then_creates_reference_declaration() {
+        let xml = format!(
+            r#"{}
+  <types>
+    <dataTypes>
+      <dataType name="IntPtr">
+        <baseType>
+          <pointer>
+            <baseType><INT/></baseType>
+          </pointer>
+        </baseType>
+      </dataType>
+    </dataTypes>
+    <pous>
+      <pou name="Main" pouType="program">
+        <interface>
+          <localVars>
+            <variable name="p">
+              <type>
+                <pointer>
+                  <baseType><derived name="IntPtr"/></baseType>
+                </pointer>
+              </type>
+            </variable>
+          </localVars>
+        </interface>
+      </pou>
+    </pous>
+  </types>
+</project>"#,
+            minimal_project_header()
+        );
+
+        let project = parse_project(&xml);
+        let library = transform_project(&project, &test_file_id()).unwrap();
+
+        assert_eq!(library.elements.len(), 2);
+        let LibraryElementKind::DataTypeDeclaration(DataTypeDeclarationKind::Reference(ref_decl)) =
+            &library.elements[0]
+        else {
+            panic!("Expected reference declaration");
+        };
+        assert_eq!(ref_decl.type_name.to_string(), "IntPtr");
+        assert_eq!(ref_decl.spec_init.referenced_type_name.to_string(), "INT");
+
+        let LibraryElementKind::ProgramDeclaration(prog_decl) = &library.elements[1] else {
+            panic!("Expected program declaration");
+        };
+        let InitialValueAssignmentKind::Reference(init) = &prog_decl.variables[0].initializer
+        else {
+            panic!("Expected reference initializer");
+        };
+        assert_eq!(init.referenced_type_name.to_string(), "IntPtr");
+    }
+
     #[test]
     fn transform_when_function_block_with_st_body_then_parses_statements() {
         let xml = format!(
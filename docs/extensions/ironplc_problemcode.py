@@ -17,8 +17,9 @@ help_topics = compiler_help_topics.union(extension_help_topics)
 problem_infos = dict()
 
 class ProblemCode:
-    def __init__(self, message):
+    def __init__(self, message, severity):
         self.message = message
+        self.severity = severity
 
 definitions = [
     join('..', 'compiler', 'problems', 'resources', 'problem-codes.csv'),
@@ -36,17 +37,21 @@ for definition in definitions:
             if code not in help_topics:
                 print('Missing help topic for ' + code)
                 exit(1)
-            problem_infos[code] = ProblemCode(row[2])
+            # Problems without a severity column are errors
+            severity = row[3] if len(row) > 3 else 'error'
+            problem_infos[code] = ProblemCode(row[2], severity)
 
 class ProblemSummary(Directive):
     required_arguments = 1
     def run(self):
         code = self.arguments[0]
         message = problem_infos[code].message
+        severity = problem_infos[code].severity
 
         items = [
             ProblemSummary.make_item('Code', code),
             ProblemSummary.make_item('Message', message),
+            ProblemSummary.make_item('Default Severity', severity),
         ]
         
         return [nodes.definition_list('', *items)]